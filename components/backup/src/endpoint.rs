@@ -912,6 +912,7 @@ impl<E: Engine, R: RegionInfoProvider + Clone + 'static> Endpoint<E, R> {
                     .linux_user
                     .clone(),
             },
+            ..Default::default()
         }
     }
 
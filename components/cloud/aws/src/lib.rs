@@ -5,6 +5,8 @@ mod kms;
 pub use kms::{AwsKms, ENCRYPTION_VENDOR_NAME_AWS_KMS};
 
 mod s3;
-pub use s3::{Config, S3Storage, STORAGE_NAME, STORAGE_VENDOR_NAME_AWS};
+pub use s3::{
+    is_archived_object, Config, Provider, S3Storage, STORAGE_NAME, STORAGE_VENDOR_NAME_AWS,
+};
 
 mod util;
@@ -1,5 +1,6 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 use std::{
+    collections::HashMap,
     error::Error as StdError,
     io,
     time::{Duration, SystemTime},
@@ -9,6 +10,7 @@ use async_trait::async_trait;
 use cloud::{
     blob::{none_to_empty, BlobConfig, BlobStorage, BucketConf, PutResource, StringNonEmpty},
     metrics::CLOUD_REQUEST_HISTOGRAM_VEC,
+    tls::TlsOptions,
 };
 use fail::fail_point;
 use futures_util::{
@@ -22,7 +24,11 @@ use rusoto_credential::{ProvideAwsCredentials, StaticProvider};
 use rusoto_s3::{util::AddressingStyle, *};
 use rusoto_sts::{StsAssumeRoleSessionCredentialsProvider, StsClient};
 use thiserror::Error;
-use tikv_util::{debug, stream::error_stream, time::Instant};
+use tikv_util::{
+    debug, info,
+    stream::{error_stream, RetryError},
+    time::Instant,
+};
 use tokio::time::{sleep, timeout};
 
 use crate::util::{self, retry_and_count};
@@ -30,6 +36,39 @@ use crate::util::{self, retry_and_count};
 const CONNECTION_TIMEOUT: Duration = Duration::from_secs(900);
 pub const STORAGE_VENDOR_NAME_AWS: &str = "aws";
 
+/// How long an auto-initiated restore may take before the read gives up.
+/// Expedited Glacier restores typically finish within 1-5 minutes.
+const DEFAULT_RESTORE_DEADLINE: Duration = Duration::from_secs(900);
+/// How often an in-progress restore is polled with `HeadObject`.
+const RESTORE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How long the restored copy stays readable before S3 removes it again.
+const RESTORE_KEEP_DAYS: i64 = 1;
+
+/// The marker in the message of errors for reads that hit an object a
+/// lifecycle rule transitioned into an archive storage class (e.g. S3
+/// Glacier). BR matches storage errors by string, so keep it stable.
+const ARCHIVED_OBJECT_MSG: &str = "object is archived";
+
+/// The error for a read of an archived object: the data exists, but S3
+/// refuses to serve it until it is restored. Recognized by
+/// [`is_archived_object`].
+fn archived_object_error(key: &str, bucket: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!(
+            "{}: key {} at bucket {} was transitioned to an archive storage class, \
+             restore it (or enable automatic restore) before reading",
+            ARCHIVED_OBJECT_MSG, key, bucket
+        ),
+    )
+}
+
+/// Whether the error marks a read of an archived object. (See
+/// [`archived_object_error`].)
+pub fn is_archived_object(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::Other && e.to_string().contains(ARCHIVED_OBJECT_MSG)
+}
+
 #[derive(Clone)]
 pub struct AccessKeyPair {
     pub access_key: StringNonEmpty,
@@ -47,6 +86,65 @@ impl std::fmt::Debug for AccessKeyPair {
     }
 }
 
+/// The service behind an S3-compatible endpoint. Defaults to plain AWS;
+/// the other providers toggle known addressing and signing quirks:
+///
+/// - `Oss` (Alibaba Cloud): virtual-host addressing breaks the header
+///   canonicalization of some operations and some regions reject unsigned
+///   payload streaming, so requests use path-style addressing and every
+///   upload carries a `Content-MD5` integrity header.
+/// - `Minio`: path-style addressing, since local deployments rarely have
+///   the wildcard DNS virtual-host addressing needs.
+/// - `Ceph` (RGW): path-style addressing, and checksum headers are dropped
+///   unless object lock demands them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Provider {
+    #[default]
+    Aws,
+    Oss,
+    Minio,
+    Ceph,
+}
+
+impl Provider {
+    /// Whether requests must use path-style addressing instead of the
+    /// virtual-host style.
+    fn force_path_style(self) -> bool {
+        !matches!(self, Provider::Aws)
+    }
+
+    /// Whether every upload must carry a `Content-MD5` header so the
+    /// payload is covered by the signature.
+    fn require_signed_payload(self) -> bool {
+        matches!(self, Provider::Oss)
+    }
+
+    /// Whether checksum headers the provider rejects must be dropped.
+    fn suppress_checksum_headers(self) -> bool {
+        matches!(self, Provider::Ceph)
+    }
+}
+
+impl std::str::FromStr for Provider {
+    type Err = io::Error;
+
+    fn from_str(hint: &str) -> io::Result<Provider> {
+        match hint {
+            "aws" => Ok(Provider::Aws),
+            "oss" => Ok(Provider::Oss),
+            "minio" => Ok(Provider::Minio),
+            "ceph" => Ok(Provider::Ceph),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unknown S3 provider {:?}, expected one of aws, oss, minio, ceph",
+                    hint
+                ),
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Config {
     bucket: BucketConf,
@@ -60,6 +158,12 @@ pub struct Config {
     object_lock_enabled: bool,
     role_arn: Option<StringNonEmpty>,
     external_id: Option<StringNonEmpty>,
+    requester_pays: bool,
+    provider: Provider,
+    anonymous: bool,
+    tls: TlsOptions,
+    restore_archived: bool,
+    restore_deadline: Duration,
 }
 
 impl Config {
@@ -77,9 +181,34 @@ impl Config {
             object_lock_enabled: false,
             role_arn: None,
             external_id: None,
+            requester_pays: false,
+            provider: Provider::Aws,
+            anonymous: false,
+            tls: TlsOptions::default(),
+            restore_archived: false,
+            restore_deadline: DEFAULT_RESTORE_DEADLINE,
         }
     }
 
+    /// Skips credential resolution and sends unsigned requests, which public
+    /// buckets accept for reads. Writes are rejected up front since S3 never
+    /// accepts unsigned writes.
+    pub fn set_anonymous(&mut self, anonymous: bool) {
+        self.anonymous = anonymous;
+    }
+
+    /// TLS constraints to apply when building the HTTP client. Storage
+    /// creation fails if the platform TLS stack cannot satisfy them.
+    pub fn set_tls_options(&mut self, tls: TlsOptions) {
+        self.tls = tls;
+    }
+
+    /// The `x-amz-request-payer` value to attach to every request, required
+    /// by requester-pays buckets.
+    fn request_payer(&self) -> Option<String> {
+        self.requester_pays.then(|| "requester".to_owned())
+    }
+
     pub fn from_input(input: InputConfig) -> io::Result<Config> {
         let storage_class = StringNonEmpty::opt(input.storage_class);
         let endpoint = StringNonEmpty::opt(input.endpoint);
@@ -116,6 +245,12 @@ impl Config {
             object_lock_enabled: input.object_lock_enabled,
             role_arn: StringNonEmpty::opt(input.role_arn),
             external_id: StringNonEmpty::opt(input.external_id),
+            requester_pays: false,
+            provider: Provider::Aws,
+            anonymous: false,
+            tls: TlsOptions::default(),
+            restore_archived: false,
+            restore_deadline: DEFAULT_RESTORE_DEADLINE,
         })
     }
 }
@@ -154,9 +289,33 @@ impl S3Storage {
         self.config.multi_part_size = size;
     }
 
+    /// Makes every request declare `x-amz-request-payer: requester`, which
+    /// requester-pays buckets demand before they accept reads or writes.
+    pub fn set_requester_pays(&mut self, requester_pays: bool) {
+        self.config.requester_pays = requester_pays;
+    }
+
+    /// Makes reads that hit an object archived to Glacier issue a
+    /// `RestoreObject` request and wait for the temporary copy instead of
+    /// failing. Without this, such reads fail with the error recognized by
+    /// [`is_archived_object`].
+    pub fn set_restore_archived(&mut self, restore_archived: bool) {
+        self.config.restore_archived = restore_archived;
+    }
+
+    /// Applies the quirks of the S3-compatible service behind the endpoint.
+    /// See [`Provider`] for the per-provider mapping.
+    pub fn set_provider(&mut self, provider: Provider) {
+        self.config.provider = provider;
+        if provider.force_path_style() {
+            self.client.config_mut().addressing_style = AddressingStyle::Path;
+        }
+    }
+
     /// Create a new S3 storage for the given config.
     pub fn new(config: Config) -> io::Result<S3Storage> {
-        Self::with_request_dispatcher(config, util::new_http_client()?)
+        let dispatcher = util::new_http_client_with_tls(&config.tls)?;
+        Self::with_request_dispatcher(config, dispatcher)
     }
 
     fn new_creds_dispatcher<Creds, Dispatcher>(
@@ -172,7 +331,7 @@ impl S3Storage {
         let bucket_endpoint = config.bucket.endpoint.clone();
         let region = util::get_region(&bucket_region, &none_to_empty(bucket_endpoint))?;
         let mut client = S3Client::new_with(dispatcher, credentials_provider, region);
-        if config.force_path_style {
+        if config.force_path_style || config.provider.force_path_style() {
             client.config_mut().addressing_style = AddressingStyle::Path;
         }
         Ok(S3Storage { config, client })
@@ -193,7 +352,11 @@ impl S3Storage {
             let bucket_endpoint = config.bucket.endpoint.clone();
             let region = util::get_region(&bucket_region, &none_to_empty(bucket_endpoint))?;
             // cannot use the same dispatcher because of move, so use another http client.
-            let sts = StsClient::new_with(util::new_http_client()?, cred_provider, region);
+            let sts = StsClient::new_with(
+                util::new_http_client_with_tls(&config.tls)?,
+                cred_provider,
+                region,
+            );
             let duration_since_epoch = SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap();
@@ -215,10 +378,30 @@ impl S3Storage {
         }
     }
 
+    /// Builds a client that sends unsigned requests, for public buckets that
+    /// allow anonymous reads.
+    fn new_anonymous_dispatcher<D>(config: Config, dispatcher: D) -> io::Result<S3Storage>
+    where
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        let bucket_region = none_to_empty(config.bucket.region.clone());
+        let bucket_endpoint = config.bucket.endpoint.clone();
+        let region = util::get_region(&bucket_region, &none_to_empty(bucket_endpoint))?;
+        let mut client =
+            S3Client::new_with_client(rusoto_core::Client::new_not_signing(dispatcher), region);
+        if config.force_path_style || config.provider.force_path_style() {
+            client.config_mut().addressing_style = AddressingStyle::Path;
+        }
+        Ok(S3Storage { config, client })
+    }
+
     pub fn with_request_dispatcher<D>(config: Config, dispatcher: D) -> io::Result<S3Storage>
     where
         D: DispatchSignedRequest + Send + Sync + 'static,
     {
+        if config.anonymous {
+            return Self::new_anonymous_dispatcher(config, dispatcher);
+        }
         // static credentials are used with minio
         if let Some(access_key_pair) = &config.access_key_pair {
             let cred_provider = StaticProvider::new(
@@ -241,6 +424,18 @@ impl S3Storage {
         key.to_owned()
     }
 
+    /// Anonymous clients send unsigned requests, which S3 rejects for writes;
+    /// fail before reading anything from the reader.
+    fn reject_anonymous_write(&self) -> io::Result<()> {
+        if self.config.anonymous {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "writes require credentials, but the storage is in anonymous mode",
+            ));
+        }
+        Ok(())
+    }
+
     fn get_range(&self, name: &str, range: Option<String>) -> cloud::blob::BlobStream<'_> {
         let key = self.maybe_prefix_key(name);
         let bucket = self.config.bucket.bucket.clone();
@@ -249,27 +444,117 @@ impl S3Storage {
             key,
             bucket: (*bucket).clone(),
             range,
+            request_payer: self.config.request_payer(),
             ..Default::default()
         };
-        Box::new(
-            self.client
-                .get_object(req)
-                .map(move |future| match future {
-                    Ok(out) => out.body.unwrap(),
-                    Err(RusotoError::Service(GetObjectError::NoSuchKey(key))) => {
-                        ByteStream::new(error_stream(io::Error::new(
-                            io::ErrorKind::NotFound,
-                            format!("no key {} at bucket {}", key, *bucket),
-                        )))
+        let read = async move {
+            match self.client.get_object(req.clone()).await {
+                Ok(out) => out.body.unwrap(),
+                Err(RusotoError::Service(GetObjectError::NoSuchKey(key))) => {
+                    ByteStream::new(error_stream(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("no key {} at bucket {}", key, *bucket),
+                    )))
+                }
+                // The object was transitioned to an archive storage class
+                // (e.g. by a lifecycle rule) and must be restored before it
+                // can be read again.
+                Err(RusotoError::Service(GetObjectError::InvalidObjectState(_))) => {
+                    if !self.config.restore_archived {
+                        return ByteStream::new(error_stream(archived_object_error(
+                            &req.key, &bucket,
+                        )));
                     }
-                    Err(e) => ByteStream::new(error_stream(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!("failed to get object {}", e),
-                    ))),
+                    match self.restore_and_wait(&req.key).await {
+                        Ok(()) => match self.client.get_object(req).await {
+                            Ok(out) => out.body.unwrap(),
+                            Err(e) => ByteStream::new(error_stream(io::Error::new(
+                                io::ErrorKind::Other,
+                                format!("failed to get object after restore {}", e),
+                            ))),
+                        },
+                        Err(e) => ByteStream::new(error_stream(e)),
+                    }
+                }
+                Err(e) => ByteStream::new(error_stream(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("failed to get object {}", e),
+                ))),
+            }
+        };
+        Box::new(read.flatten_stream().into_async_read())
+    }
+
+    /// Issues a `RestoreObject` request for an archived object and polls
+    /// `HeadObject` until the temporary copy is available, giving up once
+    /// the configured deadline passes. Standard and Bulk restores take
+    /// hours, so the job is requested at the `Expedited` tier.
+    async fn restore_and_wait(&self, key: &str) -> io::Result<()> {
+        let bucket = self.config.bucket.bucket.to_string();
+        info!("object is archived, restore initiated"; "key" => %key);
+        let restore = self
+            .client
+            .restore_object(RestoreObjectRequest {
+                bucket: bucket.clone(),
+                key: key.to_owned(),
+                restore_request: Some(RestoreRequest {
+                    days: Some(RESTORE_KEEP_DAYS),
+                    glacier_job_parameters: Some(GlacierJobParameters {
+                        tier: "Expedited".to_owned(),
+                    }),
+                    ..Default::default()
+                }),
+                request_payer: self.config.request_payer(),
+                ..Default::default()
+            })
+            .await;
+        match restore {
+            Ok(_) => (),
+            // Another reader already brought the copy back; go straight to
+            // the polling below.
+            Err(RusotoError::Service(RestoreObjectError::ObjectAlreadyInActiveTierError(_))) => (),
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("failed to restore object {}: {}", key, e),
+                ));
+            }
+        }
+        let start = Instant::now();
+        loop {
+            let head = self
+                .client
+                .head_object(HeadObjectRequest {
+                    bucket: bucket.clone(),
+                    key: key.to_owned(),
+                    request_payer: self.config.request_payer(),
+                    ..Default::default()
                 })
-                .flatten_stream()
-                .into_async_read(),
-        )
+                .await
+                .map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("failed to head object {}: {}", key, e),
+                    )
+                })?;
+            // `x-amz-restore: ongoing-request="false", ...` marks the copy
+            // as available; `"true"` means the restore is still running.
+            if let Some(restore) = head.restore.as_deref() {
+                if restore.contains("ongoing-request=\"false\"") {
+                    return Ok(());
+                }
+            }
+            if start.saturating_elapsed() >= self.config.restore_deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "restore of object {} did not finish within {:?}",
+                        key, self.config.restore_deadline
+                    ),
+                ));
+            }
+            sleep(RESTORE_POLL_INTERVAL).await;
+        }
     }
 }
 
@@ -287,6 +572,9 @@ struct S3Uploader<'client> {
     storage_class: Option<StringNonEmpty>,
     multi_part_size: usize,
     object_lock_enabled: bool,
+    request_payer: Option<String>,
+    provider: Provider,
+    metadata: Option<HashMap<String, String>>,
 
     upload_id: String,
     parts: Vec<CompletedPart>,
@@ -305,7 +593,13 @@ enum UploadError {
 
 impl<T: 'static + StdError> From<RusotoError<T>> for UploadError {
     fn from(r: RusotoError<T>) -> Self {
-        Self::Rusoto(format!("{}", r))
+        // Keep the server-side request id (if the response carried one) in
+        // the message, so the error left after retries are exhausted can be
+        // handed over to the storage provider's support.
+        match r.request_id() {
+            Some(id) => Self::Rusoto(format!("{} (request id {})", r, id)),
+            None => Self::Rusoto(format!("{}", r)),
+        }
     }
 }
 
@@ -339,6 +633,17 @@ fn get_content_md5(object_lock_enabled: bool, content: &[u8]) -> Option<String>
     })
 }
 
+/// Builds the value of the `x-amz-tagging` header: the metadata pairs as a
+/// URL-encoded query string.
+fn tagging_of(metadata: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<_> = metadata.iter().collect();
+    // Sort for a deterministic header value.
+    pairs.sort();
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    serializer.extend_pairs(pairs);
+    serializer.finish()
+}
+
 /// Specifies the minimum size to use multi-part upload.
 /// AWS S3 requires each part to be at least 5 MiB.
 const MINIMUM_PART_SIZE: usize = 5 * 1024 * 1024;
@@ -357,11 +662,36 @@ impl<'client> S3Uploader<'client> {
             storage_class: config.storage_class.as_ref().cloned(),
             multi_part_size: config.multi_part_size,
             object_lock_enabled: config.object_lock_enabled,
+            request_payer: config.request_payer(),
+            provider: config.provider,
+            metadata: None,
             upload_id: "".to_owned(),
             parts: Vec::new(),
         }
     }
 
+    /// The `Content-MD5` header for an upload of `data`: required when
+    /// object lock is enabled or the provider needs a signed payload, and
+    /// dropped for providers rejecting checksum headers (object lock wins
+    /// when both apply).
+    fn content_md5(&self, data: &[u8]) -> Option<String> {
+        if self.provider.suppress_checksum_headers() && !self.object_lock_enabled {
+            return None;
+        }
+        get_content_md5(
+            self.object_lock_enabled || self.provider.require_signed_payload(),
+            data,
+        )
+    }
+
+    /// Attaches the given map to the uploaded object, both as user metadata
+    /// (`x-amz-meta-*` headers) and as object tags, so bucket lifecycle rules
+    /// can match on it.
+    fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
     /// Executes the upload process.
     async fn run(
         mut self,
@@ -421,6 +751,9 @@ impl<'client> S3Uploader<'client> {
                         .map(|s| s.to_string()),
                     ssekms_key_id: self.sse_kms_key_id.as_ref().map(|s| s.to_string()),
                     storage_class: self.storage_class.as_ref().map(|s| s.to_string()),
+                    metadata: self.metadata.clone(),
+                    tagging: self.metadata.as_ref().map(tagging_of),
+                    request_payer: self.request_payer.clone(),
                     ..Default::default()
                 }),
         )
@@ -450,6 +783,7 @@ impl<'client> S3Uploader<'client> {
                     multipart_upload: Some(CompletedMultipartUpload {
                         parts: Some(self.parts.clone()),
                     }),
+                    request_payer: self.request_payer.clone(),
                     ..Default::default()
                 }),
         )
@@ -469,6 +803,7 @@ impl<'client> S3Uploader<'client> {
                     bucket: self.bucket.clone(),
                     key: self.key.clone(),
                     upload_id: self.upload_id.clone(),
+                    request_payer: self.request_payer.clone(),
                     ..Default::default()
                 }),
         )
@@ -497,8 +832,9 @@ impl<'client> S3Uploader<'client> {
                     upload_id: self.upload_id.clone(),
                     part_number,
                     content_length: Some(data.len() as i64),
-                    content_md5: get_content_md5(self.object_lock_enabled, data),
+                    content_md5: self.content_md5(data),
                     body: Some(data.to_vec().into()),
+                    request_payer: self.request_payer.clone(),
                     ..Default::default()
                 })
                 .await;
@@ -558,8 +894,11 @@ impl<'client> S3Uploader<'client> {
                     ssekms_key_id: self.sse_kms_key_id.as_ref().map(|s| s.to_string()),
                     storage_class: self.storage_class.as_ref().map(|s| s.to_string()),
                     content_length: Some(data.len() as i64),
-                    content_md5: get_content_md5(self.object_lock_enabled, data),
+                    content_md5: self.content_md5(data),
+                    metadata: self.metadata.clone(),
+                    tagging: self.metadata.as_ref().map(tagging_of),
                     body: Some(data.to_vec().into()),
+                    request_payer: self.request_payer.clone(),
                     ..Default::default()
                 })
                 .await;
@@ -598,6 +937,7 @@ impl BlobStorage for S3Storage {
         mut reader: PutResource,
         content_length: u64,
     ) -> io::Result<()> {
+        self.reject_anonymous_write()?;
         let key = self.maybe_prefix_key(name);
         debug!("save file to s3 storage"; "key" => %key);
 
@@ -616,6 +956,48 @@ impl BlobStorage for S3Storage {
         })
     }
 
+    async fn put_with_meta(
+        &self,
+        name: &str,
+        mut reader: PutResource,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.reject_anonymous_write()?;
+        let key = self.maybe_prefix_key(name);
+        debug!("save file to s3 storage"; "key" => %key);
+
+        let uploader = S3Uploader::new(&self.client, &self.config, key).with_metadata(metadata);
+        let result = uploader.run(&mut reader, content_length).await;
+        result.map_err(|e| {
+            let error_code = if let UploadError::Io(ref io_error) = e {
+                io_error.kind()
+            } else {
+                io::ErrorKind::Other
+            };
+            // Keep the message 'failed to put object' here for adapting the
+            // string-matching based retry logic in BR. (See `put` above.)
+            io::Error::new(error_code, format!("failed to put object {}", e))
+        })
+    }
+
+    /// Returns the user metadata (not the tags) of the given object.
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        let key = self.maybe_prefix_key(name);
+        let res = self
+            .client
+            .head_object(HeadObjectRequest {
+                bucket: self.config.bucket.bucket.to_string(),
+                key,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| {
+                io::Error::new(io::ErrorKind::Other, format!("failed to head object {}", e))
+            })?;
+        Ok(res.metadata.unwrap_or_default())
+    }
+
     fn get(&self, name: &str) -> cloud::blob::BlobStream<'_> {
         self.get_range(name, None)
     }
@@ -624,6 +1006,27 @@ impl BlobStorage for S3Storage {
         // inclusive, bytes=0-499 -> [0, 499]
         self.get_range(name, Some(format!("bytes={}-{}", off, off + len - 1)))
     }
+
+    async fn delete(&self, name: &str) -> io::Result<()> {
+        self.reject_anonymous_write()?;
+        let key = self.maybe_prefix_key(name);
+        debug!("delete file from s3 storage"; "key" => %key);
+        self.client
+            .delete_object(DeleteObjectRequest {
+                bucket: self.config.bucket.bucket.to_string(),
+                key: key.clone(),
+                request_payer: self.config.request_payer(),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("failed to delete object {}: {}", key, e),
+                )
+            })
+    }
 }
 
 #[cfg(test)]
@@ -648,6 +1051,20 @@ mod tests {
         assert!(actual.is_none())
     }
 
+    #[test]
+    fn test_s3_tagging_of() {
+        // The `x-amz-tagging` header is a URL-encoded query string with the
+        // pairs in deterministic (sorted) order.
+        let mut metadata = HashMap::new();
+        metadata.insert("tikv-version".to_owned(), "8.2.0 &=%".to_owned());
+        metadata.insert("cluster-id".to_owned(), "6971924".to_owned());
+        assert_eq!(
+            tagging_of(&metadata),
+            "cluster-id=6971924&tikv-version=8.2.0+%26%3D%25"
+        );
+        assert_eq!(tagging_of(&HashMap::new()), "");
+    }
+
     #[test]
     fn test_s3_config() {
         let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
@@ -726,6 +1143,316 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_s3_storage_requester_pays_and_acl_headers() {
+        fn assert_header(req: &SignedRequest, name: &str, value: &str) {
+            let values = req
+                .headers
+                .get(name)
+                .unwrap_or_else(|| panic!("missing header {} on {} request", name, req.method()));
+            assert_eq!(values.len(), 1, "{}", name);
+            assert_eq!(values[0], value.as_bytes(), "{}", name);
+        }
+        fn expect_payer(req: &SignedRequest) {
+            assert_header(req, "x-amz-request-payer", "requester");
+        }
+        fn expect_payer_and_acl(req: &SignedRequest) {
+            expect_payer(req);
+            // The canned ACL applies where S3 accepts it: object creation.
+            assert_header(req, "x-amz-acl", "bucket-owner-full-control");
+        }
+
+        let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+        let bucket = BucketConf::default(bucket_name);
+        let mut config = Config::default(bucket);
+        config.acl = StringNonEmpty::opt("bucket-owner-full-control".to_string());
+        config.multi_part_size = 2;
+
+        // A 6-byte object split into 2-byte parts: 1 create + 3 UploadPart +
+        // 1 CompleteMultipartUpload, then a small PutObject and a GetObject.
+        // Every single one of them must carry the request-payer header.
+        let dispatcher = MultipleMockRequestDispatcher::new(vec![
+            MockRequestDispatcher::with_status(200)
+                .with_body(
+                    r#"<?xml version="1.0" encoding="UTF-8"?>
+                   <root>
+                     <UploadId>1</UploadId>
+                   </root>"#,
+                )
+                .with_request_checker(expect_payer_and_acl),
+            MockRequestDispatcher::with_status(200).with_request_checker(expect_payer),
+            MockRequestDispatcher::with_status(200).with_request_checker(expect_payer),
+            MockRequestDispatcher::with_status(200).with_request_checker(expect_payer),
+            MockRequestDispatcher::with_status(200).with_request_checker(expect_payer),
+            MockRequestDispatcher::with_status(200).with_request_checker(expect_payer_and_acl),
+            MockRequestDispatcher::with_status(200).with_request_checker(expect_payer),
+        ]);
+
+        let credentials_provider =
+            StaticProvider::new_minimal("abc".to_string(), "xyz".to_string());
+        let mut s =
+            S3Storage::new_creds_dispatcher(config, dispatcher, credentials_provider).unwrap();
+        s.set_requester_pays(true);
+
+        let multipart_contents = "567890";
+        s.put(
+            "mykey",
+            PutResource(Box::new(multipart_contents.as_bytes())),
+            multipart_contents.len() as u64,
+        )
+        .await
+        .unwrap();
+
+        let small_contents = "56";
+        s.put(
+            "mykey",
+            PutResource(Box::new(small_contents.as_bytes())),
+            small_contents.len() as u64,
+        )
+        .await
+        .unwrap();
+
+        let mut reader = s.get("mykey");
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage_provider_quirks() {
+        async fn put_with(provider: Provider, object_lock: bool, checker: fn(&SignedRequest)) {
+            let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+            let mut bucket = BucketConf::default(bucket_name);
+            bucket.region = StringNonEmpty::opt("ap-southeast-2".to_string());
+            let mut config = Config::default(bucket);
+            config.object_lock_enabled = object_lock;
+            let dispatcher =
+                MockRequestDispatcher::with_status(200).with_request_checker(checker);
+            let credentials_provider =
+                StaticProvider::new_minimal("abc".to_string(), "xyz".to_string());
+            let mut s =
+                S3Storage::new_creds_dispatcher(config, dispatcher, credentials_provider).unwrap();
+            s.set_provider(provider);
+            let contents = "56";
+            s.put(
+                "mykey",
+                PutResource(Box::new(contents.as_bytes())),
+                contents.len() as u64,
+            )
+            .await
+            .unwrap();
+        }
+
+        // AWS keeps virtual-host addressing and sends no Content-MD5 unless
+        // object lock asks for one.
+        put_with(Provider::Aws, false, |req| {
+            assert_eq!(req.hostname(), "mybucket.s3.ap-southeast-2.amazonaws.com");
+            assert!(!req.headers.contains_key("content-md5"));
+        })
+        .await;
+
+        // OSS forces path-style addressing and signs the payload with a
+        // Content-MD5 header.
+        put_with(Provider::Oss, false, |req| {
+            assert_eq!(req.hostname(), "s3.ap-southeast-2.amazonaws.com");
+            assert_eq!(req.path(), "/mybucket/mykey");
+            assert!(req.headers.contains_key("content-md5"));
+        })
+        .await;
+
+        // MinIO only needs path-style addressing.
+        put_with(Provider::Minio, false, |req| {
+            assert_eq!(req.path(), "/mybucket/mykey");
+            assert!(!req.headers.contains_key("content-md5"));
+        })
+        .await;
+
+        // Ceph drops the checksum header on top of path-style addressing...
+        put_with(Provider::Ceph, false, |req| {
+            assert_eq!(req.path(), "/mybucket/mykey");
+            assert!(!req.headers.contains_key("content-md5"));
+        })
+        .await;
+
+        // ...unless object lock demands it.
+        put_with(Provider::Ceph, true, |req| {
+            assert!(req.headers.contains_key("content-md5"));
+        })
+        .await;
+
+        // The hint strings map onto the providers; unknown ones are refused.
+        for (hint, provider) in [
+            ("aws", Provider::Aws),
+            ("oss", Provider::Oss),
+            ("minio", Provider::Minio),
+            ("ceph", Provider::Ceph),
+        ] {
+            assert_eq!(hint.parse::<Provider>().unwrap(), provider);
+        }
+        "s2".parse::<Provider>().unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage_anonymous() {
+        let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+        let mut bucket = BucketConf::default(bucket_name);
+        bucket.region = StringNonEmpty::opt("ap-southeast-2".to_string());
+        let mut config = Config::default(bucket);
+        config.set_anonymous(true);
+        let dispatcher =
+            MockRequestDispatcher::with_status(200).with_request_checker(|req: &SignedRequest| {
+                // Unsigned requests must not carry any credentials.
+                assert!(!req.headers.contains_key("authorization"));
+                assert!(!req.headers.contains_key("x-amz-security-token"));
+                assert_eq!(req.method(), "GET");
+            });
+        let s = S3Storage::with_request_dispatcher(config, dispatcher).unwrap();
+
+        let mut reader = s.get("mykey");
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+
+        // Writes fail before anything is sent: S3 never accepts unsigned
+        // writes.
+        let contents = "56";
+        let err = s
+            .put(
+                "mykey",
+                PutResource(Box::new(contents.as_bytes())),
+                contents.len() as u64,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(
+            err.to_string().contains("writes require credentials"),
+            "{}",
+            err
+        );
+    }
+
+    // The error S3 answers with when a read hits an object transitioned to
+    // an archive storage class.
+    const INVALID_OBJECT_STATE_BODY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Error>
+          <Code>InvalidObjectState</Code>
+          <Message>The operation is not valid for the object's storage class</Message>
+        </Error>"#;
+
+    #[tokio::test]
+    async fn test_s3_storage_archived_object() {
+        let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+        let bucket = BucketConf::default(bucket_name);
+        let config = Config::default(bucket);
+        let dispatcher =
+            MockRequestDispatcher::with_status(403).with_body(INVALID_OBJECT_STATE_BODY);
+        let credentials_provider =
+            StaticProvider::new_minimal("abc".to_string(), "xyz".to_string());
+        let s = S3Storage::new_creds_dispatcher(config, dispatcher, credentials_provider).unwrap();
+
+        // Without the flag, the read fails with the dedicated error and a
+        // hint that a restore is required.
+        let mut reader = s.get("mykey");
+        let mut buf = Vec::new();
+        let err = reader.read_to_end(&mut buf).await.unwrap_err();
+        assert!(is_archived_object(&err), "{}", err);
+        assert!(err.to_string().contains("restore"), "{}", err);
+
+        // Other read failures are not mistaken for the archived state.
+        let other = io::Error::new(io::ErrorKind::Other, "failed to get object");
+        assert!(!is_archived_object(&other));
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage_restore_archived_object() {
+        let magic_contents = "brought back from the glacier";
+        let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+        let bucket = BucketConf::default(bucket_name);
+        let mut config = Config::default(bucket);
+        config.restore_archived = true;
+
+        let dispatcher = MultipleMockRequestDispatcher::new(vec![
+            // The read hits the archived object...
+            MockRequestDispatcher::with_status(403).with_body(INVALID_OBJECT_STATE_BODY),
+            // ...so a restore is requested...
+            MockRequestDispatcher::with_status(200).with_request_checker(
+                |req: &SignedRequest| {
+                    assert_eq!(req.method(), "POST");
+                    assert!(req.params.contains_key("restore"), "{:?}", req.params);
+                },
+            ),
+            // ...the poll sees the temporary copy available...
+            MockRequestDispatcher::with_status(200)
+                .with_request_checker(|req: &SignedRequest| assert_eq!(req.method(), "HEAD"))
+                .with_header(
+                    "x-amz-restore",
+                    "ongoing-request=\"false\", expiry-date=\"Sun, 1 Jan 2034 00:00:00 GMT\"",
+                ),
+            // ...and the read is retried against it.
+            MockRequestDispatcher::with_status(200).with_body(magic_contents),
+        ]);
+        let credentials_provider =
+            StaticProvider::new_minimal("abc".to_string(), "xyz".to_string());
+        let s = S3Storage::new_creds_dispatcher(config, dispatcher, credentials_provider).unwrap();
+
+        let mut reader = s.get("mykey");
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, magic_contents.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_s3_storage_restore_archived_object_timeout() {
+        let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+        let bucket = BucketConf::default(bucket_name);
+        let mut config = Config::default(bucket);
+        config.restore_archived = true;
+        // An elapsed deadline fails the read right after the first poll.
+        config.restore_deadline = Duration::ZERO;
+
+        let dispatcher = MultipleMockRequestDispatcher::new(vec![
+            MockRequestDispatcher::with_status(403).with_body(INVALID_OBJECT_STATE_BODY),
+            MockRequestDispatcher::with_status(200),
+            // The restore is still running when the deadline passes.
+            MockRequestDispatcher::with_status(200)
+                .with_header("x-amz-restore", "ongoing-request=\"true\""),
+        ]);
+        let credentials_provider =
+            StaticProvider::new_minimal("abc".to_string(), "xyz".to_string());
+        let s = S3Storage::new_creds_dispatcher(config, dispatcher, credentials_provider).unwrap();
+
+        let mut reader = s.get("mykey");
+        let mut buf = Vec::new();
+        let err = reader.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut, "{}", err);
+        assert!(err.to_string().contains("did not finish"), "{}", err);
+    }
+
+    #[test]
+    fn test_s3_storage_tls_options() {
+        let bucket_name = StringNonEmpty::required("mybucket".to_string()).unwrap();
+        let bucket = BucketConf::default(bucket_name);
+
+        // A TLS 1.2 floor is expressible through `native-tls`, so the client
+        // builds.
+        let mut config = Config::default(bucket.clone());
+        config.set_tls_options(TlsOptions {
+            min_version: cloud::tls::MinTlsVersion::Tls12,
+            cipher_list: vec![],
+        });
+        S3Storage::new(config).unwrap();
+
+        // `native-tls` has no cipher-list API, so creation fails up front
+        // with a clear message instead of silently ignoring the allow-list.
+        let mut config = Config::default(bucket);
+        config.set_tls_options(TlsOptions {
+            min_version: cloud::tls::MinTlsVersion::Default,
+            cipher_list: vec!["ECDHE-RSA-AES128-GCM-SHA256".to_owned()],
+        });
+        let err = S3Storage::new(config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains("cipher allow-list"), "{}", err);
+    }
+
     #[cfg(feature = "failpoints")]
     #[tokio::test]
     async fn test_s3_storage() {
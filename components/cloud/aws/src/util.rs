@@ -3,8 +3,13 @@
 use std::io::{self, Error, ErrorKind};
 
 use async_trait::async_trait;
-use cloud::metrics;
+use cloud::{
+    metrics,
+    tls::{MinTlsVersion, TlsOptions},
+};
 use futures::{future::TryFutureExt, Future};
+use hyper::client::HttpConnector;
+use hyper_tls::{native_tls::Protocol, HttpsConnector};
 use rusoto_core::{
     region::Region,
     request::{HttpClient, HttpConfig},
@@ -44,6 +49,13 @@ impl RetryError for CredentialsErrorWrapper {
 }
 
 pub fn new_http_client() -> io::Result<HttpClient> {
+    new_http_client_with_tls(&TlsOptions::default())
+}
+
+/// Like [`new_http_client`], but applies the given TLS constraints to the
+/// connector. Fails if `native-tls`, which the client builds on, cannot
+/// express them (it has no TLS 1.3 floor and no cipher-list API).
+pub fn new_http_client_with_tls(tls: &TlsOptions) -> io::Result<HttpClient> {
     let mut http_config = HttpConfig::new();
     // This can greatly improve performance dealing with payloads greater
     // than 100MB. See https://github.com/rusoto/rusoto/pull/1227
@@ -51,12 +63,29 @@ pub fn new_http_client() -> io::Result<HttpClient> {
     http_config.read_buf_size(READ_BUF_SIZE);
     // It is important to explicitly create the client and not use a global
     // See https://github.com/tikv/tikv/issues/7236.
-    HttpClient::new_with_config(http_config).map_err(|e| {
+    Ok(HttpClient::from_connector_with_config(
+        https_connector(tls)?,
+        http_config,
+    ))
+}
+
+/// Builds the TLS-over-TCP connector the hyper-based clients use, with the
+/// given constraints applied.
+pub fn https_connector(tls: &TlsOptions) -> io::Result<HttpsConnector<HttpConnector>> {
+    tls.check_native_tls_expressible()?;
+    let mut builder = hyper_tls::native_tls::TlsConnector::builder();
+    if tls.min_version == MinTlsVersion::Tls12 {
+        builder.min_protocol_version(Some(Protocol::Tlsv12));
+    }
+    let connector = builder.build().map_err(|e| {
         Error::new(
             ErrorKind::Other,
             format!("create aws http client error: {}", e),
         )
-    })
+    })?;
+    let mut http = HttpConnector::new();
+    http.enforce_http(false);
+    Ok(HttpsConnector::from((http, connector.into())))
 }
 
 pub fn get_region(region: &str, endpoint: &str) -> io::Result<Region> {
@@ -1,5 +1,6 @@
 // Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
 use std::{
+    collections::HashMap,
     env, io,
     ops::Deref,
     str::FromStr,
@@ -10,12 +11,14 @@ use async_trait::async_trait;
 use azure_core::{
     auth::{TokenCredential, TokenResponse},
     new_http_client,
+    request_options::Metadata,
 };
 use azure_identity::{ClientSecretCredential, TokenCredentialOptions};
 use azure_storage::{prelude::*, ConnectionString, ConnectionStringBuilder};
 use azure_storage_blobs::{blob::operations::PutBlockBlobBuilder, prelude::*};
-use cloud::blob::{
-    none_to_empty, BlobConfig, BlobStorage, BucketConf, PutResource, StringNonEmpty,
+use cloud::{
+    blob::{none_to_empty, BlobConfig, BlobStorage, BucketConf, PutResource, StringNonEmpty},
+    tls::{self, TlsOptions},
 };
 use futures::TryFutureExt;
 use futures_util::{
@@ -86,6 +89,8 @@ pub struct Config {
     env_shared_key: Option<StringNonEmpty>,
     encryption_scope: Option<StringNonEmpty>,
     encryption_customer: Option<EncryptionCustomer>,
+    anonymous: bool,
+    tls: TlsOptions,
 }
 
 impl std::fmt::Debug for Config {
@@ -117,9 +122,25 @@ impl Config {
             env_shared_key: Self::load_env_shared_key(),
             encryption_scope: None,
             encryption_customer: None,
+            anonymous: false,
+            tls: TlsOptions::default(),
         }
     }
 
+    /// Uses anonymous container access instead of resolving credentials, for
+    /// containers that allow public reads. Writes are rejected up front.
+    pub fn set_anonymous(&mut self, anonymous: bool) {
+        self.anonymous = anonymous;
+    }
+
+    /// TLS constraints to apply when building the client. The Azure SDK
+    /// transport exposes no TLS configuration, so storage creation fails on
+    /// anything beyond the defaults rather than silently ignoring the
+    /// requirement.
+    pub fn set_tls_options(&mut self, tls: TlsOptions) {
+        self.tls = tls;
+    }
+
     fn load_credential_info() -> Option<CredentialInfo> {
         if let (Some(client_id), Some(tenant_id), Some(client_secret)) = (
             env::var(ENV_CLIENT_ID).ok(),
@@ -173,6 +194,8 @@ impl Config {
             env_shared_key: Self::load_env_shared_key(),
             encryption_scope: StringNonEmpty::opt(input.encryption_scope),
             encryption_customer,
+            anonymous: false,
+            tls: TlsOptions::default(),
         })
     }
 
@@ -277,6 +300,7 @@ struct AzureUploader {
     storage_class: Option<AccessTier>,
     encryption_scope: Option<StringNonEmpty>,
     encryption_customer: Option<EncryptionCustomer>,
+    metadata: Option<Metadata>,
 }
 
 impl AzureUploader {
@@ -292,7 +316,18 @@ impl AzureUploader {
             )),
             encryption_scope: config.encryption_scope.clone(),
             encryption_customer: config.encryption_customer.clone(),
+            metadata: None,
+        }
+    }
+
+    /// Attaches the given map to the uploaded blob as blob metadata.
+    fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        let mut converted = Metadata::new();
+        for (key, value) in metadata {
+            converted.insert(key, value);
         }
+        self.metadata = Some(converted);
+        self
     }
 
     fn parse_storage_class(storage_class: String) -> Option<AccessTier> {
@@ -326,6 +361,12 @@ impl AzureUploader {
                 .blob_client(&self.name)
                 .put_block_blob(data.to_vec());
 
+            let builder = if let Some(metadata) = &self.metadata {
+                builder.metadata(metadata.clone())
+            } else {
+                builder
+            };
+
             let builder = self.adjust_put_builder(builder);
 
             builder.await?;
@@ -542,6 +583,19 @@ impl AzureStorage {
 
         let account_name = config.get_account_name()?;
         let bucket = (*config.bucket.bucket).to_owned();
+        if config.anonymous {
+            // Anonymous container access: no credentials are resolved and no
+            // Authorization header is sent.
+            let container_client = Arc::new(
+                BlobServiceClient::new(account_name, StorageCredentials::Anonymous)
+                    .container_client(bucket),
+            );
+            let client_builder = Arc::new(SharedKeyContainerBuilder { container_client });
+            return Ok(AzureStorage {
+                config,
+                client_builder,
+            });
+        }
         // priority:
         //   explicit sas token > explicit shared key > env Azure AD > env shared key
         if let Some(sas_token) = config.sas_token.as_ref() {
@@ -639,6 +693,12 @@ impl AzureStorage {
     }
 
     fn check_config(config: &Config) -> io::Result<()> {
+        if !config.tls.is_default() {
+            return Err(tls::unsupported(
+                "any TLS constraint (the SDK transport is not configurable)",
+                "Azure SDK",
+            ));
+        }
         if config.bucket.storage_class.is_some() {
             if config.encryption_scope.is_some() {
                 return Err(io::Error::new(
@@ -677,6 +737,18 @@ impl AzureStorage {
         key.to_owned()
     }
 
+    /// Anonymous container access is read-only; fail before reading anything
+    /// from the reader.
+    fn reject_anonymous_write(&self) -> io::Result<()> {
+        if self.config.anonymous {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "writes require credentials, but the storage is in anonymous mode",
+            ));
+        }
+        Ok(())
+    }
+
     fn get_range(
         &self,
         name: &str,
@@ -728,6 +800,7 @@ impl BlobStorage for AzureStorage {
         mut reader: PutResource,
         content_length: u64,
     ) -> io::Result<()> {
+        self.reject_anonymous_write()?;
         let name = self.maybe_prefix_key(name);
         debug!("save file to Azure storage"; "key" => %name);
 
@@ -736,6 +809,23 @@ impl BlobStorage for AzureStorage {
         uploader.run(&mut reader, content_length).await
     }
 
+    async fn put_with_meta(
+        &self,
+        name: &str,
+        mut reader: PutResource,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.reject_anonymous_write()?;
+        let name = self.maybe_prefix_key(name);
+        debug!("save file to Azure storage"; "key" => %name);
+
+        let uploader = AzureUploader::new(self.client_builder.clone(), &self.config, name)
+            .with_metadata(metadata);
+
+        uploader.run(&mut reader, content_length).await
+    }
+
     fn get(&self, name: &str) -> cloud::blob::BlobStream<'_> {
         self.get_range(name, None)
     }
@@ -743,6 +833,16 @@ impl BlobStorage for AzureStorage {
     fn get_part(&self, name: &str, off: u64, len: u64) -> cloud::blob::BlobStream<'_> {
         self.get_range(name, Some(off..off + len))
     }
+
+    async fn delete(&self, name: &str) -> io::Result<()> {
+        self.reject_anonymous_write()?;
+        let name = self.maybe_prefix_key(name);
+        debug!("delete file from Azure storage"; "key" => %name);
+        let blob_client = self.client_builder.get_client().await?.blob_client(name);
+        blob_client.delete().await.map(|_| ()).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("failed to delete blob: {}", e))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -768,6 +868,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_azblob_tls_options_rejected() {
+        // The Azure SDK transport exposes no TLS configuration, so any
+        // constraint refuses storage creation instead of being ignored.
+        let container_name = StringNonEmpty::static_str("container");
+        let mut config = Config::default(BucketConf::default(container_name));
+        config.set_tls_options(TlsOptions {
+            min_version: cloud::tls::MinTlsVersion::Tls12,
+            cipher_list: vec![],
+        });
+        let err = AzureStorage::new(config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains("cannot satisfy"), "{}", err);
+    }
+
     #[test]
     fn test_azblob_config() {
         let container_name = StringNonEmpty::static_str("container");
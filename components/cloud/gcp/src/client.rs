@@ -6,17 +6,19 @@ use std::{
     io,
     result::Result as StdResult,
     sync::Arc,
+    time::Duration,
 };
 
+use cloud::tls::{MinTlsVersion, TlsOptions};
 use hyper::{client::HttpConnector, Body, Client, Request, Response, StatusCode};
-use hyper_tls::HttpsConnector;
+use hyper_tls::{native_tls::Protocol, HttpsConnector};
 use serde::Deserialize;
 use tame_oauth::gcp::{
     end_user::EndUserCredentialsInner, service_account::ServiceAccountProviderInner,
     EndUserCredentialsInfo, ServiceAccountInfo, TokenOrRequest, TokenProvider,
     TokenProviderWrapper, TokenProviderWrapperInner,
 };
-use tikv_util::stream::RetryError;
+use tikv_util::stream::{parse_retry_after, RetryError};
 
 // GCS compatible storage
 #[derive(Clone)]
@@ -28,6 +30,16 @@ pub(crate) struct GcpClient {
 impl GcpClient {
     /// Create a new gcp cleint for the given config.
     pub fn with_svc_info(svc_info: Option<ServiceAccountInfo>) -> io::Result<GcpClient> {
+        Self::with_svc_info_and_tls(svc_info, &TlsOptions::default())
+    }
+
+    /// Like [`with_svc_info`](GcpClient::with_svc_info), but applies the
+    /// given TLS constraints to the connector. Fails if `native-tls`, which
+    /// the client builds on, cannot express them.
+    pub fn with_svc_info_and_tls(
+        svc_info: Option<ServiceAccountInfo>,
+        tls: &TlsOptions,
+    ) -> io::Result<GcpClient> {
         let token_provider = if let Some(info) = svc_info {
             let svc_info_provider = ServiceAccountProviderInner::new(info)
                 .or_invalid_input("invalid credentials_blob")?;
@@ -35,21 +47,24 @@ impl GcpClient {
         } else {
             None
         };
-        Ok(Self::with_token_provider(token_provider))
+        Self::with_token_provider(token_provider, tls)
     }
 
-    fn with_token_provider(token_provider: Option<TokenProviderWrapperInner>) -> Self {
-        let client = Client::builder().build(HttpsConnector::new());
-        Self {
+    fn with_token_provider(
+        token_provider: Option<TokenProviderWrapperInner>,
+        tls: &TlsOptions,
+    ) -> io::Result<Self> {
+        let client = Client::builder().build(https_connector(tls)?);
+        Ok(Self {
             token_provider: token_provider.map(|t| Arc::new(TokenProviderWrapper::wrap(t))),
             client,
-        }
+        })
     }
 
     pub fn with_default_provider() -> io::Result<GcpClient> {
         let provider = TokenProviderWrapperInner::get_default_provider()
             .map_err(|e| RequestError::OAuth(e, "default_provider".into()))?;
-        Ok(Self::with_token_provider(provider))
+        Self::with_token_provider(provider, &TlsOptions::default())
     }
 
     pub fn load_from(credentail_path: Option<&str>) -> io::Result<GcpClient> {
@@ -65,9 +80,10 @@ impl GcpClient {
                     let user_credential: EndUserCredentialsInfo =
                         serde_json::from_slice(&json_data)?;
                     let provider = EndUserCredentialsInner::new(user_credential);
-                    return Ok(Self::with_token_provider(Some(
-                        TokenProviderWrapperInner::EndUser(provider),
-                    )));
+                    return Self::with_token_provider(
+                        Some(TokenProviderWrapperInner::EndUser(provider)),
+                        &TlsOptions::default(),
+                    );
                 }
             }
         };
@@ -135,12 +151,32 @@ impl GcpClient {
             .await
             .map_err(|e| RequestError::Hyper(e, uri.clone()))?;
         if !res.status().is_success() {
-            return Err(status_code_error(res.status(), uri));
+            return Err(RequestError::from_response(&res, uri));
         }
         Ok(res)
     }
 }
 
+/// Builds the TLS-over-TCP connector the client uses, with the given
+/// constraints applied. `native-tls` has no TLS 1.3 floor and no cipher-list
+/// API, so those constraints are refused.
+fn https_connector(tls: &TlsOptions) -> io::Result<HttpsConnector<HttpConnector>> {
+    tls.check_native_tls_expressible()?;
+    let mut builder = hyper_tls::native_tls::TlsConnector::builder();
+    if tls.min_version == MinTlsVersion::Tls12 {
+        builder.min_protocol_version(Some(Protocol::Tlsv12));
+    }
+    let connector = builder.build().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("create gcp http client error: {}", e),
+        )
+    })?;
+    let mut http = HttpConnector::new();
+    http.enforce_http(false);
+    Ok(HttpsConnector::from((http, connector.into())))
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 enum CredentialType {
@@ -189,6 +225,35 @@ pub enum RequestError {
     OAuth(tame_oauth::Error, String),
     Gcs(tame_gcs::Error),
     InvalidEndpoint(http::uri::InvalidUri),
+    /// A non-success HTTP response, carrying the throttling metadata
+    /// extracted from its headers.
+    HttpStatus {
+        status: StatusCode,
+        uri: String,
+        retry_after: Option<Duration>,
+        request_id: Option<String>,
+    },
+}
+
+impl RequestError {
+    /// Builds the error for a non-success `response`, keeping the
+    /// `Retry-After` delay and the request id from its headers so retries
+    /// can honor the server's pacing and support tickets have an id.
+    pub fn from_response(response: &Response<Body>, uri: String) -> Self {
+        let headers = response.headers();
+        RequestError::HttpStatus {
+            status: response.status(),
+            uri,
+            retry_after: headers
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_retry_after),
+            request_id: headers
+                .get("x-guploader-uuid")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned),
+        }
+    }
 }
 
 impl Display for RequestError {
@@ -241,6 +306,26 @@ impl From<RequestError> for io::Error {
                 io::ErrorKind::InvalidInput,
                 format!("invalid GCS endpoint URI: {}", e),
             ),
+            RequestError::HttpStatus {
+                status,
+                uri,
+                request_id,
+                ..
+            } => {
+                let id = request_id
+                    .map(|id| format!(" (request id {})", id))
+                    .unwrap_or_default();
+                let fmt = format!("GCS: {}: {}{}", uri, status, id);
+                match status.as_u16() {
+                    401 | 403 => Self::new(io::ErrorKind::PermissionDenied, fmt),
+                    404 => Self::new(io::ErrorKind::NotFound, fmt),
+                    // `Other` keeps server errors in the resumable class
+                    // without reusing `Interrupted`, the kind a cancelled
+                    // transfer carries.
+                    _ if status.is_server_error() => Self::new(io::ErrorKind::Other, fmt),
+                    _ => Self::new(io::ErrorKind::InvalidInput, fmt),
+                }
+            }
         }
     }
 }
@@ -259,8 +344,27 @@ impl RetryError for RequestError {
             Self::OAuth(tame_oauth::Error::HttpStatus(StatusCode::TOO_MANY_REQUESTS), _) => true,
             Self::OAuth(tame_oauth::Error::HttpStatus(StatusCode::REQUEST_TIMEOUT), _) => true,
             Self::OAuth(tame_oauth::Error::HttpStatus(status), _) => status.is_server_error(),
+            Self::HttpStatus { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+                    || *status == StatusCode::REQUEST_TIMEOUT
+                    || status.is_server_error()
+            }
             // Consider everything else not retryable.
             _ => false,
         }
     }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    fn request_id(&self) -> Option<String> {
+        match self {
+            Self::HttpStatus { request_id, .. } => request_id.clone(),
+            _ => None,
+        }
+    }
 }
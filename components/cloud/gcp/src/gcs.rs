@@ -1,17 +1,18 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
-use std::{fmt::Display, io};
+use std::{collections::HashMap, fmt::Display, io};
 
 use async_trait::async_trait;
 use cloud::{
     blob::{none_to_empty, BlobConfig, BlobStorage, BucketConf, PutResource, StringNonEmpty},
     metrics,
+    tls::TlsOptions,
 };
 use futures_util::{
     future::TryFutureExt,
     io::{self as async_io, AsyncRead, Cursor},
     stream::{StreamExt, TryStreamExt},
 };
-use http::HeaderValue;
+use http::{HeaderValue, StatusCode};
 use hyper::{Body, Request, Response};
 pub use kvproto::brpb::Gcs as InputConfig;
 use tame_gcs::{
@@ -32,6 +33,8 @@ use crate::{
 
 const GOOGLE_APIS: &str = "https://www.googleapis.com";
 const HARDCODED_ENDPOINTS_SUFFIX: &[&str] = &["upload/storage/v1/", "storage/v1/"];
+/// The GCS compose API accepts at most 32 source objects per call.
+const MAX_COMPOSE_SOURCES: usize = 32;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -39,6 +42,9 @@ pub struct Config {
     predefined_acl: Option<PredefinedAcl>,
     storage_class: Option<StorageClass>,
     svc_info: Option<ServiceAccountInfo>,
+    compose_threshold: Option<u64>,
+    anonymous: bool,
+    tls: TlsOptions,
 }
 
 impl Config {
@@ -49,9 +55,33 @@ impl Config {
             predefined_acl: None,
             storage_class: None,
             svc_info: None,
+            compose_threshold: None,
+            anonymous: false,
+            tls: TlsOptions::default(),
         }
     }
 
+    /// Objects larger than `threshold` bytes are uploaded as chunk objects
+    /// of `threshold` bytes each and assembled with the compose API, which
+    /// sidesteps resumable-upload flakiness in some regions. `None` (the
+    /// default) keeps the single-request path for everything.
+    pub fn set_compose_threshold(&mut self, threshold: Option<u64>) {
+        self.compose_threshold = threshold;
+    }
+
+    /// Skips credential resolution and attaches no Authorization header, for
+    /// public buckets that allow unauthenticated reads. Writes are rejected
+    /// up front.
+    pub fn set_anonymous(&mut self, anonymous: bool) {
+        self.anonymous = anonymous;
+    }
+
+    /// TLS constraints to apply when building the HTTP client. Storage
+    /// creation fails if the platform TLS stack cannot satisfy them.
+    pub fn set_tls_options(&mut self, tls: TlsOptions) {
+        self.tls = tls;
+    }
+
     pub fn missing_credentials() -> io::Error {
         io::Error::new(io::ErrorKind::InvalidInput, "missing credentials")
     }
@@ -79,6 +109,9 @@ impl Config {
             predefined_acl,
             svc_info,
             storage_class,
+            compose_threshold: None,
+            anonymous: false,
+            tls: TlsOptions::default(),
         })
     }
 }
@@ -141,7 +174,13 @@ impl GcsStorage {
 
     /// Create a new GCS storage for the given config.
     pub fn new(config: Config) -> io::Result<GcsStorage> {
-        let client = GcpClient::with_svc_info(config.svc_info.clone())?;
+        let client = if config.anonymous {
+            // A client without a token provider attaches no Authorization
+            // header, which is what public buckets expect.
+            GcpClient::with_svc_info_and_tls(None, &config.tls)?
+        } else {
+            GcpClient::with_svc_info_and_tls(config.svc_info.clone(), &config.tls)?
+        };
         Ok(GcsStorage { config, client })
     }
 
@@ -280,6 +319,51 @@ impl BlobStorage for GcsStorage {
     }
 
     async fn put(&self, name: &str, reader: PutResource, content_length: u64) -> io::Result<()> {
+        self.put_opt(name, reader, content_length, None).await
+    }
+
+    async fn put_with_meta(
+        &self,
+        name: &str,
+        reader: PutResource,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.put_opt(name, reader, content_length, Some(metadata))
+            .await
+    }
+
+    fn get(&self, name: &str) -> cloud::blob::BlobStream<'_> {
+        self.get_range(name, None)
+    }
+
+    fn get_part(&self, name: &str, off: u64, len: u64) -> cloud::blob::BlobStream<'_> {
+        // inclusive, bytes=0-499 -> [0, 499]
+        self.get_range(name, Some(format!("bytes={}-{}", off, off + len - 1)))
+    }
+
+    async fn delete(&self, name: &str) -> io::Result<()> {
+        let name = self.maybe_prefix_key(name);
+        debug!("delete file from GCS storage"; "key" => %name);
+        self.delete_object(&name).await
+    }
+}
+
+impl GcsStorage {
+    /// Uploads an object, attaching `custom_metadata` to it when given.
+    async fn put_opt(
+        &self,
+        name: &str,
+        reader: PutResource,
+        content_length: u64,
+        custom_metadata: Option<HashMap<String, String>>,
+    ) -> io::Result<()> {
+        if self.config.anonymous {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "writes require credentials, but the storage is in anonymous mode",
+            ));
+        }
         if content_length == 0 {
             // It is probably better to just write the empty file
             // However, currently going forward results in a body write aborted error
@@ -291,14 +375,6 @@ impl BlobStorage for GcsStorage {
 
         let key = self.maybe_prefix_key(name);
         debug!("save file to GCS storage"; "key" => %key);
-        let bucket = BucketName::try_from(self.config.bucket.bucket.to_string())
-            .or_invalid_input(format_args!("invalid bucket {}", self.config.bucket.bucket))?;
-
-        let metadata = Metadata {
-            name: Some(key),
-            storage_class: self.config.storage_class,
-            ..Default::default()
-        };
 
         // FIXME: Switch to upload() API so we don't need to read the entire data into
         // memory in order to retry.
@@ -308,7 +384,48 @@ impl BlobStorage for GcsStorage {
         metrics::CLOUD_REQUEST_HISTOGRAM_VEC
             .with_label_values(&["gcp", "read_local"])
             .observe(begin.saturating_elapsed_secs());
-        let begin = Instant::now_coarse();
+        match self.config.compose_threshold {
+            Some(threshold) if content_length > threshold => {
+                let begin = Instant::now_coarse();
+                let api = GcsComposeApi {
+                    storage: self,
+                    custom_metadata,
+                };
+                compose_upload(&api, &key, &data, threshold.max(1) as usize).await?;
+                metrics::CLOUD_REQUEST_HISTOGRAM_VEC
+                    .with_label_values(&["gcp", "compose"])
+                    .observe(begin.saturating_elapsed_secs());
+            }
+            _ => {
+                let begin = Instant::now_coarse();
+                self.insert_object(key, data, custom_metadata).await?;
+                metrics::CLOUD_REQUEST_HISTOGRAM_VEC
+                    .with_label_values(&["gcp", "insert_multipart"])
+                    .observe(begin.saturating_elapsed_secs());
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads one object with a single multipart insert. `key` is already
+    /// prefixed.
+    async fn insert_object(
+        &self,
+        key: String,
+        data: Vec<u8>,
+        custom_metadata: Option<HashMap<String, String>>,
+    ) -> io::Result<()> {
+        let bucket = BucketName::try_from(self.config.bucket.bucket.to_string())
+            .or_invalid_input(format_args!("invalid bucket {}", self.config.bucket.bucket))?;
+
+        let metadata = Metadata {
+            name: Some(key),
+            storage_class: self.config.storage_class,
+            metadata: custom_metadata,
+            ..Default::default()
+        };
+
+        let content_length = data.len() as u64;
         retry(
             || async {
                 let data = Cursor::new(data.clone());
@@ -329,26 +446,240 @@ impl BlobStorage for GcsStorage {
             "insert_multipart",
         )
         .await?;
-        metrics::CLOUD_REQUEST_HISTOGRAM_VEC
-            .with_label_values(&["gcp", "insert_multipart"])
-            .observe(begin.saturating_elapsed_secs());
-        Ok::<_, io::Error>(())
+        Ok(())
     }
 
-    fn get(&self, name: &str) -> cloud::blob::BlobStream<'_> {
-        self.get_range(name, None)
+    /// Composes `sources` (already prefixed, in order) into `dest`. The
+    /// user-visible metadata and ACL are only attached when `final_object`
+    /// is set, i.e. on the compose producing the requested object.
+    async fn compose_objects(
+        &self,
+        sources: &[String],
+        dest: &str,
+        final_object: bool,
+        custom_metadata: Option<HashMap<String, String>>,
+    ) -> io::Result<()> {
+        let mut url = object_api_url(&self.config.bucket.bucket, dest, Some("compose"))?;
+        if final_object {
+            if let Some(acl) = self.config.predefined_acl {
+                if let Some(acl) = serde_json::to_value(acl)
+                    .or_io_error("serialize predefined_acl")?
+                    .as_str()
+                {
+                    url.query_pairs_mut()
+                        .append_pair("destinationPredefinedAcl", acl);
+                }
+            }
+        }
+        let mut destination = serde_json::Map::new();
+        if let Some(sc) = self.config.storage_class {
+            destination.insert(
+                "storageClass".to_owned(),
+                serde_json::to_value(sc).or_io_error("serialize storage_class")?,
+            );
+        }
+        if final_object {
+            if let Some(meta) = custom_metadata {
+                destination.insert(
+                    "metadata".to_owned(),
+                    serde_json::to_value(meta).or_io_error("serialize metadata")?,
+                );
+            }
+        }
+        let body = serde_json::json!({
+            "sourceObjects": sources
+                .iter()
+                .map(|name| serde_json::json!({ "name": name }))
+                .collect::<Vec<_>>(),
+            "destination": destination,
+        })
+        .to_string();
+        retry(
+            || async {
+                let req = Request::post(url.as_str())
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.clone()))
+                    .map_err(|e| RequestError::Gcs(tame_gcs::Error::Http(e)))?;
+                self.make_request(req, tame_gcs::Scopes::ReadWrite).await
+            },
+            "compose",
+        )
+        .await?;
+        Ok(())
     }
 
-    fn get_part(&self, name: &str, off: u64, len: u64) -> cloud::blob::BlobStream<'_> {
-        // inclusive, bytes=0-499 -> [0, 499]
-        self.get_range(name, Some(format!("bytes={}-{}", off, off + len - 1)))
+    /// Deletes one object (already prefixed). A missing object counts as
+    /// deleted, so cleanup can be retried safely.
+    async fn delete_object(&self, name: &str) -> io::Result<()> {
+        let url = object_api_url(&self.config.bucket.bucket, name, None)?;
+        retry(
+            || async {
+                let req = Request::delete(url.as_str())
+                    .body(Body::empty())
+                    .map_err(|e| RequestError::Gcs(tame_gcs::Error::Http(e)))?;
+                match self.make_request(req, tame_gcs::Scopes::ReadWrite).await {
+                    Ok(_) => Ok(()),
+                    Err(RequestError::HttpStatus { status, .. })
+                        if status == StatusCode::NOT_FOUND =>
+                    {
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
+            },
+            "delete_object",
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Builds the JSON API URL of `object` in `bucket`, percent-encoding the
+/// object name, with an optional trailing verb like `compose`.
+fn object_api_url(bucket: &str, object: &str, verb: Option<&str>) -> io::Result<url::Url> {
+    let mut url = url::Url::parse(GOOGLE_APIS).or_invalid_input("parse GCS base url")?;
+    {
+        let mut segments = url
+            .path_segments_mut()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "GCS base url is opaque"))?;
+        segments.extend(["storage", "v1", "b", bucket, "o", object]);
+        if let Some(verb) = verb {
+            segments.push(verb);
+        }
+    }
+    Ok(url)
+}
+
+/// The storage calls compose-based uploads are built from, split out so the
+/// orchestration can be exercised against a mock in tests.
+#[async_trait]
+trait ComposeApi {
+    /// Uploads one chunk object.
+    async fn upload_chunk(&self, name: &str, data: Vec<u8>) -> io::Result<()>;
+    /// Composes `sources` (in order) into `dest`. `final_object` marks the
+    /// compose producing the requested object.
+    async fn compose(&self, sources: &[String], dest: &str, final_object: bool) -> io::Result<()>;
+    /// Deletes one chunk object.
+    async fn delete(&self, name: &str) -> io::Result<()>;
+}
+
+struct GcsComposeApi<'a> {
+    storage: &'a GcsStorage,
+    custom_metadata: Option<HashMap<String, String>>,
+}
+
+#[async_trait]
+impl ComposeApi for GcsComposeApi<'_> {
+    async fn upload_chunk(&self, name: &str, data: Vec<u8>) -> io::Result<()> {
+        self.storage.insert_object(name.to_owned(), data, None).await
+    }
+
+    async fn compose(&self, sources: &[String], dest: &str, final_object: bool) -> io::Result<()> {
+        let custom_metadata = if final_object {
+            self.custom_metadata.clone()
+        } else {
+            None
+        };
+        self.storage
+            .compose_objects(sources, dest, final_object, custom_metadata)
+            .await
+    }
+
+    async fn delete(&self, name: &str) -> io::Result<()> {
+        self.storage.delete_object(name).await
+    }
+}
+
+fn chunk_object_name(key: &str, level: usize, index: usize) -> String {
+    format!("{}.compose.{:02}.{:08}", key, level, index)
+}
+
+/// Uploads `data` as chunk objects of `chunk_size` bytes each and assembles
+/// them into `key` with compose calls of at most [`MAX_COMPOSE_SOURCES`]
+/// sources, going through intermediate objects when there are more chunks.
+/// On failure every chunk object that can still be deleted is removed
+/// best-effort, and those that could not are named in the returned error.
+async fn compose_upload(
+    api: &dyn ComposeApi,
+    key: &str,
+    data: &[u8],
+    chunk_size: usize,
+) -> io::Result<()> {
+    let mut live = Vec::new();
+    if let Err(e) = try_compose_upload(api, key, data, chunk_size, &mut live).await {
+        let names = live.clone();
+        delete_level(api, &names, &mut live).await;
+        let msg = if live.is_empty() {
+            format!(
+                "compose upload of {} failed (all chunk objects cleaned up): {}",
+                key, e
+            )
+        } else {
+            format!(
+                "compose upload of {} failed, orphan chunk objects remain: [{}]: {}",
+                key,
+                live.join(", "),
+                e
+            )
+        };
+        return Err(io::Error::new(e.kind(), msg));
+    }
+    if !live.is_empty() {
+        warn!("compose upload left orphan chunk objects"; "key" => %key, "objects" => ?live);
+    }
+    Ok(())
+}
+
+async fn try_compose_upload(
+    api: &dyn ComposeApi,
+    key: &str,
+    data: &[u8],
+    chunk_size: usize,
+    live: &mut Vec<String>,
+) -> io::Result<()> {
+    // Level 0: upload the raw chunks.
+    let mut current = Vec::new();
+    for (i, chunk) in data.chunks(chunk_size).enumerate() {
+        let name = chunk_object_name(key, 0, i);
+        api.upload_chunk(&name, chunk.to_vec()).await?;
+        live.push(name.clone());
+        current.push(name);
+    }
+    // Compose up the tree until one call covers everything, then emit the
+    // requested object. The sources of a completed level are deleted
+    // best-effort; stragglers stay in `live` for the caller to report.
+    let mut level = 1;
+    while current.len() > MAX_COMPOSE_SOURCES {
+        let mut next = Vec::new();
+        for (i, group) in current.chunks(MAX_COMPOSE_SOURCES).enumerate() {
+            let dest = chunk_object_name(key, level, i);
+            api.compose(group, &dest, false).await?;
+            live.push(dest.clone());
+            next.push(dest);
+        }
+        delete_level(api, &current, live).await;
+        current = next;
+        level += 1;
+    }
+    api.compose(&current, key, true).await?;
+    delete_level(api, &current, live).await;
+    Ok(())
+}
+
+/// Deletes `names` best-effort, dropping the successfully deleted ones from
+/// `live`.
+async fn delete_level(api: &dyn ComposeApi, names: &[String], live: &mut Vec<String>) {
+    for name in names {
+        if api.delete(name).await.is_ok() {
+            live.retain(|l| l != name);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     extern crate test;
-    use std::task::Poll;
+    use std::{collections::HashSet, sync::Mutex, task::Poll};
 
     use futures_util::AsyncReadExt;
     use matches::assert_matches;
@@ -359,6 +690,29 @@ mod tests {
         "https://www.googleapis.com/storage/v1",
     ];
 
+    #[test]
+    fn test_gcs_tls_options() {
+        // A TLS 1.2 floor is expressible through `native-tls`, so the client
+        // builds.
+        let mut config = Config::default(BucketConf::default(StringNonEmpty::static_str("bucket")));
+        config.set_tls_options(TlsOptions {
+            min_version: cloud::tls::MinTlsVersion::Tls12,
+            cipher_list: vec![],
+        });
+        GcsStorage::new(config).unwrap();
+
+        // `native-tls` has no cipher-list API, so creation fails up front
+        // with a clear message instead of silently ignoring the allow-list.
+        let mut config = Config::default(BucketConf::default(StringNonEmpty::static_str("bucket")));
+        config.set_tls_options(TlsOptions {
+            min_version: cloud::tls::MinTlsVersion::Default,
+            cipher_list: vec!["ECDHE-RSA-AES128-GCM-SHA256".to_owned()],
+        });
+        let err = GcsStorage::new(config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(err.to_string().contains("cipher allow-list"), "{}", err);
+    }
+
     #[test]
     fn test_change_host() {
         let host = StringNonEmpty::static_str("http://localhost:4443");
@@ -441,6 +795,136 @@ mod tests {
         );
     }
 
+    #[derive(Default)]
+    struct MockComposeApi {
+        uploads: Mutex<Vec<String>>,
+        composes: Mutex<Vec<(Vec<String>, String, bool)>>,
+        live: Mutex<HashSet<String>>,
+        fail_upload_at: Option<usize>,
+        fail_deletes: HashSet<String>,
+    }
+
+    #[async_trait]
+    impl ComposeApi for MockComposeApi {
+        async fn upload_chunk(&self, name: &str, _data: Vec<u8>) -> io::Result<()> {
+            let mut uploads = self.uploads.lock().unwrap();
+            if Some(uploads.len()) == self.fail_upload_at {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "injected upload failure",
+                ));
+            }
+            uploads.push(name.to_owned());
+            self.live.lock().unwrap().insert(name.to_owned());
+            Ok(())
+        }
+
+        async fn compose(
+            &self,
+            sources: &[String],
+            dest: &str,
+            final_object: bool,
+        ) -> io::Result<()> {
+            assert!(sources.len() <= MAX_COMPOSE_SOURCES);
+            let mut live = self.live.lock().unwrap();
+            for s in sources {
+                assert!(live.contains(s), "compose of missing source {}", s);
+            }
+            live.insert(dest.to_owned());
+            self.composes
+                .lock()
+                .unwrap()
+                .push((sources.to_vec(), dest.to_owned(), final_object));
+            Ok(())
+        }
+
+        async fn delete(&self, name: &str) -> io::Result<()> {
+            if self.fail_deletes.contains(name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "injected delete failure",
+                ));
+            }
+            self.live.lock().unwrap().remove(name);
+            Ok(())
+        }
+    }
+
+    fn block_on<F: std::future::Future>(f: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(f)
+    }
+
+    #[test]
+    fn test_compose_upload_recursion() {
+        let api = MockComposeApi::default();
+        // 80 bytes in 2-byte chunks -> 40 chunk objects, which exceeds the
+        // 32-source compose limit and forces an intermediate level.
+        let data = vec![42u8; 80];
+        block_on(compose_upload(&api, "key", &data, 2)).unwrap();
+
+        assert_eq!(api.uploads.lock().unwrap().len(), 40);
+        let composes = api.composes.lock().unwrap();
+        assert_eq!(composes.len(), 3);
+        assert_eq!(composes[0].0.len(), 32);
+        assert_eq!(composes[0].1, chunk_object_name("key", 1, 0));
+        assert!(!composes[0].2);
+        assert_eq!(composes[1].0.len(), 8);
+        assert_eq!(composes[1].1, chunk_object_name("key", 1, 1));
+        assert!(!composes[1].2);
+        assert_eq!(
+            composes[2].0,
+            vec![chunk_object_name("key", 1, 0), chunk_object_name("key", 1, 1)]
+        );
+        assert_eq!(composes[2].1, "key");
+        assert!(composes[2].2);
+        // Only the final object remains; every chunk object was deleted.
+        let live = api.live.lock().unwrap();
+        assert_eq!(live.len(), 1);
+        assert!(live.contains("key"));
+    }
+
+    #[test]
+    fn test_compose_upload_cleanup() {
+        let api = MockComposeApi {
+            fail_upload_at: Some(5),
+            ..Default::default()
+        };
+        let data = vec![42u8; 80];
+        let err = block_on(compose_upload(&api, "key", &data, 2)).unwrap_err();
+        assert!(
+            err.to_string().contains("all chunk objects cleaned up"),
+            "{}",
+            err
+        );
+        assert!(api.live.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compose_upload_cleanup_reports_orphans() {
+        let stuck = chunk_object_name("key", 0, 2);
+        let mut fail_deletes = HashSet::new();
+        fail_deletes.insert(stuck.clone());
+        let api = MockComposeApi {
+            fail_upload_at: Some(5),
+            fail_deletes,
+            ..Default::default()
+        };
+        let data = vec![42u8; 80];
+        let err = block_on(compose_upload(&api, "key", &data, 2)).unwrap_err();
+        assert!(
+            err.to_string().contains("orphan chunk objects remain"),
+            "{}",
+            err
+        );
+        assert!(err.to_string().contains(&stuck), "{}", err);
+        let live = api.live.lock().unwrap();
+        assert_eq!(live.len(), 1);
+        assert!(live.contains(&stuck));
+    }
+
     enum ThrottleReadState {
         Spawning,
         Emitting,
@@ -1,6 +1,6 @@
 // Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
 
-use std::{io, marker::Unpin, pin::Pin, task::Poll};
+use std::{collections::HashMap, io, marker::Unpin, pin::Pin, task::Poll};
 
 use async_trait::async_trait;
 use futures_io::AsyncRead;
@@ -45,11 +45,36 @@ pub trait BlobStorage: 'static + Send + Sync {
     /// Write all contents of the read to the given path.
     async fn put(&self, name: &str, reader: PutResource, content_length: u64) -> io::Result<()>;
 
+    /// Like [`BlobStorage::put`], but attaches `metadata` to the stored
+    /// object where the backend supports it (object tags and user metadata on
+    /// S3, custom metadata on GCS, blob metadata on Azure). The default
+    /// implementation drops the metadata and delegates to `put`.
+    async fn put_with_meta(
+        &self,
+        name: &str,
+        reader: PutResource,
+        content_length: u64,
+        _metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.put(name, reader, content_length).await
+    }
+
+    /// Returns the metadata previously attached to the given object, or an
+    /// empty map when the backend keeps none.
+    async fn head(&self, _name: &str) -> io::Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+
     /// Read all contents of the given path.
     fn get(&self, name: &str) -> BlobStream<'_>;
 
     /// Read part of contents of the given path.
     fn get_part(&self, name: &str, off: u64, len: u64) -> BlobStream<'_>;
+
+    /// Deletes the named object. Whether deleting a missing object is an
+    /// error differs between the backends, so callers should only delete
+    /// objects they have put.
+    async fn delete(&self, name: &str) -> io::Result<()>;
 }
 
 impl BlobConfig for dyn BlobStorage {
@@ -73,6 +98,21 @@ impl BlobStorage for Box<dyn BlobStorage> {
         fut.await
     }
 
+    async fn put_with_meta(
+        &self,
+        name: &str,
+        reader: PutResource,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        let fut = (**self).put_with_meta(name, reader, content_length, metadata);
+        fut.await
+    }
+
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        (**self).head(name).await
+    }
+
     fn get(&self, name: &str) -> BlobStream<'_> {
         (**self).get(name)
     }
@@ -80,6 +120,10 @@ impl BlobStorage for Box<dyn BlobStorage> {
     fn get_part(&self, name: &str, off: u64, len: u64) -> BlobStream<'_> {
         (**self).get_part(name, off, len)
     }
+
+    async fn delete(&self, name: &str) -> io::Result<()> {
+        (**self).delete(name).await
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -15,3 +15,6 @@ pub mod blob;
 pub use blob::{none_to_empty, BucketConf, StringNonEmpty};
 
 pub mod metrics;
+
+pub mod tls;
+pub use tls::{MinTlsVersion, TlsOptions};
@@ -0,0 +1,137 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! TLS constraints for the HTTP clients talking to cloud storage.
+//!
+//! Security policies commonly require a minimum TLS version (and sometimes a
+//! cipher allow-list) on every outbound connection. The provider crates build
+//! their clients on different TLS stacks with different capabilities, so the
+//! constraints are expressed here in stack-neutral terms and each backend
+//! either applies them or refuses storage creation with a clear message via
+//! [`unsupported`].
+
+use std::{fmt::Display, io};
+
+/// The lowest TLS protocol version a connection may negotiate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MinTlsVersion {
+    /// Whatever the TLS stack negotiates by default.
+    #[default]
+    Default,
+    Tls12,
+    Tls13,
+}
+
+impl std::str::FromStr for MinTlsVersion {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<MinTlsVersion> {
+        match s {
+            "" | "default" => Ok(MinTlsVersion::Default),
+            "1.2" => Ok(MinTlsVersion::Tls12),
+            "1.3" => Ok(MinTlsVersion::Tls13),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "unknown minimum TLS version {:?}, expected 1.2, 1.3 or default",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// TLS constraints to apply when building a backend's HTTP client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlsOptions {
+    pub min_version: MinTlsVersion,
+    /// OpenSSL-style cipher suite names; empty means the stack default.
+    pub cipher_list: Vec<String>,
+}
+
+impl TlsOptions {
+    /// Whether these options ask for anything beyond the stack defaults.
+    pub fn is_default(&self) -> bool {
+        self.min_version == MinTlsVersion::Default && self.cipher_list.is_empty()
+    }
+
+    /// Checks the constraints are expressible through `native-tls`, which
+    /// the hyper-based clients (S3, GCS) build on: a TLS 1.2 floor is, a
+    /// TLS 1.3 floor and cipher allow-lists are not.
+    pub fn check_native_tls_expressible(&self) -> io::Result<()> {
+        if self.min_version == MinTlsVersion::Tls13 {
+            return Err(unsupported("a minimum version of TLS 1.3", "native-tls"));
+        }
+        if !self.cipher_list.is_empty() {
+            return Err(unsupported(
+                format_args!("the cipher allow-list {:?}", self.cipher_list),
+                "native-tls",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The error storage creation fails with when the platform TLS stack cannot
+/// satisfy a requested constraint.
+pub fn unsupported(constraint: impl Display, stack: impl Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "the platform TLS stack ({}) cannot satisfy {}",
+            stack, constraint
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_min_tls_version() {
+        assert_eq!(
+            "1.2".parse::<MinTlsVersion>().unwrap(),
+            MinTlsVersion::Tls12
+        );
+        assert_eq!(
+            "1.3".parse::<MinTlsVersion>().unwrap(),
+            MinTlsVersion::Tls13
+        );
+        assert_eq!(
+            "default".parse::<MinTlsVersion>().unwrap(),
+            MinTlsVersion::Default
+        );
+        "tls1.2".parse::<MinTlsVersion>().unwrap_err();
+    }
+
+    #[test]
+    fn test_native_tls_expressible() {
+        TlsOptions::default().check_native_tls_expressible().unwrap();
+        TlsOptions {
+            min_version: MinTlsVersion::Tls12,
+            cipher_list: vec![],
+        }
+        .check_native_tls_expressible()
+        .unwrap();
+
+        let err = TlsOptions {
+            min_version: MinTlsVersion::Tls13,
+            cipher_list: vec![],
+        }
+        .check_native_tls_expressible()
+        .unwrap_err();
+        assert!(err.to_string().contains("cannot satisfy"), "{}", err);
+
+        let err = TlsOptions {
+            min_version: MinTlsVersion::Default,
+            cipher_list: vec!["ECDHE-RSA-AES128-GCM-SHA256".to_owned()],
+        }
+        .check_native_tls_expressible()
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("cipher allow-list"),
+            "{}",
+            err
+        );
+    }
+}
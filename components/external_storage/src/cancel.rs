@@ -0,0 +1,252 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Cancellation support for in-flight transfers.
+
+use std::{
+    collections::HashMap,
+    fmt, io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use futures_io::AsyncRead;
+pub use tokio_util::sync::CancellationToken;
+
+use crate::{annotate_url_scheme, ExternalData, ExternalStorage, UnpinReader};
+
+/// The payload carried by the error a cancelled transfer surfaces, so
+/// [`is_cancelled`] can recognize a cancellation by downcasting instead of by
+/// error kind. Kinds are shared with the backends (the GCS client reports
+/// server errors as `Interrupted`, for instance), so kind equality would
+/// misreport ordinary backend failures as cancellations.
+#[derive(Debug)]
+struct TransferCancelled;
+
+impl fmt::Display for TransferCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transfer cancelled")
+    }
+}
+
+impl std::error::Error for TransferCancelled {}
+
+/// The error a cancelled transfer surfaces.
+pub fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, TransferCancelled)
+}
+
+/// Returns whether `e` reports a cancelled transfer.
+pub fn is_cancelled(e: &io::Error) -> bool {
+    e.get_ref()
+        .map_or(false, |inner| inner.is::<TransferCancelled>())
+}
+
+/// Wraps a storage so that in-flight reads and writes stop at the next chunk
+/// boundary once `token` is cancelled, instead of running a multi-GB
+/// transfer to completion after the task which wanted it is gone. Created by
+/// [`create_storage`](crate::create_storage) when
+/// [`BackendConfig::cancellation`](crate::BackendConfig) is set.
+///
+/// Cancellation surfaces as an error from the transfer stream, so a backend
+/// unwinds exactly like on an I/O failure: the local backend never renames
+/// its temporary file into place and the S3 uploader aborts its multipart
+/// upload, leaving no partial final object behind.
+pub struct CancellableStorage<S> {
+    inner: S,
+    token: CancellationToken,
+}
+
+impl<S: ExternalStorage> CancellableStorage<S> {
+    pub fn new(inner: S, token: CancellationToken) -> Self {
+        CancellableStorage { inner, token }
+    }
+
+    fn check(&self) -> io::Result<()> {
+        if self.token.is_cancelled() {
+            Err(cancelled_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn guard<'a>(&self, data: ExternalData<'a>) -> ExternalData<'a> {
+        Box::new(CancelOnPoll {
+            inner: data,
+            token: self.token.clone(),
+        })
+    }
+}
+
+/// Checks the token in front of every chunk delivered by `inner`.
+struct CancelOnPoll<R> {
+    inner: R,
+    token: CancellationToken,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CancelOnPoll<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.token.is_cancelled() {
+            return Poll::Ready(Err(cancelled_error()));
+        }
+        Pin::new(&mut this.inner).poll_read(cx, buf)
+    }
+}
+
+#[async_trait]
+impl<S: ExternalStorage> ExternalStorage for CancellableStorage<S> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn url(&self) -> io::Result<url::Url> {
+        annotate_url_scheme(self.inner.url()?, "cancellable")
+    }
+    async fn write(&self, name: &str, reader: UnpinReader, content_length: u64) -> io::Result<()> {
+        self.check()?;
+        let reader = UnpinReader(Box::new(CancelOnPoll {
+            inner: reader.0,
+            token: self.token.clone(),
+        }));
+        self.inner.write(name, reader, content_length).await
+    }
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.check()?;
+        let reader = UnpinReader(Box::new(CancelOnPoll {
+            inner: reader.0,
+            token: self.token.clone(),
+        }));
+        self.inner
+            .write_with_meta(name, reader, content_length, metadata)
+            .await
+    }
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        self.check()?;
+        self.inner.head(name).await
+    }
+    fn read(&self, name: &str) -> ExternalData<'_> {
+        self.guard(self.inner.read(name))
+    }
+    fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
+        self.guard(self.inner.read_part(name, off, len))
+    }
+    fn support_resumable_read(&self) -> bool {
+        self.inner.support_resumable_read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::AsyncReadExt;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::{create_storage, make_local_backend, BackendConfig};
+
+    /// Delivers `remaining` chunks of filler, cancelling `token` after the
+    /// first one, like a backup task torn down mid-transfer.
+    struct CancellingReader {
+        remaining: usize,
+        token: CancellationToken,
+    }
+
+    impl AsyncRead for CancellingReader {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            if this.remaining == 0 {
+                return Poll::Ready(Ok(0));
+            }
+            this.remaining -= 1;
+            this.token.cancel();
+            let n = buf.len().min(4096);
+            buf[..n].fill(b'x');
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_mid_write() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let backend = make_local_backend(temp_dir.path());
+        let token = CancellationToken::new();
+        let config = BackendConfig {
+            cancellation: Some(token.clone()),
+            ..Default::default()
+        };
+        let storage = create_storage(&backend, config).unwrap();
+
+        let reader = CancellingReader {
+            remaining: 1024,
+            token: token.clone(),
+        };
+        let err = storage
+            .write("a.log", UnpinReader(Box::new(reader)), 0)
+            .await
+            .unwrap_err();
+        assert!(is_cancelled(&err), "{}", err);
+        // The transfer stopped before the temporary file was renamed into
+        // place, so no partial final object remains.
+        assert!(!temp_dir.path().join("a.log").exists());
+
+        // Once cancelled, further writes are refused up front.
+        let content: &[u8] = b"late";
+        let err = storage
+            .write("b.log", UnpinReader(Box::new(content)), content.len() as u64)
+            .await
+            .unwrap_err();
+        assert!(is_cancelled(&err), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_mid_read() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let backend = make_local_backend(temp_dir.path());
+
+        // Store an object without any cancellation in play.
+        let storage = create_storage(&backend, Default::default()).unwrap();
+        let content: &[u8] = b"some stored content";
+        storage
+            .write("a.log", UnpinReader(Box::new(content)), content.len() as u64)
+            .await
+            .unwrap();
+
+        let token = CancellationToken::new();
+        let config = BackendConfig {
+            cancellation: Some(token.clone()),
+            ..Default::default()
+        };
+        let storage = create_storage(&backend, config).unwrap();
+        token.cancel();
+        let mut buf = Vec::new();
+        let err = storage
+            .read("a.log")
+            .read_to_end(&mut buf)
+            .await
+            .unwrap_err();
+        assert!(is_cancelled(&err), "{}", err);
+    }
+
+    #[test]
+    fn test_is_cancelled_requires_marker() {
+        assert!(is_cancelled(&cancelled_error()));
+        // Backends report some of their own failures as `Interrupted` too
+        // (the GCS client does for server errors); sharing the kind must not
+        // make them read as cancellations.
+        let e = io::Error::new(io::ErrorKind::Interrupted, "HTTP 503");
+        assert!(!is_cancelled(&e));
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Content-addressed deduplication of identical uploads.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use futures_io::AsyncRead;
+use futures_util::AsyncReadExt;
+use openssl::hash::{Hasher, MessageDigest};
+use rand::Rng;
+
+use crate::{annotate_url_scheme, ExternalData, ExternalStorage, UnpinReader};
+
+/// The prefix every object managed by [`ContentAddressedStorage`] lives
+/// under, so a lifecycle rule on it can expire the leftover staging objects.
+pub const CAS_PREFIX: &str = "cas";
+
+fn content_key(digest: &str) -> String {
+    format!("{}/{}", CAS_PREFIX, digest)
+}
+
+fn index_key(name: &str) -> String {
+    format!("{}/index/{}", CAS_PREFIX, name)
+}
+
+fn staging_key() -> String {
+    let uid: u64 = rand::thread_rng().gen();
+    format!("{}/staging/{:016x}", CAS_PREFIX, uid)
+}
+
+fn hasher_error(stage: &str, err: openssl::error::ErrorStack) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!("openssl hasher {} failed: {}", stage, err),
+    )
+}
+
+/// Wraps a storage so that identical uploads share one stored copy. Created
+/// by [`create_storage`](crate::create_storage) when
+/// [`BackendConfig::content_addressed`](crate::BackendConfig) is set.
+///
+/// `write` streams the data into a staging object while computing its sha256,
+/// then probes for a content object named by the digest under the `cas/`
+/// prefix. Only when the probe misses is the staging object copied into
+/// place; a second identical upload performs no data write beyond the probe.
+/// Either way a small index object records the name → digest mapping, and
+/// reads resolve through it transparently; names without an index entry fall
+/// through to the wrapped storage unchanged.
+///
+/// The trait exposes no delete operation, so staging objects are left behind
+/// under `cas/staging/`; they are small (one per upload of a new content) and
+/// a bucket lifecycle rule on the prefix can expire them.
+pub struct ContentAddressedStorage<S> {
+    inner: S,
+}
+
+impl<S: ExternalStorage> ContentAddressedStorage<S> {
+    pub fn new(inner: S) -> Self {
+        ContentAddressedStorage { inner }
+    }
+
+    /// Returns whether the object exists. Probed through `read` rather than
+    /// `head`, since `head` cannot distinguish a missing object from one
+    /// written without metadata.
+    async fn exists(&self, name: &str) -> io::Result<bool> {
+        let mut probe = [0u8; 1];
+        match self.inner.read(name).read(&mut probe).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the digest recorded for `name`, or `None` if the name was
+    /// never written through this wrapper.
+    async fn resolve(&self, name: &str) -> io::Result<Option<String>> {
+        let mut digest = String::new();
+        match self
+            .inner
+            .read(&index_key(name))
+            .read_to_string(&mut digest)
+            .await
+        {
+            Ok(_) => Ok(Some(digest)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stages the data while hashing it, copies it into the content object
+    /// unless an identical one exists already, and returns the digest for the
+    /// index entry.
+    async fn store_content(
+        &self,
+        reader: UnpinReader,
+        content_length: u64,
+    ) -> io::Result<String> {
+        let hasher = Hasher::new(MessageDigest::sha256()).map_err(|e| hasher_error("init", e))?;
+        let hasher = Arc::new(Mutex::new(hasher));
+        let tee = UnpinReader(Box::new(HashingReader {
+            inner: reader.0,
+            hasher: hasher.clone(),
+        }));
+        let staging = staging_key();
+        self.inner.write(&staging, tee, content_length).await?;
+        let digest = {
+            let mut hasher = hasher.lock().unwrap();
+            let bytes = hasher.finish().map_err(|e| hasher_error("finish", e))?;
+            bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+        };
+        let content = content_key(&digest);
+        if !self.exists(&content).await? {
+            // The trait has no server-side copy, so the copy reads the
+            // staging object back through the wrapped storage.
+            let staged = self.inner.read(&staging);
+            self.inner
+                .write(&content, UnpinReader(Box::new(staged)), content_length)
+                .await?;
+        }
+        Ok(digest)
+    }
+}
+
+/// Feeds every chunk delivered by `inner` to the shared hasher.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Arc<Mutex<Hasher>>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for HashingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            Poll::Ready(Ok(n)) => n,
+            other => return other,
+        };
+        this.hasher
+            .lock()
+            .unwrap()
+            .update(&buf[..n])
+            .map_err(|e| hasher_error("update", e))?;
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// A reader which resolves the object to stream from on the first poll, so
+/// the synchronous `read` signature can hide the index lookup.
+struct LazyReader<'a> {
+    state: LazyState<'a>,
+}
+
+enum LazyState<'a> {
+    Pending(Pin<Box<dyn Future<Output = io::Result<ExternalData<'a>>> + Send + 'a>>),
+    Ready(ExternalData<'a>),
+}
+
+impl<'a> LazyReader<'a> {
+    fn new(fut: impl Future<Output = io::Result<ExternalData<'a>>> + Send + 'a) -> Self {
+        LazyReader {
+            state: LazyState::Pending(Box::pin(fut)),
+        }
+    }
+}
+
+impl AsyncRead for LazyReader<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                LazyState::Pending(fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready(Ok(reader)) => this.state = LazyState::Ready(reader),
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                    Poll::Pending => return Poll::Pending,
+                },
+                LazyState::Ready(reader) => return Pin::new(reader).poll_read(cx, buf),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S: ExternalStorage> ExternalStorage for ContentAddressedStorage<S> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn url(&self) -> io::Result<url::Url> {
+        annotate_url_scheme(self.inner.url()?, "cas")
+    }
+    async fn write(&self, name: &str, reader: UnpinReader, content_length: u64) -> io::Result<()> {
+        let digest = self.store_content(reader, content_length).await?;
+        let len = digest.len() as u64;
+        self.inner
+            .write(
+                &index_key(name),
+                UnpinReader(Box::new(futures_util::io::Cursor::new(digest.into_bytes()))),
+                len,
+            )
+            .await
+    }
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        let digest = self.store_content(reader, content_length).await?;
+        let len = digest.len() as u64;
+        // The content object is shared between names, so the metadata lives
+        // on the per-name index object.
+        self.inner
+            .write_with_meta(
+                &index_key(name),
+                UnpinReader(Box::new(futures_util::io::Cursor::new(digest.into_bytes()))),
+                len,
+                metadata,
+            )
+            .await
+    }
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        match self.inner.head(&index_key(name)).await {
+            Ok(meta) if !meta.is_empty() => Ok(meta),
+            // Names which never went through the wrapper keep their own
+            // metadata, if any.
+            Ok(_) => self.inner.head(name).await,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => self.inner.head(name).await,
+            Err(e) => Err(e),
+        }
+    }
+    fn read(&self, name: &str) -> ExternalData<'_> {
+        let name = name.to_owned();
+        Box::new(LazyReader::new(async move {
+            Ok(match self.resolve(&name).await? {
+                Some(digest) => self.inner.read(&content_key(&digest)),
+                None => self.inner.read(&name),
+            })
+        }))
+    }
+    fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
+        let name = name.to_owned();
+        Box::new(LazyReader::new(async move {
+            Ok(match self.resolve(&name).await? {
+                Some(digest) => self.inner.read_part(&content_key(&digest), off, len),
+                None => self.inner.read_part(&name, off, len),
+            })
+        }))
+    }
+    fn support_resumable_read(&self) -> bool {
+        self.inner.support_resumable_read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::{create_storage, make_local_backend, BackendConfig, LocalStorage};
+
+    /// Records the names written through it, so the tests can tell which
+    /// writes an upload performed.
+    struct CountingStorage {
+        inner: LocalStorage,
+        written: Mutex<Vec<String>>,
+    }
+
+    impl CountingStorage {
+        fn new(inner: LocalStorage) -> Self {
+            CountingStorage {
+                inner,
+                written: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn take_written(&self) -> Vec<String> {
+            std::mem::take(&mut *self.written.lock().unwrap())
+        }
+    }
+
+    #[async_trait]
+    impl ExternalStorage for Arc<CountingStorage> {
+        fn name(&self) -> &'static str {
+            self.inner.name()
+        }
+        fn url(&self) -> io::Result<url::Url> {
+            self.inner.url()
+        }
+        async fn write(
+            &self,
+            name: &str,
+            reader: UnpinReader,
+            content_length: u64,
+        ) -> io::Result<()> {
+            self.written.lock().unwrap().push(name.to_owned());
+            self.inner.write(name, reader, content_length).await
+        }
+        async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+            self.inner.head(name).await
+        }
+        fn read(&self, name: &str) -> ExternalData<'_> {
+            self.inner.read(name)
+        }
+        fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
+            self.inner.read_part(name, off, len)
+        }
+    }
+
+    async fn read_to_vec(storage: &dyn ExternalStorage, name: &str) -> Vec<u8> {
+        let mut buf = Vec::new();
+        storage.read(name).read_to_end(&mut buf).await.unwrap();
+        buf
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_dedup() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let counting = Arc::new(CountingStorage::new(
+            LocalStorage::new(temp_dir.path()).unwrap(),
+        ));
+        let storage = ContentAddressedStorage::new(counting.clone());
+
+        let content: &[u8] = b"identical meta object";
+        storage
+            .write("r1/meta", UnpinReader(Box::new(content)), content.len() as u64)
+            .await
+            .unwrap();
+        let written = counting.take_written();
+        // The first upload stages, copies into the content object and
+        // records the index entry.
+        assert_eq!(written.len(), 3, "{:?}", written);
+        let digest_object = written
+            .iter()
+            .find(|n| !n.contains("staging") && !n.contains("index"))
+            .unwrap()
+            .clone();
+
+        // The second identical upload performs no data write beyond the
+        // probe: only the staging object and the index entry are written.
+        storage
+            .write("r2/meta", UnpinReader(Box::new(content)), content.len() as u64)
+            .await
+            .unwrap();
+        let written = counting.take_written();
+        assert_eq!(written.len(), 2, "{:?}", written);
+        assert!(!written.contains(&digest_object), "{:?}", written);
+
+        // Both names resolve to the shared content.
+        assert_eq!(read_to_vec(&storage, "r1/meta").await, content);
+        assert_eq!(read_to_vec(&storage, "r2/meta").await, content);
+
+        // Different content gets its own content object.
+        let other: &[u8] = b"a different meta object";
+        storage
+            .write("r3/meta", UnpinReader(Box::new(other)), other.len() as u64)
+            .await
+            .unwrap();
+        let written = counting.take_written();
+        assert_eq!(written.len(), 3, "{:?}", written);
+        assert_eq!(read_to_vec(&storage, "r3/meta").await, other);
+
+        // Ranged reads resolve through the index as well.
+        let mut buf = Vec::new();
+        storage
+            .read_part("r1/meta", 10, 4)
+            .read_to_end(&mut buf)
+            .await
+            .unwrap();
+        assert_eq!(&buf, b"meta");
+    }
+
+    #[tokio::test]
+    async fn test_content_addressed_passthrough() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let backend = make_local_backend(temp_dir.path());
+
+        // An object written without the option in play...
+        let plain = create_storage(&backend, Default::default()).unwrap();
+        let content: &[u8] = b"written before the option";
+        plain
+            .write("old.log", UnpinReader(Box::new(content)), content.len() as u64)
+            .await
+            .unwrap();
+
+        // ...is still readable through the wrapper, which falls through on a
+        // missing index entry.
+        let config = BackendConfig {
+            content_addressed: true,
+            ..Default::default()
+        };
+        let storage = create_storage(&backend, config).unwrap();
+        assert_eq!(read_to_vec(storage.as_ref(), "old.log").await, content);
+        // Reads of missing objects still report NotFound.
+        let mut buf = Vec::new();
+        let err = storage
+            .read("missing.log")
+            .read_to_end(&mut buf)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound, "{}", err);
+
+        let url = storage.url().unwrap();
+        assert_eq!(url.scheme(), "cas+local");
+    }
+}
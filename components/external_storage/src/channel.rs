@@ -0,0 +1,162 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Feeding a `write` from an async producer through a bounded channel.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Buf, Bytes};
+use futures::{channel::mpsc, Stream};
+use futures_io::AsyncRead;
+
+use crate::UnpinReader;
+
+/// Adapts the receiving half of a bounded channel into an `AsyncRead`, so a
+/// producer can stream an object into [`ExternalStorage::write`] without
+/// buffering the whole payload. The channel bound is the back-pressure: a
+/// producer which outruns the uploader blocks in `send` until a chunk is
+/// consumed, and an uploader which outruns the producer parks in `poll_read`
+/// until the next chunk arrives.
+///
+/// Dropping every sender ends the stream, which the reader reports as EOF;
+/// a producer which gives up early thus looks like a truncated object, and
+/// the backend's `content_length` accounting rejects the write.
+///
+/// [`ExternalStorage::write`]: crate::ExternalStorage::write
+pub struct ChannelReader {
+    rx: mpsc::Receiver<Bytes>,
+    chunk: Bytes,
+}
+
+impl ChannelReader {
+    pub fn new(rx: mpsc::Receiver<Bytes>) -> Self {
+        ChannelReader {
+            rx,
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl AsyncRead for ChannelReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        // Empty chunks must be skipped, not delivered: a zero-length read
+        // means EOF to every consumer.
+        while this.chunk.is_empty() {
+            match Pin::new(&mut this.rx).poll_next(cx) {
+                Poll::Ready(Some(chunk)) => this.chunk = chunk,
+                Poll::Ready(None) => return Poll::Ready(Ok(0)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        let n = this.chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&this.chunk[..n]);
+        this.chunk.advance(n);
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl UnpinReader {
+    /// Wraps the receiving half of a bounded channel, ready to be handed to
+    /// [`ExternalStorage::write`](crate::ExternalStorage::write).
+    pub fn from_channel(rx: mpsc::Receiver<Bytes>) -> Self {
+        UnpinReader(Box::new(ChannelReader::new(rx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::SinkExt;
+    use futures_util::AsyncReadExt;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::{create_storage, make_local_backend, ExternalStorage};
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn test_producer_slower_than_uploader() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let storage = create_storage(&make_local_backend(temp_dir.path()), Default::default())
+            .unwrap();
+
+        let content = payload(64 * 1024);
+        let (mut tx, rx) = mpsc::channel(1);
+        let producer = {
+            let content = content.clone();
+            tokio::spawn(async move {
+                for chunk in content.chunks(4096) {
+                    // The uploader spends most of its time parked in
+                    // `poll_read` waiting for us.
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    tx.send(Bytes::copy_from_slice(chunk)).await.unwrap();
+                    // An empty chunk must not read as EOF.
+                    tx.send(Bytes::new()).await.unwrap();
+                }
+            })
+        };
+
+        storage
+            .write(
+                "a.log",
+                UnpinReader::from_channel(rx),
+                content.len() as u64,
+            )
+            .await
+            .unwrap();
+        producer.await.unwrap();
+
+        let mut stored = Vec::new();
+        storage
+            .read("a.log")
+            .read_to_end(&mut stored)
+            .await
+            .unwrap();
+        assert_eq!(stored, content);
+    }
+
+    #[tokio::test]
+    async fn test_uploader_slower_than_producer() {
+        let content = payload(64 * 1024);
+        let (mut tx, rx) = mpsc::channel(1);
+        let producer = {
+            let content = content.clone();
+            tokio::spawn(async move {
+                for chunk in content.chunks(4096) {
+                    // Back-pressure: this blocks until the reader drains the
+                    // channel, so at no point are more than a couple of
+                    // chunks in flight.
+                    tx.send(Bytes::copy_from_slice(chunk)).await.unwrap();
+                }
+            })
+        };
+
+        // A deliberately slow consumer with a buffer which does not line up
+        // with the chunk size, so chunks are delivered across read calls.
+        let mut reader = ChannelReader::new(rx);
+        let mut received = Vec::new();
+        let mut buf = [0u8; 1000];
+        loop {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            let n = reader.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            received.extend_from_slice(&buf[..n]);
+        }
+        producer.await.unwrap();
+        assert_eq!(received, content);
+    }
+}
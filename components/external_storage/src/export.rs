@@ -1,32 +1,122 @@
 // Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
 
-use std::{io, path::Path, sync::Arc};
+use std::{collections::HashMap, io, path::Path, sync::Arc};
 
 use async_trait::async_trait;
-pub use aws::{Config as S3Config, S3Storage};
+pub use aws::{is_archived_object, Config as S3Config, S3Storage};
 pub use azure::{AzureStorage, Config as AzureConfig};
 use cloud::blob::{BlobStorage, PutResource};
 use encryption::DataKeyManager;
-use gcp::GcsStorage;
+use futures_util::AsyncReadExt;
+use gcp::{Config as GcsConfig, GcsStorage};
 use kvproto::brpb::{
     AzureBlobStorage, Gcs, Noop, StorageBackend, StorageBackend_oneof_backend as Backend, S3,
 };
-use tikv_util::time::{Instant, Limiter};
+use rand::Rng;
+use tikv_util::{
+    stream::block_on_external_io,
+    time::{Instant, Limiter},
+};
 
 use crate::{
-    compression_reader_dispatcher, encrypt_wrap_reader, read_external_storage_into_file,
-    record_storage_create, BackendConfig, ExternalData, ExternalStorage, HdfsStorage, LocalStorage,
-    NoopStorage, RestoreConfig, UnpinReader,
+    annotate_url_scheme, compression_reader_dispatcher, encrypt_wrap_reader, preflight_error,
+    read_external_storage_into_file, record_storage_create, BackendConfig, CancellableStorage,
+    ExternalData, ExternalStorage, HdfsStorage, LocalStorage, NoopStorage, Permission,
+    RestoreConfig, TlsOptions, UnpinReader, PREFLIGHT_CONTENT, PREFLIGHT_PREFIX,
 };
 
 pub fn create_storage(
     storage_backend: &StorageBackend,
     config: BackendConfig,
 ) -> io::Result<Box<dyn ExternalStorage>> {
-    if let Some(backend) = &storage_backend.backend {
-        create_backend(backend, config)
-    } else {
-        Err(bad_storage_backend(storage_backend))
+    StorageBuilder::new(storage_backend.clone(), config).build()
+}
+
+/// Builds an [`ExternalStorage`] from a [`StorageBackend`] description and a
+/// [`BackendConfig`], composing the optional wrappers in one fixed order so
+/// call sites do not hand-nest them. From outermost to innermost:
+///
+/// 1. tracing (`tracing` feature) — the spans must cover the whole stack;
+/// 2. cancellation ([`CancellableStorage`]) — a cancelled token should stop
+///    an operation before any inner wrapper works on it;
+/// 3. write-once ([`WriteOnceStorage`]);
+/// 4. content addressing ([`ContentAddressedStorage`]) — innermost, so the
+///    outer wrappers guard the logical names rather than the
+///    digest-addressed objects;
+/// 5. the raw backend.
+///
+/// A wrapper whose feature is off in the config is skipped entirely. Each
+/// wrapper annotates the storage URL scheme, so the composed stack can be
+/// read off [`ExternalStorage::url`], e.g.
+/// `cancellable+write-once+cas+local`.
+pub struct StorageBuilder {
+    backend: StorageBackend,
+    config: BackendConfig,
+}
+
+impl StorageBuilder {
+    pub fn new(backend: StorageBackend, config: BackendConfig) -> Self {
+        StorageBuilder { backend, config }
+    }
+
+    /// See [`BackendConfig::content_addressed`].
+    #[must_use]
+    pub fn content_addressed(mut self, enabled: bool) -> Self {
+        self.config.content_addressed = enabled;
+        self
+    }
+
+    /// See [`BackendConfig::overwrite`].
+    #[must_use]
+    pub fn overwrite(mut self, enabled: bool) -> Self {
+        self.config.overwrite = enabled;
+        self
+    }
+
+    /// See [`BackendConfig::cancellation`].
+    #[must_use]
+    pub fn cancellation(mut self, token: crate::CancellationToken) -> Self {
+        self.config.cancellation = Some(token);
+        self
+    }
+
+    /// See [`BackendConfig::preflight`].
+    #[must_use]
+    pub fn preflight(mut self, enabled: bool) -> Self {
+        self.config.preflight = enabled;
+        self
+    }
+
+    pub fn build(self) -> io::Result<Box<dyn ExternalStorage>> {
+        let StorageBuilder { backend, config } = self;
+        let backend = match &backend.backend {
+            Some(backend) => backend,
+            None => return Err(bad_storage_backend(&backend)),
+        };
+        let preflight = config.preflight;
+        let content_addressed = config.content_addressed;
+        let overwrite = config.overwrite;
+        let cancellation = config.cancellation.clone();
+        let mut storage = create_backend(backend, config)?;
+        if content_addressed {
+            storage = Box::new(crate::ContentAddressedStorage::new(storage));
+        }
+        if !overwrite {
+            storage = Box::new(WriteOnceStorage::new(storage));
+        }
+        if let Some(token) = cancellation {
+            storage = Box::new(CancellableStorage::new(storage, token));
+        }
+        #[cfg(feature = "tracing")]
+        {
+            storage = Box::new(crate::TracingStorage::new(storage));
+        }
+        if preflight {
+            block_on_external_io(
+                storage.check_permissions(&[Permission::Read, Permission::Write]),
+            )?;
+        }
+        Ok(storage)
     }
 }
 
@@ -49,6 +139,15 @@ fn blob_store<Blob: BlobStorage>(store: Blob) -> Box<dyn ExternalStorage> {
     Box::new(BlobStore::new(store)) as Box<dyn ExternalStorage>
 }
 
+/// The TLS constraints the cloud backends should apply, from the flat
+/// `BackendConfig` fields.
+fn tls_options(config: &BackendConfig) -> TlsOptions {
+    TlsOptions {
+        min_version: config.min_tls_version,
+        cipher_list: config.tls_ciphers.clone(),
+    }
+}
+
 fn create_backend(
     backend: &Backend,
     backend_config: BackendConfig,
@@ -64,12 +163,30 @@ fn create_backend(
         }
         Backend::Noop(_) => Box::<NoopStorage>::default() as Box<dyn ExternalStorage>,
         Backend::S3(config) => {
-            let mut s = S3Storage::from_input(config.clone())?;
+            let mut conf = S3Config::from_input(config.clone())?;
+            conf.set_anonymous(backend_config.anonymous);
+            conf.set_tls_options(tls_options(&backend_config));
+            let mut s = S3Storage::new(conf)?;
             s.set_multi_part_size(backend_config.s3_multi_part_size);
+            s.set_requester_pays(backend_config.s3_requester_pays);
+            s.set_restore_archived(backend_config.s3_restore_archived);
+            if !backend_config.s3_provider.is_empty() {
+                s.set_provider(backend_config.s3_provider.parse()?);
+            }
             blob_store(s)
         }
-        Backend::Gcs(config) => blob_store(GcsStorage::from_input(config.clone())?),
-        Backend::AzureBlobStorage(config) => blob_store(AzureStorage::from_input(config.clone())?),
+        Backend::Gcs(config) => {
+            let mut conf = GcsConfig::from_input(config.clone())?;
+            conf.set_anonymous(backend_config.anonymous);
+            conf.set_tls_options(tls_options(&backend_config));
+            blob_store(GcsStorage::new(conf)?)
+        }
+        Backend::AzureBlobStorage(config) => {
+            let mut conf = AzureConfig::from_input(config.clone())?;
+            conf.set_anonymous(backend_config.anonymous);
+            conf.set_tls_options(tls_options(&backend_config));
+            blob_store(AzureStorage::new(conf)?)
+        }
         Backend::CloudDynamic(dyn_backend) => {
             // CloudDynamic backend is no longer supported.
             return Err(bad_backend(Backend::CloudDynamic(dyn_backend.clone())));
@@ -127,6 +244,16 @@ impl<Blob: BlobStorage> BlobStore<Blob> {
     pub fn new(inner: Blob) -> Self {
         BlobStore(inner)
     }
+
+    async fn put_probe(&self, probe: &str) -> io::Result<()> {
+        self.0
+            .put(
+                probe,
+                PutResource(Box::new(PREFLIGHT_CONTENT)),
+                PREFLIGHT_CONTENT.len() as u64,
+            )
+            .await
+    }
 }
 
 impl<Blob: BlobStorage> std::ops::Deref for BlobStore<Blob> {
@@ -147,17 +274,37 @@ impl<S: ExternalStorage> ExternalStorage for EncryptedExternalStorage<S> {
         self.storage.name()
     }
     fn url(&self) -> io::Result<url::Url> {
-        self.storage.url()
+        annotate_url_scheme(self.storage.url()?, "encrypted")
     }
     async fn write(&self, name: &str, reader: UnpinReader, content_length: u64) -> io::Result<()> {
         self.storage.write(name, reader, content_length).await
     }
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.storage
+            .write_with_meta(name, reader, content_length, metadata)
+            .await
+    }
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        self.storage.head(name).await
+    }
     fn read(&self, name: &str) -> ExternalData<'_> {
         self.storage.read(name)
     }
     fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
         self.storage.read_part(name, off, len)
     }
+    fn support_resumable_read(&self) -> bool {
+        self.storage.support_resumable_read()
+    }
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        self.storage.check_permissions(required).await
+    }
     async fn restore(
         &self,
         storage_name: &str,
@@ -212,6 +359,22 @@ impl<Blob: BlobStorage> ExternalStorage for BlobStore<Blob> {
             .await
     }
 
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        (**self)
+            .put_with_meta(name, PutResource(reader.0), content_length, metadata)
+            .await
+    }
+
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        (**self).head(name).await
+    }
+
     fn read(&self, name: &str) -> ExternalData<'_> {
         (**self).get(name)
     }
@@ -219,6 +382,161 @@ impl<Blob: BlobStorage> ExternalStorage for BlobStore<Blob> {
     fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
         (**self).get_part(name, off, len)
     }
+
+    /// Unlike the default implementation, `Delete` can be probed through the
+    /// blob API, and the probe object is deleted afterwards instead of being
+    /// left behind. `List` remains unsupported: the blob interface exposes
+    /// no list operation.
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        let uid: u64 = rand::thread_rng().gen();
+        let probe = format!("{}/probe-{:016x}", PREFLIGHT_PREFIX, uid);
+        let mut written = false;
+        let mut result = Ok(());
+        for perm in required {
+            let res = async {
+                match perm {
+                    Permission::Write => {
+                        self.put_probe(&probe).await?;
+                        written = true;
+                    }
+                    Permission::Read => {
+                        if !written {
+                            self.put_probe(&probe).await?;
+                            written = true;
+                        }
+                        let mut content = Vec::new();
+                        (**self).get(&probe).read_to_end(&mut content).await?;
+                        if content != PREFLIGHT_CONTENT {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "probe content mismatch",
+                            ));
+                        }
+                    }
+                    Permission::Delete => {
+                        if !written {
+                            self.put_probe(&probe).await?;
+                        }
+                        (**self).delete(&probe).await?;
+                        written = false;
+                    }
+                    Permission::List => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Unsupported,
+                            format!(
+                                "preflight List check is not supported by {} storage",
+                                self.name()
+                            ),
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            if let Err(e) = res {
+                result = Err(preflight_error(*perm, &probe, &e));
+                break;
+            }
+        }
+        // Clean up the probe object even on failure, so repeated preflights
+        // don't pile objects up under the prefix.
+        if written {
+            let _ = (**self).delete(&probe).await;
+        }
+        result
+    }
+}
+
+/// Maps a backend error reporting an HTTP 412 (a conditional
+/// `If-None-Match: *` put lost the race against a concurrent writer) to
+/// `AlreadyExists`, so callers of [`WriteOnceStorage`] see a single error
+/// kind no matter which side detected the existing object.
+fn map_precondition_failed(e: io::Error, name: &str) -> io::Error {
+    let msg = e.to_string();
+    if msg.contains("PreconditionFailed") || msg.contains("Precondition Failed") {
+        io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("object {} already exists: {}", name, msg),
+        )
+    } else {
+        e
+    }
+}
+
+/// Wraps a storage so that writes never replace an existing object,
+/// guarding finished backups against a misconfigured prefix. Created by
+/// [`create_storage`] when [`BackendConfig::overwrite`] is false.
+///
+/// The guard probes for the object before uploading, so a concurrent writer
+/// can still slip in between the probe and the upload. Backends whose SDKs
+/// send conditional put headers report that lost race as a precondition
+/// failure, which is mapped to `AlreadyExists` as well; for the rest
+/// (including the local backend) the probe is the only check.
+pub struct WriteOnceStorage<S> {
+    inner: S,
+}
+
+impl<S: ExternalStorage> WriteOnceStorage<S> {
+    pub fn new(inner: S) -> Self {
+        WriteOnceStorage { inner }
+    }
+
+    async fn check_absent(&self, name: &str) -> io::Result<()> {
+        let mut probe = [0u8; 1];
+        // `head` cannot distinguish a missing object from one written
+        // without metadata, so probe through `read`: an empty read still
+        // proves the object exists.
+        match self.inner.read(name).read(&mut probe).await {
+            Ok(_) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("refusing to overwrite existing object {}", name),
+            )),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: ExternalStorage> ExternalStorage for WriteOnceStorage<S> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn url(&self) -> io::Result<url::Url> {
+        annotate_url_scheme(self.inner.url()?, "write-once")
+    }
+    async fn write(&self, name: &str, reader: UnpinReader, content_length: u64) -> io::Result<()> {
+        self.check_absent(name).await?;
+        self.inner
+            .write(name, reader, content_length)
+            .await
+            .map_err(|e| map_precondition_failed(e, name))
+    }
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.check_absent(name).await?;
+        self.inner
+            .write_with_meta(name, reader, content_length, metadata)
+            .await
+            .map_err(|e| map_precondition_failed(e, name))
+    }
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        self.inner.head(name).await
+    }
+    fn read(&self, name: &str) -> ExternalData<'_> {
+        self.inner.read(name)
+    }
+    fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
+        self.inner.read_part(name, off, len)
+    }
+    fn support_resumable_read(&self) -> bool {
+        self.inner.support_resumable_read()
+    }
 }
 
 #[cfg(test)]
@@ -242,10 +560,234 @@ mod tests {
         let backend = make_local_backend(path);
         create_storage(&backend, Default::default()).unwrap();
 
+        // Preflight probes a writable directory successfully.
+        let config = BackendConfig {
+            preflight: true,
+            ..Default::default()
+        };
+        create_storage(&backend, config).unwrap();
+
         let backend = make_noop_backend();
         create_storage(&backend, Default::default()).unwrap();
 
         let backend = StorageBackend::default();
         assert!(create_storage(&backend, Default::default()).is_err());
     }
+
+    #[test]
+    fn test_wrapped_storage_url() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let backend = make_local_backend(temp_dir.path());
+
+        let storage = create_storage(&backend, Default::default()).unwrap();
+        assert_eq!(storage.url().unwrap().scheme(), "local");
+
+        // Each wrapper annotates the scheme of the storage it wraps, so the
+        // logged URL shows the whole stack.
+        let config = BackendConfig {
+            overwrite: false,
+            cancellation: Some(crate::CancellationToken::new()),
+            ..Default::default()
+        };
+        let storage = create_storage(&backend, config).unwrap();
+        let url = storage.url().unwrap();
+        assert_eq!(url.scheme(), "cancellable+write-once+local");
+        assert_eq!(url.path(), temp_dir.path().to_str().unwrap());
+    }
+
+    #[test]
+    fn test_storage_builder_composition() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let backend = make_local_backend(temp_dir.path());
+
+        // Everything on: the URL scheme annotations list the wrappers in
+        // the fixed order, outermost first.
+        let storage = StorageBuilder::new(backend.clone(), Default::default())
+            .content_addressed(true)
+            .overwrite(false)
+            .cancellation(crate::CancellationToken::new())
+            .build()
+            .unwrap();
+        assert_eq!(
+            storage.url().unwrap().scheme(),
+            "cancellable+write-once+cas+local"
+        );
+
+        // Disabling a feature skips exactly its wrapper; the rest keep
+        // their relative order.
+        let storage = StorageBuilder::new(backend.clone(), Default::default())
+            .content_addressed(true)
+            .overwrite(false)
+            .build()
+            .unwrap();
+        assert_eq!(storage.url().unwrap().scheme(), "write-once+cas+local");
+
+        let storage = StorageBuilder::new(backend.clone(), Default::default())
+            .content_addressed(true)
+            .cancellation(crate::CancellationToken::new())
+            .build()
+            .unwrap();
+        assert_eq!(storage.url().unwrap().scheme(), "cancellable+cas+local");
+
+        let storage = StorageBuilder::new(backend.clone(), Default::default())
+            .overwrite(false)
+            .cancellation(crate::CancellationToken::new())
+            .build()
+            .unwrap();
+        assert_eq!(
+            storage.url().unwrap().scheme(),
+            "cancellable+write-once+local"
+        );
+
+        // Nothing on: just the raw backend.
+        let storage = StorageBuilder::new(backend, Default::default())
+            .build()
+            .unwrap();
+        assert_eq!(storage.url().unwrap().scheme(), "local");
+
+        // A backend-less proto still fails cleanly.
+        StorageBuilder::new(StorageBackend::default(), Default::default())
+            .build()
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_write_once() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let backend = make_local_backend(temp_dir.path());
+        let config = BackendConfig {
+            overwrite: false,
+            ..Default::default()
+        };
+        let storage = create_storage(&backend, config).unwrap();
+
+        async fn write(storage: &dyn ExternalStorage, name: &str) -> io::Result<()> {
+            let content: &[u8] = b"write once";
+            storage
+                .write(name, UnpinReader(Box::new(content)), content.len() as u64)
+                .await
+        }
+        write(storage.as_ref(), "a.log").await.unwrap();
+        // The second write over the same name is refused.
+        let err = write(storage.as_ref(), "a.log").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists, "{}", err);
+        // Another name is still writable.
+        write(storage.as_ref(), "b.log").await.unwrap();
+
+        // The default config keeps the overwriting behavior.
+        let storage = create_storage(&backend, Default::default()).unwrap();
+        write(storage.as_ref(), "a.log").await.unwrap();
+    }
+
+    #[test]
+    fn test_map_precondition_failed() {
+        let e = io::Error::new(
+            io::ErrorKind::Other,
+            "failed to put object: PreconditionFailed: at least one of the preconditions \
+             you specified did not hold",
+        );
+        let mapped = map_precondition_failed(e, "a.log");
+        assert_eq!(mapped.kind(), io::ErrorKind::AlreadyExists);
+        assert!(mapped.to_string().contains("a.log"), "{}", mapped);
+
+        // Unrelated errors pass through untouched.
+        let e = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+        assert_eq!(
+            map_precondition_failed(e, "a.log").kind(),
+            io::ErrorKind::TimedOut
+        );
+    }
+
+    /// An in-memory blob storage recording its objects, so the preflight
+    /// probes can be checked for cleaning up after themselves.
+    #[derive(Default)]
+    struct MemBlobStorage {
+        objects: std::sync::Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    #[derive(Clone)]
+    struct MemBlobConfig;
+
+    impl cloud::blob::BlobConfig for MemBlobConfig {
+        fn name(&self) -> &'static str {
+            "mem"
+        }
+
+        fn url(&self) -> io::Result<url::Url> {
+            Ok(url::Url::parse("mem:///").unwrap())
+        }
+    }
+
+    #[async_trait]
+    impl BlobStorage for MemBlobStorage {
+        fn config(&self) -> Box<dyn cloud::blob::BlobConfig> {
+            Box::new(MemBlobConfig)
+        }
+
+        async fn put(
+            &self,
+            name: &str,
+            mut reader: PutResource,
+            _content_length: u64,
+        ) -> io::Result<()> {
+            let mut content = Vec::new();
+            reader.0.read_to_end(&mut content).await?;
+            self.objects
+                .lock()
+                .unwrap()
+                .insert(name.to_owned(), content);
+            Ok(())
+        }
+
+        fn get(&self, name: &str) -> cloud::blob::BlobStream<'_> {
+            use futures_util::TryStreamExt;
+            match self.objects.lock().unwrap().get(name) {
+                Some(content) => Box::new(futures_util::io::Cursor::new(content.clone())),
+                None => Box::new(
+                    tikv_util::stream::error_stream(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("no object {}", name),
+                    ))
+                    .into_async_read(),
+                ),
+            }
+        }
+
+        fn get_part(&self, _name: &str, _off: u64, _len: u64) -> cloud::blob::BlobStream<'_> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, name: &str) -> io::Result<()> {
+            match self.objects.lock().unwrap().remove(name) {
+                Some(_) => Ok(()),
+                None => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no object {}", name),
+                )),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blob_check_permissions_cleans_up() {
+        let storage = BlobStore::new(MemBlobStorage::default());
+        storage
+            .check_permissions(&[Permission::Write, Permission::Read, Permission::Delete])
+            .await
+            .unwrap();
+        assert!(storage.objects.lock().unwrap().is_empty());
+
+        // `Read` alone writes its own probe and still cleans it up.
+        storage.check_permissions(&[Permission::Read]).await.unwrap();
+        assert!(storage.objects.lock().unwrap().is_empty());
+
+        // Listing cannot be probed through the blob interface, and the
+        // failed preflight doesn't leave its probe object behind either.
+        let err = storage
+            .check_permissions(&[Permission::Read, Permission::List])
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        assert!(storage.objects.lock().unwrap().is_empty());
+    }
 }
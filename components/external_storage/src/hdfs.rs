@@ -7,7 +7,7 @@ use tokio::{io as async_io, process::Command};
 use tokio_util::compat::FuturesAsyncReadCompatExt;
 use url::Url;
 
-use crate::{ExternalData, ExternalStorage, UnpinReader};
+use crate::{ExternalData, ExternalStorage, Permission, UnpinReader};
 
 /// Convert `hdfs:///path` to `/path`
 fn try_convert_to_path(url: &Url) -> &str {
@@ -22,6 +22,12 @@ fn try_convert_to_path(url: &Url) -> &str {
 pub struct HdfsConfig {
     pub hadoop_home: String,
     pub linux_user: String,
+    /// Replication factor of the written files, overriding the cluster
+    /// default (`-D dfs.replication=`).
+    pub replication: Option<u16>,
+    /// Octal permission of the written files (e.g. `0o600`), applied with
+    /// `-chmod` after a successful put. The cluster umask applies when unset.
+    pub permission: Option<u32>,
 }
 
 /// A storage to upload file to HDFS
@@ -62,6 +68,57 @@ impl HdfsStorage {
         self.get_hadoop_home()
             .map(|hadoop| format!("{}/bin/hdfs", hadoop))
     }
+
+    /// Builds the `hdfs dfs -put` command line, with the optional `sudo -u`
+    /// prefix and replication override.
+    fn put_command(&self, cmd_path: &str, path: &str) -> Vec<String> {
+        let mut cmd_with_args = Vec::new();
+        if let Some(user) = self.get_linux_user() {
+            cmd_with_args.extend(["sudo".to_owned(), "-u".to_owned(), user]);
+        }
+        cmd_with_args.extend([cmd_path.to_owned(), "dfs".to_owned()]);
+        if let Some(replication) = self.config.replication {
+            cmd_with_args.extend(["-D".to_owned(), format!("dfs.replication={}", replication)]);
+        }
+        cmd_with_args.extend(["-put".to_owned(), "-".to_owned(), path.to_owned()]);
+        cmd_with_args
+    }
+
+    /// Builds the `hdfs dfs -chmod` command line applied after a successful
+    /// put when a permission override is configured.
+    fn chmod_command(&self, cmd_path: &str, path: &str, permission: u32) -> Vec<String> {
+        let mut cmd_with_args = Vec::new();
+        if let Some(user) = self.get_linux_user() {
+            cmd_with_args.extend(["sudo".to_owned(), "-u".to_owned(), user]);
+        }
+        cmd_with_args.extend([
+            cmd_path.to_owned(),
+            "dfs".to_owned(),
+            "-chmod".to_owned(),
+            format!("{:o}", permission),
+            path.to_owned(),
+        ]);
+        cmd_with_args
+    }
+}
+
+/// Maps a non-zero hdfs exit status to an error, logging its output.
+fn check_status(output: &std::process::Output) -> io::Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    error!(
+        "hdfs returned non-zero status";
+        "code" => output.status.code(),
+        "stdout" => stdout.as_ref(),
+        "stderr" => stderr.as_ref(),
+    );
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        format!("hdfs returned non-zero status: {:?}", output.status.code()),
+    ))
 }
 
 const STORAGE_NAME: &str = "hdfs";
@@ -93,15 +150,9 @@ impl ExternalStorage for HdfsStorage {
         let remote_url = self.remote.clone().join(name).unwrap();
         let path = try_convert_to_path(&remote_url);
 
-        let mut cmd_with_args = vec![];
-        let user = self.get_linux_user();
-
-        if let Some(user) = &user {
-            cmd_with_args.extend(["sudo", "-u", user]);
-        }
-        cmd_with_args.extend([&cmd_path, "dfs", "-put", "-", path]);
+        let cmd_with_args = self.put_command(&cmd_path, path);
         info!("calling hdfs"; "cmd" => ?cmd_with_args);
-        let mut hdfs_cmd = Command::new(cmd_with_args[0])
+        let mut hdfs_cmd = Command::new(&cmd_with_args[0])
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -112,23 +163,19 @@ impl ExternalStorage for HdfsStorage {
         async_io::copy(&mut reader.0.compat(), &mut stdin).await?;
 
         let output = hdfs_cmd.wait_with_output().await?;
-        if output.status.success() {
-            debug!("save file to hdfs"; "path" => ?path);
-            Ok(())
-        } else {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            error!(
-                "hdfs returned non-zero status";
-                "code" => output.status.code(),
-                "stdout" => stdout.as_ref(),
-                "stderr" => stderr.as_ref(),
-            );
-            Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("hdfs returned non-zero status: {:?}", output.status.code()),
-            ))
+        check_status(&output)?;
+        debug!("save file to hdfs"; "path" => ?path);
+
+        if let Some(permission) = self.config.permission {
+            let cmd_with_args = self.chmod_command(&cmd_path, path, permission);
+            info!("calling hdfs"; "cmd" => ?cmd_with_args);
+            let output = Command::new(&cmd_with_args[0])
+                .args(&cmd_with_args[1..])
+                .output()
+                .await?;
+            check_status(&output)?;
         }
+        Ok(())
     }
 
     fn read(&self, _name: &str) -> ExternalData<'_> {
@@ -138,6 +185,19 @@ impl ExternalStorage for HdfsStorage {
     fn read_part(&self, _name: &str, _off: u64, _len: u64) -> ExternalData<'_> {
         unimplemented!("currently only HDFS export is implemented")
     }
+
+    fn support_resumable_read(&self) -> bool {
+        false
+    }
+
+    /// HDFS cannot be probed: `read` is unimplemented and `write` forbids
+    /// names with a parent, which the probe prefix requires.
+    async fn check_permissions(&self, _required: &[Permission]) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("preflight checks are not supported by {} storage", STORAGE_NAME),
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -170,6 +230,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_put_and_chmod_command() {
+        let backend = HdfsStorage::new(
+            "hdfs://",
+            HdfsConfig {
+                linux_user: "hadoop".to_string(),
+                replication: Some(2),
+                permission: Some(0o600),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            backend.put_command("/opt/hadoop/bin/hdfs", "/backup/1.sst"),
+            [
+                "sudo",
+                "-u",
+                "hadoop",
+                "/opt/hadoop/bin/hdfs",
+                "dfs",
+                "-D",
+                "dfs.replication=2",
+                "-put",
+                "-",
+                "/backup/1.sst",
+            ]
+        );
+        assert_eq!(
+            backend.chmod_command("/opt/hadoop/bin/hdfs", "/backup/1.sst", 0o600),
+            [
+                "sudo",
+                "-u",
+                "hadoop",
+                "/opt/hadoop/bin/hdfs",
+                "dfs",
+                "-chmod",
+                "600",
+                "/backup/1.sst",
+            ]
+        );
+
+        // Without overrides the command line is unchanged.
+        std::env::remove_var("HADOOP_LINUX_USER");
+        let backend = HdfsStorage::new("hdfs://", HdfsConfig::default()).unwrap();
+        assert_eq!(
+            backend.put_command("/opt/hadoop/bin/hdfs", "/backup/1.sst"),
+            ["/opt/hadoop/bin/hdfs", "dfs", "-put", "-", "/backup/1.sst"]
+        );
+    }
+
     #[test]
     fn test_try_convert_to_path() {
         let url = Url::parse("hdfs:///some/path").unwrap();
@@ -9,6 +9,7 @@ extern crate slog_global;
 extern crate tikv_alloc;
 
 use std::{
+    collections::HashMap,
     io::{self, Write},
     marker::Unpin,
     sync::Arc,
@@ -17,6 +18,7 @@ use std::{
 
 use async_compression::futures::bufread::ZstdDecoder;
 use async_trait::async_trait;
+pub use cloud::tls::{MinTlsVersion, TlsOptions};
 use encryption::{DecrypterReader, FileEncryptionInfo, Iv};
 use file_system::File;
 use futures::io::BufReader;
@@ -24,6 +26,7 @@ use futures_io::AsyncRead;
 use futures_util::AsyncReadExt;
 use kvproto::brpb::CompressionType;
 use openssl::hash::{Hasher, MessageDigest};
+use rand::Rng;
 use tikv_util::{
     future::RescheduleChecker,
     stream::READ_BUF_SIZE,
@@ -31,6 +34,12 @@ use tikv_util::{
 };
 use tokio::time::timeout;
 
+mod cancel;
+pub use cancel::{cancelled_error, is_cancelled, CancellableStorage, CancellationToken};
+mod cas;
+pub use cas::{ContentAddressedStorage, CAS_PREFIX};
+mod channel;
+pub use channel::ChannelReader;
 mod hdfs;
 pub use hdfs::{HdfsConfig, HdfsStorage};
 pub mod local;
@@ -39,6 +48,12 @@ mod noop;
 pub use noop::NoopStorage;
 mod metrics;
 use metrics::EXT_STORAGE_CREATE_HISTOGRAM;
+mod resume;
+pub use resume::{read_part_resumable, read_resumable};
+#[cfg(feature = "tracing")]
+mod trace;
+#[cfg(feature = "tracing")]
+pub use trace::TracingStorage;
 mod export;
 pub use export::*;
 
@@ -56,10 +71,94 @@ pub struct UnpinReader(pub Box<dyn AsyncRead + Unpin + Send>);
 
 pub type ExternalData<'a> = Box<dyn AsyncRead + Unpin + Send + 'a>;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct BackendConfig {
     pub s3_multi_part_size: usize,
+    /// Whether S3 requests should declare `x-amz-request-payer: requester`,
+    /// which requester-pays buckets demand before they accept any operation.
+    pub s3_requester_pays: bool,
+    /// A hint naming the S3-compatible service behind the endpoint (`aws`,
+    /// `oss`, `minio` or `ceph`); non-AWS providers toggle known addressing
+    /// and signing quirks. Empty means `aws`.
+    pub s3_provider: String,
+    /// Whether reads that hit an S3 object archived to Glacier should issue
+    /// a `RestoreObject` request and wait for the temporary copy instead of
+    /// failing. Without this, such reads fail with the error recognized by
+    /// [`is_archived_object`].
+    pub s3_restore_archived: bool,
+    /// Whether the cloud backends (S3, GCS, Azure) should skip credential
+    /// resolution and send unsigned requests, for public buckets that allow
+    /// anonymous reads. Writes are rejected with a clear error.
+    pub anonymous: bool,
+    /// The lowest TLS version the cloud backends may negotiate. Storage
+    /// creation fails with a clear message if the platform TLS stack cannot
+    /// enforce the floor.
+    pub min_tls_version: MinTlsVersion,
+    /// Cipher suites the cloud backends may use (OpenSSL-style names); empty
+    /// means the stack default. Storage creation fails if the platform TLS
+    /// stack cannot restrict its ciphers.
+    pub tls_ciphers: Vec<String>,
     pub hdfs_config: HdfsConfig,
+    /// Whether `create_storage` should verify the credentials by probe
+    /// operations before returning the storage. (See
+    /// [`ExternalStorage::check_permissions`].)
+    pub preflight: bool,
+    /// Whether identical uploads should share one stored copy, addressed by
+    /// their sha256 under the [`CAS_PREFIX`] prefix. Useful when a task
+    /// uploads many identical small objects, like the log backup meta
+    /// objects. (See [`ContentAddressedStorage`].)
+    pub content_addressed: bool,
+    /// Whether `write` may replace an existing object. When false, the
+    /// storage refuses to write over an object which is already present and
+    /// reports `AlreadyExists` instead, guarding prior backups against a
+    /// misconfigured prefix. (See [`WriteOnceStorage`].)
+    pub overwrite: bool,
+    /// When set, in-flight reads and writes stop at the next chunk boundary
+    /// once the token is cancelled, failing with the error recognized by
+    /// [`is_cancelled`]. (See [`CancellableStorage`].)
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        BackendConfig {
+            s3_multi_part_size: 0,
+            s3_requester_pays: false,
+            s3_provider: String::new(),
+            s3_restore_archived: false,
+            anonymous: false,
+            min_tls_version: MinTlsVersion::Default,
+            tls_ciphers: Vec::new(),
+            hdfs_config: HdfsConfig::default(),
+            preflight: false,
+            content_addressed: false,
+            // Overwriting is what every existing caller expects.
+            overwrite: true,
+            cancellation: None,
+        }
+    }
+}
+
+/// The permissions an external storage may grant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    Read,
+    Write,
+    Delete,
+    List,
+}
+
+/// The prefix under which [`ExternalStorage::check_permissions`] performs its
+/// probe operations.
+pub const PREFLIGHT_PREFIX: &str = ".tikv-preflight";
+
+pub(crate) const PREFLIGHT_CONTENT: &[u8] = b"tikv preflight probe";
+
+pub(crate) fn preflight_error(perm: Permission, probe: &str, e: &io::Error) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!("preflight {:?} check failed on {}: {}", perm, probe, e),
+    )
 }
 
 #[derive(Debug, Default)]
@@ -93,6 +192,14 @@ pub fn compression_reader_dispatcher(
     }
 }
 
+/// Prefixes the scheme of `url` with `annotation` plus `+`, so that wrapper
+/// storages can advertise themselves when the URL is logged (e.g.
+/// `write-once+s3://bucket/prefix`).
+pub fn annotate_url_scheme(url: url::Url, annotation: &str) -> io::Result<url::Url> {
+    url::Url::parse(&format!("{}+{}", annotation, url))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
 /// An abstraction of an external storage.
 // TODO: these should all be returning a future (i.e. async fn).
 #[async_trait]
@@ -104,12 +211,108 @@ pub trait ExternalStorage: 'static + Send + Sync {
     /// Write all contents of the read to the given path.
     async fn write(&self, name: &str, reader: UnpinReader, content_length: u64) -> io::Result<()>;
 
+    /// Like [`ExternalStorage::write`], but attaches `metadata` to the stored
+    /// object so bucket lifecycle rules and restores can identify it (e.g.
+    /// cluster id, backup ts and TiKV version). The mapping is backend
+    /// specific: object tags and user metadata on S3, custom metadata on GCS,
+    /// blob metadata on Azure and a sidecar file for local storage. The
+    /// default implementation drops the metadata and delegates to `write`.
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        _metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.write(name, reader, content_length).await
+    }
+
+    /// Returns the metadata attached to the given object by
+    /// [`ExternalStorage::write_with_meta`]. Backends without metadata
+    /// support, and objects written without metadata, yield an empty map.
+    async fn head(&self, _name: &str) -> io::Result<HashMap<String, String>> {
+        Ok(HashMap::new())
+    }
+
     /// Read all contents of the given path.
     fn read(&self, name: &str) -> ExternalData<'_>;
 
     /// Read part of contents of the given path.
     fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_>;
 
+    /// Whether `read_part` is usable, so that an interrupted `read` can be
+    /// resumed from the last delivered offset. (See [`read_resumable`].)
+    fn support_resumable_read(&self) -> bool {
+        true
+    }
+
+    /// Checks whether this storage grants the `required` permissions by
+    /// performing tiny probe operations under the [`PREFLIGHT_PREFIX`] prefix,
+    /// so misconfigured credentials are caught before a long task starts
+    /// writing real data.
+    ///
+    /// The default implementation only probes `Read` and `Write`, since the
+    /// trait exposes no delete or list operations, and it cannot remove the
+    /// probe object afterwards. The shipped backends override this: the blob
+    /// and local storages probe `Delete` (the local one `List` as well) and
+    /// delete their probe object afterwards.
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        let uid: u64 = rand::thread_rng().gen();
+        let probe = format!("{}/probe-{:016x}", PREFLIGHT_PREFIX, uid);
+        let mut written = false;
+        for perm in required {
+            match perm {
+                Permission::Write => {
+                    self.write(
+                        &probe,
+                        UnpinReader(Box::new(PREFLIGHT_CONTENT)),
+                        PREFLIGHT_CONTENT.len() as u64,
+                    )
+                    .await
+                    .map_err(|e| preflight_error(*perm, &probe, &e))?;
+                    written = true;
+                }
+                Permission::Read => {
+                    if !written {
+                        self.write(
+                            &probe,
+                            UnpinReader(Box::new(PREFLIGHT_CONTENT)),
+                            PREFLIGHT_CONTENT.len() as u64,
+                        )
+                        .await
+                        .map_err(|e| preflight_error(*perm, &probe, &e))?;
+                        written = true;
+                    }
+                    let mut content = Vec::new();
+                    self.read(&probe)
+                        .read_to_end(&mut content)
+                        .await
+                        .map_err(|e| preflight_error(*perm, &probe, &e))?;
+                    if content != PREFLIGHT_CONTENT {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "preflight {:?} check failed on {}: probe content mismatch",
+                                perm, probe
+                            ),
+                        ));
+                    }
+                }
+                Permission::Delete | Permission::List => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        format!(
+                            "preflight {:?} check is not supported by {} storage",
+                            perm,
+                            self.name()
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Read from external storage and restore to the given path
     async fn restore(
         &self,
@@ -169,6 +372,22 @@ impl ExternalStorage for Arc<dyn ExternalStorage> {
         (**self).write(name, reader, content_length).await
     }
 
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        (**self)
+            .write_with_meta(name, reader, content_length, metadata)
+            .await
+    }
+
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        (**self).head(name).await
+    }
+
     fn read(&self, name: &str) -> ExternalData<'_> {
         (**self).read(name)
     }
@@ -177,6 +396,14 @@ impl ExternalStorage for Arc<dyn ExternalStorage> {
         (**self).read_part(name, off, len)
     }
 
+    fn support_resumable_read(&self) -> bool {
+        (**self).support_resumable_read()
+    }
+
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        (**self).check_permissions(required).await
+    }
+
     async fn restore(
         &self,
         storage_name: &str,
@@ -211,6 +438,22 @@ impl ExternalStorage for Box<dyn ExternalStorage> {
         self.as_ref().write(name, reader, content_length).await
     }
 
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.as_ref()
+            .write_with_meta(name, reader, content_length, metadata)
+            .await
+    }
+
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        self.as_ref().head(name).await
+    }
+
     fn read(&self, name: &str) -> ExternalData<'_> {
         self.as_ref().read(name)
     }
@@ -219,6 +462,14 @@ impl ExternalStorage for Box<dyn ExternalStorage> {
         self.as_ref().read_part(name, off, len)
     }
 
+    fn support_resumable_read(&self) -> bool {
+        self.as_ref().support_resumable_read()
+    }
+
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        self.as_ref().check_permissions(required).await
+    }
+
     async fn restore(
         &self,
         storage_name: &str,
@@ -1,6 +1,7 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
 use std::{
+    collections::HashMap,
     fs::File as StdFile,
     io::{self, BufReader, Read, Seek},
     path::{Path, PathBuf},
@@ -16,9 +17,33 @@ use tokio::fs::{self, File};
 use tokio_util::compat::FuturesAsyncReadCompatExt;
 
 use super::ExternalStorage;
-use crate::UnpinReader;
+use crate::{preflight_error, Permission, UnpinReader, PREFLIGHT_CONTENT, PREFLIGHT_PREFIX};
 
 const LOCAL_STORAGE_TMP_FILE_SUFFIX: &str = "tmp";
+/// The suffix of the sidecar file which carries the metadata attached to an
+/// object by `write_with_meta`, since plain files have no native metadata.
+const LOCAL_STORAGE_META_FILE_SUFFIX: &str = "meta";
+
+fn meta_name(name: &str) -> String {
+    format!("{}.{}", name, LOCAL_STORAGE_META_FILE_SUFFIX)
+}
+
+/// Serializes the metadata map into the sidecar content. The pairs are
+/// percent-encoded so that keys and values can contain arbitrary characters.
+fn encode_metadata(metadata: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<_> = metadata.iter().collect();
+    // Sort for a deterministic sidecar content.
+    pairs.sort();
+    let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+    serializer.extend_pairs(pairs);
+    serializer.finish()
+}
+
+fn decode_metadata(content: &[u8]) -> HashMap<String, String> {
+    url::form_urlencoded::parse(content)
+        .into_owned()
+        .collect()
+}
 
 /// A storage saves files in local file system.
 #[derive(Clone)]
@@ -117,6 +142,35 @@ impl ExternalStorage for LocalStorage {
         self.base_dir.sync_all().await
     }
 
+    /// Writes the object, then its metadata into a `<name>.meta` sidecar
+    /// file. The sidecar is written last so a reader never sees metadata for
+    /// an object that does not exist yet.
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        self.write(name, reader, content_length).await?;
+        let encoded = encode_metadata(&metadata);
+        self.write(
+            &meta_name(name),
+            UnpinReader(Box::new(encoded.as_bytes())),
+            encoded.len() as u64,
+        )
+        .await
+    }
+
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        match fs::read(self.base.join(meta_name(name))).await {
+            Ok(content) => Ok(decode_metadata(&content)),
+            // Objects written without metadata have no sidecar.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e),
+        }
+    }
+
     fn read(&self, name: &str) -> crate::ExternalData<'_> {
         debug!("read file from local storage";
             "name" => %name, "base" => %self.base.display());
@@ -145,6 +199,62 @@ impl ExternalStorage for LocalStorage {
         let take = reader.take(len);
         Box::new(AllowStdIo::new(take)) as _
     }
+
+    /// Unlike the default implementation, all four permissions can be probed
+    /// on the file system, and the probe file is removed afterwards.
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        let probe_dir = self.base.join(PREFLIGHT_PREFIX);
+        let uid: u64 = rand::thread_rng().gen();
+        let probe = probe_dir.join(format!("probe-{:016x}", uid));
+        let setup = || async {
+            if fs::metadata(&probe).await.is_err() {
+                fs::create_dir_all(&probe_dir).await?;
+                fs::write(&probe, PREFLIGHT_CONTENT).await?;
+            }
+            io::Result::Ok(())
+        };
+        let mut result = Ok(());
+        for perm in required {
+            let res = match perm {
+                Permission::Write => async {
+                    fs::create_dir_all(&probe_dir).await?;
+                    fs::write(&probe, PREFLIGHT_CONTENT).await
+                }
+                .await,
+                Permission::Read => async {
+                    setup().await?;
+                    if fs::read(&probe).await? != PREFLIGHT_CONTENT {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "probe content mismatch",
+                        ));
+                    }
+                    Ok(())
+                }
+                .await,
+                Permission::List => async {
+                    setup().await?;
+                    fs::read_dir(&probe_dir).await?.next_entry().await?;
+                    Ok(())
+                }
+                .await,
+                Permission::Delete => async {
+                    setup().await?;
+                    fs::remove_file(&probe).await
+                }
+                .await,
+            };
+            if let Err(e) = res {
+                result = Err(preflight_error(*perm, &probe.display().to_string(), &e));
+                break;
+            }
+        }
+        // Clean up the probe file even on failure. The directory is only
+        // removed when no other probe is using it.
+        let _ = fs::remove_file(&probe).await;
+        let _ = fs::remove_dir(&probe_dir).await;
+        result
+    }
 }
 
 #[cfg(test)]
@@ -233,6 +343,79 @@ mod tests {
         assert_eq!(url_for(Path::new("/tmp/a")).to_string(), "local:///tmp/a");
     }
 
+    #[tokio::test]
+    async fn test_check_permissions() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let path = temp_dir.path();
+        let ls = LocalStorage::new(path).unwrap();
+
+        ls.check_permissions(&[
+            Permission::Write,
+            Permission::Read,
+            Permission::List,
+            Permission::Delete,
+        ])
+        .await
+        .unwrap();
+        // The probes clean up after themselves.
+        assert!(!path.join(PREFLIGHT_PREFIX).exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_check_permissions_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let path = temp_dir.path();
+        let ls = LocalStorage::new(path).unwrap();
+
+        fs::set_permissions(path, fs::Permissions::from_mode(0o555)).unwrap();
+        let err = ls.check_permissions(&[Permission::Write]).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(
+            err.to_string().contains("preflight Write check failed"),
+            "{}",
+            err
+        );
+        // Restore write permissions so the temp dir can be removed.
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_with_meta() {
+        let temp_dir = Builder::new().tempdir().unwrap();
+        let path = temp_dir.path();
+        let ls = LocalStorage::new(path).unwrap();
+
+        let contents: &[u8] = b"5678";
+        let mut metadata = HashMap::new();
+        metadata.insert("cluster-id".to_owned(), "6971924".to_owned());
+        metadata.insert("backup-ts".to_owned(), "449217000000000000".to_owned());
+        // Values with characters the encoding must escape.
+        metadata.insert("version".to_owned(), "8.2.0-alpha &=%".to_owned());
+        ls.write_with_meta(
+            "a.log",
+            UnpinReader(Box::new(contents)),
+            contents.len() as u64,
+            metadata.clone(),
+        )
+        .await
+        .unwrap();
+
+        // Both the object and its metadata round-trip.
+        let mut read_buff: Vec<u8> = Vec::new();
+        ls.read("a.log").read_to_end(&mut read_buff).await.unwrap();
+        assert_eq!(&read_buff, contents);
+        assert_eq!(ls.head("a.log").await.unwrap(), metadata);
+
+        // Objects written without metadata yield an empty map.
+        ls.write("b.log", UnpinReader(Box::new(contents)), contents.len() as u64)
+            .await
+            .unwrap();
+        assert!(ls.head("b.log").await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_write_existed_file() {
         let temp_dir = Builder::new().tempdir().unwrap();
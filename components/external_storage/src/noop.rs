@@ -5,7 +5,7 @@ use tokio::io;
 use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
 
 use super::ExternalStorage;
-use crate::{ExternalData, UnpinReader};
+use crate::{ExternalData, Permission, UnpinReader};
 
 /// A storage saves files into void.
 /// It is mainly for test use.
@@ -48,6 +48,12 @@ impl ExternalStorage for NoopStorage {
     fn read_part(&self, _name: &str, _off: u64, _len: u64) -> ExternalData<'_> {
         Box::new(io::empty().compat())
     }
+
+    /// The void grants everything. (The default probe would fail, since
+    /// nothing written can be read back.)
+    async fn check_permissions(&self, _required: &[Permission]) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
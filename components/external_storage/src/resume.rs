@@ -0,0 +1,339 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_io::AsyncRead;
+
+use crate::{ExternalData, ExternalStorage};
+
+/// The maximum times a single read stream would be resumed.
+///
+/// Keep this small: every resume issues a fresh request to the backend, and
+/// the backoff of the request itself is handled by the backend already.
+const MAX_RESUME_TIMES: usize = 4;
+
+/// Returns whether an I/O error during a streaming read is worth resuming.
+///
+/// Permission or not-found style failures would fail again at the same
+/// offset, hence they are returned to the caller directly. A cancelled
+/// transfer is surfaced as well: re-opening it would defeat the
+/// cancellation.
+fn is_resumable_err(err: &io::Error) -> bool {
+    !crate::is_cancelled(err)
+        && matches!(
+            err.kind(),
+            io::ErrorKind::Interrupted
+                | io::ErrorKind::TimedOut
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::Other
+        )
+}
+
+/// Reads all contents of the given path, transparently resuming from the
+/// last delivered byte when the underlying stream fails halfway.
+///
+/// On a resumable failure the wrapper reopens the object with
+/// `read_part(name, delivered_offset, remaining)`, so downstream observes one
+/// continuous, byte-exact stream instead of a stream restarting from offset 0.
+pub fn read_resumable<'a>(storage: &'a dyn ExternalStorage, name: &str) -> ExternalData<'a> {
+    Box::new(ResumableRead::new(storage, name, 0, None))
+}
+
+/// Like [`read_resumable`], but reads `len` bytes starting from `off`.
+pub fn read_part_resumable<'a>(
+    storage: &'a dyn ExternalStorage,
+    name: &str,
+    off: u64,
+    len: u64,
+) -> ExternalData<'a> {
+    Box::new(ResumableRead::new(storage, name, off, Some(off + len)))
+}
+
+/// An [`AsyncRead`] counting the bytes it has delivered, so a mid-stream
+/// failure can be resumed with a range read instead of restarting from the
+/// beginning.
+struct ResumableRead<'a> {
+    storage: &'a dyn ExternalStorage,
+    name: String,
+    // Absolute offset of the next byte to deliver to the caller.
+    cursor: u64,
+    // Absolute end offset (exclusive), `None` when reading to EOF.
+    end: Option<u64>,
+    reader: ExternalData<'a>,
+    resume_times: usize,
+}
+
+impl<'a> ResumableRead<'a> {
+    fn new(storage: &'a dyn ExternalStorage, name: &str, off: u64, end: Option<u64>) -> Self {
+        let reader = match end {
+            Some(end) => storage.read_part(name, off, end - off),
+            None if off == 0 => storage.read(name),
+            None => storage.read_part(name, off, u64::MAX - off),
+        };
+        ResumableRead {
+            storage,
+            name: name.to_owned(),
+            cursor: off,
+            end,
+            reader,
+            resume_times: 0,
+        }
+    }
+
+    fn resume(&mut self, cause: io::Error) -> io::Result<()> {
+        // Errors that would not be resumed anyway (and errors past the resume
+        // budget) keep their original kind, so callers still see `NotFound` or
+        // `PermissionDenied` as such on any backend.
+        if !is_resumable_err(&cause) || self.resume_times >= MAX_RESUME_TIMES {
+            return Err(cause);
+        }
+        if !self.storage.support_resumable_read() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "cannot resume read of {} at offset {}: backend {} doesn't support range reads: {}",
+                    self.name,
+                    self.cursor,
+                    self.storage.name(),
+                    cause
+                ),
+            ));
+        }
+        self.resume_times += 1;
+        let remaining = self.end.unwrap_or(u64::MAX) - self.cursor;
+        self.reader = self.storage.read_part(&self.name, self.cursor, remaining);
+        Ok(())
+    }
+}
+
+impl<'a> AsyncRead for ResumableRead<'a> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.reader).poll_read(cx, buf) {
+                Poll::Ready(Ok(n)) => {
+                    this.cursor += n as u64;
+                    return Poll::Ready(Ok(n));
+                }
+                Poll::Ready(Err(e)) => {
+                    // Reopening the stream is synchronous, so retry the read
+                    // in place instead of returning the error.
+                    this.resume(e)?;
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+    use futures::io::Cursor;
+    use futures_util::AsyncReadExt;
+
+    use super::*;
+    use crate::UnpinReader;
+
+    /// A storage over a fixed buffer whose full reads fail after delivering
+    /// `fail_after` bytes. Range reads always succeed.
+    struct FlakyStorage {
+        content: Vec<u8>,
+        fail_after: usize,
+        reads: AtomicUsize,
+    }
+
+    struct FailingRead {
+        inner: Cursor<Vec<u8>>,
+    }
+
+    impl AsyncRead for FailingRead {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            match Pin::new(&mut self.inner).poll_read(cx, buf) {
+                Poll::Ready(Ok(0)) => {
+                    Poll::Ready(Err(io::Error::new(io::ErrorKind::ConnectionReset, "bang")))
+                }
+                other => other,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ExternalStorage for FlakyStorage {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn url(&self) -> io::Result<url::Url> {
+            Ok(url::Url::parse("flaky:///").unwrap())
+        }
+
+        async fn write(
+            &self,
+            _name: &str,
+            _reader: UnpinReader,
+            _content_length: u64,
+        ) -> io::Result<()> {
+            unimplemented!()
+        }
+
+        fn read(&self, _name: &str) -> ExternalData<'_> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let truncated = self.content[..self.fail_after].to_vec();
+            Box::new(FailingRead {
+                inner: Cursor::new(truncated),
+            })
+        }
+
+        fn read_part(&self, _name: &str, off: u64, len: u64) -> ExternalData<'_> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let end = self.content.len().min(off.saturating_add(len) as usize);
+            Box::new(Cursor::new(self.content[off as usize..end].to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_delivered_offset() {
+        let content: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        let storage = FlakyStorage {
+            content: content.clone(),
+            fail_after: 1000,
+            reads: AtomicUsize::new(0),
+        };
+        let mut output = Vec::new();
+        read_resumable(&storage, "a")
+            .read_to_end(&mut output)
+            .await
+            .unwrap();
+        assert_eq!(output, content);
+        // One full read failing at byte 1000, then one range read for the rest.
+        assert_eq!(storage.reads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resume_part_read() {
+        let content: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+        let storage = FlakyStorage {
+            content: content.clone(),
+            fail_after: 0,
+            reads: AtomicUsize::new(0),
+        };
+        let mut output = Vec::new();
+        read_part_resumable(&storage, "a", 100, 200)
+            .read_to_end(&mut output)
+            .await
+            .unwrap();
+        assert_eq!(output, &content[100..300]);
+    }
+
+    #[tokio::test]
+    async fn test_non_resumable_backend() {
+        struct NoRange(FlakyStorage);
+
+        #[async_trait]
+        impl ExternalStorage for NoRange {
+            fn name(&self) -> &'static str {
+                "no-range"
+            }
+            fn url(&self) -> io::Result<url::Url> {
+                self.0.url()
+            }
+            fn support_resumable_read(&self) -> bool {
+                false
+            }
+            async fn write(
+                &self,
+                name: &str,
+                reader: UnpinReader,
+                content_length: u64,
+            ) -> io::Result<()> {
+                self.0.write(name, reader, content_length).await
+            }
+            fn read(&self, name: &str) -> ExternalData<'_> {
+                self.0.read(name)
+            }
+            fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
+                self.0.read_part(name, off, len)
+            }
+        }
+
+        let storage = NoRange(FlakyStorage {
+            content: vec![0; 4096],
+            fail_after: 1000,
+            reads: AtomicUsize::new(0),
+        });
+        let mut output = Vec::new();
+        let err = read_resumable(&storage, "a")
+            .read_to_end(&mut output)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_non_resumable_err_passes_through() {
+        /// A backend without range reads whose streams fail with `NotFound`.
+        struct Missing;
+
+        #[async_trait]
+        impl ExternalStorage for Missing {
+            fn name(&self) -> &'static str {
+                "missing"
+            }
+            fn url(&self) -> io::Result<url::Url> {
+                Ok(url::Url::parse("missing:///").unwrap())
+            }
+            fn support_resumable_read(&self) -> bool {
+                false
+            }
+            async fn write(
+                &self,
+                _name: &str,
+                _reader: UnpinReader,
+                _content_length: u64,
+            ) -> io::Result<()> {
+                unimplemented!()
+            }
+            fn read(&self, _name: &str) -> ExternalData<'_> {
+                Box::new(futures::io::AllowStdIo::new(ErrReader(io::ErrorKind::NotFound)))
+            }
+            fn read_part(&self, _name: &str, _off: u64, _len: u64) -> ExternalData<'_> {
+                unimplemented!()
+            }
+        }
+
+        struct ErrReader(io::ErrorKind);
+
+        impl std::io::Read for ErrReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(self.0, "no such object"))
+            }
+        }
+
+        // An error that would not have been resumed keeps its kind even
+        // though the backend doesn't support range reads.
+        let mut output = Vec::new();
+        let err = read_resumable(&Missing, "a")
+            .read_to_end(&mut output)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,392 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Tracing spans around every storage operation.
+//!
+//! Only compiled with the `tracing` feature; without it neither the wrapper
+//! nor its overhead exists.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use futures_io::AsyncRead;
+use tikv_util::time::Limiter;
+use tracing::{info_span, warn, Instrument, Span};
+
+use crate::{ExternalData, ExternalStorage, Permission, RestoreConfig, UnpinReader};
+
+/// Redacts an object key to its directory prefix plus a hash of the final
+/// component, so spans still group by directory and correlate by key without
+/// carrying the (possibly sensitive) object name.
+fn redact_key(name: &str) -> String {
+    let (prefix, base) = match name.rfind('/') {
+        Some(i) => name.split_at(i + 1),
+        None => ("", name),
+    };
+    let mut hasher = DefaultHasher::new();
+    base.hash(&mut hasher);
+    format!("{}{:016x}", prefix, hasher.finish())
+}
+
+/// Wraps a storage so every operation runs inside a `tracing` span carrying
+/// the operation, the backend kind, the redacted object key and the byte
+/// count where one is known, for correlating TiKV-side timing with
+/// object-store latency. A failed attempt records a warning event inside its
+/// span, so a caller's retry loop shows up as repeated spans whose earlier
+/// instances carry the failure. Created by
+/// [`create_storage`](crate::create_storage) as the outermost wrapper when
+/// the `tracing` feature is enabled.
+pub struct TracingStorage<S> {
+    inner: S,
+}
+
+impl<S: ExternalStorage> TracingStorage<S> {
+    pub fn new(inner: S) -> Self {
+        TracingStorage { inner }
+    }
+
+    fn traced<'a>(&self, span: Span, data: ExternalData<'a>) -> ExternalData<'a> {
+        Box::new(TracedRead { inner: data, span })
+    }
+}
+
+/// Keeps the span of a streaming read entered across every chunk it
+/// delivers, so the span's lifetime covers the whole download rather than
+/// just opening the stream.
+struct TracedRead<R> {
+    inner: R,
+    span: Span,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for TracedRead<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let _enter = this.span.enter();
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Err(e)) = &result {
+            warn!(error = %e, "read attempt failed");
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl<S: ExternalStorage> ExternalStorage for TracingStorage<S> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+    fn url(&self) -> io::Result<url::Url> {
+        self.inner.url()
+    }
+    async fn write(&self, name: &str, reader: UnpinReader, content_length: u64) -> io::Result<()> {
+        let span = info_span!(
+            "external_storage.write",
+            backend = self.inner.name(),
+            key = %redact_key(name),
+            bytes = content_length,
+        );
+        async {
+            let result = self.inner.write(name, reader, content_length).await;
+            if let Err(e) = &result {
+                warn!(error = %e, "write attempt failed");
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
+    async fn write_with_meta(
+        &self,
+        name: &str,
+        reader: UnpinReader,
+        content_length: u64,
+        metadata: HashMap<String, String>,
+    ) -> io::Result<()> {
+        let span = info_span!(
+            "external_storage.write_with_meta",
+            backend = self.inner.name(),
+            key = %redact_key(name),
+            bytes = content_length,
+        );
+        async {
+            let result = self
+                .inner
+                .write_with_meta(name, reader, content_length, metadata)
+                .await;
+            if let Err(e) = &result {
+                warn!(error = %e, "write attempt failed");
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
+    async fn head(&self, name: &str) -> io::Result<HashMap<String, String>> {
+        let span = info_span!(
+            "external_storage.head",
+            backend = self.inner.name(),
+            key = %redact_key(name),
+        );
+        async {
+            let result = self.inner.head(name).await;
+            if let Err(e) = &result {
+                warn!(error = %e, "head attempt failed");
+            }
+            result
+        }
+        .instrument(span)
+        .await
+    }
+    fn read(&self, name: &str) -> ExternalData<'_> {
+        let span = info_span!(
+            "external_storage.read",
+            backend = self.inner.name(),
+            key = %redact_key(name),
+        );
+        self.traced(span.clone(), span.in_scope(|| self.inner.read(name)))
+    }
+    fn read_part(&self, name: &str, off: u64, len: u64) -> ExternalData<'_> {
+        let span = info_span!(
+            "external_storage.read_part",
+            backend = self.inner.name(),
+            key = %redact_key(name),
+            off,
+            bytes = len,
+        );
+        self.traced(span.clone(), span.in_scope(|| self.inner.read_part(name, off, len)))
+    }
+    fn support_resumable_read(&self) -> bool {
+        self.inner.support_resumable_read()
+    }
+    async fn check_permissions(&self, required: &[Permission]) -> io::Result<()> {
+        let span = info_span!(
+            "external_storage.check_permissions",
+            backend = self.inner.name(),
+        );
+        self.inner.check_permissions(required).instrument(span).await
+    }
+    async fn restore(
+        &self,
+        storage_name: &str,
+        restore_name: std::path::PathBuf,
+        expected_length: u64,
+        speed_limiter: &Limiter,
+        restore_config: RestoreConfig,
+    ) -> io::Result<()> {
+        let span = info_span!(
+            "external_storage.restore",
+            backend = self.inner.name(),
+            key = %redact_key(storage_name),
+            bytes = expected_length,
+        );
+        self.inner
+            .restore(
+                storage_name,
+                restore_name,
+                expected_length,
+                speed_limiter,
+                restore_config,
+            )
+            .instrument(span)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct CapturedSpan {
+        name: &'static str,
+        parent: Option<u64>,
+        fields: String,
+    }
+
+    #[derive(Default)]
+    struct State {
+        stack: Vec<u64>,
+        // Indexed by id - 1.
+        spans: Vec<CapturedSpan>,
+        // (enclosing span, formatted fields) per event.
+        events: Vec<(Option<u64>, String)>,
+    }
+
+    /// A minimal subscriber recording span parentage and events, enough to
+    /// assert the hierarchy the wrapper produces.
+    #[derive(Default)]
+    struct CapturingSubscriber {
+        state: Mutex<State>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut String);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            use std::fmt::Write;
+            write!(self.0, "{}={:?} ", field.name(), value).unwrap();
+        }
+    }
+
+    impl Subscriber for CapturingSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut state = self.state.lock().unwrap();
+            let mut fields = String::new();
+            span.record(&mut FieldVisitor(&mut fields));
+            let parent = state.stack.last().copied();
+            state.spans.push(CapturedSpan {
+                name: span.metadata().name(),
+                parent,
+                fields,
+            });
+            Id::from_u64(state.spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut state = self.state.lock().unwrap();
+            let mut fields = String::new();
+            event.record(&mut FieldVisitor(&mut fields));
+            let current = state.stack.last().copied();
+            state.events.push((current, fields));
+        }
+
+        fn enter(&self, span: &Id) {
+            self.state.lock().unwrap().stack.push(span.into_u64());
+        }
+
+        fn exit(&self, _span: &Id) {
+            self.state.lock().unwrap().stack.pop();
+        }
+    }
+
+    /// Fails the first `failures_left` writes, like a backend riding out
+    /// transient object-store errors.
+    struct FlakyWriteStorage {
+        failures_left: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ExternalStorage for FlakyWriteStorage {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+        fn url(&self) -> io::Result<url::Url> {
+            Ok(url::Url::parse("flaky:///").unwrap())
+        }
+        async fn write(
+            &self,
+            _name: &str,
+            _reader: UnpinReader,
+            _content_length: u64,
+        ) -> io::Result<()> {
+            if self
+                .failures_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(io::Error::new(io::ErrorKind::Other, "injected failure"));
+            }
+            Ok(())
+        }
+        fn read(&self, _name: &str) -> ExternalData<'_> {
+            Box::new(&b""[..])
+        }
+        fn read_part(&self, _name: &str, _off: u64, _len: u64) -> ExternalData<'_> {
+            Box::new(&b""[..])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_span_hierarchy_with_retries() {
+        let subscriber = Arc::new(CapturingSubscriber::default());
+        let _guard = tracing::subscriber::set_default(subscriber.clone());
+
+        let storage = TracingStorage::new(FlakyWriteStorage {
+            failures_left: AtomicUsize::new(2),
+        });
+        let parent = tracing::info_span!("backup_file");
+        async {
+            for attempt in 0.. {
+                let content: &[u8] = b"payload";
+                match storage
+                    .write(
+                        "sst/000001.sst",
+                        UnpinReader(Box::new(content)),
+                        content.len() as u64,
+                    )
+                    .await
+                {
+                    Ok(()) => break,
+                    Err(_) if attempt < 2 => continue,
+                    Err(e) => panic!("write not retried to success: {}", e),
+                }
+            }
+        }
+        .instrument(parent)
+        .await;
+
+        let state = subscriber.state.lock().unwrap();
+        // One parent span, plus one write span per attempt, all children of
+        // the parent.
+        assert_eq!(state.spans.len(), 4);
+        assert_eq!(state.spans[0].name, "backup_file");
+        assert_eq!(state.spans[0].parent, None);
+        for span in &state.spans[1..] {
+            assert_eq!(span.name, "external_storage.write");
+            assert_eq!(span.parent, Some(1));
+            assert!(span.fields.contains("backend=\"flaky\""), "{}", span.fields);
+            // The key keeps its directory but not the object name.
+            assert!(span.fields.contains("sst/"), "{}", span.fields);
+            assert!(!span.fields.contains("000001"), "{}", span.fields);
+            assert!(span.fields.contains("bytes=7"), "{}", span.fields);
+        }
+        // The two injected failures each recorded an event inside their own
+        // attempt's span; the successful third attempt recorded none.
+        let failures: Vec<_> = state
+            .events
+            .iter()
+            .filter(|(_, fields)| fields.contains("injected failure"))
+            .collect();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].0, Some(2));
+        assert_eq!(failures[1].0, Some(3));
+    }
+
+    #[test]
+    fn test_redact_key() {
+        let redacted = redact_key("backup/2026/000001.sst");
+        assert!(redacted.starts_with("backup/2026/"), "{}", redacted);
+        assert!(!redacted.contains("000001"), "{}", redacted);
+        // The same key redacts to the same value, so spans still correlate.
+        assert_eq!(redacted, redact_key("backup/2026/000001.sst"));
+        assert_ne!(redacted, redact_key("backup/2026/000002.sst"));
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::codec::mysql::{Json, JsonArrayBuilder};
+
+/// Builds `n` distinct string elements so the encoded values are not
+/// trivially small.
+fn build_elements(n: u64) -> Vec<Json> {
+    (0..n)
+        .map(|i| format!(r#""element-{:016}""#, i).parse().unwrap())
+        .collect()
+}
+
+fn bench_array_from_refs(c: &mut Criterion) {
+    let elems = build_elements(50_000);
+    c.bench_function("from_ref_array_50k_elements", |b| {
+        b.iter(|| {
+            let refs = elems.iter().map(|j| j.as_ref()).collect();
+            black_box(Json::from_ref_array(refs).unwrap())
+        })
+    });
+    c.bench_function("array_builder_50k_elements", |b| {
+        b.iter(|| {
+            let mut builder = JsonArrayBuilder::new();
+            builder.reserve_hint(elems.iter().map(|j| j.as_ref().value().len()).sum());
+            for j in &elems {
+                builder.push_ref(&j.as_ref()).unwrap();
+            }
+            black_box(builder.finish().unwrap())
+        })
+    });
+}
+
+criterion_group!(benches, bench_array_from_refs);
+criterion_main!(benches);
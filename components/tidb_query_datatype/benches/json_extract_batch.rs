@@ -0,0 +1,44 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::codec::mysql::{
+    extract_batch, json::JsonRef, parse_json_path_expr, CompiledPathList, Json,
+};
+
+fn build_docs(n: usize) -> Vec<Json> {
+    (0..n)
+        .map(|i| {
+            format!(r#"{{"a": {{"b": {}}}, "c": [{}, {}]}}"#, i, i % 7, i % 11)
+                .parse()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn bench_extract_batch(c: &mut Criterion) {
+    let docs = build_docs(100_000);
+    let docs_ref: Vec<Option<JsonRef<'_>>> = docs.iter().map(|d| Some(d.as_ref())).collect();
+    let paths = vec![parse_json_path_expr("$.a.b").unwrap()];
+
+    c.bench_function("extract_100k_per_row", |b| {
+        b.iter(|| {
+            let mut out = Vec::with_capacity(docs_ref.len());
+            for doc in &docs_ref {
+                out.push(doc.and_then(|d| d.extract(&paths).unwrap()));
+            }
+            black_box(out)
+        })
+    });
+
+    let compiled = CompiledPathList::new(paths);
+    c.bench_function("extract_100k_batch", |b| {
+        b.iter(|| {
+            let mut out = Vec::with_capacity(docs_ref.len());
+            extract_batch(&docs_ref, &compiled, &mut out).unwrap();
+            black_box(out)
+        })
+    });
+}
+
+criterion_group!(benches, bench_extract_batch);
+criterion_main!(benches);
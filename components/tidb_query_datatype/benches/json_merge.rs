@@ -0,0 +1,24 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::codec::mysql::Json;
+
+/// Builds an object with `keys` keys, offset by `start` so two documents
+/// built with different starts overlap on half of their keys.
+fn build_wide_object(start: u64, keys: u64) -> Json {
+    let entries: Vec<String> = (start..start + keys)
+        .map(|i| format!(r#""key-{:08}": {}"#, i, i))
+        .collect();
+    format!("{{{}}}", entries.join(",")).parse().unwrap()
+}
+
+fn bench_merge_wide_objects(c: &mut Criterion) {
+    let left = build_wide_object(0, 5_000);
+    let right = build_wide_object(2_500, 5_000);
+    c.bench_function("merge_two_5k_key_objects", |b| {
+        b.iter(|| black_box(Json::merge(vec![left.as_ref(), right.as_ref()]).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_merge_wide_objects);
+criterion_main!(benches);
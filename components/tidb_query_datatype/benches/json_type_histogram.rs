@@ -0,0 +1,37 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::codec::mysql::Json;
+
+/// Builds a document whose binary encoding is roughly 1 MiB: an array of
+/// objects with mixed scalar fields and a small nested array each.
+fn build_1mb_document() -> Json {
+    let rows: Vec<String> = (0..11_000u64)
+        .map(|i| {
+            format!(
+                r#"{{"id": {}, "name": "row-{:08}", "score": {}.5, "ok": {}, "tags": ["a", "b", "c"]}}"#,
+                i,
+                i,
+                i % 100,
+                i % 2 == 0,
+            )
+        })
+        .collect();
+    format!("[{}]", rows.join(",")).parse().unwrap()
+}
+
+fn bench_type_histogram(c: &mut Criterion) {
+    let doc = build_1mb_document();
+    c.bench_function("type_histogram_1mb", |b| {
+        b.iter(|| {
+            black_box(
+                doc.as_ref()
+                    .type_histogram(black_box(u32::MAX))
+                    .unwrap(),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, bench_type_histogram);
+criterion_main!(benches);
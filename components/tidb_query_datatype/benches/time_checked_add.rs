@@ -0,0 +1,62 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::{
+    codec::mysql::{Duration, Time},
+    expr::EvalContext,
+};
+
+/// Builds `n` pseudo-random wall times within the timestamp range, so the
+/// same inputs can be parsed as datetimes and as timestamps.
+fn build_strings(n: u64) -> Vec<String> {
+    (0..n)
+        .map(|i| {
+            format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+                1971 + (i * 7919) % 60,
+                1 + (i * 13) % 12,
+                1 + (i * 17) % 28,
+                (i * 23) % 24,
+                (i * 29) % 60,
+                (i * 31) % 60,
+                i % 1000
+            )
+        })
+        .collect()
+}
+
+/// Datetimes take the native day-number arithmetic path; timestamps still
+/// round-trip through chrono for time zone handling, which is what the whole
+/// function did before the rewrite.
+fn bench_checked_add(c: &mut Criterion) {
+    let mut ctx = EvalContext::default();
+    let strings = build_strings(10_000);
+    let rhs = Duration::parse(&mut ctx, "25:30:45.678901", 6).unwrap();
+
+    let datetimes: Vec<Time> = strings
+        .iter()
+        .map(|s| Time::parse_datetime(&mut ctx, s, 6, false).unwrap())
+        .collect();
+    c.bench_function("checked_add_10k_datetimes", |b| {
+        b.iter(|| {
+            for t in &datetimes {
+                black_box(t.checked_add(&mut ctx, rhs).unwrap());
+            }
+        })
+    });
+
+    let timestamps: Vec<Time> = strings
+        .iter()
+        .map(|s| Time::parse_timestamp(&mut ctx, s, 6, false).unwrap())
+        .collect();
+    c.bench_function("checked_add_10k_timestamps", |b| {
+        b.iter(|| {
+            for t in &timestamps {
+                black_box(t.checked_add(&mut ctx, rhs).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_checked_add);
+criterion_main!(benches);
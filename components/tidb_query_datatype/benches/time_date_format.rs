@@ -0,0 +1,49 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::{codec::mysql::Time, expr::EvalContext};
+
+const LAYOUT: &str = "%Y-%m-%d %H:%i:%s.%f (%W, week %V of %X)";
+
+/// `date_format` allocates a fresh `String` per row; `date_format_into`
+/// reuses one buffer across the batch.
+fn bench_date_format(c: &mut Criterion) {
+    let mut ctx = EvalContext::default();
+    let times: Vec<Time> = (0..10_000u64)
+        .map(|i| {
+            let s = format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+                1971 + (i * 7919) % 60,
+                1 + (i * 13) % 12,
+                1 + (i * 17) % 28,
+                (i * 23) % 24,
+                (i * 29) % 60,
+                (i * 31) % 60,
+                i % 1000
+            );
+            Time::parse_datetime(&mut ctx, &s, 6, false).unwrap()
+        })
+        .collect();
+
+    c.bench_function("date_format_10k_alloc_per_row", |b| {
+        b.iter(|| {
+            for t in &times {
+                black_box(t.date_format(LAYOUT).unwrap());
+            }
+        })
+    });
+
+    c.bench_function("date_format_10k_reused_buffer", |b| {
+        let mut buf = String::new();
+        b.iter(|| {
+            for t in &times {
+                buf.clear();
+                t.date_format_into(LAYOUT, &mut buf).unwrap();
+                black_box(buf.as_str());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_date_format);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::{
+    codec::mysql::{Time, TimeType},
+    expr::EvalContext,
+};
+
+/// Builds `n` pseudo-random datetime strings of the shape a CAST column
+/// feeds the parser.
+fn build_strings(n: u64) -> Vec<String> {
+    (0..n)
+        .map(|i| {
+            format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+                1971 + (i * 7919) % 60,
+                1 + (i * 13) % 12,
+                1 + (i * 17) % 28,
+                (i * 23) % 24,
+                (i * 29) % 60,
+                (i * 31) % 60,
+                i % 1000
+            )
+        })
+        .collect()
+}
+
+/// The scalar loop re-checks the fsp for every row; the batch API pays it
+/// once.
+fn bench_parse_batch(c: &mut Criterion) {
+    let mut ctx = EvalContext::default();
+    let strings = build_strings(10_000);
+    let inputs: Vec<&str> = strings.iter().map(String::as_str).collect();
+
+    c.bench_function("parse_scalar_10k_datetimes", |b| {
+        b.iter(|| {
+            for s in &inputs {
+                black_box(Time::parse_datetime(&mut ctx, s, 6, true).unwrap());
+            }
+        })
+    });
+
+    c.bench_function("parse_batch_10k_datetimes", |b| {
+        b.iter(|| {
+            black_box(Time::parse_batch(&mut ctx, &inputs, TimeType::DateTime, 6, true).unwrap())
+        })
+    });
+}
+
+criterion_group!(benches, bench_parse_batch);
+criterion_main!(benches);
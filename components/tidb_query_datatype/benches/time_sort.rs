@@ -0,0 +1,39 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::{codec::mysql::Time, expr::EvalContext};
+
+/// Builds `n` pseudo-random datetimes; the multipliers are primes so the
+/// components don't correlate and comparisons rarely short-circuit early.
+fn build_times(n: u64) -> Vec<Time> {
+    let mut ctx = EvalContext::default();
+    (0..n)
+        .map(|i| {
+            let s = format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+                1000 + (i * 7919) % 9000,
+                1 + (i * 13) % 12,
+                1 + (i * 17) % 28,
+                (i * 23) % 24,
+                (i * 29) % 60,
+                (i * 31) % 60,
+                i % 1000
+            );
+            Time::parse_datetime(&mut ctx, &s, 3, false).unwrap()
+        })
+        .collect()
+}
+
+fn bench_sort_datetimes(c: &mut Criterion) {
+    let times = build_times(1_000_000);
+    c.bench_function("sort_1m_datetimes", |b| {
+        b.iter(|| {
+            let mut v = times.clone();
+            v.sort_unstable();
+            black_box(v)
+        })
+    });
+}
+
+criterion_group!(benches, bench_sort_datetimes);
+criterion_main!(benches);
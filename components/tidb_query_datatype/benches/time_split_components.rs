@@ -0,0 +1,38 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::{
+    codec::mysql::Time,
+    expr::EvalContext,
+};
+
+/// Datetime spellings that drive the component splitter through its
+/// different shapes: a plain datetime, a fractional part, a timezone
+/// suffix (which pops components back off), and a compact whole-number
+/// form.
+const INPUTS: &[&str] = &[
+    "2020-12-23 15:59:23",
+    "2020-12-23 15:59:23.233333",
+    "2020-12-23T15:59:23.233333+08:00",
+    "2020-12-24T15:37:50-0800",
+    "2020-10-10T19:27:10Z",
+    "20201223155923.2333",
+];
+
+/// The splitter keeps its component and separator lists on the stack, so
+/// parsing a string should not allocate for them; this pins the cost of
+/// the split-and-resolve path itself.
+fn bench_split_components(c: &mut Criterion) {
+    let mut ctx = EvalContext::default();
+
+    c.bench_function("parse_split_components_mixed", |b| {
+        b.iter(|| {
+            for s in INPUTS {
+                black_box(Time::parse_datetime(&mut ctx, s, 6, true).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_split_components);
+criterion_main!(benches);
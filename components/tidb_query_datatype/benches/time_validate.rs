@@ -0,0 +1,36 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tidb_query_datatype::{
+    codec::mysql::{Time, TimeType},
+    expr::EvalContext,
+};
+
+/// Builds `n` numeric dates confined to a few months, mimicking a bulk
+/// INSERT of roughly contemporaneous rows: the shape the two-entry
+/// `last_day_of_month` cache is meant to serve.
+fn build_dates(n: i64) -> Vec<i64> {
+    (0..n)
+        .map(|i| {
+            let month = 1 + (i / 28) % 3;
+            let day = 1 + i % 28;
+            20_240_000 + month * 100 + day
+        })
+        .collect()
+}
+
+fn bench_validate(c: &mut Criterion) {
+    let mut ctx = EvalContext::default();
+    let dates = build_dates(1_000_000);
+
+    c.bench_function("validate_1m_dates_few_months", |b| {
+        b.iter(|| {
+            for &d in &dates {
+                black_box(Time::parse_from_i64(&mut ctx, d, TimeType::Date, 0).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_validate);
+criterion_main!(benches);
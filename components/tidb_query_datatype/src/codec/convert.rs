@@ -457,17 +457,13 @@ impl ToInt for DateTime {
     #[inline]
     fn to_int(&self, ctx: &mut EvalContext, tp: FieldTypeTp) -> Result<i64> {
         let t = self.round_frac(ctx, DEFAULT_FSP)?;
-        let dec: Decimal = t.convert(ctx)?;
-        let val = dec.as_i64();
-        let val = val.into_result(ctx)?;
-        val.to_int(ctx, tp)
+        t.to_numeric_i64().to_int(ctx, tp)
     }
 
     #[inline]
     fn to_uint(&self, ctx: &mut EvalContext, tp: FieldTypeTp) -> Result<u64> {
         let t = self.round_frac(ctx, DEFAULT_FSP)?;
-        let dec: Decimal = t.convert(ctx)?;
-        decimal_as_u64(ctx, dec, tp)
+        t.to_numeric_u64().to_uint(ctx, tp)
     }
 }
 
@@ -513,7 +509,15 @@ impl<'a> ToInt for JsonRef<'a> {
             JsonType::Literal => Ok(self.get_literal().map_or(0, |x| x as i64)),
             JsonType::I64 => Ok(self.get_i64()),
             JsonType::U64 => Ok(self.get_u64() as i64),
-            JsonType::Double => self.get_double().to_int(ctx, tp),
+            JsonType::Double => {
+                let d = self.get_double();
+                if d.fract() != 0.0 {
+                    // The fractional part is rounded away; MySQL reports the
+                    // lossy cast as a warning.
+                    ctx.append_warning(Error::truncated_wrong_val("INTEGER", self.to_string()));
+                }
+                d.to_int(ctx, tp)
+            }
             JsonType::String => self.get_str_bytes()?.to_int(ctx, tp),
             _ => Ok(ctx
                 .handle_truncate_err(Error::truncated_wrong_val("Integer", self.to_string()))
@@ -529,7 +533,13 @@ impl<'a> ToInt for JsonRef<'a> {
             JsonType::Literal => Ok(self.get_literal().map_or(0, |x| x as u64)),
             JsonType::I64 => Ok(self.get_i64() as u64),
             JsonType::U64 => Ok(self.get_u64()),
-            JsonType::Double => self.get_double().to_uint(ctx, tp),
+            JsonType::Double => {
+                let d = self.get_double();
+                if d.fract() != 0.0 {
+                    ctx.append_warning(Error::truncated_wrong_val("INTEGER", self.to_string()));
+                }
+                d.to_uint(ctx, tp)
+            }
             JsonType::String => self.get_str_bytes()?.to_uint(ctx, tp),
             _ => Ok(ctx
                 .handle_truncate_err(Error::truncated_wrong_val("Integer", self.to_string()))
@@ -1534,6 +1544,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_json_to_int_lossy_warning() {
+        // (json, expected warnings)
+        let test_cases = vec![
+            ("3", 0),
+            ("4.0", 0),
+            // The fractional part is rounded away.
+            ("4.5", 1),
+            ("-4.1", 1),
+        ];
+        for (jstr, warning_cnt) in test_cases {
+            let mut ctx = EvalContext::default();
+            let json: Json = jstr.parse().unwrap();
+            json.to_int(&mut ctx, FieldTypeTp::LongLong).unwrap();
+            assert_eq!(
+                ctx.warnings.warning_cnt, warning_cnt,
+                "json: {}, expect {} warnings but got {:?}",
+                jstr, warning_cnt, ctx.warnings.warnings
+            );
+        }
+    }
+
     #[test]
     fn test_cast_err_when_json_array_or_object_to_int() {
         let test_cases = vec![
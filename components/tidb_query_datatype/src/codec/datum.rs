@@ -573,6 +573,21 @@ impl Datum {
         }
     }
 
+    /// `into_json_candidate` is the single mapping from an SQL scalar to the
+    /// JSON value MySQL compares it as in MEMBER OF, JSON_CONTAINS and JSON
+    /// comparisons. It only differs from `into_json` for booleans: a datum
+    /// cannot tell a boolean expression from a plain integer, so the caller
+    /// passes `is_boolean` (derived from the expression's field type) and
+    /// gets the JSON true/false literal instead of the number 1/0, which
+    /// never equals it. SQL NULL keeps mapping to the JSON null literal.
+    pub fn into_json_candidate(self, is_boolean: bool) -> Result<Json> {
+        match self {
+            Datum::I64(b) if is_boolean => Json::from_bool(b != 0),
+            Datum::U64(b) if is_boolean => Json::from_bool(b != 0),
+            d => d.into_json(),
+        }
+    }
+
     /// `to_json_path_expr` parses Datum::Bytes(b) to a JSON PathExpression.
     pub fn to_json_path_expr(&self) -> Result<PathExpression> {
         let v = match *self {
@@ -2000,6 +2015,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_datum_into_json_candidate() {
+        let tests = vec![
+            (Datum::I64(1), true, "true"),
+            (Datum::I64(0), true, "false"),
+            (Datum::U64(2), true, "true"),
+            (Datum::I64(1), false, "1.0"),
+            (Datum::Null, true, "null"),
+            (Datum::Null, false, "null"),
+            (Datum::Bytes(b"true".to_vec()), true, r#""true""#),
+        ];
+
+        for (d, is_boolean, json) in tests {
+            assert_eq!(
+                d.into_json_candidate(is_boolean).unwrap(),
+                json.parse().unwrap()
+            );
+        }
+    }
+
     #[test]
     fn test_into_f64() {
         let mut ctx = EvalContext::new(Arc::new(EvalConfig::default_for_test()));
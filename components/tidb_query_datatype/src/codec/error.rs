@@ -23,7 +23,9 @@ pub const ERR_DIVISION_BY_ZERO: i32 = 1365;
 pub const ERR_DATA_TOO_LONG: i32 = 1406;
 pub const ERR_INCORRECT_PARAMETERS: i32 = 1583;
 pub const ERR_DATA_OUT_OF_RANGE: i32 = 1690;
+pub const ERR_INVALID_JSON_TEXT: i32 = 3140;
 pub const ERR_CANNOT_CONVERT_STRING: i32 = 3854;
+pub const ERR_MISSING_JSON_VALUE: i32 = 3966;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -129,6 +131,13 @@ impl Error {
         Error::Eval(msg, ERR_TRUNCATE_WRONG_VALUE)
     }
 
+    /// [`Error::incorrect_datetime_value`] with the reason of the failure
+    /// appended to the message, keeping the error code unchanged.
+    pub fn incorrect_datetime_value_with_reason(val: impl Display, reason: impl Display) -> Error {
+        let msg = format!("Incorrect datetime value: '{}' ({})", val, reason);
+        Error::Eval(msg, ERR_TRUNCATE_WRONG_VALUE)
+    }
+
     pub fn zlib_length_corrupted() -> Error {
         let msg = "ZLIB: Not enough room in the output buffer (probably, length of uncompressed data was corrupted)";
         Error::Eval(msg.into(), ZLIB_LENGTH_CORRUPTED)
@@ -149,6 +158,28 @@ impl Error {
     pub fn regexp_error(msg: String) -> Error {
         Error::Eval(msg, ERR_REGEXP)
     }
+
+    pub fn invalid_json_text(reason: impl Display, position: usize, excerpt: &str) -> Error {
+        let msg = if excerpt.is_empty() {
+            format!("Invalid JSON text: \"{}\" at position {}", reason, position)
+        } else {
+            format!(
+                "Invalid JSON text: \"{}\" at position {}, near '{}'",
+                reason, position, excerpt
+            )
+        };
+        Error::Eval(msg, ERR_INVALID_JSON_TEXT)
+    }
+
+    pub fn missing_json_value(path: impl Display) -> Error {
+        let msg = format!("No value was found by the path expression {}", path);
+        Error::Eval(msg, ERR_MISSING_JSON_VALUE)
+    }
+
+    pub fn json_scalar_autowrapped(val: impl Display) -> Error {
+        let msg = format!("Scalar value {} was autowrapped as an array for merge", val);
+        Error::Eval(msg, ERR_UNKNOWN)
+    }
 }
 
 impl From<Error> for tipb::Error {
@@ -8,7 +8,7 @@ use std::{
 use codec::prelude::*;
 use tipb::FieldType;
 
-use super::{check_fsp, Decimal, DEFAULT_FSP};
+use super::{check_fsp, scale_micro_to_fsp, widen_frac_digits, Decimal, DEFAULT_FSP};
 use crate::{
     codec::{
         convert::ConvertTo,
@@ -202,13 +202,18 @@ mod parser {
         }
 
         let (rest, digits) = digit0(rest)?;
-        let ((_, frac), len) = if fsp >= digits.len() {
-            (number(digits)?, digits.len())
-        } else {
-            (number(&digits[..=fsp])?, fsp + 1)
-        };
+        // Keep one digit beyond `fsp` as a guard: `new_from_parts` rounds the
+        // nanosecond part half-up to `fsp` once the seconds are summed in.
+        let digits = &digits.as_bytes()[..digits.len().min(fsp + 1)];
+        let (_, micro) =
+            widen_frac_digits(&digits[..digits.len().min(MICRO_WIDTH)], MAX_FSP as u8, false)
+                .ok_or(nom::Err::Error(()))?;
+        let mut nanos = micro * NANOS_PER_MICRO as u32;
+        if let Some(guard) = digits.get(MICRO_WIDTH) {
+            nanos += u32::from(guard - b'0') * 100;
+        }
 
-        Ok((rest, frac * TEN_POW[NANO_WIDTH.saturating_sub(len)]))
+        Ok((rest, nanos))
     }
 
     pub fn parse(
@@ -552,7 +557,7 @@ impl Duration {
         .unwrap();
 
         if self.fsp > 0 {
-            let frac = self.subsec_nanos() / TEN_POW[NANO_WIDTH - self.fsp as usize];
+            let frac = scale_micro_to_fsp(self.subsec_micros(), self.fsp);
             write!(string, ".{:0width$}", frac, width = self.fsp as usize).unwrap();
         }
 
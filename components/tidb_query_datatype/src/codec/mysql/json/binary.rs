@@ -4,8 +4,8 @@ use std::convert::TryInto;
 
 use codec::number::NumberCodec;
 
-use super::{constants::*, JsonRef, JsonType, ERR_CONVERT_FAILED};
-use crate::codec::{mysql::json::path_expr::ArrayIndex, Result};
+use super::{constants::*, Json, JsonRef, JsonType, ERR_CONVERT_FAILED};
+use crate::codec::{mysql::json::path_expr::ArrayIndex, Error, Result};
 
 impl<'a> JsonRef<'a> {
     /// Gets the index from the ArrayIndex
@@ -32,17 +32,32 @@ impl<'a> JsonRef<'a> {
     ///
     /// See `arrayGetElem()` in TiDB `json/binary.go`
     pub fn array_get_elem(&self, idx: usize) -> Result<JsonRef<'a>> {
-        self.val_entry_get(HEADER_LEN + idx * VALUE_ENTRY_LEN)
+        if self.is_small() {
+            self.val_entry_get(SMALL_HEADER_LEN + idx * SMALL_VALUE_ENTRY_LEN)
+        } else {
+            self.val_entry_get(HEADER_LEN + idx * VALUE_ENTRY_LEN)
+        }
     }
 
     /// Return the `i`th key in current Object json
     ///
     /// See `objectGetKey()` in TiDB `types/json_binary.go`
     pub fn object_get_key(&self, i: usize) -> &'a [u8] {
-        let key_off_start = HEADER_LEN + i * KEY_ENTRY_LEN;
-        let key_off = NumberCodec::decode_u32_le(&self.value()[key_off_start..]) as usize;
-        let key_len =
-            NumberCodec::decode_u16_le(&self.value()[key_off_start + KEY_OFFSET_LEN..]) as usize;
+        let (key_off, key_len) = if self.is_small() {
+            let key_off_start = SMALL_HEADER_LEN + i * SMALL_KEY_ENTRY_LEN;
+            (
+                NumberCodec::decode_u16_le(&self.value()[key_off_start..]) as usize,
+                NumberCodec::decode_u16_le(&self.value()[key_off_start + SMALL_KEY_OFFSET_LEN..])
+                    as usize,
+            )
+        } else {
+            let key_off_start = HEADER_LEN + i * KEY_ENTRY_LEN;
+            (
+                NumberCodec::decode_u32_le(&self.value()[key_off_start..]) as usize,
+                NumberCodec::decode_u16_le(&self.value()[key_off_start + KEY_OFFSET_LEN..])
+                    as usize,
+            )
+        };
         &self.value()[key_off..key_off + key_len]
     }
 
@@ -51,8 +66,40 @@ impl<'a> JsonRef<'a> {
     /// See `objectGetVal()` in TiDB `types/json_binary.go`
     pub fn object_get_val(&self, i: usize) -> Result<JsonRef<'a>> {
         let ele_count = self.get_elem_count();
-        let val_entry_off = HEADER_LEN + ele_count * KEY_ENTRY_LEN + i * VALUE_ENTRY_LEN;
-        self.val_entry_get(val_entry_off)
+        if self.is_small() {
+            let val_entry_off =
+                SMALL_HEADER_LEN + ele_count * SMALL_KEY_ENTRY_LEN + i * SMALL_VALUE_ENTRY_LEN;
+            self.val_entry_get(val_entry_off)
+        } else {
+            let val_entry_off = HEADER_LEN + ele_count * KEY_ENTRY_LEN + i * VALUE_ENTRY_LEN;
+            self.val_entry_get(val_entry_off)
+        }
+    }
+
+    /// Like [`array_get_elem`](JsonRef::array_get_elem), but every offset
+    /// read from the document is validated against the buffer, so a corrupted
+    /// value entry yields an error instead of a panic. Code paths which may
+    /// face damaged documents should prefer this; the unchecked accessors
+    /// remain for code working on documents it has just built itself.
+    pub fn try_array_get_elem(&self, idx: usize) -> Result<JsonRef<'a>> {
+        if self.is_small() {
+            self.try_val_entry_get(SMALL_HEADER_LEN + idx * SMALL_VALUE_ENTRY_LEN)
+        } else {
+            self.try_val_entry_get(HEADER_LEN + idx * VALUE_ENTRY_LEN)
+        }
+    }
+
+    /// Checked counterpart of [`object_get_val`](JsonRef::object_get_val);
+    /// see [`try_array_get_elem`](JsonRef::try_array_get_elem).
+    pub fn try_object_get_val(&self, i: usize) -> Result<JsonRef<'a>> {
+        let ele_count = self.get_elem_count();
+        if self.is_small() {
+            self.try_val_entry_get(
+                SMALL_HEADER_LEN + ele_count * SMALL_KEY_ENTRY_LEN + i * SMALL_VALUE_ENTRY_LEN,
+            )
+        } else {
+            self.try_val_entry_get(HEADER_LEN + ele_count * KEY_ENTRY_LEN + i * VALUE_ENTRY_LEN)
+        }
     }
 
     /// Searches the value index by the give `key` in Object.
@@ -80,9 +127,22 @@ impl<'a> JsonRef<'a> {
     ///
     /// See `arrayGetElem()` in TiDB `json/binary.go`
     pub fn val_entry_get(&self, val_entry_off: usize) -> Result<JsonRef<'a>> {
-        let val_type: JsonType = self.value()[val_entry_off].try_into()?;
-        let val_offset =
-            NumberCodec::decode_u32_le(&self.value()[val_entry_off + TYPE_LEN..]) as usize;
+        let type_code = self.value()[val_entry_off];
+        // MySQL may nest a "small" container inside any container; TiKV
+        // never emits one, so map it to the corresponding large type with
+        // the small flag set. (See the doc of the `json` module.)
+        let (val_type, child_small) = match type_code {
+            JSON_TYPE_CODE_SMALL_OBJECT => (JsonType::Object, true),
+            JSON_TYPE_CODE_SMALL_ARRAY => (JsonType::Array, true),
+            _ => (type_code.try_into()?, false),
+        };
+        // In a small container, the offset-or-inlined-value of a value
+        // entry is a u16 instead of a u32.
+        let val_offset = if self.is_small() {
+            NumberCodec::decode_u16_le(&self.value()[val_entry_off + TYPE_LEN..]) as usize
+        } else {
+            NumberCodec::decode_u32_le(&self.value()[val_entry_off + TYPE_LEN..]) as usize
+        };
         Ok(match val_type {
             JsonType::Literal => {
                 let offset = val_entry_off + TYPE_LEN;
@@ -116,14 +176,242 @@ impl<'a> JsonRef<'a> {
                 &self.value()[val_offset..val_offset + DURATION_LEN],
             ),
             _ => {
-                let data_size =
-                    NumberCodec::decode_u32_le(&self.value()[val_offset + ELEMENT_COUNT_LEN..])
-                        as usize;
-                JsonRef::new(val_type, &self.value()[val_offset..val_offset + data_size])
+                if child_small {
+                    let data_size = NumberCodec::decode_u16_le(
+                        &self.value()[val_offset + SMALL_ELEMENT_COUNT_LEN..],
+                    ) as usize;
+                    JsonRef::new_small(val_type, &self.value()[val_offset..val_offset + data_size])
+                } else {
+                    let data_size =
+                        NumberCodec::decode_u32_le(&self.value()[val_offset + ELEMENT_COUNT_LEN..])
+                            as usize;
+                    JsonRef::new(val_type, &self.value()[val_offset..val_offset + data_size])
+                }
+            }
+        })
+    }
+
+    /// Returns `value()[offset..offset + len]`, reporting a corruption error
+    /// instead of panicking when the range falls outside the buffer.
+    fn checked_slice(&self, offset: usize, len: usize) -> Result<&'a [u8]> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.value().get(offset..end))
+            .ok_or_else(|| {
+                Error::InvalidDataType(format!(
+                    "corrupted JSON: range {}..{}+{} out of bounds of {} bytes",
+                    offset,
+                    offset,
+                    len,
+                    self.value().len()
+                ))
+            })
+    }
+
+    /// Returns `value()[offset..]` with the same error reporting as
+    /// [`checked_slice`](JsonRef::checked_slice).
+    fn checked_tail(&self, offset: usize) -> Result<&'a [u8]> {
+        self.value().get(offset..).ok_or_else(|| {
+            Error::InvalidDataType(format!(
+                "corrupted JSON: offset {} out of bounds of {} bytes",
+                offset,
+                self.value().len()
+            ))
+        })
+    }
+
+    /// Checked variant of [`val_entry_get`](JsonRef::val_entry_get) backing
+    /// the `try_*` accessors: validates the value entry and every offset it
+    /// carries before slicing.
+    pub fn try_val_entry_get(&self, val_entry_off: usize) -> Result<JsonRef<'a>> {
+        let entry_len = if self.is_small() {
+            SMALL_VALUE_ENTRY_LEN
+        } else {
+            VALUE_ENTRY_LEN
+        };
+        let entry = self.checked_slice(val_entry_off, entry_len)?;
+        let type_code = entry[0];
+        let (val_type, child_small) = match type_code {
+            JSON_TYPE_CODE_SMALL_OBJECT => (JsonType::Object, true),
+            JSON_TYPE_CODE_SMALL_ARRAY => (JsonType::Array, true),
+            _ => (type_code.try_into()?, false),
+        };
+        let val_offset = if self.is_small() {
+            NumberCodec::decode_u16_le(&entry[TYPE_LEN..]) as usize
+        } else {
+            NumberCodec::decode_u32_le(&entry[TYPE_LEN..]) as usize
+        };
+        Ok(match val_type {
+            JsonType::Literal => JsonRef::new(
+                val_type,
+                self.checked_slice(val_entry_off + TYPE_LEN, LITERAL_LEN)?,
+            ),
+            JsonType::U64 | JsonType::I64 | JsonType::Double => {
+                JsonRef::new(val_type, self.checked_slice(val_offset, NUMBER_LEN)?)
+            }
+            JsonType::String => {
+                let (str_len, len_len) =
+                    NumberCodec::try_decode_var_u64(self.checked_tail(val_offset)?)?;
+                JsonRef::new(
+                    val_type,
+                    self.checked_slice(val_offset, str_len as usize + len_len)?,
+                )
+            }
+            JsonType::Opaque => {
+                let (opaque_bytes_len, len_len) =
+                    NumberCodec::try_decode_var_u64(self.checked_tail(val_offset + TYPE_LEN)?)?;
+                JsonRef::new(
+                    val_type,
+                    self.checked_slice(val_offset, opaque_bytes_len as usize + len_len + 1)?,
+                )
+            }
+            JsonType::Date | JsonType::Datetime | JsonType::Timestamp => {
+                JsonRef::new(val_type, self.checked_slice(val_offset, TIME_LEN)?)
+            }
+            JsonType::Time => JsonRef::new(val_type, self.checked_slice(val_offset, DURATION_LEN)?),
+            _ => {
+                if child_small {
+                    let data_size = NumberCodec::decode_u16_le(
+                        self.checked_slice(val_offset + SMALL_ELEMENT_COUNT_LEN, SMALL_SIZE_LEN)?,
+                    ) as usize;
+                    if data_size < SMALL_HEADER_LEN {
+                        return Err(Error::InvalidDataType(format!(
+                            "corrupted JSON: container size {} below header size",
+                            data_size
+                        )));
+                    }
+                    JsonRef::new_small(val_type, self.checked_slice(val_offset, data_size)?)
+                } else {
+                    let data_size = NumberCodec::decode_u32_le(
+                        self.checked_slice(val_offset + ELEMENT_COUNT_LEN, SIZE_LEN)?,
+                    ) as usize;
+                    if data_size < HEADER_LEN {
+                        return Err(Error::InvalidDataType(format!(
+                            "corrupted JSON: container size {} below header size",
+                            data_size
+                        )));
+                    }
+                    JsonRef::new(val_type, self.checked_slice(val_offset, data_size)?)
+                }
             }
         })
     }
 
+    /// Checked counterpart of [`object_get_key`](JsonRef::object_get_key):
+    /// validates the key entry and the key range it describes against the
+    /// buffer before slicing.
+    pub fn try_object_get_key(&self, i: usize) -> Result<&'a [u8]> {
+        let (key_off, key_len) = if self.is_small() {
+            let entry =
+                self.checked_slice(SMALL_HEADER_LEN + i * SMALL_KEY_ENTRY_LEN, SMALL_KEY_ENTRY_LEN)?;
+            (
+                NumberCodec::decode_u16_le(entry) as usize,
+                NumberCodec::decode_u16_le(&entry[SMALL_KEY_OFFSET_LEN..]) as usize,
+            )
+        } else {
+            let entry = self.checked_slice(HEADER_LEN + i * KEY_ENTRY_LEN, KEY_ENTRY_LEN)?;
+            (
+                NumberCodec::decode_u32_le(entry) as usize,
+                NumberCodec::decode_u16_le(&entry[KEY_OFFSET_LEN..]) as usize,
+            )
+        };
+        self.checked_slice(key_off, key_len)
+    }
+
+    /// Walks the whole document through the checked accessors, verifying
+    /// that every type code, offset and length is consistent with the
+    /// buffer, so a corrupted document (e.g. an offset table thrown off by a
+    /// damaged key length) surfaces as a clean error up front instead of a
+    /// panic at use time. Object keys are additionally required to be in
+    /// strictly ascending order, as the binary search over them assumes.
+    pub fn validate(&self) -> Result<()> {
+        match self.get_type() {
+            JsonType::Object => {
+                let header_len = if self.is_small() {
+                    SMALL_HEADER_LEN
+                } else {
+                    HEADER_LEN
+                };
+                self.checked_slice(0, header_len)?;
+                let elem_count = self.get_elem_count();
+                let mut prev_key: Option<&[u8]> = None;
+                for i in 0..elem_count {
+                    let key = self.try_object_get_key(i)?;
+                    if prev_key.map_or(false, |prev| prev >= key) {
+                        return Err(Error::InvalidDataType(format!(
+                            "corrupted JSON: object keys out of order at index {}",
+                            i
+                        )));
+                    }
+                    prev_key = Some(key);
+                    self.try_object_get_val(i)?.validate()?;
+                }
+                Ok(())
+            }
+            JsonType::Array => {
+                let header_len = if self.is_small() {
+                    SMALL_HEADER_LEN
+                } else {
+                    HEADER_LEN
+                };
+                self.checked_slice(0, header_len)?;
+                for i in 0..self.get_elem_count() {
+                    self.try_array_get_elem(i)?.validate()?;
+                }
+                Ok(())
+            }
+            JsonType::Literal => self.checked_slice(0, LITERAL_LEN).map(|_| ()),
+            JsonType::I64 | JsonType::U64 | JsonType::Double => {
+                self.checked_slice(0, NUMBER_LEN).map(|_| ())
+            }
+            JsonType::String => {
+                let (str_len, len_len) = NumberCodec::try_decode_var_u64(self.value())?;
+                self.checked_slice(len_len, str_len as usize).map(|_| ())
+            }
+            JsonType::Opaque => {
+                let (bytes_len, len_len) =
+                    NumberCodec::try_decode_var_u64(self.checked_tail(TYPE_LEN)?)?;
+                self.checked_slice(TYPE_LEN + len_len, bytes_len as usize)
+                    .map(|_| ())
+            }
+            JsonType::Date | JsonType::Datetime | JsonType::Timestamp => {
+                self.checked_slice(0, TIME_LEN).map(|_| ())
+            }
+            JsonType::Time => self.checked_slice(0, DURATION_LEN).map(|_| ()),
+        }
+    }
+
+    /// Rebuilds the document in the large binary format.
+    ///
+    /// Containers are rebuilt recursively, so documents carrying the MySQL
+    /// "small" object/array format (which TiKV decodes but never emits) come
+    /// out in the canonical large format. Scalars are copied as-is.
+    pub fn normalize(&self) -> Result<Json> {
+        match self.get_type() {
+            JsonType::Object => {
+                let elem_count = self.get_elem_count();
+                let mut values = Vec::with_capacity(elem_count);
+                for i in 0..elem_count {
+                    values.push(self.object_get_val(i)?.normalize()?);
+                }
+                let entries = (0..elem_count)
+                    .map(|i| self.object_get_key(i))
+                    .zip(values.iter().map(|v| v.as_ref()))
+                    .collect();
+                Json::from_kv_pairs(entries)
+            }
+            JsonType::Array => {
+                let elem_count = self.get_elem_count();
+                let mut values = Vec::with_capacity(elem_count);
+                for i in 0..elem_count {
+                    values.push(self.array_get_elem(i)?.normalize()?);
+                }
+                Json::from_ref_array(values.iter().map(|v| v.as_ref()).collect())
+            }
+            tp => Ok(Json::new(tp, self.value().to_owned())),
+        }
+    }
+
     /// Returns a raw pointer to the underlying values buffer.
     pub(super) fn as_ptr(&self) -> *const u8 {
         self.value.as_ptr()
@@ -181,6 +469,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_accessors_reject_corruption() {
+        // On a well-formed document the checked accessor agrees with the
+        // unchecked one.
+        let json: Json = r#"["abcdefg", 3.5]"#.parse().unwrap();
+        assert_eq!(
+            json.as_ref().try_array_get_elem(1).unwrap().get_double(),
+            json.as_ref().array_get_elem(1).unwrap().get_double()
+        );
+
+        // Array whose first value entry's offset points past the buffer.
+        let mut bytes = json.as_ref().value().to_vec();
+        let end = bytes.len() as u32;
+        NumberCodec::encode_u32_le(&mut bytes[HEADER_LEN + TYPE_LEN..], end + 1);
+        let corrupted = Json::new(JsonType::Array, bytes);
+        corrupted.as_ref().try_array_get_elem(0).unwrap_err();
+
+        // Object whose first value entry (after the key entries) is corrupted
+        // the same way.
+        let json: Json = r#"{"key": "value"}"#.parse().unwrap();
+        let mut bytes = json.as_ref().value().to_vec();
+        let entry_off = HEADER_LEN + KEY_ENTRY_LEN;
+        let end = bytes.len() as u32;
+        NumberCodec::encode_u32_le(&mut bytes[entry_off + TYPE_LEN..], end + 1);
+        let corrupted = Json::new(JsonType::Object, bytes);
+        corrupted.as_ref().try_object_get_val(0).unwrap_err();
+
+        // A value entry which itself lies outside the buffer.
+        let json: Json = r#"[1]"#.parse().unwrap();
+        let len = json.as_ref().value().len();
+        json.as_ref().try_val_entry_get(len).unwrap_err();
+    }
+
+    #[test]
+    fn test_validate() {
+        // Well-formed documents of every shape pass.
+        let legal = vec![
+            "null",
+            "true",
+            "3",
+            "-3",
+            "18446744073709551615",
+            "3.5",
+            r#""hello, 世界""#,
+            "[]",
+            "[1, [2, 3], null]",
+            "{}",
+            r#"{"a": 1, "b": [true, {"c": "d"}]}"#,
+        ];
+        for s in legal {
+            let json: Json = s.parse().unwrap();
+            json.as_ref().validate().unwrap_or_else(|e| panic!("{}: {}", s, e));
+        }
+
+        // A key length wrapped by a too-long key throws the offset table
+        // off: crafting that corruption by hand must yield a clean error.
+        let json: Json = r#"{"key": "value"}"#.parse().unwrap();
+        let mut bytes = json.as_ref().value().to_vec();
+        NumberCodec::encode_u16_le(&mut bytes[HEADER_LEN + KEY_OFFSET_LEN..], 0xffff);
+        let corrupted = Json::new(JsonType::Object, bytes);
+        corrupted.as_ref().validate().unwrap_err();
+
+        // A corrupted value entry offset is caught as well, including in a
+        // nested container.
+        let json: Json = r#"{"a": [1]}"#.parse().unwrap();
+        let mut bytes = json.as_ref().value().to_vec();
+        let entry_off = HEADER_LEN + KEY_ENTRY_LEN;
+        let end = bytes.len() as u32;
+        NumberCodec::encode_u32_le(&mut bytes[entry_off + TYPE_LEN..], end + 1);
+        let corrupted = Json::new(JsonType::Object, bytes);
+        corrupted.as_ref().validate().unwrap_err();
+
+        // Swapping the key entries of a two-key object breaks the ordering
+        // the binary search relies on.
+        let json: Json = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+        let mut bytes = json.as_ref().value().to_vec();
+        let (left, right) = bytes[HEADER_LEN..HEADER_LEN + 2 * KEY_ENTRY_LEN]
+            .split_at_mut(KEY_ENTRY_LEN);
+        left.swap_with_slice(right);
+        let corrupted = Json::new(JsonType::Object, bytes);
+        corrupted.as_ref().validate().unwrap_err();
+
+        // A scalar cut short is reported, not sliced past the end.
+        let json: Json = "3.5".parse().unwrap();
+        let bytes = json.as_ref().value()[..4].to_vec();
+        let corrupted = Json::new(JsonType::Double, bytes);
+        corrupted.as_ref().validate().unwrap_err();
+    }
+
     #[test]
     fn test_array_get_elem() {
         let mut ctx = EvalContext::default();
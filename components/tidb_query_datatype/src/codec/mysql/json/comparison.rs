@@ -3,6 +3,7 @@
 use std::{
     cmp::{Ord, Ordering},
     f64,
+    hash::{Hash, Hasher},
 };
 
 use super::{super::Result, constants::*, Json, JsonRef, JsonType, ERR_CONVERT_FAILED};
@@ -63,6 +64,108 @@ impl<'a> JsonRef<'a> {
     }
 }
 
+/// All numbers hash through their `f64` form, so the same value stored as
+/// `I64`, `U64` or `Double` lands in one bucket; `+0.0` and `-0.0` compare
+/// equal and must share bits as well. Distinct integers collapsing onto the
+/// same `f64` is just a hash collision.
+fn sem_hash_f64<H: Hasher>(d: f64, state: &mut H) {
+    PRECEDENCE_NUMBER.hash(state);
+    let d = if d == 0.0 { 0.0 } else { d };
+    d.to_bits().hash(state);
+}
+
+impl<'a> JsonRef<'a> {
+    /// Hashes with the equivalence of the comparison below: two values for
+    /// which `partial_cmp` returns `Equal` hash identically, so an
+    /// aggregation hash table keyed by a JSON column groups them together.
+    /// Containers hash structurally, numbers through the canonical form of
+    /// [`sem_hash_f64`], and the remaining scalars by their payload under a
+    /// precedence tag (`Datetime` and `Timestamp` share one, as they share a
+    /// precedence class in the comparison).
+    ///
+    /// The one equivalence this cannot honor is the epsilon window of
+    /// `compare_f64_with_epsilon`: doubles closer than `f64::EPSILON` with
+    /// different bit patterns compare equal yet hash apart. TiDB's
+    /// `BinaryJSON.HashValue` carries the same caveat.
+    pub fn sem_hash<H: Hasher>(&self, state: &mut H) {
+        match self.get_type() {
+            JsonType::Literal => match self.get_literal() {
+                None => PRECEDENCE_NULL.hash(state),
+                Some(b) => {
+                    PRECEDENCE_BOOLEAN.hash(state);
+                    b.hash(state);
+                }
+            },
+            JsonType::I64 => sem_hash_f64(self.get_i64() as f64, state),
+            JsonType::U64 => sem_hash_f64(self.get_u64() as f64, state),
+            JsonType::Double => sem_hash_f64(self.get_double(), state),
+            JsonType::String => {
+                PRECEDENCE_STRING.hash(state);
+                if let Ok(s) = self.get_str_bytes() {
+                    s.hash(state);
+                }
+            }
+            JsonType::Array => {
+                PRECEDENCE_ARRAY.hash(state);
+                let count = self.get_elem_count();
+                count.hash(state);
+                for i in 0..count {
+                    if let Ok(elem) = self.try_array_get_elem(i) {
+                        elem.sem_hash(state);
+                    }
+                }
+            }
+            JsonType::Object => {
+                PRECEDENCE_OBJECT.hash(state);
+                let count = self.get_elem_count();
+                count.hash(state);
+                for i in 0..count {
+                    self.object_get_key(i).hash(state);
+                    if let Ok(val) = self.object_get_val(i) {
+                        val.sem_hash(state);
+                    }
+                }
+            }
+            JsonType::Opaque => {
+                PRECEDENCE_OPAQUE.hash(state);
+                if let Ok(bytes) = self.get_opaque_bytes() {
+                    bytes.hash(state);
+                }
+            }
+            JsonType::Date => {
+                PRECEDENCE_DATE.hash(state);
+                if let Ok(time) = self.get_time() {
+                    time.hash(state);
+                }
+            }
+            JsonType::Datetime | JsonType::Timestamp => {
+                PRECEDENCE_DATETIME.hash(state);
+                if let Ok(time) = self.get_time() {
+                    time.hash(state);
+                }
+            }
+            JsonType::Time => {
+                PRECEDENCE_TIME.hash(state);
+                if let Ok(duration) = self.get_duration() {
+                    duration.to_nanos().hash(state);
+                }
+            }
+        }
+    }
+}
+
+/// A `Json` wrapper whose `Hash` matches the semantic `Eq` of `Json` (see
+/// [`JsonRef::sem_hash`]), so `GROUP BY` on a JSON column can key its
+/// aggregation hash table directly by the value.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct JsonHashKey(pub Json);
+
+impl Hash for JsonHashKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_ref().sem_hash(state)
+    }
+}
+
 impl<'a> Eq for JsonRef<'a> {}
 
 impl<'a> Ord for JsonRef<'a> {
@@ -131,7 +234,7 @@ impl<'a> PartialOrd for JsonRef<'a> {
                     let mut i = 0;
                     while i < left_count && i < right_count {
                         if let (Ok(left_ele), Ok(right_ele)) =
-                            (self.array_get_elem(i), right.array_get_elem(i))
+                            (self.try_array_get_elem(i), right.try_array_get_elem(i))
                         {
                             match left_ele.partial_cmp(&right_ele) {
                                 order @ None
@@ -203,6 +306,10 @@ impl PartialOrd for Json {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::hash_map::DefaultHasher;
+
+    use proptest::prelude::*;
+
     use super::*;
     use crate::{
         codec::{
@@ -429,4 +536,77 @@ mod tests {
             assert_eq!(l.cmp(&r), result)
         }
     }
+
+    fn sem_hash_of(j: &Json) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        j.as_ref().sem_hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Scalars plus nested arrays and objects of them. Doubles come from
+    /// `i32` so they stay outside the epsilon window where equality is not
+    /// hashable.
+    fn arb_json() -> impl Strategy<Value = Json> {
+        let leaf = prop_oneof![
+            Just(Json::none().unwrap()),
+            any::<bool>().prop_map(|b| Json::from_bool(b).unwrap()),
+            any::<i64>().prop_map(|v| Json::from_i64(v).unwrap()),
+            any::<u64>().prop_map(|v| Json::from_u64(v).unwrap()),
+            any::<i32>().prop_map(|v| Json::from_f64(f64::from(v)).unwrap()),
+            "[a-z]{0,8}".prop_map(|s| Json::from_string(s).unwrap()),
+        ];
+        leaf.prop_recursive(3, 24, 4, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4)
+                    .prop_map(|v| Json::from_array(v).unwrap()),
+                prop::collection::btree_map("[a-z]{0,4}", inner, 0..4)
+                    .prop_map(|m| Json::from_object(m).unwrap()),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_sem_hash_consistent_with_eq(a in arb_json(), b in arb_json()) {
+            prop_assert_eq!(sem_hash_of(&a), sem_hash_of(&a.clone()));
+            if a == b {
+                prop_assert_eq!(sem_hash_of(&a), sem_hash_of(&b));
+            }
+        }
+
+        #[test]
+        fn test_sem_hash_number_representations(v in any::<u32>()) {
+            // The same value stored as I64, U64 and Double compares equal
+            // across types and must land in the same hash bucket.
+            let variants = [
+                Json::from_i64(i64::from(v)).unwrap(),
+                Json::from_u64(u64::from(v)).unwrap(),
+                Json::from_f64(f64::from(v)).unwrap(),
+            ];
+            for l in &variants {
+                for r in &variants {
+                    prop_assert_eq!(l, r);
+                    prop_assert_eq!(sem_hash_of(l), sem_hash_of(r));
+                }
+            }
+        }
+
+        #[test]
+        fn test_sem_hash_nested_number_representations(
+            vals in prop::collection::vec(any::<u32>(), 0..8)
+        ) {
+            // Arrays compare elementwise, so mixed storage types inside a
+            // document must still group together.
+            let as_i64 = Json::from_array(
+                vals.iter().map(|&v| Json::from_i64(i64::from(v)).unwrap()).collect(),
+            )
+            .unwrap();
+            let as_u64 = Json::from_array(
+                vals.iter().map(|&v| Json::from_u64(u64::from(v)).unwrap()).collect(),
+            )
+            .unwrap();
+            prop_assert_eq!(&as_i64, &as_u64);
+            prop_assert_eq!(sem_hash_of(&as_i64), sem_hash_of(&as_u64));
+        }
+    }
 }
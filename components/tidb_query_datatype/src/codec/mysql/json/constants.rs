@@ -5,6 +5,13 @@ pub const JSON_LITERAL_NIL: u8 = 0x00;
 pub const JSON_LITERAL_TRUE: u8 = 0x01;
 pub const JSON_LITERAL_FALSE: u8 = 0x02;
 
+// Type codes of the MySQL "small" object/array variants. TiKV only emits
+// the large variants, but data written by other MySQL-compatible tools may
+// carry these; they are accepted for decoding only and get normalized to
+// the large format. See `JsonRef::normalize`.
+pub const JSON_TYPE_CODE_SMALL_OBJECT: u8 = 0x00;
+pub const JSON_TYPE_CODE_SMALL_ARRAY: u8 = 0x02;
+
 // Binary json constants
 pub const TYPE_LEN: usize = 1;
 pub const LITERAL_LEN: usize = 1;
@@ -17,10 +24,21 @@ pub const HEADER_LEN: usize = ELEMENT_COUNT_LEN + SIZE_LEN; // element size + da
 pub const KEY_OFFSET_LEN: usize = U32_LEN;
 pub const KEY_LEN_LEN: usize = U16_LEN;
 pub const KEY_ENTRY_LEN: usize = KEY_OFFSET_LEN + KEY_LEN_LEN;
+// The key length is stored as a u16 in the key entry, so longer keys are
+// unrepresentable; the encoders reject them.
+pub const MAX_OBJECT_KEY_LEN: usize = u16::MAX as usize;
 pub const VALUE_ENTRY_LEN: usize = TYPE_LEN + U32_LEN;
 pub const ELEMENT_COUNT_LEN: usize = U32_LEN;
 pub const SIZE_LEN: usize = U32_LEN;
 
+// The "small" variants use u16 counts, sizes and offsets instead of u32.
+pub const SMALL_ELEMENT_COUNT_LEN: usize = U16_LEN;
+pub const SMALL_SIZE_LEN: usize = U16_LEN;
+pub const SMALL_HEADER_LEN: usize = SMALL_ELEMENT_COUNT_LEN + SMALL_SIZE_LEN;
+pub const SMALL_KEY_OFFSET_LEN: usize = U16_LEN;
+pub const SMALL_KEY_ENTRY_LEN: usize = SMALL_KEY_OFFSET_LEN + KEY_LEN_LEN;
+pub const SMALL_VALUE_ENTRY_LEN: usize = TYPE_LEN + U16_LEN;
+
 // Type precedence for json comparison
 pub const PRECEDENCE_BLOB: i32 = -1;
 pub const PRECEDENCE_BIT: i32 = -2;
@@ -10,6 +10,77 @@ use crate::{
     FieldTypeTp,
 };
 
+/// Incrementally builds the binary value of a JSON array from `JsonRef`
+/// elements in a single pass.
+///
+/// Elements stream into the value buffer as they are pushed; the header and
+/// the absolute value offsets are written once in [`finish`](Self::finish),
+/// so building a large array never re-patches size fields per element. The
+/// output is byte-identical to `Json::from_ref_array`.
+#[derive(Default)]
+pub struct JsonArrayBuilder {
+    value_entries: Vec<u8>,
+    values: Vec<u8>,
+}
+
+impl JsonArrayBuilder {
+    pub fn new() -> JsonArrayBuilder {
+        JsonArrayBuilder::default()
+    }
+
+    /// Reserves room for `total_bytes` of encoded element values, for
+    /// callers that know the total size up front.
+    pub fn reserve_hint(&mut self, total_bytes: usize) {
+        self.values.reserve(total_bytes);
+    }
+
+    /// Appends one element to the array.
+    pub fn push_ref(&mut self, v: &JsonRef<'_>) -> Result<()> {
+        let tp = v.get_type();
+        self.value_entries.write_u8(tp as u8)?;
+        match tp {
+            JsonType::Literal => {
+                self.value_entries.write_u8(v.value()[0])?;
+                let left = U32_LEN - LITERAL_LEN;
+                for _ in 0..left {
+                    self.value_entries.write_u8(JSON_LITERAL_NIL)?;
+                }
+            }
+            _ => {
+                // Relative to the start of the values section; made absolute
+                // in `finish` once the element count is known.
+                self.value_entries.write_u32_le(self.values.len() as u32)?;
+                self.values.write_bytes(v.value())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Finalizes the array: writes the header and patches the value offsets
+    /// to their absolute positions.
+    pub fn finish(self) -> Result<Json> {
+        let JsonArrayBuilder {
+            mut value_entries,
+            values,
+        } = self;
+        let element_count = value_entries.len() / VALUE_ENTRY_LEN;
+        let total_size = ELEMENT_COUNT_LEN + SIZE_LEN + value_entries.len() + values.len();
+        let values_start = (ELEMENT_COUNT_LEN + SIZE_LEN + value_entries.len()) as u32;
+        for entry in value_entries.chunks_exact_mut(VALUE_ENTRY_LEN) {
+            if entry[0] != JsonType::Literal as u8 {
+                let offset = NumberCodec::decode_u32_le(&entry[TYPE_LEN..]);
+                NumberCodec::encode_u32_le(&mut entry[TYPE_LEN..], offset + values_start);
+            }
+        }
+        let mut value = Vec::with_capacity(total_size);
+        value.write_u32_le(element_count as u32)?;
+        value.write_u32_le(total_size as u32)?;
+        value.write_bytes(&value_entries)?;
+        value.write_bytes(&values)?;
+        Ok(Json::new(JsonType::Array, value))
+    }
+}
+
 impl<'a> JsonRef<'a> {
     fn encoded_len(&self) -> usize {
         match self.type_code {
@@ -23,6 +94,9 @@ impl<'a> JsonRef<'a> {
 
 pub trait JsonEncoder: NumberEncoder {
     fn write_json(&mut self, data: JsonRef<'_>) -> Result<()> {
+        // Small containers are decode-only: `read_json` normalizes them to
+        // the large format before they can reach an encoder.
+        debug_assert!(!data.is_small());
         self.write_u8(data.get_type() as u8)?;
         self.write_bytes(data.value()).map_err(Error::from)
     }
@@ -33,6 +107,31 @@ pub trait JsonEncoder: NumberEncoder {
         mut entries: Vec<(&[u8], JsonRef<'_>)>,
     ) -> Result<()> {
         entries.sort_by(|a, b| a.0.cmp(b.0));
+        self.write_json_obj_from_sorted_keys_values(entries)
+    }
+
+    /// Like [`write_json_obj_from_keys_values`], but trusts the caller to
+    /// pass the entries already sorted by key, skipping the re-sort. Callers
+    /// which naturally produce the entries in key order (e.g. merging the
+    /// key-sorted binary objects) should prefer this; the ordering is only
+    /// verified in debug builds, and a violation produces a malformed
+    /// document.
+    ///
+    /// [`write_json_obj_from_keys_values`]:
+    /// JsonEncoder::write_json_obj_from_keys_values
+    fn write_json_obj_from_sorted_keys_values(
+        &mut self,
+        entries: Vec<(&[u8], JsonRef<'_>)>,
+    ) -> Result<()> {
+        debug_assert!(entries.windows(2).all(|w| w[0].0 <= w[1].0));
+        // A longer key would silently wrap the u16 length field and corrupt
+        // the offset table.
+        if entries.iter().any(|(k, _)| k.len() > MAX_OBJECT_KEY_LEN) {
+            return Err(box_err!(
+                "JSON object key too long (max {} bytes)",
+                MAX_OBJECT_KEY_LEN
+            ));
+        }
         // object: element-count size key-entry* value-entry* key* value*
         let element_count = entries.len();
         // key-entry ::= key-offset(uint32) key-length(uint16)
@@ -78,6 +177,14 @@ pub trait JsonEncoder: NumberEncoder {
 
     // See `appendBinaryObject` in TiDB `types/json/binary.go`
     fn write_json_obj(&mut self, data: &BTreeMap<String, Json>) -> Result<()> {
+        // A longer key would silently wrap the u16 length field and corrupt
+        // the offset table.
+        if data.keys().any(|k| k.len() > MAX_OBJECT_KEY_LEN) {
+            return Err(box_err!(
+                "JSON object key too long (max {} bytes)",
+                MAX_OBJECT_KEY_LEN
+            ));
+        }
         // object: element-count size key-entry* value-entry* key* value*
         let element_count = data.len();
         // key-entry ::= key-offset(uint32) key-length(uint16)
@@ -240,7 +347,26 @@ pub trait JsonDecoder: NumberDecoder {
         if self.bytes().is_empty() {
             return Err(box_err!("Cant read json from empty bytes"));
         }
-        let tp: JsonType = self.read_u8()?.try_into()?;
+        let type_byte = self.read_u8()?;
+        // TiKV itself only emits the large container format, but accept the
+        // MySQL "small" format for interoperability and normalize it to the
+        // large format, so downstream never sees a small document.
+        match type_byte {
+            JSON_TYPE_CODE_SMALL_OBJECT | JSON_TYPE_CODE_SMALL_ARRAY => {
+                let tp = if type_byte == JSON_TYPE_CODE_SMALL_OBJECT {
+                    JsonType::Object
+                } else {
+                    JsonType::Array
+                };
+                let value = self.bytes();
+                let data_size =
+                    NumberCodec::decode_u16_le(&value[SMALL_ELEMENT_COUNT_LEN..]) as usize;
+                let value = self.read_bytes(data_size)?;
+                return JsonRef::new_small(tp, value).normalize();
+            }
+            _ => {}
+        }
+        let tp: JsonType = type_byte.try_into()?;
         let value = match tp {
             JsonType::Object | JsonType::Array => {
                 let value = self.bytes();
@@ -308,4 +434,173 @@ mod tests {
             assert_eq!(input_str, output_str);
         }
     }
+
+    #[test]
+    fn test_array_builder_matches_encoder() {
+        let elems: Vec<Json> = vec![
+            "null",
+            "true",
+            "false",
+            "3",
+            "-3",
+            "4.5",
+            r#""hello, 世界""#,
+            "[1, [2, 3]]",
+            r#"{"aa": "bb"}"#,
+        ]
+        .into_iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        let refs: Vec<JsonRef<'_>> = elems.iter().map(|j| j.as_ref()).collect();
+        let mut expected = vec![];
+        expected.write_json_ref_array(&refs).unwrap();
+
+        let mut builder = JsonArrayBuilder::new();
+        builder.reserve_hint(refs.iter().map(|v| v.value().len()).sum());
+        for v in &refs {
+            builder.push_ref(v).unwrap();
+        }
+        let built = builder.finish().unwrap();
+        assert_eq!(built.as_ref().get_type(), JsonType::Array);
+        assert_eq!(built.as_ref().value(), expected.as_slice());
+
+        // `from_ref_array` goes through the builder and stays byte-identical.
+        let from_refs = Json::from_ref_array(refs).unwrap();
+        assert_eq!(from_refs.as_ref().value(), expected.as_slice());
+
+        // The empty array is a bare header.
+        let mut expected = vec![];
+        expected.write_json_ref_array(&[]).unwrap();
+        let built = JsonArrayBuilder::new().finish().unwrap();
+        assert_eq!(built.as_ref().value(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_write_json_obj_from_sorted_keys_values() {
+        let values: Vec<Json> = vec![
+            "1".parse().unwrap(),
+            r#""s""#.parse().unwrap(),
+            "true".parse().unwrap(),
+            "[1, 2]".parse().unwrap(),
+            r#"{"x": null}"#.parse().unwrap(),
+        ];
+        let entries: Vec<(&[u8], JsonRef<'_>)> = [
+            &b"a"[..],
+            &b"aa"[..],
+            &b"b"[..],
+            &b"yyy"[..],
+            &b"z"[..],
+        ]
+        .into_iter()
+        .zip(values.iter().map(|v| v.as_ref()))
+        .collect();
+
+        // The unchecked writer on sorted entries matches the checked writer
+        // on the same entries in any order, byte for byte.
+        let mut sorted = vec![];
+        sorted
+            .write_json_obj_from_sorted_keys_values(entries.clone())
+            .unwrap();
+        let mut shuffled = entries;
+        shuffled.reverse();
+        shuffled.swap(1, 3);
+        let mut checked = vec![];
+        checked.write_json_obj_from_keys_values(shuffled).unwrap();
+        assert_eq!(sorted, checked);
+
+        let mut empty = vec![];
+        empty.write_json_obj_from_sorted_keys_values(vec![]).unwrap();
+        let mut empty_checked = vec![];
+        empty_checked.write_json_obj_from_keys_values(vec![]).unwrap();
+        assert_eq!(empty, empty_checked);
+    }
+
+    #[test]
+    fn test_object_key_too_long() {
+        let value = Json::from_u64(1).unwrap();
+
+        // A 70KB key does not fit the u16 key-length field.
+        let long_key = vec![b'k'; 70 * 1024];
+        let mut buf = vec![];
+        let err = buf
+            .write_json_obj_from_keys_values(vec![(long_key.as_slice(), value.as_ref())])
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("JSON object key too long"),
+            "{}",
+            err
+        );
+
+        // The BTreeMap path rejects it the same way.
+        let map = BTreeMap::from([(
+            String::from_utf8(long_key).unwrap(),
+            Json::from_u64(1).unwrap(),
+        )]);
+        let mut buf = vec![];
+        buf.write_json_obj(&map).unwrap_err();
+
+        // A key of exactly the maximum length still encodes and decodes.
+        let max_key = vec![b'k'; MAX_OBJECT_KEY_LEN];
+        let mut buf = vec![];
+        buf.write_json_obj_from_keys_values(vec![(max_key.as_slice(), value.as_ref())])
+            .unwrap();
+        let json = Json::new(JsonType::Object, buf);
+        assert_eq!(json.as_ref().object_get_key(0), max_key.as_slice());
+        json.as_ref().validate().unwrap();
+    }
+
+    #[test]
+    fn test_decode_small_json() {
+        // Fixture bytes built per the MySQL binary JSON spec
+        // (mysql-server/sql/json_binary.h): the small object (0x00) and
+        // small array (0x02) use u16 counts, sizes and offsets.
+
+        // {"a": 1} with the value stored as int64 at offset 12.
+        let small_object: Vec<u8> = vec![
+            0x00, // small object
+            0x01, 0x00, // element count
+            0x14, 0x00, // size (20)
+            0x0b, 0x00, 0x01, 0x00, // key entry: offset 11, length 1
+            0x09, 0x0c, 0x00, // value entry: int64 at offset 12
+            b'a', // key
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // value
+        ];
+        // [true, "hi"] with the literal inlined in its value entry.
+        let small_array: Vec<u8> = vec![
+            0x02, // small array
+            0x02, 0x00, // element count
+            0x0d, 0x00, // size (13)
+            0x04, 0x01, 0x00, // value entry: literal true, inlined
+            0x0c, 0x0a, 0x00, // value entry: string at offset 10
+            0x02, b'h', b'i', // "hi"
+        ];
+        // [{"a": 1}]: a small object nested in a small array.
+        let mut nested: Vec<u8> = vec![
+            0x02, // small array
+            0x01, 0x00, // element count
+            0x1b, 0x00, // size (27)
+            0x00, 0x07, 0x00, // value entry: small object at offset 7
+        ];
+        nested.extend_from_slice(&small_object[1..]);
+
+        let cases: Vec<(Vec<u8>, &str)> = vec![
+            (small_object, r#"{"a":1}"#),
+            (small_array, r#"[true,"hi"]"#),
+            (nested, r#"[{"a":1}]"#),
+        ];
+
+        for (bytes, expected_str) in cases {
+            let expected: Json = expected_str.parse().unwrap();
+            let output = bytes.as_slice().read_json().unwrap();
+            // The decoded document is normalized to the large format...
+            assert_eq!(output.get_type(), expected.get_type());
+            assert_eq!(output.to_string(), expected.to_string());
+            // ... and re-encodes byte-identical to the large encoding.
+            let mut reencoded = vec![];
+            reencoded.write_json(output.as_ref()).unwrap();
+            let mut large = vec![];
+            large.write_json(expected.as_ref()).unwrap();
+            assert_eq!(reencoded, large, "case {}", expected_str);
+        }
+    }
 }
@@ -9,6 +9,12 @@ impl<'a> JsonRef<'a> {
     /// <https://dev.mysql.com/doc/refman/5.7/en/json-search-functions.html#function_json-contains>
     /// See `ContainsBinaryJSON()` in TiDB `types/json_binary_functions.go`
     pub fn json_contains(&self, target: JsonRef<'_>) -> Result<bool> {
+        if self.is_scalar() {
+            return match self.partial_cmp(&target).unwrap() {
+                Ordering::Equal => Ok(true),
+                _ => Ok(false),
+            };
+        }
         match self.type_code {
             JsonType::Object => {
                 if target.type_code == JsonType::Object {
@@ -49,12 +55,7 @@ impl<'a> JsonRef<'a> {
                     }
                 }
             }
-            _ => {
-                return match self.partial_cmp(&target).unwrap() {
-                    Ordering::Equal => Ok(true),
-                    _ => Ok(false),
-                };
-            }
+            _ => unreachable!(),
         };
         Ok(false)
     }
@@ -104,4 +105,29 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_json_contains_sql_literal_parity() {
+        use crate::codec::datum::Datum;
+        // SQL booleans and NULL go through `into_json_candidate`, so a
+        // boolean 1 matches JSON true but not the number 1, as in MySQL.
+        let mut test_cases = vec![
+            (r#"[true, false]"#, Datum::I64(1), true, true),
+            (r#"[true, false]"#, Datum::I64(1), false, false),
+            (r#"[1, 0]"#, Datum::I64(1), false, true),
+            (r#"[1, 0]"#, Datum::I64(1), true, false),
+            (r#"[null]"#, Datum::Null, false, true),
+            (r#"[true]"#, Datum::Null, false, false),
+        ];
+        for (i, (js, d, is_boolean, expected)) in test_cases.drain(..).enumerate() {
+            let j: Json = js.parse().unwrap();
+            let target = d.into_json_candidate(is_boolean).unwrap();
+            let got = j.as_ref().json_contains(target.as_ref()).unwrap();
+            assert_eq!(
+                got, expected,
+                "#{} expect {:?}, but got {:?}",
+                i, expected, got
+            );
+        }
+    }
 }
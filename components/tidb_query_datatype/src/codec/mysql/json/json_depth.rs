@@ -11,33 +11,23 @@ impl<'a> JsonRef<'a> {
 
 // See `GetElemDepth()` in TiDB `json/binary_function.go`
 fn depth_json(j: &JsonRef<'_>) -> Result<i64> {
-    Ok(match j.get_type() {
-        JsonType::Object => {
-            let length = j.get_elem_count();
-            let mut max_depth = 0;
-            for i in 0..length {
-                let val = j.object_get_val(i)?;
-                let depth = depth_json(&val)?;
-                if depth > max_depth {
-                    max_depth = depth;
-                }
-            }
-            max_depth
-        }
-        JsonType::Array => {
-            let length = j.get_elem_count();
-            let mut max_depth = 0;
-            for i in 0..length {
-                let val = j.array_get_elem(i)?;
-                let depth = depth_json(&val)?;
-                if depth > max_depth {
-                    max_depth = depth;
-                }
-            }
-            max_depth
+    if j.is_scalar() {
+        return Ok(1);
+    }
+    let length = j.get_elem_count();
+    let mut max_depth = 0;
+    for i in 0..length {
+        let val = if j.get_type() == JsonType::Object {
+            j.object_get_val(i)?
+        } else {
+            j.array_get_elem(i)?
+        };
+        let depth = depth_json(&val)?;
+        if depth > max_depth {
+            max_depth = depth;
         }
-        _ => 0,
-    } + 1)
+    }
+    Ok(max_depth + 1)
 }
 
 #[cfg(test)]
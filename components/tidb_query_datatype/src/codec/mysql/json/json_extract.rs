@@ -17,15 +17,12 @@ impl<'a> JsonRef<'a> {
     ///
     /// See `Extract()` in TiDB `json.binary_function.go`
     pub fn extract(&self, path_expr_list: &[PathExpression]) -> Result<Option<Json>> {
-        let mut could_return_multiple_matches = path_expr_list.len() > 1;
+        let could_return_multiple_matches = path_expr_list.len() > 1
+            || path_expr_list
+                .iter()
+                .any(|p| p.contains_any_asterisk() || p.contains_any_range());
 
-        let mut elem_list = Vec::with_capacity(path_expr_list.len());
-        for path_expr in path_expr_list {
-            could_return_multiple_matches |= path_expr.contains_any_asterisk();
-            could_return_multiple_matches |= path_expr.contains_any_range();
-
-            elem_list.append(&mut extract_json(*self, &path_expr.legs)?)
-        }
+        let mut elem_list = self.extract_ref(path_expr_list)?;
 
         if elem_list.is_empty() {
             Ok(None)
@@ -37,6 +34,81 @@ impl<'a> JsonRef<'a> {
             Ok(Some(elem_list.remove(0).to_owned()))
         }
     }
+
+    /// Like `extract`, but returns references into the source document
+    /// instead of copying the matched values, so extracting a large
+    /// sub-document costs nothing. The references stay valid for the
+    /// lifetime of the underlying value, which allows chaining further
+    /// operations on a match without materializing it.
+    ///
+    /// An empty vector means no path matched. Unlike `extract`, matches
+    /// are never autowrapped into an array.
+    pub fn extract_ref(&self, path_expr_list: &[PathExpression]) -> Result<Vec<JsonRef<'a>>> {
+        let mut elem_list = Vec::with_capacity(path_expr_list.len());
+        for path_expr in path_expr_list {
+            elem_list.append(&mut extract_json(*self, &path_expr.legs)?)
+        }
+        Ok(elem_list)
+    }
+}
+
+/// Path expressions prepared for evaluation over many documents: the
+/// decisions which depend only on the paths (whether matches may need
+/// autowrapping into an array) are made once instead of per row.
+pub struct CompiledPathList {
+    paths: Vec<PathExpression>,
+    could_return_multiple_matches: bool,
+}
+
+impl CompiledPathList {
+    pub fn new(paths: Vec<PathExpression>) -> Self {
+        let could_return_multiple_matches = paths.len() > 1
+            || paths
+                .iter()
+                .any(|p| p.contains_any_asterisk() || p.contains_any_range());
+        CompiledPathList {
+            paths,
+            could_return_multiple_matches,
+        }
+    }
+}
+
+/// Evaluates `paths` against a column of documents, appending one result per
+/// document to `out` (`None` rows stay `None`, as do rows without a match).
+/// Each row produces exactly what [`JsonRef::extract`] would, but the path
+/// handling is amortized over the column and the match scratch buffer is
+/// reused across rows, so the executor doesn't pay the per-row setup when
+/// the paths are constant.
+pub fn extract_batch<'a>(
+    docs: &[Option<JsonRef<'a>>],
+    paths: &CompiledPathList,
+    out: &mut Vec<Option<Json>>,
+) -> Result<()> {
+    out.reserve(docs.len());
+    let mut elem_list: Vec<JsonRef<'a>> = Vec::new();
+    for doc in docs {
+        let doc = match doc {
+            Some(doc) => doc,
+            None => {
+                out.push(None);
+                continue;
+            }
+        };
+        elem_list.clear();
+        for path_expr in &paths.paths {
+            elem_list.append(&mut extract_json(*doc, &path_expr.legs)?);
+        }
+        if elem_list.is_empty() {
+            out.push(None);
+        } else if paths.could_return_multiple_matches {
+            out.push(Some(Json::from_array(
+                elem_list.drain(..).map(|j| j.to_owned()).collect(),
+            )?));
+        } else {
+            out.push(Some(elem_list[0].to_owned()));
+        }
+    }
+    Ok(())
 }
 
 #[derive(Eq)]
@@ -205,12 +277,48 @@ mod tests {
         },
         *,
     };
-    use crate::codec::mysql::json::path_expr::{ArrayIndex, PATH_EXPRESSION_CONTAINS_RANGE};
+    use crate::codec::mysql::json::path_expr::{
+        parse_json_path_expr, ArrayIndex, PATH_EXPRESSION_CONTAINS_RANGE,
+    };
 
     fn select_from_left(index: usize) -> PathLeg {
         PathLeg::ArraySelection(ArraySelection::Index(ArrayIndex::Left(index as u32)))
     }
 
+    #[test]
+    fn test_extract_batch_matches_row_by_row() {
+        let docs_src: Vec<Option<Json>> = vec![
+            Some(r#"{"a": {"b": 1}}"#.parse().unwrap()),
+            None,
+            Some("[1, 2, 3]".parse().unwrap()),
+            Some(r#"{"a": [true, false]}"#.parse().unwrap()),
+            Some("7".parse().unwrap()),
+        ];
+        let docs: Vec<Option<JsonRef<'_>>> = docs_src
+            .iter()
+            .map(|d| d.as_ref().map(|j| j.as_ref()))
+            .collect();
+
+        // Single paths, an autowrapping path and a multi-path list.
+        for paths in [
+            vec![parse_json_path_expr("$.a.b").unwrap()],
+            vec![parse_json_path_expr("$[0]").unwrap()],
+            vec![parse_json_path_expr("$.a[*]").unwrap()],
+            vec![
+                parse_json_path_expr("$.a").unwrap(),
+                parse_json_path_expr("$[2]").unwrap(),
+            ],
+        ] {
+            let mut out = Vec::new();
+            extract_batch(&docs, &CompiledPathList::new(paths.clone()), &mut out).unwrap();
+            let expected: Vec<Option<Json>> = docs
+                .iter()
+                .map(|d| d.and_then(|d| d.extract(&paths).unwrap()))
+                .collect();
+            assert_eq!(out, expected, "{:?}", paths);
+        }
+    }
+
     #[test]
     fn test_json_extract() {
         let mut test_cases = vec![
@@ -633,4 +741,35 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_json_extract_ref() {
+        // The returned references borrow from the document only, so they
+        // stay valid after the path expressions are dropped.
+        let j: Json = r#"{"a": {"b": [1, 2, 3]}}"#.parse().unwrap();
+        let sub = {
+            let exprs = vec![parse_json_path_expr("$.a").unwrap()];
+            let mut elem_list = j.as_ref().extract_ref(&exprs).unwrap();
+            assert_eq!(elem_list.len(), 1);
+            elem_list.remove(0)
+        };
+        assert_eq!(sub.to_string(), r#"{"b": [1, 2, 3]}"#);
+
+        // Re-extracting from a borrowed sub-document copies nothing either.
+        let inner = {
+            let exprs = vec![parse_json_path_expr("$.b").unwrap()];
+            let mut elem_list = sub.extract_ref(&exprs).unwrap();
+            assert_eq!(elem_list.len(), 1);
+            elem_list.remove(0)
+        };
+        assert_eq!(inner.to_string(), "[1, 2, 3]");
+
+        // No match yields an empty vector, and matches are not autowrapped.
+        let exprs = vec![parse_json_path_expr("$.c").unwrap()];
+        assert!(j.as_ref().extract_ref(&exprs).unwrap().is_empty());
+        let exprs = vec![parse_json_path_expr("$.a.b[*]").unwrap()];
+        let elem_list = j.as_ref().extract_ref(&exprs).unwrap();
+        assert_eq!(elem_list.len(), 3);
+        assert_eq!(elem_list[0].to_string(), "1");
+    }
 }
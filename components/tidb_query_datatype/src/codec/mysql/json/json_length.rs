@@ -1,12 +1,13 @@
 // Copyright 2019 TiKV Project Authors. Licensed under Apache-2.0.
 
-use super::{super::Result, path_expr::PathExpression, JsonRef, JsonType};
+use super::{super::Result, path_expr::PathExpression, JsonRef};
 
 impl<'a> JsonRef<'a> {
     fn len(&self) -> i64 {
-        match self.get_type() {
-            JsonType::Array | JsonType::Object => self.get_elem_count() as i64,
-            _ => 1,
+        if self.is_container() {
+            self.get_elem_count() as i64
+        } else {
+            1
         }
     }
 
@@ -19,7 +20,19 @@ impl<'a> JsonRef<'a> {
         if path_expr_list.len() == 1 && path_expr_list[0].contains_any_asterisk() {
             return Ok(None);
         }
-        Ok(self.extract(path_expr_list)?.map(|j| j.as_ref().len()))
+        // Borrow the matches instead of copying them out: only their length is
+        // needed. When `extract` would autowrap multiple matches into an
+        // array, the length is simply the number of matches.
+        let could_return_multiple_matches = path_expr_list.len() > 1
+            || path_expr_list
+                .iter()
+                .any(|p| p.contains_any_asterisk() || p.contains_any_range());
+        let elem_list = self.extract_ref(path_expr_list)?;
+        Ok(match elem_list.len() {
+            0 => None,
+            1 if !could_return_multiple_matches => Some(elem_list[0].len()),
+            n => Some(n as i64),
+        })
     }
 }
 
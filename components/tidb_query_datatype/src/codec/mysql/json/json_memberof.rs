@@ -75,4 +75,31 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_member_of_sql_literal_parity() {
+        use crate::codec::datum::Datum;
+        // SQL booleans and NULL map to the JSON literals through
+        // `into_json_candidate`, so `true MEMBER OF ('[true]')` holds while
+        // `1 MEMBER OF ('[true]')` does not, matching MySQL.
+        let mut test_cases = vec![
+            (Datum::I64(1), true, r#"[true]"#, true),
+            (Datum::I64(1), false, r#"[true]"#, false),
+            (Datum::I64(1), false, r#"[1]"#, true),
+            (Datum::I64(0), true, r#"[false]"#, true),
+            (Datum::I64(0), true, r#"[0]"#, false),
+            (Datum::Null, false, r#"[null]"#, true),
+            (Datum::Null, true, r#"[true, false]"#, false),
+        ];
+        for (i, (d, is_boolean, array, expected)) in test_cases.drain(..).enumerate() {
+            let candidate = d.into_json_candidate(is_boolean).unwrap();
+            let array: Json = array.parse().unwrap();
+            let got = candidate.as_ref().member_of(array.as_ref()).unwrap();
+            assert_eq!(
+                got, expected,
+                "#{} expect {:?}, but got {:?}",
+                i, expected, got
+            );
+        }
+    }
 }
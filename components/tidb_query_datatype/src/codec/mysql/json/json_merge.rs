@@ -3,7 +3,10 @@
 use std::collections::BTreeMap;
 
 use super::{Json, JsonRef, JsonType};
-use crate::codec::{Error, Result};
+use crate::{
+    codec::{Error, Result},
+    expr::EvalContext,
+};
 
 impl Json {
     /// `merge` is the implementation for JSON_MERGE in mysql
@@ -45,6 +48,21 @@ impl Json {
         merge_binary_array(&result)
     }
 
+    /// Like [`merge`](Json::merge), but appends a warning through `ctx` for
+    /// every scalar operand that is implicitly autowrapped as an array
+    /// (rule 3), which `merge` does silently.
+    pub fn merge_with_ctx(ctx: &mut EvalContext, bjs: Vec<JsonRef<'_>>) -> Result<Json> {
+        // A single operand is returned as-is, so nothing gets wrapped.
+        if bjs.len() > 1 {
+            for j in &bjs {
+                if !matches!(j.get_type(), JsonType::Object | JsonType::Array) {
+                    ctx.append_warning(Error::json_scalar_autowrapped(j.to_string()));
+                }
+            }
+        }
+        Self::merge(bjs)
+    }
+
     #[allow(clippy::comparison_chain)]
     // See `mergePatchBinaryJSON()` in TiDB `pkg/types/json_binary_functions.go`
     pub fn merge_patch(target: JsonRef<'_>, patch: JsonRef<'_>) -> Result<Json> {
@@ -127,23 +145,50 @@ fn merge_binary_array(elems: &[MergeUnit<'_>]) -> Result<Json> {
 }
 
 // See `mergeBinaryObject()` in TiDB `json/binary_function.go`
+//
+// Binary objects store their keys sorted, so instead of collecting into a
+// `BTreeMap` (and re-sorting on encode) the objects are folded together with
+// a two-way merge walk, which keeps the entries in key order the whole way.
 fn merge_binary_object(objects: &mut Vec<JsonRef<'_>>) -> Result<Json> {
-    let mut kv_map: BTreeMap<String, Json> = BTreeMap::new();
+    let mut merged: Vec<(&[u8], MergeUnit<'_>)> = vec![];
     for j in objects.drain(..) {
-        let elem_count = j.get_elem_count();
-        for i in 0..elem_count {
-            let key = j.object_get_key(i);
-            let val = j.object_get_val(i)?;
-            let key = String::from_utf8(key.to_owned()).map_err(Error::from)?;
-            if let Some(old) = kv_map.remove(&key) {
-                let new = Json::merge(vec![old.as_ref(), val])?;
-                kv_map.insert(key, new);
-            } else {
-                kv_map.insert(key, val.to_owned());
+        merged = merge_sorted_entries(merged, j)?;
+    }
+    let entries = merged.iter().map(|(k, v)| (*k, v.as_ref())).collect();
+    Json::from_sorted_kv_pairs(entries)
+}
+
+// Merges the entries of the binary object `right` into the key-sorted
+// `left`, preserving the ordering. Values present on both sides are merged
+// recursively per the JSON_MERGE rules.
+fn merge_sorted_entries<'a>(
+    left: Vec<(&'a [u8], MergeUnit<'a>)>,
+    right: JsonRef<'a>,
+) -> Result<Vec<(&'a [u8], MergeUnit<'a>)>> {
+    let right_count = right.get_elem_count();
+    let mut out = Vec::with_capacity(left.len() + right_count);
+    let mut left = left.into_iter().peekable();
+    let mut i = 0;
+    while i < right_count {
+        let right_key = right.object_get_key(i);
+        match left.peek() {
+            Some((left_key, _)) if *left_key < right_key => {
+                out.push(left.next().unwrap());
+            }
+            Some((left_key, _)) if *left_key == right_key => {
+                let (left_key, left_val) = left.next().unwrap();
+                let new = Json::merge(vec![left_val.as_ref(), right.object_get_val(i)?])?;
+                out.push((left_key, MergeUnit::Owned(new)));
+                i += 1;
+            }
+            _ => {
+                out.push((right_key, MergeUnit::Ref(right.object_get_val(i)?)));
+                i += 1;
             }
         }
     }
-    Json::from_object(kv_map)
+    out.extend(left);
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -212,4 +257,33 @@ mod tests {
             assert_eq!(res, expect);
         }
     }
+
+    #[test]
+    fn test_merge_with_ctx() {
+        // (operands, expected warnings)
+        let test_cases = vec![
+            (vec![r#"{"a": 1}"#, r#"{"b": 2}"#], 0),
+            (vec![r#"[1]"#, r#"[2]"#], 0),
+            // Scalars are autowrapped as arrays before merging.
+            (vec![r#"[1]"#, r#"4"#], 1),
+            (vec![r#"{"a": 1}"#, r#"4"#], 1),
+            (vec!["1", "2", "3"], 3),
+        ];
+        for (i, (operands, warning_cnt)) in test_cases.into_iter().enumerate() {
+            let mut ctx = EvalContext::default();
+            let jsons = operands
+                .iter()
+                .map(|s| s.parse::<Json>().unwrap())
+                .collect::<Vec<Json>>();
+            let expected = Json::merge(jsons.iter().map(|j| j.as_ref()).collect()).unwrap();
+            let res =
+                Json::merge_with_ctx(&mut ctx, jsons.iter().map(|j| j.as_ref()).collect()).unwrap();
+            assert_eq!(res, expected, "#{} result mismatch", i);
+            assert_eq!(
+                ctx.warnings.warning_cnt, warning_cnt,
+                "#{} expect {} warnings but got {:?}",
+                i, warning_cnt, ctx.warnings.warnings
+            );
+        }
+    }
 }
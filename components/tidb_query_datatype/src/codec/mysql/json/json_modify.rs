@@ -1,6 +1,13 @@
 // Copyright 2017 TiKV Project Authors. Licensed under Apache-2.0.
 
-use super::{super::Result, modifier::BinaryModifier, path_expr::PathExpression, Json, JsonRef};
+use super::{
+    super::{Error, Result},
+    json_extract::extract_json,
+    modifier::BinaryModifier,
+    path_expr::PathExpression,
+    Json, JsonRef,
+};
+use crate::expr::EvalContext;
 
 /// `ModifyType` is for modify a JSON.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -51,6 +58,63 @@ impl<'a> JsonRef<'a> {
         }
         Ok(res)
     }
+
+    /// Like [`modify`](JsonRef::modify), but appends a warning through `ctx`
+    /// for every path that resolves to nothing and therefore leaves the
+    /// document untouched, which `modify` silently ignores. MySQL lets the
+    /// client inspect these as warnings.
+    pub fn modify_with_ctx(
+        &self,
+        ctx: &mut EvalContext,
+        path_expr_list: &[PathExpression],
+        values: Vec<Json>,
+        mt: ModifyType,
+    ) -> Result<Json> {
+        if path_expr_list.len() != values.len() {
+            return Err(box_err!(
+                "Incorrect number of parameters: expected: {:?}, found {:?}",
+                values.len(),
+                path_expr_list.len()
+            ));
+        }
+        for expr in path_expr_list {
+            if expr.contains_any_asterisk() || expr.contains_any_range() {
+                return Err(box_err!(
+                    "Invalid path expression: expected no asterisk, found {:?}",
+                    expr
+                ));
+            }
+        }
+        let mut res = self.to_owned();
+        for (expr, value) in path_expr_list.iter().zip(values.into_iter()) {
+            if !modify_applies(res.as_ref(), expr, mt)? {
+                ctx.append_warning(Error::missing_json_value(format!("{:?}", expr)));
+            }
+            let modifier = BinaryModifier::new(res.as_ref());
+            res = match mt {
+                ModifyType::Insert => modifier.insert(expr, value)?,
+                ModifyType::Replace => modifier.replace(expr, value)?,
+                ModifyType::Set => modifier.set(expr, value)?,
+            };
+        }
+        Ok(res)
+    }
+}
+
+// Whether a `modify` with `mt` at `path` can take effect on `j`. Mirrors the
+// no-op rules in `BinaryModifier`: a replace needs the full path to exist,
+// while an insert or a set can also create a new last leg under an existing
+// parent.
+fn modify_applies(j: JsonRef<'_>, path: &PathExpression, mt: ModifyType) -> Result<bool> {
+    let target_exists = !extract_json(j, &path.legs)?.is_empty();
+    Ok(match mt {
+        ModifyType::Replace => target_exists,
+        ModifyType::Insert | ModifyType::Set => {
+            target_exists
+                || (!path.legs.is_empty()
+                    && !extract_json(j, &path.legs[..path.legs.len() - 1])?.is_empty())
+        }
+    })
 }
 
 #[cfg(test)]
@@ -229,4 +293,86 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_json_modify_null_member() {
+        // Storing an explicit JSON null is not a removal: the member stays
+        // present with a Literal-null value, and `remove` stays the only way
+        // to delete it.
+        let json: Json = r#"{"a": 1}"#.parse().unwrap();
+        let path = parse_json_path_expr("$.a").unwrap();
+        let null = Json::none().unwrap();
+
+        let set = json
+            .as_ref()
+            .modify(&[path.clone()], vec![null.clone()], ModifyType::Set)
+            .unwrap();
+        assert_eq!(set, r#"{"a": null}"#.parse().unwrap());
+        // Path extraction still finds the member...
+        let extracted = extract_json(set.as_ref(), &path.legs).unwrap();
+        assert_eq!(extracted.len(), 1);
+        assert_eq!(extracted[0].to_owned(), null);
+        // ...while removing it leaves nothing behind.
+        let removed = set.as_ref().remove(&[path.clone()]).unwrap();
+        assert_eq!(removed, "{}".parse().unwrap());
+        assert!(
+            extract_json(removed.as_ref(), &path.legs)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Insert and replace store the same explicit null.
+        let inserted = json
+            .as_ref()
+            .modify(
+                &[parse_json_path_expr("$.b").unwrap()],
+                vec![null.clone()],
+                ModifyType::Insert,
+            )
+            .unwrap();
+        assert_eq!(inserted, r#"{"a": 1, "b": null}"#.parse().unwrap());
+        let replaced = json
+            .as_ref()
+            .modify(&[path], vec![null], ModifyType::Replace)
+            .unwrap();
+        assert_eq!(replaced, r#"{"a": null}"#.parse().unwrap());
+    }
+
+    #[test]
+    fn test_json_modify_with_ctx() {
+        // (json, path, value, mt, expected warnings)
+        let test_cases = vec![
+            (r#"{"a": [3, 4]}"#, "$.a[2]", "5", ModifyType::Set, 0),
+            // The path without last leg doesn't exist: nothing changes.
+            (r#"{"a": [3, 4]}"#, "$.b[1]", "3", ModifyType::Set, 1),
+            (r#"{"a": [3, 4]}"#, "$.a[2].b", "3", ModifyType::Set, 1),
+            // Replace needs the full path to exist.
+            (r#"{"a": [3, 4]}"#, "$.a[2]", "30", ModifyType::Replace, 1),
+            // Insert on an existing value is a deliberate no-op, not a
+            // missing path.
+            (r#"{"a": [3, 4]}"#, "$.a[0]", "30", ModifyType::Insert, 0),
+            // The empty path can never be inserted into.
+            (r#"{}"#, "$", "1", ModifyType::Insert, 1),
+        ];
+        for (i, (json, path, value, mt, warning_cnt)) in test_cases.into_iter().enumerate() {
+            let mut ctx = EvalContext::default();
+            let json: Json = json.parse().unwrap();
+            let path = parse_json_path_expr(path).unwrap();
+            let value: Json = value.parse().unwrap();
+            let expected = json
+                .as_ref()
+                .modify(&[path.clone()], vec![value.clone()], mt)
+                .unwrap();
+            let result = json
+                .as_ref()
+                .modify_with_ctx(&mut ctx, &[path], vec![value], mt)
+                .unwrap();
+            assert_eq!(result, expected, "#{} result mismatch", i);
+            assert_eq!(
+                ctx.warnings.warning_cnt, warning_cnt,
+                "#{} expect {} warnings but got {:?}",
+                i, warning_cnt, ctx.warnings.warnings
+            );
+        }
+    }
 }
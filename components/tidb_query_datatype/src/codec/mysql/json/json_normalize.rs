@@ -0,0 +1,138 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::{collections::BTreeMap, str};
+
+use super::{super::Result, Json, JsonRef, JsonType};
+
+/// Rewrites a double carrying no fractional part into the matching integer
+/// type, preferring `I64` like the text parser does. Values out of integer
+/// range (or with a fraction, or non-finite) are kept as doubles, so the
+/// rewrite is always lossless.
+fn normalize_f64(d: f64) -> Result<Json> {
+    if d.fract() == 0.0 {
+        // `i64::MAX as f64` rounds up to 2^63 and `u64::MAX as f64` to 2^64,
+        // both exactly representable, so exclusive bounds keep the casts in
+        // range: the largest admitted doubles are 2^63 - 1024 and 2^64 - 2048.
+        if d >= i64::MIN as f64 && d < i64::MAX as f64 {
+            return Json::from_i64(d as i64);
+        }
+        if d >= 0.0 && d < u64::MAX as f64 {
+            return Json::from_u64(d as u64);
+        }
+    }
+    Json::from_f64(d)
+}
+
+impl<'a> JsonRef<'a> {
+    /// See [`Json::normalize_numbers`].
+    pub fn normalize_numbers(&self) -> Result<Json> {
+        match self.get_type() {
+            JsonType::Double => normalize_f64(self.get_double()),
+            JsonType::Array => {
+                let elem_count = self.get_elem_count();
+                let mut elems = Vec::with_capacity(elem_count);
+                for i in 0..elem_count {
+                    elems.push(self.array_get_elem(i)?.normalize_numbers()?);
+                }
+                Json::from_array(elems)
+            }
+            JsonType::Object => {
+                let elem_count = self.get_elem_count();
+                let mut map = BTreeMap::new();
+                for i in 0..elem_count {
+                    let key = str::from_utf8(self.object_get_key(i))?.to_owned();
+                    map.insert(key, self.object_get_val(i)?.normalize_numbers()?);
+                }
+                Json::from_object(map)
+            }
+            _ => Ok(self.to_owned()),
+        }
+    }
+}
+
+impl Json {
+    /// Recursively rewrites `Double` values whose fraction is zero and which
+    /// fit an integer type into `I64`/`U64`, leaving every other value
+    /// untouched.
+    ///
+    /// Documents produced through the f64 path store `5.0` where the source
+    /// system emits `5`; the two compare equal but do not encode to the same
+    /// bytes, which breaks byte-level deduplication. Normalizing makes such
+    /// documents encode exactly like their integer-sourced counterparts.
+    pub fn normalize_numbers(&self) -> Result<Json> {
+        self.as_ref().normalize_numbers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Json;
+    use crate::codec::mysql::json::JsonType;
+
+    #[test]
+    fn test_normalize_numbers() {
+        // (input, expected text after normalization)
+        let mut test_cases = vec![
+            ("5.0", "5"),
+            ("-5.0", "-5"),
+            ("0.0", "0"),
+            ("3.5", "3.5"),
+            ("-3.5", "-3.5"),
+            ("[5.0, 3.5, \"a\", null, true]", "[5, 3.5, \"a\", null, true]"),
+            (
+                "{\"a\": 5.0, \"b\": {\"c\": [1.0, 2.5]}}",
+                "{\"a\": 5, \"b\": {\"c\": [1, 2.5]}}",
+            ),
+            // 2^63 only fits u64.
+            ("9.223372036854776e18", "9223372036854775808"),
+            // Out of u64 range: stays a double instead of converting lossily.
+            ("1e20", "1e20"),
+            ("-1e20", "-1e20"),
+        ];
+        for (i, (input, expected)) in test_cases.drain(..).enumerate() {
+            let j: Json = input.parse().unwrap();
+            let normalized = j.normalize_numbers().unwrap();
+            assert_eq!(
+                normalized.to_string(),
+                expected,
+                "#{} input {}",
+                i,
+                input
+            );
+        }
+
+        // The rewritten document encodes byte-identically to one parsed from
+        // integer literals.
+        let from_double: Json = "[5.0]".parse().unwrap();
+        let from_int: Json = "[5]".parse().unwrap();
+        assert_ne!(from_double.as_ref().value(), from_int.as_ref().value());
+        let normalized = from_double.normalize_numbers().unwrap();
+        assert_eq!(normalized.as_ref().value(), from_int.as_ref().value());
+
+        // An integer candidate is contained in the normalized array.
+        let five: Json = "5".parse().unwrap();
+        assert!(
+            normalized
+                .as_ref()
+                .json_contains(five.as_ref())
+                .unwrap()
+        );
+
+        // Large doubles survive untouched.
+        let large: Json = "1e20".parse().unwrap();
+        let normalized = large.normalize_numbers().unwrap();
+        assert_eq!(normalized.as_ref().get_type(), JsonType::Double);
+        assert_eq!(normalized, large);
+    }
+
+    #[test]
+    fn test_parse_text_normalize() {
+        let normalized = Json::parse_text_opt(b"{\"a\": [5.0, 2.5]}", true, false).unwrap();
+        let expected: Json = "{\"a\": [5, 2.5]}".parse().unwrap();
+        assert_eq!(normalized.as_ref().value(), expected.as_ref().value());
+
+        // Without the flag the double is kept as-is.
+        let plain = Json::parse_text_opt(b"[5.0]", false, false).unwrap();
+        assert_eq!(plain.to_string(), "[5.0]");
+    }
+}
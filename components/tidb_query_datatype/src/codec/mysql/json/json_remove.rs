@@ -1,6 +1,13 @@
 // Copyright 2017 TiKV Project Authors. Licensed under Apache-2.0.
 
-use super::{super::Result, modifier::BinaryModifier, path_expr::PathExpression, Json, JsonRef};
+use super::{
+    super::{Error, Result},
+    json_extract::extract_json,
+    modifier::BinaryModifier,
+    path_expr::PathExpression,
+    Json, JsonRef,
+};
+use crate::expr::EvalContext;
 
 impl<'a> JsonRef<'a> {
     /// Removes elements from Json,
@@ -20,6 +27,31 @@ impl<'a> JsonRef<'a> {
         }
         Ok(res)
     }
+
+    /// Like [`remove`](JsonRef::remove), but appends a warning through `ctx`
+    /// for every path that matches nothing and therefore removes nothing,
+    /// which `remove` silently ignores.
+    pub fn remove_with_ctx(
+        &self,
+        ctx: &mut EvalContext,
+        path_expr_list: &[PathExpression],
+    ) -> Result<Json> {
+        if path_expr_list.iter().any(|expr| {
+            expr.legs.is_empty() || expr.contains_any_asterisk() || expr.contains_any_range()
+        }) {
+            return Err(box_err!("Invalid path expression"));
+        }
+
+        let mut res = self.to_owned();
+        for expr in path_expr_list {
+            if extract_json(res.as_ref(), &expr.legs)?.is_empty() {
+                ctx.append_warning(Error::missing_json_value(format!("{:?}", expr)));
+            }
+            let modifier = BinaryModifier::new(res.as_ref());
+            res = modifier.remove(&expr.legs)?;
+        }
+        Ok(res)
+    }
 }
 
 #[cfg(test)]
@@ -71,4 +103,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_json_remove_with_ctx() {
+        // (json, path, expected warnings)
+        let test_cases = vec![
+            (r#"{"a": [3, 4]}"#, "$.a[0]", 0),
+            // The path matches nothing: nothing is removed.
+            (r#"{"a": [3, 4]}"#, "$.b[1]", 1),
+            (r#"{"a": [3, 4]}"#, "$.a[0].b", 1),
+        ];
+        for (i, (json, path, warning_cnt)) in test_cases.into_iter().enumerate() {
+            let mut ctx = EvalContext::default();
+            let json: Json = json.parse().unwrap();
+            let path = parse_json_path_expr(path).unwrap();
+            let expected = json.as_ref().remove(&[path.clone()]).unwrap();
+            let result = json.as_ref().remove_with_ctx(&mut ctx, &[path]).unwrap();
+            assert_eq!(result, expected, "#{} result mismatch", i);
+            assert_eq!(
+                ctx.warnings.warning_cnt, warning_cnt,
+                "#{} expect {} warnings but got {:?}",
+                i, warning_cnt, ctx.warnings.warnings
+            );
+        }
+    }
 }
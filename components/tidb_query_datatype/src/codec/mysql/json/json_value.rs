@@ -0,0 +1,307 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use super::{
+    super::{Error, Result},
+    path_expr::PathExpression,
+    JsonRef, JsonType,
+};
+use crate::{
+    codec::{
+        convert::{ConvertTo, ToInt},
+        datum::Datum,
+        mysql::{Decimal, Time, MAX_FSP},
+    },
+    expr::EvalContext,
+    FieldTypeTp,
+};
+
+/// The `RETURNING` type of a [`JsonRef::value_at`] extraction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueReturnType {
+    Signed,
+    Unsigned,
+    Double,
+    Decimal,
+    String,
+    Datetime,
+}
+
+/// What [`JsonRef::value_at`] does when the path matches nothing
+/// (`ON EMPTY`) or when the cast of the matched value fails (`ON ERROR`).
+#[derive(Clone, Debug)]
+pub enum ValueBehavior {
+    /// `NULL ON EMPTY/ERROR`, the MySQL default.
+    Null,
+    /// `DEFAULT <literal> ON EMPTY/ERROR`. The literal is supplied by the
+    /// caller already cast to the `RETURNING` type.
+    Default(Datum),
+    /// `ERROR ON EMPTY/ERROR`.
+    Error,
+}
+
+impl ValueBehavior {
+    /// The datum the behavior substitutes, or the given error for
+    /// [`ValueBehavior::Error`].
+    fn apply(&self, err: Error) -> Result<Datum> {
+        match self {
+            ValueBehavior::Null => Ok(Datum::Null),
+            ValueBehavior::Default(datum) => Ok(datum.clone()),
+            ValueBehavior::Error => Err(err),
+        }
+    }
+}
+
+impl<'a> JsonRef<'a> {
+    /// Extracts the value at `path` cast to `return_type`, the way MySQL's
+    /// `JSON_VALUE(doc, path RETURNING type ... ON EMPTY ... ON ERROR)`
+    /// does: a path matching nothing triggers `on_empty`, a failing cast
+    /// triggers `on_error`. The path must identify at most one value, so
+    /// wildcards and ranges are rejected.
+    pub fn value_at(
+        &self,
+        path: &PathExpression,
+        return_type: ValueReturnType,
+        on_empty: &ValueBehavior,
+        on_error: &ValueBehavior,
+        ctx: &mut EvalContext,
+    ) -> Result<Datum> {
+        if path.contains_any_asterisk() || path.contains_any_range() {
+            return Err(box_err!(
+                "Invalid path expression: expected no asterisk, found {:?}",
+                path
+            ));
+        }
+        let matches = self.extract_ref(std::slice::from_ref(path))?;
+        let matched = match matches.first() {
+            Some(matched) => matched,
+            None => return on_empty.apply(Error::missing_json_value(format!("{:?}", path))),
+        };
+        match matched.cast_to(return_type, ctx) {
+            Ok(datum) => Ok(datum),
+            Err(e) => on_error.apply(e),
+        }
+    }
+
+    /// Casts one matched value to the `RETURNING` type through the
+    /// conversions the CAST expressions use.
+    fn cast_to(&self, return_type: ValueReturnType, ctx: &mut EvalContext) -> Result<Datum> {
+        Ok(match return_type {
+            ValueReturnType::Signed => Datum::I64(self.to_int(ctx, FieldTypeTp::LongLong)?),
+            ValueReturnType::Unsigned => Datum::U64(self.to_uint(ctx, FieldTypeTp::LongLong)?),
+            ValueReturnType::Double => {
+                let val: f64 = self.convert(ctx)?;
+                Datum::F64(val)
+            }
+            ValueReturnType::Decimal => {
+                let val: Decimal = self.convert(ctx)?;
+                Datum::Dec(val)
+            }
+            // MySQL returns the unquoted text of the value, not its JSON
+            // rendering.
+            ValueReturnType::String => Datum::Bytes(self.unquote()?.into_bytes()),
+            ValueReturnType::Datetime => Datum::Time(self.cast_to_datetime(ctx)?),
+        })
+    }
+
+    /// Temporal values are taken as they are; strings are parsed like a
+    /// datetime literal. Everything else has no datetime interpretation.
+    fn cast_to_datetime(&self, ctx: &mut EvalContext) -> Result<Time> {
+        match self.get_type() {
+            JsonType::Date | JsonType::Datetime | JsonType::Timestamp => self.get_time(),
+            JsonType::String => Time::parse_datetime(ctx, self.get_str()?, MAX_FSP, true),
+            _ => Err(Error::truncated_wrong_val("DATETIME", self.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{
+        super::{path_expr::parse_json_path_expr, Json},
+        *,
+    };
+    use crate::{
+        codec::error::{ERR_MISSING_JSON_VALUE, ERR_TRUNCATE_WRONG_VALUE},
+        expr::EvalConfig,
+    };
+
+    fn ctx() -> EvalContext {
+        // Strict: cast failures surface as errors instead of warnings, so
+        // the ON ERROR behavior is what decides the result.
+        EvalContext::new(Arc::new(EvalConfig::new()))
+    }
+
+    #[test]
+    fn test_value_at() {
+        let mut ctx = ctx();
+        let doc: Json = r#"{
+            "int": -5,
+            "uint": 5,
+            "float": 4.5,
+            "dec": "3.14",
+            "str": "tikv",
+            "dt": "2020-02-29 10:30:45",
+            "obj": {"a": 1},
+            "arr": [1, 2]
+        }"#
+        .parse()
+        .unwrap();
+        let doc = doc.as_ref();
+        let expected_time = Time::parse_datetime(&mut ctx, "2020-02-29 10:30:45", MAX_FSP, true)
+            .unwrap();
+
+        // Every RETURNING type over a value it accepts.
+        let cases = vec![
+            ("$.int", ValueReturnType::Signed, Datum::I64(-5)),
+            ("$.uint", ValueReturnType::Unsigned, Datum::U64(5)),
+            ("$.float", ValueReturnType::Double, Datum::F64(4.5)),
+            (
+                "$.dec",
+                ValueReturnType::Decimal,
+                Datum::Dec("3.14".parse().unwrap()),
+            ),
+            // The string comes back unquoted.
+            (
+                "$.str",
+                ValueReturnType::String,
+                Datum::Bytes(b"tikv".to_vec()),
+            ),
+            // Containers keep their JSON text.
+            (
+                "$.arr",
+                ValueReturnType::String,
+                Datum::Bytes(b"[1,2]".to_vec()),
+            ),
+            ("$.dt", ValueReturnType::Datetime, Datum::Time(expected_time)),
+        ];
+        for (path, return_type, expected) in cases {
+            let path = parse_json_path_expr(path).unwrap();
+            let got = doc
+                .value_at(
+                    &path,
+                    return_type,
+                    &ValueBehavior::Error,
+                    &ValueBehavior::Error,
+                    &mut ctx,
+                )
+                .unwrap();
+            assert_eq!(got, expected, "{:?} {:?}", path, return_type);
+        }
+
+        // A wildcard path is rejected whatever the document holds.
+        let wildcard = parse_json_path_expr("$.obj.*").unwrap();
+        doc.value_at(
+            &wildcard,
+            ValueReturnType::Signed,
+            &ValueBehavior::Null,
+            &ValueBehavior::Null,
+            &mut ctx,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn test_value_at_on_empty_and_on_error() {
+        let mut ctx = ctx();
+        let doc: Json = r#"{"obj": {"a": 1}, "str": "not a number"}"#.parse().unwrap();
+        let doc = doc.as_ref();
+        let missing = parse_json_path_expr("$.missing").unwrap();
+        let dt_default =
+            Time::parse_datetime(&mut ctx, "2021-01-01 00:00:00", MAX_FSP, true).unwrap();
+
+        // Per RETURNING type: the DEFAULT literal of that type and a path
+        // whose value fails the cast. RETURNING CHAR has no failing cast:
+        // every value has a JSON text.
+        let cases = vec![
+            (ValueReturnType::Signed, Datum::I64(42), Some("$.obj")),
+            (ValueReturnType::Unsigned, Datum::U64(42), Some("$.obj")),
+            (ValueReturnType::Double, Datum::F64(1.5), Some("$.obj")),
+            (
+                ValueReturnType::Decimal,
+                Datum::Dec(Decimal::from(42i64)),
+                Some("$.obj"),
+            ),
+            (
+                ValueReturnType::String,
+                Datum::Bytes(b"fallback".to_vec()),
+                None,
+            ),
+            (
+                ValueReturnType::Datetime,
+                Datum::Time(dt_default),
+                Some("$.str"),
+            ),
+        ];
+        for (return_type, default, err_path) in cases {
+            // ON EMPTY: NULL, the DEFAULT literal, or the error.
+            let got = doc
+                .value_at(
+                    &missing,
+                    return_type,
+                    &ValueBehavior::Null,
+                    &ValueBehavior::Error,
+                    &mut ctx,
+                )
+                .unwrap();
+            assert_eq!(got, Datum::Null, "{:?}", return_type);
+            let got = doc
+                .value_at(
+                    &missing,
+                    return_type,
+                    &ValueBehavior::Default(default.clone()),
+                    &ValueBehavior::Error,
+                    &mut ctx,
+                )
+                .unwrap();
+            assert_eq!(got, default, "{:?}", return_type);
+            let err = doc
+                .value_at(
+                    &missing,
+                    return_type,
+                    &ValueBehavior::Error,
+                    &ValueBehavior::Null,
+                    &mut ctx,
+                )
+                .unwrap_err();
+            assert_eq!(err.code(), ERR_MISSING_JSON_VALUE, "{:?}", return_type);
+
+            // ON ERROR, on a path that matches but does not cast.
+            let err_path = match err_path {
+                Some(path) => parse_json_path_expr(path).unwrap(),
+                None => continue,
+            };
+            let got = doc
+                .value_at(
+                    &err_path,
+                    return_type,
+                    &ValueBehavior::Error,
+                    &ValueBehavior::Null,
+                    &mut ctx,
+                )
+                .unwrap();
+            assert_eq!(got, Datum::Null, "{:?}", return_type);
+            let got = doc
+                .value_at(
+                    &err_path,
+                    return_type,
+                    &ValueBehavior::Error,
+                    &ValueBehavior::Default(default.clone()),
+                    &mut ctx,
+                )
+                .unwrap();
+            assert_eq!(got, default, "{:?}", return_type);
+            let err = doc
+                .value_at(
+                    &err_path,
+                    return_type,
+                    &ValueBehavior::Error,
+                    &ValueBehavior::Error,
+                    &mut ctx,
+                )
+                .unwrap_err();
+            assert_eq!(err.code(), ERR_TRUNCATE_WRONG_VALUE, "{:?}", return_type);
+        }
+    }
+}
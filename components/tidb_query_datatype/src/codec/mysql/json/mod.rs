@@ -54,6 +54,11 @@
 //!                             // lengths up to 127, 2 bytes to represent
 //!                             // lengths up to 16383, and so on...
 //! ```
+//!
+//! TiKV only ever emits the large object/array types. The MySQL "small"
+//! types (0x00/0x02, with u16 counts and offsets) are accepted when
+//! decoding documents written by other tools and are normalized to the
+//! large format, so a `Json` in memory is always in the large format.
 
 mod binary;
 mod comparison;
@@ -64,6 +69,8 @@ mod jcodec;
 mod modifier;
 mod path_expr;
 mod serde;
+mod to_array;
+mod type_histogram;
 // json functions
 mod json_contains;
 mod json_depth;
@@ -73,9 +80,11 @@ mod json_length;
 mod json_memberof;
 mod json_merge;
 mod json_modify;
+mod json_normalize;
 mod json_remove;
 mod json_type;
 pub mod json_unquote;
+mod json_value;
 
 use std::{
     collections::BTreeMap,
@@ -88,9 +97,13 @@ use constants::{JSON_LITERAL_FALSE, JSON_LITERAL_NIL, JSON_LITERAL_TRUE};
 use tikv_util::is_even;
 
 pub use self::{
-    jcodec::{JsonDatumPayloadChunkEncoder, JsonDecoder, JsonEncoder},
+    comparison::JsonHashKey,
+    jcodec::{JsonArrayBuilder, JsonDatumPayloadChunkEncoder, JsonDecoder, JsonEncoder},
+    json_extract::{extract_batch, CompiledPathList},
     json_modify::ModifyType,
+    json_value::{ValueBehavior, ValueReturnType},
     path_expr::{parse_json_path_expr, PathExpression},
+    type_histogram::TypeHistogram,
 };
 use super::super::{datum::Datum, Error, Result};
 use crate::{
@@ -137,17 +150,50 @@ impl TryFrom<u8> for JsonType {
 #[derive(Clone, Copy, Debug)]
 pub struct JsonRef<'a> {
     type_code: JsonType,
+    // Whether the referred container is in the MySQL "small" format
+    // (u16 counts and offsets). TiKV never emits this format; such
+    // references are decode-only and normalized to the large format
+    // before they may escape the codec. See `JsonRef::normalize`.
+    small: bool,
     // Referred value
     value: &'a [u8],
 }
 
 impl<'a> JsonRef<'a> {
     pub fn new(type_code: JsonType, value: &[u8]) -> JsonRef<'_> {
-        JsonRef { type_code, value }
+        JsonRef {
+            type_code,
+            small: false,
+            value,
+        }
+    }
+
+    /// Creates a reference to a container in the MySQL "small" format.
+    pub(crate) fn new_small(type_code: JsonType, value: &[u8]) -> JsonRef<'_> {
+        debug_assert!(matches!(type_code, JsonType::Object | JsonType::Array));
+        JsonRef {
+            type_code,
+            small: true,
+            value,
+        }
+    }
+
+    /// Returns whether the referred container is in the MySQL "small"
+    /// format.
+    pub(crate) fn is_small(&self) -> bool {
+        self.small
     }
 
     /// Returns an owned Json via copying
     pub fn to_owned(&self) -> Json {
+        if self.small {
+            // Owning a small container rebuilds it in the large format, so
+            // the rest of TiKV only ever works on large documents. Corrupt
+            // bytes fall back to a raw copy and get rejected on access.
+            if let Ok(normalized) = self.normalize() {
+                return normalized;
+            }
+        }
         Json {
             type_code: self.type_code,
             value: self.value.to_owned(),
@@ -159,6 +205,18 @@ impl<'a> JsonRef<'a> {
         self.type_code
     }
 
+    /// Returns whether the JSON value is an object or an array, i.e. holds
+    /// nested elements.
+    pub fn is_container(&self) -> bool {
+        matches!(self.type_code, JsonType::Object | JsonType::Array)
+    }
+
+    /// Returns whether the JSON value is a scalar. Opaque and temporal values
+    /// count as scalars like any other non-container type.
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+
     /// Returns the underlying value slice
     pub fn value(&self) -> &'a [u8] {
         self.value
@@ -192,8 +250,12 @@ impl<'a> JsonRef<'a> {
     //
     // See `GetElemCount()` in TiDB `json/binary.go`
     pub(crate) fn get_elem_count(&self) -> usize {
-        assert!((self.type_code == JsonType::Object) | (self.type_code == JsonType::Array));
-        NumberCodec::decode_u32_le(self.value()) as usize
+        assert!(self.is_container());
+        if self.small {
+            NumberCodec::decode_u16_le(self.value()) as usize
+        } else {
+            NumberCodec::decode_u32_le(self.value()) as usize
+        }
     }
 
     // Returns `None` if the JSON value is `null`. Otherwise, returns
@@ -381,9 +443,12 @@ impl Json {
 
     /// Creates a `array` JSON from a collection of `JsonRef`
     pub fn from_ref_array(array: Vec<JsonRef<'_>>) -> Result<Self> {
-        let mut value = vec![];
-        value.write_json_ref_array(&array)?;
-        Ok(Self::new(JsonType::Array, value))
+        let mut builder = JsonArrayBuilder::new();
+        builder.reserve_hint(array.iter().map(|v| v.value().len()).sum());
+        for v in &array {
+            builder.push_ref(v)?;
+        }
+        builder.finish()
     }
 
     /// Creates a `array` JSON from a collection of `Json`
@@ -400,6 +465,15 @@ impl Json {
         Ok(Self::new(JsonType::Object, value))
     }
 
+    /// Creates a `object` JSON from key-value pairs which are already sorted
+    /// by key, skipping the re-sort of [`from_kv_pairs`](Json::from_kv_pairs).
+    /// The ordering is only verified in debug builds.
+    pub fn from_sorted_kv_pairs(entries: Vec<(&[u8], JsonRef<'_>)>) -> Result<Self> {
+        let mut value = vec![];
+        value.write_json_obj_from_sorted_keys_values(entries)?;
+        Ok(Self::new(JsonType::Object, value))
+    }
+
     /// Creates a `object` JSON from key-value pairs in BTreeMap
     pub fn from_object(map: BTreeMap<String, Json>) -> Result<Self> {
         let mut value = vec![];
@@ -439,6 +513,7 @@ impl Json {
     pub fn as_ref(&self) -> JsonRef<'_> {
         JsonRef {
             type_code: self.type_code,
+            small: false,
             value: self.value.as_slice(),
         }
     }
@@ -647,6 +722,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scalar_classification() {
+        let mut ctx = EvalContext::new(Arc::new(EvalConfig::default_for_test()));
+        let date = Time::parse_date(&mut ctx, "2020-01-01").unwrap();
+        let datetime = Time::parse_datetime(&mut ctx, "2020-01-01 10:10:10", 0, false).unwrap();
+        let timestamp = Time::parse_timestamp(&mut ctx, "2020-01-01 10:10:10", 0, false).unwrap();
+        let duration = Duration::parse(&mut ctx, "10:10:10", 0).unwrap();
+
+        // One value of every scalar `JsonType`: all of them must behave alike
+        // for the attribute functions.
+        let scalars = vec![
+            Json::none().unwrap(),
+            Json::from_bool(true).unwrap(),
+            Json::from_i64(-1).unwrap(),
+            Json::from_u64(1).unwrap(),
+            Json::from_f64(1.5).unwrap(),
+            Json::from_string("tikv".to_owned()).unwrap(),
+            Json::from_opaque(FieldTypeTp::VarString, &[0xAB]).unwrap(),
+            Json::from_time(date).unwrap(),
+            Json::from_time(datetime).unwrap(),
+            Json::from_time(timestamp).unwrap(),
+            Json::from_duration(duration).unwrap(),
+        ];
+        for json in scalars {
+            let r = json.as_ref();
+            assert!(r.is_scalar(), "{:?}", r.get_type());
+            assert!(!r.is_container(), "{:?}", r.get_type());
+            assert_eq!(r.depth().unwrap(), 1, "{:?}", r.get_type());
+            assert_eq!(r.json_length(&[]).unwrap(), Some(1), "{:?}", r.get_type());
+            assert_eq!(r.keys(&[]).unwrap(), None, "{:?}", r.get_type());
+        }
+
+        for json in ["{}".parse::<Json>().unwrap(), "[]".parse::<Json>().unwrap()] {
+            let r = json.as_ref();
+            assert!(r.is_container(), "{:?}", r.get_type());
+            assert!(!r.is_scalar(), "{:?}", r.get_type());
+            assert_eq!(r.depth().unwrap(), 1, "{:?}", r.get_type());
+            assert_eq!(r.json_length(&[]).unwrap(), Some(0), "{:?}", r.get_type());
+        }
+    }
+
     #[test]
     fn test_cast_to_real() {
         let test_cases = vec![
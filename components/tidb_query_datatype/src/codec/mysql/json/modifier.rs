@@ -23,8 +23,10 @@ pub struct BinaryModifier<'a> {
     old: JsonRef<'a>,
     // The ptr point to the memory location of `old.value` that `new_value` should be appended
     to_be_modified_ptr: *const u8,
-    // The new encoded value
-    // TODO(fullstop000): Can we just use Json instead ?
+    // The new encoded value. `None` only means "no pending edit" (the
+    // rebuild copies the old document verbatim); an explicit JSON null is
+    // always `Some` of a Literal-null `Json`, so storing null through
+    // set/insert/replace stays distinct from removal.
     new_value: Option<Json>,
 }
 
@@ -97,7 +99,7 @@ impl<'a> BinaryModifier<'a> {
                         let elem_count = parent_node.get_elem_count();
                         let mut elems = Vec::with_capacity(elem_count + 1);
                         for i in 0..elem_count {
-                            elems.push(parent_node.array_get_elem(i)?);
+                            elems.push(parent_node.try_array_get_elem(i)?);
                         }
                         // We can ignore the idx in the PathLeg here since we have checked the
                         // path-value existence
@@ -126,7 +128,7 @@ impl<'a> BinaryModifier<'a> {
                             }
                             entries.push((
                                 parent_node.object_get_key(i),
-                                parent_node.object_get_val(i)?,
+                                parent_node.try_object_get_val(i)?,
                             ));
                         }
                     }
@@ -134,7 +136,7 @@ impl<'a> BinaryModifier<'a> {
                         for i in 0..elem_count {
                             entries.push((
                                 parent_node.object_get_key(i),
-                                parent_node.object_get_val(i)?,
+                                parent_node.try_object_get_val(i)?,
                             ));
                         }
                         entries.push((insert_key.as_bytes(), new.as_ref()))
@@ -177,7 +179,7 @@ impl<'a> BinaryModifier<'a> {
                     if let Some(remove_idx) = parent_node.array_get_index(*remove_idx) {
                         for i in 0..elems_count {
                             if i != remove_idx {
-                                elems.push(parent_node.array_get_elem(i)?);
+                                elems.push(parent_node.try_array_get_elem(i)?);
                             }
                         }
 
@@ -194,7 +196,7 @@ impl<'a> BinaryModifier<'a> {
                     for i in 0..elem_count {
                         let key = parent_node.object_get_key(i);
                         if key != remove_key.as_bytes() {
-                            entries.push((key, parent_node.object_get_val(i)?));
+                            entries.push((key, parent_node.try_object_get_val(i)?));
                         }
                     }
                     self.new_value = Some(Json::from_kv_pairs(entries)?);
@@ -268,7 +270,7 @@ impl<'a> BinaryModifier<'a> {
                 // Resolve values
                 for i in 0..elem_count {
                     let val_entry_offset = val_entry_start + i * VALUE_ENTRY_LEN;
-                    self.old = current.val_entry_get(val_entry_offset)?;
+                    self.old = current.try_val_entry_get(val_entry_offset)?;
                     let val_offset = buf.len() - doc_off;
                     // loop until finding the target ptr to be modified
                     let new_tp = self.rebuild_to(buf)?;
@@ -9,7 +9,7 @@ use serde::{
 use serde_json::Serializer as JsonSerializer;
 
 use super::{Json, JsonRef, JsonType};
-use crate::codec::Error;
+use crate::{codec::Error, FieldTypeTp};
 
 /// MySQL formatter follows the implementation in TiDB
 /// https://github.com/pingcap/tidb/blob/master/types/json/binary.go
@@ -150,13 +150,132 @@ impl ToString for Json {
     }
 }
 
+/// The length of the excerpt of the offending input which is embedded into a
+/// JSON text parse error message.
+const PARSE_ERROR_EXCERPT_LEN: usize = 16;
+
+/// Converts serde's 1-based `line` / `column` error location into a byte
+/// offset into `bytes`.
+fn byte_offset(bytes: &[u8], line: usize, column: usize) -> usize {
+    let skipped: usize = bytes
+        .split(|b| *b == b'\n')
+        .take(line.saturating_sub(1))
+        .map(|l| l.len() + 1)
+        .sum();
+    (skipped + column).min(bytes.len())
+}
+
+impl Json {
+    /// Parses a JSON document from `bytes`.
+    ///
+    /// All JSON text parsing (the `FromStr` impl, casts from strings) goes
+    /// through this entry point, so a malformed document always surfaces
+    /// MySQL's `Invalid JSON text` error (code 3140) with the byte position
+    /// of the failure and an excerpt of the input around it, instead of
+    /// serde's line/column format.
+    pub fn parse_text(bytes: &[u8]) -> Result<Self, Error> {
+        Self::parse_text_opt(bytes, false, false)
+    }
+
+    /// Like [`Json::parse_text`], with `normalize_numbers` additionally
+    /// applying [`Json::normalize_numbers`] to the document on ingest, so
+    /// doubles equal to integers come out as integer types, and
+    /// `decode_opaque_values` applying [`Json::decode_opaque_strings`], so
+    /// the text form of opaque values parses back into opaque values.
+    pub fn parse_text_opt(
+        bytes: &[u8],
+        normalize_numbers: bool,
+        decode_opaque_values: bool,
+    ) -> Result<Self, Error> {
+        let json: Json = serde_json::from_slice(bytes).map_err(|err| {
+            // serde reports where parsing stopped, which is one byte past
+            // the offending character (or the end of a truncated document).
+            let stop = byte_offset(bytes, err.line(), err.column());
+            let position = stop.saturating_sub(1);
+            let start = position.min(bytes.len());
+            let end = (start + PARSE_ERROR_EXCERPT_LEN).min(bytes.len());
+            let excerpt = String::from_utf8_lossy(&bytes[start..end]);
+            let mut reason = err.to_string();
+            // Drop serde's trailing ` at line L column C`: the byte position
+            // already carries the location.
+            if let Some(idx) = reason.rfind(" at line ") {
+                reason.truncate(idx);
+            }
+            Error::invalid_json_text(reason, position, &excerpt)
+        })?;
+        let json = if normalize_numbers {
+            json.normalize_numbers()?
+        } else {
+            json
+        };
+        if decode_opaque_values {
+            json.decode_opaque_strings()
+        } else {
+            Ok(json)
+        }
+    }
+
+    /// Recursively rewrites strings of the `base64:type<N>:<base64>` form —
+    /// the text that [`Serialize`] above prints for opaque values — back into
+    /// the opaque values they came from, so a document round-tripped through
+    /// text keeps the types of its opaque members.
+    ///
+    /// Only fully well-formed payloads (a known type code and valid base64)
+    /// are rewritten; anything else stays a plain string, since user data may
+    /// legitimately start with the prefix. This is also why the rewrite is
+    /// opt-in via [`Json::parse_text_opt`] rather than always on.
+    pub fn decode_opaque_strings(&self) -> Result<Self, Error> {
+        self.as_ref().decode_opaque_strings()
+    }
+}
+
+/// The prefix MySQL prints before the base64 payload of an opaque value.
+const OPAQUE_STR_PREFIX: &str = "base64:type";
+
+/// Tries to reinterpret `s` as the text form of an opaque value. Returns
+/// `None` unless the whole string is well formed.
+fn decode_opaque_str(s: &str) -> Option<Json> {
+    let rest = s.strip_prefix(OPAQUE_STR_PREFIX)?;
+    let (code, payload) = rest.split_once(':')?;
+    let typ = FieldTypeTp::from_u8(code.parse::<u8>().ok()?)?;
+    let bytes = base64::decode(payload).ok()?;
+    Json::from_opaque(typ, &bytes).ok()
+}
+
+impl<'a> JsonRef<'a> {
+    /// See [`Json::decode_opaque_strings`].
+    pub fn decode_opaque_strings(&self) -> Result<Json, Error> {
+        match self.get_type() {
+            JsonType::String => match decode_opaque_str(self.get_str()?) {
+                Some(opaque) => Ok(opaque),
+                None => Ok(self.to_owned()),
+            },
+            JsonType::Array => {
+                let elem_count = self.get_elem_count();
+                let mut elems = Vec::with_capacity(elem_count);
+                for i in 0..elem_count {
+                    elems.push(self.array_get_elem(i)?.decode_opaque_strings()?);
+                }
+                Json::from_array(elems)
+            }
+            JsonType::Object => {
+                let elem_count = self.get_elem_count();
+                let mut map = BTreeMap::new();
+                for i in 0..elem_count {
+                    let key = str::from_utf8(self.object_get_key(i))?.to_owned();
+                    map.insert(key, self.object_get_val(i)?.decode_opaque_strings()?);
+                }
+                Json::from_object(map)
+            }
+            _ => Ok(self.to_owned()),
+        }
+    }
+}
+
 impl FromStr for Json {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match serde_json::from_str(s) {
-            Ok(value) => Ok(value),
-            Err(e) => Err(invalid_type!("Illegal Json text: {:?}", e)),
-        }
+        Json::parse_text(s.as_bytes())
     }
 }
 
@@ -249,7 +368,7 @@ impl<'de> Deserialize<'de> for Json {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::FieldTypeTp;
+    use crate::{codec::error::ERR_INVALID_JSON_TEXT, FieldTypeTp};
 
     #[test]
     fn test_from_str_for_object() {
@@ -304,6 +423,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_text_errors() {
+        let cases = vec![
+            (
+                r#"{"a": 12,}"#,
+                r#"Invalid JSON text: "trailing comma" at position 9, near '}'"#,
+            ),
+            (
+                r#"{a: 12}"#,
+                r#"Invalid JSON text: "key must be a string" at position 1, near 'a: 12}'"#,
+            ),
+            (
+                r#"{"a": [1, 2"#,
+                r#"Invalid JSON text: "EOF while parsing a list" at position 10, near '2'"#,
+            ),
+            (
+                "",
+                r#"Invalid JSON text: "EOF while parsing a value" at position 0"#,
+            ),
+        ];
+
+        for (input, expected) in cases {
+            match Json::parse_text(input.as_bytes()).unwrap_err() {
+                Error::Eval(msg, code) => {
+                    assert_eq!(msg, expected, "input: {}", input);
+                    assert_eq!(code, ERR_INVALID_JSON_TEXT, "input: {}", input);
+                }
+                err => panic!("input: {}, unexpected error: {:?}", input, err),
+            }
+        }
+    }
+
     #[test]
     fn test_to_str() {
         let legal_cases = vec![
@@ -339,4 +490,49 @@ mod tests {
             assert_eq!(json.to_string(), json_str);
         }
     }
+
+    #[test]
+    fn test_opaque_round_trip() {
+        let opaques = vec![
+            Json::from_opaque(FieldTypeTp::Blob, &[0xAB, 0xCD, 0xEF]).unwrap(),
+            Json::from_opaque(FieldTypeTp::Bit, &[0b1010_0101]).unwrap(),
+        ];
+        for json in opaques {
+            let text = json.to_string();
+
+            // With the flag the opaque value comes back with its type code
+            // and bytes intact, also when nested in a document.
+            let parsed = Json::parse_text_opt(text.as_bytes(), false, true).unwrap();
+            assert_eq!(parsed, json, "{}", text);
+            let nested = format!(r#"{{"a": [{}]}}"#, text);
+            let parsed = Json::parse_text_opt(nested.as_bytes(), false, true).unwrap();
+            assert_eq!(parsed.to_string(), nested);
+            let elem = parsed
+                .as_ref()
+                .object_get_val(0)
+                .unwrap()
+                .array_get_elem(0)
+                .unwrap()
+                .to_owned();
+            assert_eq!(elem, json, "{}", nested);
+
+            // Without the flag the text parses to a plain string.
+            let parsed = Json::parse_text(text.as_bytes()).unwrap();
+            assert_eq!(parsed.as_ref().get_type(), JsonType::String, "{}", text);
+        }
+
+        // Strings that only look like the form stay strings even with the
+        // flag enabled: unknown type code, invalid base64, missing pieces.
+        let lookalikes = vec![
+            r#""base64:type100:q80=""#,
+            r#""base64:type252:!!!""#,
+            r#""base64:type252""#,
+            r#""base64:type:q80=""#,
+            r#""base64 but not really""#,
+        ];
+        for s in lookalikes {
+            let parsed = Json::parse_text_opt(s.as_bytes(), false, true).unwrap();
+            assert_eq!(parsed.as_ref().get_type(), JsonType::String, "{}", s);
+        }
+    }
 }
@@ -0,0 +1,166 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use super::{super::Result, JsonRef, JsonType, ERR_CONVERT_FAILED};
+
+impl<'a> JsonRef<'a> {
+    /// Decodes an array of homogeneous scalars into a `Vec` in one pass.
+    /// `decode` returns `None` for a non-conforming element, which is turned
+    /// into an error naming the element and its position.
+    fn to_scalar_array<T>(
+        &self,
+        target: &str,
+        mut decode: impl FnMut(JsonRef<'a>) -> Result<Option<T>>,
+    ) -> Result<Vec<T>> {
+        if self.get_type() != JsonType::Array {
+            return Err(invalid_type!(
+                "{} from {} to an array of {}",
+                ERR_CONVERT_FAILED,
+                self.to_string(),
+                target
+            ));
+        }
+        let elem_count = self.get_elem_count();
+        let mut result = Vec::with_capacity(elem_count);
+        for i in 0..elem_count {
+            let elem = self.array_get_elem(i)?;
+            match decode(elem)? {
+                Some(v) => result.push(v),
+                None => {
+                    return Err(invalid_type!(
+                        "{} from {} (element {}) to {}",
+                        ERR_CONVERT_FAILED,
+                        elem.to_string(),
+                        i,
+                        target
+                    ));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Decodes an array of integers into a plain `Vec<i64>`, without
+    /// materializing a `JsonRef` per element at the caller. Unsigned values
+    /// are accepted as long as they fit; anything else (including `true`,
+    /// `false` and `null`) fails, naming the first non-conforming element.
+    pub fn to_i64_array(&self) -> Result<Vec<i64>> {
+        self.to_scalar_array("i64", |elem| {
+            Ok(match elem.get_type() {
+                JsonType::I64 => Some(elem.get_i64()),
+                JsonType::U64 => i64::try_from(elem.get_u64()).ok(),
+                _ => None,
+            })
+        })
+    }
+
+    /// Decodes an array of numbers into a plain `Vec<f64>`. Signed and
+    /// unsigned integers are widened like in a MySQL float context; any
+    /// non-number fails, naming the first non-conforming element.
+    pub fn to_f64_array(&self) -> Result<Vec<f64>> {
+        self.to_scalar_array("f64", |elem| {
+            Ok(match elem.get_type() {
+                JsonType::I64 => Some(elem.get_i64() as f64),
+                JsonType::U64 => Some(elem.get_u64() as f64),
+                JsonType::Double => Some(elem.get_double()),
+                _ => None,
+            })
+        })
+    }
+
+    /// Decodes an array of strings into a plain `Vec<String>`. Elements are
+    /// not quoted or coerced: any non-string fails, naming the first
+    /// non-conforming element.
+    pub fn to_string_array(&self) -> Result<Vec<String>> {
+        self.to_scalar_array("string", |elem| {
+            Ok(match elem.get_type() {
+                JsonType::String => Some(elem.get_str()?.to_owned()),
+                _ => None,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Json;
+
+    #[test]
+    fn test_to_i64_array() {
+        let cases = vec![
+            ("[]", Some(vec![])),
+            ("[1, -2, 3]", Some(vec![1, -2, 3])),
+            ("[9223372036854775807]", Some(vec![i64::MAX])),
+            ("[-9223372036854775808]", Some(vec![i64::MIN])),
+            // An unsigned value beyond i64 does not fit.
+            ("[1, 18446744073709551615]", None),
+            ("[1, 2.5]", None),
+            (r#"[1, "2"]"#, None),
+            // Inlined literals are not integers.
+            ("[1, null, 3]", None),
+            ("[true]", None),
+            ("[[1]]", None),
+            ("3", None),
+        ];
+        for (input, expected) in cases {
+            let j: Json = input.parse().unwrap();
+            let result = j.as_ref().to_i64_array();
+            match expected {
+                Some(array) => assert_eq!(result.unwrap(), array, "input: {}", input),
+                None => assert!(result.is_err(), "input: {}", input),
+            }
+        }
+
+        // The error names the first non-conforming element.
+        let j: Json = "[1, null, 3]".parse().unwrap();
+        let err = j.as_ref().to_i64_array().unwrap_err();
+        assert!(err.to_string().contains("element 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_to_f64_array() {
+        let cases = vec![
+            ("[]", Some(vec![])),
+            ("[1, 2.5, -3]", Some(vec![1.0, 2.5, -3.0])),
+            ("[18446744073709551615]", Some(vec![18446744073709551615.0])),
+            (r#"["x"]"#, None),
+            ("[1.5, false]", None),
+            ("[null]", None),
+            (r#"{"a": 1}"#, None),
+        ];
+        for (input, expected) in cases {
+            let j: Json = input.parse().unwrap();
+            let result = j.as_ref().to_f64_array();
+            match expected {
+                Some(array) => assert_eq!(result.unwrap(), array, "input: {}", input),
+                None => assert!(result.is_err(), "input: {}", input),
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_string_array() {
+        let cases = vec![
+            ("[]", Some(vec![])),
+            (
+                r#"["a", "", "字符"]"#,
+                Some(vec!["a".to_owned(), "".to_owned(), "字符".to_owned()]),
+            ),
+            (r#"["a", 1]"#, None),
+            (r#"["a", true]"#, None),
+            (r#"[["a"]]"#, None),
+            (r#""a""#, None),
+        ];
+        for (input, expected) in cases {
+            let j: Json = input.parse().unwrap();
+            let result = j.as_ref().to_string_array();
+            match expected {
+                Some(array) => assert_eq!(result.unwrap(), array, "input: {}", input),
+                None => assert!(result.is_err(), "input: {}", input),
+            }
+        }
+
+        let j: Json = r#"["a", 1]"#.parse().unwrap();
+        let err = j.as_ref().to_string_array().unwrap_err();
+        assert!(err.to_string().contains("element 1"), "{}", err);
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+use super::{super::Result, JsonRef, JsonType};
+
+/// The number of `JsonType` variants, sizing the count table of
+/// [`TypeHistogram`].
+const JSON_TYPE_COUNT: usize = 12;
+
+/// Maps a type to its slot in [`TypeHistogram`].
+fn type_index(tp: JsonType) -> usize {
+    match tp {
+        JsonType::Object => 0,
+        JsonType::Array => 1,
+        JsonType::Literal => 2,
+        JsonType::I64 => 3,
+        JsonType::U64 => 4,
+        JsonType::Double => 5,
+        JsonType::String => 6,
+        JsonType::Opaque => 7,
+        JsonType::Date => 8,
+        JsonType::Datetime => 9,
+        JsonType::Timestamp => 10,
+        JsonType::Time => 11,
+    }
+}
+
+/// A cheap summary of a JSON document for optimizer statistics: how many
+/// values of each type it contains and how large its containers are.
+///
+/// The container sizes are raw quantile inputs in visit order; turning them
+/// into an actual quantile sketch is left to the consumer, which typically
+/// [`merge`](TypeHistogram::merge)s the histograms of many rows first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TypeHistogram {
+    counts: [u64; JSON_TYPE_COUNT],
+    container_sizes: Vec<u64>,
+}
+
+impl TypeHistogram {
+    /// Returns how many values of type `tp` were counted.
+    pub fn count(&self, tp: JsonType) -> u64 {
+        self.counts[type_index(tp)]
+    }
+
+    /// Returns the total number of counted values.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the element counts of the visited containers.
+    pub fn container_sizes(&self) -> &[u64] {
+        &self.container_sizes
+    }
+
+    /// Folds `other` into this histogram, e.g. to combine the per-row
+    /// histograms of a column.
+    pub fn merge(&mut self, other: TypeHistogram) {
+        for (acc, count) in self.counts.iter_mut().zip(other.counts) {
+            *acc += count;
+        }
+        self.container_sizes.extend(other.container_sizes);
+    }
+}
+
+impl<'a> JsonRef<'a> {
+    /// Summarizes the types and container sizes of this document for
+    /// statistics pushdown.
+    ///
+    /// The walk is iterative and does not descend below `max_depth` (the
+    /// top-level value sits at depth 1), so the cost on a pathological
+    /// document stays bounded; values below the cutoff are not counted at
+    /// all.
+    pub fn type_histogram(&self, max_depth: u32) -> Result<TypeHistogram> {
+        let mut histogram = TypeHistogram::default();
+        if max_depth == 0 {
+            return Ok(histogram);
+        }
+        let mut stack = vec![(*self, 1)];
+        while let Some((value, depth)) = stack.pop() {
+            histogram.counts[type_index(value.get_type())] += 1;
+            if !value.is_container() {
+                continue;
+            }
+            let elem_count = value.get_elem_count();
+            histogram.container_sizes.push(elem_count as u64);
+            if depth >= max_depth {
+                continue;
+            }
+            for i in 0..elem_count {
+                let elem = if value.get_type() == JsonType::Object {
+                    value.object_get_val(i)?
+                } else {
+                    value.array_get_elem(i)?
+                };
+                stack.push((elem, depth + 1));
+            }
+        }
+        Ok(histogram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::Json, *};
+
+    const FIXTURE: &str =
+        r#"{"a": [1, 2.5, "x", null], "b": {"c": true, "d": 18446744073709551615}, "e": -3}"#;
+
+    fn sorted_sizes(histogram: &TypeHistogram) -> Vec<u64> {
+        let mut sizes = histogram.container_sizes().to_vec();
+        sizes.sort_unstable();
+        sizes
+    }
+
+    #[test]
+    fn test_type_histogram() {
+        let j: Json = FIXTURE.parse().unwrap();
+        let histogram = j.as_ref().type_histogram(u32::MAX).unwrap();
+        assert_eq!(histogram.count(JsonType::Object), 2);
+        assert_eq!(histogram.count(JsonType::Array), 1);
+        assert_eq!(histogram.count(JsonType::I64), 2);
+        assert_eq!(histogram.count(JsonType::U64), 1);
+        assert_eq!(histogram.count(JsonType::Double), 1);
+        assert_eq!(histogram.count(JsonType::String), 1);
+        assert_eq!(histogram.count(JsonType::Literal), 2);
+        assert_eq!(histogram.total(), 10);
+        assert_eq!(sorted_sizes(&histogram), vec![2, 3, 4]);
+
+        // A scalar document has one value and no containers.
+        let j: Json = "3".parse().unwrap();
+        let histogram = j.as_ref().type_histogram(u32::MAX).unwrap();
+        assert_eq!(histogram.count(JsonType::I64), 1);
+        assert_eq!(histogram.total(), 1);
+        assert!(histogram.container_sizes().is_empty());
+    }
+
+    #[test]
+    fn test_type_histogram_max_depth() {
+        let j: Json = FIXTURE.parse().unwrap();
+
+        // Nothing is counted with a zero depth budget.
+        let histogram = j.as_ref().type_histogram(0).unwrap();
+        assert_eq!(histogram, TypeHistogram::default());
+
+        // Depth 1 sees the root object but none of its members.
+        let histogram = j.as_ref().type_histogram(1).unwrap();
+        assert_eq!(histogram.count(JsonType::Object), 1);
+        assert_eq!(histogram.total(), 1);
+        assert_eq!(sorted_sizes(&histogram), vec![3]);
+
+        // Depth 2 sees the members but not the containers' contents. The
+        // nested containers still report their sizes.
+        let histogram = j.as_ref().type_histogram(2).unwrap();
+        assert_eq!(histogram.count(JsonType::Object), 2);
+        assert_eq!(histogram.count(JsonType::Array), 1);
+        assert_eq!(histogram.count(JsonType::I64), 1);
+        assert_eq!(histogram.total(), 4);
+        assert_eq!(sorted_sizes(&histogram), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn test_type_histogram_merge() {
+        let left: Json = FIXTURE.parse().unwrap();
+        let right: Json = r#"[false, [7], "y"]"#.parse().unwrap();
+
+        let mut histogram = left.as_ref().type_histogram(u32::MAX).unwrap();
+        histogram.merge(right.as_ref().type_histogram(u32::MAX).unwrap());
+        assert_eq!(histogram.count(JsonType::Object), 2);
+        assert_eq!(histogram.count(JsonType::Array), 3);
+        assert_eq!(histogram.count(JsonType::I64), 3);
+        assert_eq!(histogram.count(JsonType::String), 2);
+        assert_eq!(histogram.count(JsonType::Literal), 3);
+        assert_eq!(histogram.total(), 15);
+        assert_eq!(sorted_sizes(&histogram), vec![1, 2, 3, 3, 4]);
+
+        // The default histogram is the identity of `merge`.
+        let mut merged = TypeHistogram::default();
+        merged.merge(histogram.clone());
+        assert_eq!(merged, histogram);
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2016 TiKV Project Authors. Licensed under Apache-2.0.
 
-use super::Result;
+use self::duration::MICRO_WIDTH;
+use super::{Result, TEN_POW};
 
 /// `UNSPECIFIED_FSP` is the unspecified fractional seconds part.
 pub const UNSPECIFIED_FSP: i8 = -1;
@@ -25,6 +26,68 @@ fn check_fsp(fsp: i8) -> Result<u8> {
     Ok(fsp as u8)
 }
 
+/// Round `frac` with `fsp`, return if there is a carry and the result.
+/// NOTE: we assume that `frac` is less than `100_000_000` and `fsp` is valid.
+/// ```ignore
+/// assert_eq!(123460, round_frac(123456, 5));
+/// assert_eq!(1_000_000, round_frac(999999, 5));
+/// assert_eq!(1230, round_frac(1234, 5)); // .001234, fsp = 5 => .001230
+/// ```
+fn round_frac(frac: u32, fsp: u8) -> (bool, u32) {
+    debug_assert!(frac < 100_000_000);
+    debug_assert!(fsp < 7);
+    if frac < 1_000_000 && fsp == 6 {
+        return (false, frac);
+    }
+
+    let fsp = usize::from(fsp);
+    let width: usize = if frac >= 1_000_000 { 7 } else { 6 };
+    let mask = TEN_POW[width - fsp - 1];
+    let result = (frac / mask + 5) / 10 * mask * if width == 6 { 10 } else { 1 };
+    (result >= 1_000_000, result)
+}
+
+/// Scales a microsecond part down to `fsp` digits, as the fractional part is
+/// printed by `Display` and the numeric string representations.
+/// NOTE: This function assumes that `micro` is less than `1_000_000` and `fsp`
+/// is in range: [0, 6].
+fn scale_micro_to_fsp(micro: u32, fsp: u8) -> u32 {
+    debug_assert!(micro < 1_000_000);
+    debug_assert!(usize::from(fsp) <= MICRO_WIDTH);
+    micro / TEN_POW[MICRO_WIDTH - usize::from(fsp)]
+}
+
+/// Widens ascii `digits` of a fractional part to a microsecond part with `fsp`
+/// digits of precision, rounding the result with one guard digit if `round` is
+/// true. Returns whether the rounding carried into the seconds part along with
+/// the result, or `None` if `digits` is not numeric.
+/// NOTE: This function assumes that `fsp` is in range: [0, 6].
+fn widen_frac_digits(digits: &[u8], fsp: u8, round: bool) -> Option<(bool, u32)> {
+    debug_assert!(usize::from(fsp) <= MICRO_WIDTH);
+    let fsp = usize::from(fsp);
+    let len = digits.len();
+
+    let (digits, len) = if fsp >= len {
+        (digits, len)
+    } else {
+        (&digits[..fsp + round as usize], fsp + round as usize)
+    };
+
+    let frac = digits.iter().try_fold(0u32, |acc, d| {
+        if !d.is_ascii_digit() {
+            return None;
+        }
+        acc.checked_mul(10)
+            .and_then(|t| t.checked_add(u32::from(d - b'0')))
+    })? * TEN_POW[MICRO_WIDTH.saturating_sub(len)];
+
+    Some(if round {
+        round_frac(frac, fsp as u8)
+    } else {
+        (false, frac)
+    })
+}
+
 pub mod binary_literal;
 pub mod charset;
 pub mod decimal;
@@ -39,9 +102,112 @@ pub use self::{
     duration::{Duration, DurationDecoder, DurationEncoder},
     enums::{Enum, EnumDecoder, EnumEncoder, EnumRef},
     json::{
-        parse_json_path_expr, Json, JsonDatumPayloadChunkEncoder, JsonDecoder, JsonEncoder,
-        JsonType, ModifyType, PathExpression,
+        extract_batch, parse_json_path_expr, CompiledPathList, Json, JsonArrayBuilder,
+        JsonDatumPayloadChunkEncoder, JsonDecoder, JsonEncoder, JsonHashKey, JsonType, ModifyType,
+        PathExpression, TypeHistogram, ValueBehavior, ValueReturnType,
     },
     set::{Set, SetRef},
-    time::{Time, TimeDecoder, TimeEncoder, TimeType, Tz},
+    time::{
+        AmbiguityPolicy, FspRounding, IntervalUnit, Time, TimeDecoder, TimeEncoder,
+        TimeParseErrorKind, TimeType, Tz,
+    },
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_micro_to_fsp() {
+        let cases = vec![
+            (123456, 0, 0),
+            (123456, 1, 1),
+            (123456, 2, 12),
+            (123456, 3, 123),
+            (123456, 4, 1234),
+            (123456, 5, 12345),
+            (123456, 6, 123456),
+            (999999, 0, 0),
+            (999999, 6, 999999),
+            (0, 3, 0),
+            (1, 5, 0),
+            (1, 6, 1),
+        ];
+        for (micro, fsp, expected) in cases {
+            assert_eq!(
+                scale_micro_to_fsp(micro, fsp),
+                expected,
+                "micro: {}, fsp: {}",
+                micro,
+                fsp
+            );
+        }
+    }
+
+    #[test]
+    fn test_widen_frac_digits() {
+        let cases = vec![
+            // (digits, fsp, round, carry, expected)
+            (&b""[..], 0, false, false, 0),
+            (b"", 6, true, false, 0),
+            (b"1", 6, false, false, 100000),
+            (b"123456", 6, false, false, 123456),
+            (b"123456", 3, false, false, 123000),
+            (b"1234567", 6, false, false, 123456),
+            (b"123456", 6, true, false, 123456),
+            (b"1234567", 6, true, false, 123457),
+            (b"9999995", 6, true, true, 1000000),
+            (b"999999", 5, true, true, 1000000),
+            (b"45", 1, true, false, 500000),
+            // Only one guard digit takes part in the rounding.
+            (b"4450", 1, true, false, 400000),
+            (b"5", 0, true, true, 1000000),
+            (b"4", 0, true, false, 0),
+            (b"999999", 0, false, false, 0),
+        ];
+        for (digits, fsp, round, carry, expected) in cases {
+            assert_eq!(
+                widen_frac_digits(digits, fsp, round),
+                Some((carry, expected)),
+                "digits: {:?}, fsp: {}, round: {}",
+                digits,
+                fsp,
+                round
+            );
+        }
+
+        assert_eq!(widen_frac_digits(b"12a", 6, false), None);
+
+        // Exhaustively compare against a straightforward model over all fsp
+        // and digit lengths.
+        let digits: &[u8] = b"987604321";
+        for round in [false, true] {
+            for fsp in 0..=6u8 {
+                for len in 0..=digits.len() {
+                    let input = &digits[..len];
+                    let keep = len.min(usize::from(fsp) + round as usize);
+                    // The kept digits, scaled up to a 7-digit fraction.
+                    let mut frac: u64 = 0;
+                    for &d in &input[..keep] {
+                        frac = frac * 10 + u64::from(d - b'0');
+                    }
+                    frac *= 10u64.pow(7 - keep as u32);
+                    if round {
+                        // Round half-up to `fsp` digits.
+                        let step = 10u64.pow(7 - u32::from(fsp));
+                        frac = (frac + step / 2) / step * step;
+                    }
+                    let micro = (frac / 10) as u32;
+                    assert_eq!(
+                        widen_frac_digits(input, fsp, round),
+                        Some((micro >= 1_000_000, micro)),
+                        "digits: {:?}, fsp: {}, round: {}",
+                        input,
+                        fsp,
+                        round
+                    );
+                }
+            }
+        }
+    }
+}
@@ -36,7 +36,7 @@ impl WeekdayExtension for Weekday {
 }
 
 pub trait DateTimeExtension {
-    fn days(&self) -> i32;
+    fn days(&self) -> Option<i32>;
     fn calc_year_week(
         &self,
         monday_first: bool,
@@ -47,14 +47,14 @@ pub trait DateTimeExtension {
     fn week(&self, mode: WeekMode) -> i32;
     fn year_week(&self, mode: WeekMode) -> (i32, i32);
     fn abbr_day_of_month(&self) -> &'static str;
-    fn day_number(&self) -> i32;
-    fn second_number(&self) -> i64;
+    fn day_number(&self) -> Option<i32>;
+    fn second_number(&self) -> Option<i64>;
 }
 
 impl DateTimeExtension for Time {
-    /// returns the day of year starting from 1.
-    /// implements TiDB YearDay().
-    fn days(&self) -> i32 {
+    /// returns the day of year starting from 1, or `None` for zero-in-date
+    /// values. implements TiDB YearDay().
+    fn days(&self) -> Option<i32> {
         self.ordinal()
     }
 
@@ -72,8 +72,13 @@ impl DateTimeExtension for Time {
         first_weekday: bool,
     ) -> (i32, i32) {
         let mut year = self.year() as i32;
-        let daynr = calc_day_number(year, self.month() as i32, self.day() as i32);
-        let mut first_daynr = calc_day_number(year, 1, 1);
+        // Zero-in-date values have no week; `week()` filters them out, but
+        // guard here as well so the day numbers below always exist.
+        if self.month() == 0 || self.day() == 0 {
+            return (year, 0);
+        }
+        let daynr = calc_day_number(year, self.month() as i32, self.day() as i32).unwrap();
+        let mut first_daynr = calc_day_number(year, 1, 1).unwrap();
         let mut weekday = calc_weekday(first_daynr, !monday_first);
         let mut days: i32;
 
@@ -141,25 +146,31 @@ impl DateTimeExtension for Time {
         }
     }
 
-    /// returns the days since 0000-00-00
-    fn day_number(&self) -> i32 {
+    /// returns the days since 0000-00-00, or `None` for zero-in-date values,
+    /// agreeing with [`Time::ordinal`] on what is representable.
+    fn day_number(&self) -> Option<i32> {
         calc_day_number(self.year() as i32, self.month() as i32, self.day() as i32)
     }
 
-    /// returns the seconds since 0000-00-00 00:00:00
-    fn second_number(&self) -> i64 {
-        let days = self.day_number();
-        days as i64 * 86400
-            + self.hour() as i64 * 3600
-            + self.minute() as i64 * 60
-            + self.second() as i64
+    /// returns the seconds since 0000-00-00 00:00:00, or `None` for
+    /// zero-in-date values.
+    fn second_number(&self) -> Option<i64> {
+        let days = self.day_number()?;
+        Some(
+            days as i64 * 86400
+                + self.hour() as i64 * 3600
+                + self.minute() as i64 * 60
+                + self.second() as i64,
+        )
     }
 }
 
-// calculates days since 0000-00-00.
-fn calc_day_number(mut year: i32, month: i32, day: i32) -> i32 {
-    if year == 0 && month == 0 {
-        return 0;
+// calculates days since 0000-00-00. Zero-in-date values (month or day being
+// 0) have no day number; the old special case of returning 0 for year 0 only
+// made `0000-00-00` disagree with `ordinal()`, which also rejects them now.
+pub(crate) fn calc_day_number(mut year: i32, month: i32, day: i32) -> Option<i32> {
+    if month == 0 || day == 0 {
+        return None;
     }
     let mut delsum = 365 * year + 31 * (month - 1) + day;
     if month <= 2 {
@@ -168,7 +179,7 @@ fn calc_day_number(mut year: i32, month: i32, day: i32) -> i32 {
         delsum -= (month * 4 + 23) / 10;
     }
     let temp = ((year / 100 + 1) * 3) / 4;
-    delsum + year / 4 - temp
+    Some(delsum + year / 4 - temp)
 }
 
 /// calculates days in one year, it works with 0 <= year <= 99.
@@ -179,11 +190,97 @@ fn calc_days_in_year(year: i32) -> i32 {
     365
 }
 
+// the day number of 9999-12-31, the largest date the packed representation
+// can hold.
+const MAX_DAY_NUMBER: i32 = 3_652_424;
+
+const DAYS_IN_MONTH: [i32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+// the inverse of `calc_day_number`: splits days since 0000-00-00 back into
+// (year, month, day). This is a port of MySQL's `get_date_from_daynr`,
+// extended downwards to cover year 0 (day number 1 is 0000-01-01), which
+// MySQL rejects but this module stores. Day numbers outside of
+// [1, MAX_DAY_NUMBER] have no representable date and yield `None`.
+pub(crate) fn calc_date_from_day_number(daynr: i32) -> Option<(u32, u32, u32)> {
+    if !(1..=MAX_DAY_NUMBER).contains(&daynr) {
+        return None;
+    }
+
+    // The estimate never overshoots: it divides by the Julian year length,
+    // which is longer than the Gregorian average, so the loop below only
+    // needs to walk forwards.
+    let mut year = daynr * 100 / 36525;
+    let temp = ((year - 1) / 100 + 1) * 3 / 4;
+    let mut day_of_year = daynr - year * 365 - (year - 1) / 4 + temp;
+    let mut days_in_year = calc_days_in_year(year);
+    while day_of_year > days_in_year {
+        day_of_year -= days_in_year;
+        year += 1;
+        days_in_year = calc_days_in_year(year);
+    }
+
+    let mut leap_day = 0;
+    if days_in_year == 366 && day_of_year > 31 + 28 {
+        day_of_year -= 1;
+        if day_of_year == 31 + 28 {
+            leap_day = 1;
+        }
+    }
+
+    let mut month = 0;
+    while day_of_year > DAYS_IN_MONTH[month] {
+        day_of_year -= DAYS_IN_MONTH[month];
+        month += 1;
+    }
+
+    Some((year as u32, month as u32 + 1, (day_of_year + leap_day) as u32))
+}
+
 /// calculates weekday from daynr, returns 0 for Monday, 1 for Tuesday ...
-fn calc_weekday(mut daynr: i32, sunday_first_day: bool) -> i32 {
+pub(crate) fn calc_weekday(mut daynr: i32, sunday_first_day: bool) -> i32 {
     daynr += 5;
     if sunday_first_day {
         daynr += 1;
     }
     daynr % 7
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calc_date_from_day_number_round_trip() {
+        assert_eq!(calc_date_from_day_number(-1), None);
+        assert_eq!(calc_date_from_day_number(0), None);
+        assert_eq!(calc_date_from_day_number(MAX_DAY_NUMBER + 1), None);
+
+        // Exhaustively walk every representable date and check that the
+        // split agrees with `calc_day_number` on its day number.
+        let mut daynr = 0;
+        for year in 0..=9999 {
+            for month in 1..=12 {
+                let days = DAYS_IN_MONTH[month as usize - 1]
+                    + (month == 2 && calc_days_in_year(year) == 366) as i32;
+                for day in 1..=days {
+                    daynr += 1;
+                    assert_eq!(
+                        calc_day_number(year, month, day),
+                        Some(daynr),
+                        "{}-{}-{}",
+                        year,
+                        month,
+                        day
+                    );
+                    assert_eq!(
+                        calc_date_from_day_number(daynr),
+                        Some((year as u32, month as u32, day as u32)),
+                        "day number: {}",
+                        daynr
+                    );
+                }
+            }
+        }
+        assert_eq!(daynr, MAX_DAY_NUMBER);
+    }
+}
@@ -5,6 +5,7 @@ mod tz;
 pub mod weekmode;
 
 use std::{
+    cell::Cell,
     cmp::Ordering,
     convert::{TryFrom, TryInto},
     fmt::Write,
@@ -22,7 +23,12 @@ use crate::{
     codec::{
         convert::ConvertTo,
         data_type::Real,
-        mysql::{check_fsp, Decimal, Duration},
+        mysql::{
+            check_fsp,
+            duration::{MICROS_PER_SEC, MICRO_WIDTH},
+            round_frac, scale_micro_to_fsp, widen_frac_digits, Decimal, Duration, RoundMode,
+            UNSPECIFIED_FSP,
+        },
         Error, Result, TEN_POW,
     },
     expr::{EvalContext, Flag, SqlMode},
@@ -30,7 +36,10 @@ use crate::{
 };
 
 const MIN_TIMESTAMP: i64 = 0;
-pub const MAX_TIMESTAMP: i64 = (1 << 31) - 1;
+/// The latest moment a timestamp accepts: 3001-01-18 23:59:59.999999 UTC,
+/// matching the bound MySQL 8.0.28 raised the type to and the limit TiDB
+/// uses for its range checks, rather than the 2038 rollover of `i32`.
+pub const MAX_TIMESTAMP: i64 = 32_536_771_199;
 const MICRO_WIDTH: usize = 6;
 const MAX_COMPONENTS_LEN: usize = 9;
 pub const MIN_YEAR: u32 = 1901;
@@ -67,6 +76,34 @@ fn last_day_of_month(year: u32, month: u32) -> u32 {
     }
 }
 
+thread_local! {
+    /// The last two answers of [`cached_last_day_of_month`], most recent
+    /// first. Both entries start out as the (correct) answer for `(0, 0)`,
+    /// so a lookup can never observe a day that does not belong to its key.
+    static LAST_DAY_CACHE: Cell<[(u32, u32, u32); 2]> = Cell::new([(0, 0, 31); 2]);
+}
+
+/// `last_day_of_month`, remembering the last two distinct `(year, month)`
+/// answers per thread. Bulk validation (e.g. the date checks of a large
+/// INSERT) asks about long runs of dates from the same month or two, so the
+/// leap-year branch is skipped for almost every row; two entries keep both
+/// sides of a month boundary hot while rounding carries across it.
+fn cached_last_day_of_month(year: u32, month: u32) -> u32 {
+    LAST_DAY_CACHE.with(|cache| {
+        let [front, back] = cache.get();
+        if (front.0, front.1) == (year, month) {
+            return front.2;
+        }
+        let day = if (back.0, back.1) == (year, month) {
+            back.2
+        } else {
+            last_day_of_month(year, month)
+        };
+        cache.set([(year, month, day), front]);
+        day
+    })
+}
+
 /// Round each component.
 /// ```ignore
 /// let mut parts = [2019, 12, 1, 23, 59, 59, 1000000];
@@ -82,7 +119,7 @@ fn round_components(parts: &mut [u32]) -> Option<()> {
     let modulus = [
         u32::MAX,
         12,
-        last_day_of_month(parts[0], parts[1]),
+        cached_last_day_of_month(parts[0], parts[1]),
         // hms[.fraction]
         24,
         60,
@@ -102,6 +139,64 @@ fn round_components(parts: &mut [u32]) -> Option<()> {
     Some(())
 }
 
+/// How to resolve a local time which is ambiguous in its time zone, i.e. one
+/// which occurs twice when clocks are set back at the end of daylight saving
+/// time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmbiguityPolicy {
+    /// Pick the earlier of the two instants, the one still on the daylight
+    /// saving offset. This is the historical behavior of this module and what
+    /// timestamp packing keeps for compatibility.
+    Earliest,
+    /// Pick the later of the two instants, the one on the standard offset.
+    Latest,
+    /// Refuse to guess and report a truncation error.
+    Error,
+}
+
+/// How a fractional part with more digits than the target fsp is brought
+/// down to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FspRounding {
+    /// Round half up, carrying into the seconds part when the fractional
+    /// part overflows: what every entry point taking `round = true` does.
+    #[default]
+    Round,
+    /// Drop the extra digits, never carrying, and append [`Error::truncated`]
+    /// as a warning when a non-zero digit was dropped: MySQL's
+    /// CAST-with-truncate behavior.
+    Truncate,
+}
+
+/// The calendar and clock units understood by [`Time::truncate_to`] and
+/// [`Time::extract`]. The units up to `Microsecond` are ordered from
+/// coarsest to finest; the compound units after them pack several components
+/// into one number (`DayHour` is `ddhh`, `YearMonth` is `yyyymm`, ...) and
+/// only make sense for extraction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntervalUnit {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Microsecond,
+    SecondMicrosecond,
+    MinuteMicrosecond,
+    MinuteSecond,
+    HourMicrosecond,
+    HourSecond,
+    HourMinute,
+    DayMicrosecond,
+    DaySecond,
+    DayMinute,
+    DayHour,
+    YearMonth,
+}
+
 #[inline]
 #[allow(clippy::too_many_arguments)]
 fn chrono_datetime<T: TimeZone>(
@@ -113,6 +208,7 @@ fn chrono_datetime<T: TimeZone>(
     minute: u32,
     second: u32,
     micro: u32,
+    policy: AmbiguityPolicy,
 ) -> Result<DateTime<T>> {
     // NOTE: We are not using `tz::from_ymd_opt` as suggested in chrono's README due
     // to chronotope/chrono-tz #23.
@@ -121,7 +217,14 @@ fn chrono_datetime<T: TimeZone>(
     NaiveDate::from_ymd_opt(year as i32, month, day)
         .and_then(|date| date.and_hms_opt(hour, minute, second))
         .and_then(|t| t.checked_add_signed(chrono::Duration::microseconds(i64::from(micro))))
-        .and_then(|datetime| time_zone.from_local_datetime(&datetime).earliest())
+        .and_then(|datetime| {
+            let local = time_zone.from_local_datetime(&datetime);
+            match policy {
+                AmbiguityPolicy::Earliest => local.earliest(),
+                AmbiguityPolicy::Latest => local.latest(),
+                AmbiguityPolicy::Error => local.single(),
+            }
+        })
         .ok_or_else(Error::truncated)
 }
 
@@ -141,25 +244,31 @@ fn chrono_naive_datetime(
         .ok_or_else(Error::truncated)
 }
 
-/// Round `frac` with `fsp`, return if there is a carry and the result.
-/// NOTE: we assume that `frac` is less than `100_000_000` and `fsp` is valid.
-/// ```ignore
-/// assert_eq!(123460, round_frac(123456, 5));
-/// assert_eq!(1_000_000, round_frac(999999, 5));
-/// assert_eq!(1230, round_frac(1234, 5)); // .001234, fsp = 5 => .001230
-/// ```
-fn round_frac(frac: u32, fsp: u8) -> (bool, u32) {
-    debug_assert!(frac < 100_000_000);
-    debug_assert!(fsp < 7);
-    if frac < 1_000_000 && fsp == 6 {
-        return (false, frac);
-    }
-
-    let fsp = usize::from(fsp);
-    let width: usize = if frac >= 1_000_000 { 7 } else { 6 };
-    let mask = TEN_POW[width - fsp - 1];
-    let result = (frac / mask + 5) / 10 * mask * if width == 6 { 10 } else { 1 };
-    (result >= 1_000_000, result)
+/// A cheap borrow of a time zone.
+///
+/// By convention, functions in this module take `&mut EvalContext` only when
+/// they may append warnings to it. Read-only helpers which merely need the
+/// zone take a `TzRef` instead, so a caller holding a bare [`Tz`] or only a
+/// shared borrow of the context can still use them.
+#[derive(Clone, Copy)]
+pub struct TzRef<'a>(&'a Tz);
+
+impl<'a> From<&'a Tz> for TzRef<'a> {
+    fn from(tz: &'a Tz) -> Self {
+        TzRef(tz)
+    }
+}
+
+impl<'a> From<&'a EvalContext> for TzRef<'a> {
+    fn from(ctx: &'a EvalContext) -> Self {
+        TzRef(&ctx.cfg.tz)
+    }
+}
+
+impl<'a> From<&'a mut EvalContext> for TzRef<'a> {
+    fn from(ctx: &'a mut EvalContext) -> Self {
+        TzRef(&ctx.cfg.tz)
+    }
 }
 
 bitfield! {
@@ -264,6 +373,91 @@ impl Time {
         self.get_day()
     }
 
+    // The raw bitfield setters silently truncate a value to the field width
+    // (month 16 becomes 0), so they stay private to this module. Mutating a
+    // single component from outside goes through the checked `try_set_*`
+    // setters below, which reject an out-of-range value and leave the time
+    // untouched.
+
+    /// Sets the year, rejecting values beyond 9999.
+    #[inline]
+    pub fn try_set_year(&mut self, year: u32) -> Result<()> {
+        if year > 9999 {
+            return Err(Error::truncated_wrong_val("YEAR", year));
+        }
+        self.set_year(year);
+        Ok(())
+    }
+
+    /// Sets the month, rejecting values beyond 12. Month 0 is kept for zero
+    /// dates. The day is not revalidated: set the day after the month when
+    /// changing both.
+    #[inline]
+    pub fn try_set_month(&mut self, month: u32) -> Result<()> {
+        if month > 12 {
+            return Err(Error::truncated_wrong_val("MONTH", month));
+        }
+        self.set_month(month);
+        Ok(())
+    }
+
+    /// Sets the day. When the month is known the day is validated against
+    /// the length of that month (leap years included); otherwise anything up
+    /// to 31 passes. Day 0 is kept for zero dates.
+    #[inline]
+    pub fn try_set_day(&mut self, day: u32) -> Result<()> {
+        let max = if (1..=12).contains(&self.month()) {
+            self.last_day_of_month()
+        } else {
+            31
+        };
+        if day > max {
+            return Err(Error::truncated_wrong_val("DAY", day));
+        }
+        self.set_day(day);
+        Ok(())
+    }
+
+    /// Sets the hour, rejecting values beyond 23.
+    #[inline]
+    pub fn try_set_hour(&mut self, hour: u32) -> Result<()> {
+        if hour > 23 {
+            return Err(Error::truncated_wrong_val("HOUR", hour));
+        }
+        self.set_hour(hour);
+        Ok(())
+    }
+
+    /// Sets the minute, rejecting values beyond 59.
+    #[inline]
+    pub fn try_set_minute(&mut self, minute: u32) -> Result<()> {
+        if minute > 59 {
+            return Err(Error::truncated_wrong_val("MINUTE", minute));
+        }
+        self.set_minute(minute);
+        Ok(())
+    }
+
+    /// Sets the second, rejecting values beyond 59.
+    #[inline]
+    pub fn try_set_second(&mut self, second: u32) -> Result<()> {
+        if second > 59 {
+            return Err(Error::truncated_wrong_val("SECOND", second));
+        }
+        self.set_second(second);
+        Ok(())
+    }
+
+    /// Sets the microsecond part, rejecting values beyond 999999.
+    #[inline]
+    pub fn try_set_micro(&mut self, micro: u32) -> Result<()> {
+        if micro > 999_999 {
+            return Err(Error::truncated_wrong_val("MICROSECOND", micro));
+        }
+        self.set_micro(micro);
+        Ok(())
+    }
+
     /// used to convert period to month
     pub fn period_to_month(period: u64) -> u64 {
         if period == 0 {
@@ -295,9 +489,72 @@ impl Time {
     }
 }
 
+/// Why a datetime string (or component set) failed to parse. The error a
+/// client receives keeps the `ERR_TRUNCATE_WRONG_VALUE` code it always had;
+/// the variant only sharpens the message, so logs and warnings can tell a
+/// bad timezone offset from an out-of-range month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeParseErrorKind {
+    /// The input is empty or all-whitespace.
+    Empty,
+    /// A separator is misplaced or an unexpected character interrupts a
+    /// component.
+    BadSeparator,
+    /// A component is not a number the field can hold.
+    BadNumber,
+    /// More components than the widest accepted format has.
+    TooManyComponents,
+    /// A component lies outside its calendar or clock range.
+    OutOfRangeComponent { which: &'static str },
+    /// The timezone suffix does not denote a valid offset.
+    InvalidTzOffset,
+    /// The trailing word does not name a known time zone, or the zone is
+    /// named twice.
+    InvalidTzName,
+    /// The fractional seconds precision exceeds [`MAX_FSP`].
+    FspTooLarge,
+    /// A zero date (or zero month/day) rejected by the sql mode.
+    ZeroDateForbidden,
+    /// The value lies outside the range a timestamp covers.
+    TimestampOutOfRange,
+    /// Nothing more specific: no accepted format matched the input.
+    Unrecognized,
+}
+
+impl std::fmt::Display for TimeParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeParseErrorKind::Empty => write!(f, "empty datetime string"),
+            TimeParseErrorKind::BadSeparator => {
+                write!(f, "misplaced separator or unexpected character")
+            }
+            TimeParseErrorKind::BadNumber => write!(f, "component is not a valid number"),
+            TimeParseErrorKind::TooManyComponents => write!(f, "too many components"),
+            TimeParseErrorKind::OutOfRangeComponent { which } => {
+                write!(f, "{} is out of range", which)
+            }
+            TimeParseErrorKind::InvalidTzOffset => write!(f, "invalid timezone offset"),
+            TimeParseErrorKind::InvalidTzName => write!(f, "invalid time zone name"),
+            TimeParseErrorKind::FspTooLarge => {
+                write!(f, "fractional seconds precision exceeds {}", MAX_FSP)
+            }
+            TimeParseErrorKind::ZeroDateForbidden => {
+                write!(f, "zero date forbidden by sql mode")
+            }
+            TimeParseErrorKind::TimestampOutOfRange => write!(f, "timestamp out of range"),
+            TimeParseErrorKind::Unrecognized => write!(f, "no datetime format matched"),
+        }
+    }
+}
+
 mod parser {
     use super::*;
 
+    /// The result type of the parsing routines: the reason of a failure is
+    /// kept so the error surfaced to the user (and the warnings in
+    /// non-strict modes) can carry it.
+    pub type ParseResult<T> = std::result::Result<T, TimeParseErrorKind>;
+
     fn bytes_to_u32(input: &[u8]) -> Option<u32> {
         input.iter().try_fold(0u32, |acc, d| {
             d.is_ascii_digit().as_option()?;
@@ -320,6 +577,31 @@ mod parser {
         Some((&input[end..], &input[..end]))
     }
 
+    /// Match at least one ascii alphabetic and return the rest of the slice.
+    /// ```ignore
+    ///  alpha1(b"Jan-2020") == Some((b"-2020", b"Jan"))
+    ///  alpha1(b"-2020") == None
+    /// ```
+    fn alpha1(input: &[u8]) -> Option<(&[u8], &[u8])> {
+        let end = input
+            .iter()
+            .position(|&c| !c.is_ascii_alphabetic())
+            .unwrap_or(input.len());
+        (end != 0).as_option()?;
+        Some((&input[end..], &input[..end]))
+    }
+
+    /// Maps an English month name or its three-letter abbreviation, in any
+    /// case, to the 1-based month number.
+    fn month_name_to_number(token: &[u8]) -> Option<u32> {
+        let token = std::str::from_utf8(token).ok()?;
+        MONTH_NAMES
+            .iter()
+            .chain(MONTH_NAMES_ABBR.iter())
+            .position(|name| name.eq_ignore_ascii_case(token))
+            .map(|idx| (idx % 12) as u32 + 1)
+    }
+
     /// Match at least one space and return the rest of the slice.
     /// ```ignore
     ///  space1(b"    12:32") == Some((b"    ", b"12:32"))
@@ -342,13 +624,64 @@ mod parser {
         Some((&input[end..], &input[..end]))
     }
 
+    /// A fixed-capacity vector of subslices living entirely on the stack.
+    /// The splitter runs for every datetime string a CAST parses, so its
+    /// collections should not hit the heap; `push` reports overflow instead,
+    /// which the splitter treats as one more way for the input to be
+    /// malformed (no accepted format has more than `MAX_COMPONENTS_LEN`
+    /// components).
+    struct StackVec<'a, const N: usize> {
+        buf: [&'a [u8]; N],
+        len: usize,
+    }
+
+    impl<'a, const N: usize> StackVec<'a, N> {
+        fn new() -> Self {
+            StackVec {
+                buf: [&[]; N],
+                len: 0,
+            }
+        }
+
+        fn push(&mut self, slice: &'a [u8]) -> Option<()> {
+            (self.len < N).as_option()?;
+            self.buf[self.len] = slice;
+            self.len += 1;
+            Some(())
+        }
+
+        fn pop(&mut self) -> Option<&'a [u8]> {
+            self.len = self.len.checked_sub(1)?;
+            Some(self.buf[self.len])
+        }
+    }
+
+    impl<'a, const N: usize> std::ops::Deref for StackVec<'a, N> {
+        type Target = [&'a [u8]];
+
+        fn deref(&self) -> &Self::Target {
+            &self.buf[..self.len]
+        }
+    }
+
+    /// `Ok` when `cond` holds, the given failure otherwise; the
+    /// [`ParseResult`] counterpart of the `as_option` idiom the `Option`
+    /// helpers use.
+    fn ensure(cond: bool, kind: TimeParseErrorKind) -> ParseResult<()> {
+        if cond { Ok(()) } else { Err(kind) }
+    }
+
     /// We assume that the `input` is trimmed and is not empty.
     /// ```ignore
     ///  split_components_with_tz(b"2020-12-24T15:37:50+0800")?.1 == Some(480*60)
     /// ```
     /// the second value if not None indicates the offset in seconds of the
     /// timezone parsed
-    fn split_components_with_tz(input: &str) -> Option<(Vec<&[u8]>, Option<i32>)> {
+    fn split_components_with_tz(
+        input: &str,
+    ) -> ParseResult<(StackVec<'_, MAX_COMPONENTS_LEN>, Option<i32>)> {
+        use TimeParseErrorKind::*;
+
         let mut buffer = input.as_bytes();
 
         debug_assert!(
@@ -357,45 +690,55 @@ mod parser {
                 && !buffer.last().unwrap().is_ascii_whitespace()
         );
 
-        let mut components = Vec::with_capacity(MAX_COMPONENTS_LEN);
-        let mut separators = Vec::with_capacity(MAX_COMPONENTS_LEN - 1);
+        let mut components = StackVec::new();
+        let mut separators: StackVec<'_, { MAX_COMPONENTS_LEN - 1 }> = StackVec::new();
 
         while !buffer.is_empty() {
-            let (mut rest, digits): (&[u8], &[u8]) = digit1(buffer)?;
+            // An alphabetic month name may stand in the second position
+            // ("12-Jan-2020", "2020-January-12"); whether it actually names
+            // a month is checked when the components are resolved. Anything
+            // alphabetic elsewhere is still rejected.
+            let (mut rest, component): (&[u8], &[u8]) = match digit1(buffer) {
+                Some(matched) => matched,
+                None if components.len() == 1 && buffer[0].is_ascii_alphabetic() => {
+                    alpha1(buffer).ok_or(BadSeparator)?
+                }
+                None => return Err(BadSeparator),
+            };
 
-            components.push(digits);
+            components.push(component).ok_or(TooManyComponents)?;
 
             if !rest.is_empty() {
                 // If a whitespace is acquired, we expect we have already collected ymd.
                 if rest[0].is_ascii_whitespace() {
-                    (components.len() == 3).as_option()?;
-                    let result = space1(rest)?;
+                    ensure(components.len() == 3, BadSeparator)?;
+                    let result = space1(rest).ok_or(BadSeparator)?;
                     rest = result.0;
-                    separators.push(result.1);
+                    separators.push(result.1).ok_or(TooManyComponents)?;
                 }
                 // If a 'T' is acquired, we expect we have already collected ymd.
                 else if rest[0] == b'T' {
-                    (components.len() == 3).as_option()?;
-                    separators.push(&rest[..1]);
+                    ensure(components.len() == 3, BadSeparator)?;
+                    separators.push(&rest[..1]).ok_or(TooManyComponents)?;
                     rest = &rest[1..];
                 }
                 // If a 'Z' is acquired, we expect that we are parsing timezone now.
                 // the time should be in ISO8601 format, e.g. 2020-10-10T19:27:10Z, so there should
                 // be 6 part ahead or 7 if considering fsp.
                 else if rest[0] == b'Z' {
-                    (components.len() == 6 || components.len() == 7).as_option()?;
-                    separators.push(&rest[..1]);
+                    ensure(components.len() == 6 || components.len() == 7, InvalidTzOffset)?;
+                    separators.push(&rest[..1]).ok_or(TooManyComponents)?;
                     rest = &rest[1..];
                 }
                 // If a punctuation is acquired, move forward the pointer. Note that we should
                 // consume multiple punctuations if existing because MySQL allows to parse time
                 // like 2020--12..16T18::58^^45.
                 else if rest[0].is_ascii_punctuation() {
-                    let result = punct1(rest)?;
-                    separators.push(result.1);
+                    let result = punct1(rest).ok_or(BadSeparator)?;
+                    separators.push(result.1).ok_or(TooManyComponents)?;
                     rest = result.0;
                 } else {
-                    return None;
+                    return Err(BadSeparator);
                 }
             }
 
@@ -411,35 +754,35 @@ mod parser {
         match components.len() {
             9 => {
                 // 2020-12-23 15:59:23.233333+08:00
-                (separators.len() == 8).as_option()?;
+                ensure(separators.len() == 8, BadSeparator)?;
                 match separators[6..] {
                     [b"+", b":"] | [b"-", b":"] => {
                         has_tz = true;
                         tz_sign = separators[6];
-                        tz_minute = components.pop()?;
-                        tz_hour = components.pop()?;
+                        tz_minute = components.pop().ok_or(InvalidTzOffset)?;
+                        tz_hour = components.pop().ok_or(InvalidTzOffset)?;
                     }
-                    _ => return None,
+                    _ => return Err(InvalidTzOffset),
                 };
             }
             8 => {
                 // 2020-12-23 15:59:23.2333-08
                 // 2020-12-23 15:59:23.2333-0800
                 // 2020-12-23 15:59:23+08:00
-                (separators.len() == 7).as_option()?;
+                ensure(separators.len() == 7, BadSeparator)?;
                 match separators[5..] {
                     [b".", b"-"] | [b".", b"+"] => {
                         has_tz = true;
                         tz_sign = separators[6];
-                        tz_hour = components.pop()?;
+                        tz_hour = components.pop().ok_or(InvalidTzOffset)?;
                     }
                     [b"+", b":"] | [b"-", b":"] => {
                         has_tz = true;
                         tz_sign = separators[5];
-                        tz_minute = components.pop()?;
-                        tz_hour = components.pop()?;
+                        tz_minute = components.pop().ok_or(InvalidTzOffset)?;
+                        tz_hour = components.pop().ok_or(InvalidTzOffset)?;
                     }
-                    _ => return None,
+                    _ => return Err(InvalidTzOffset),
                 }
             }
             7 => {
@@ -448,22 +791,22 @@ mod parser {
                 // 2020-12-23 15:59:23-08
                 match separators.len() {
                     7 => {
-                        (separators.last()? == b"Z").as_option()?;
+                        ensure(separators.last() == Some(&&b"Z"[..]), BadSeparator)?;
                         has_tz = true;
                     }
                     6 => {
                         tz_sign = separators[5];
                         if tz_sign == b"+" || tz_sign == b"-" {
                             has_tz = true;
-                            tz_hour = components.pop()?;
+                            tz_hour = components.pop().ok_or(InvalidTzOffset)?;
                         }
                     }
-                    _ => return None, // this branch can never be reached
+                    _ => return Err(BadSeparator), // this branch can never be reached
                 }
             }
             6 => {
                 // 2020-12-23 15:59:23Z
-                if separators.len() == 6 && separators.last()? == b"Z" {
+                if separators.len() == 6 && separators.last() == Some(&&b"Z"[..]) {
                     has_tz = true;
                 }
             }
@@ -475,36 +818,58 @@ mod parser {
                 tz_hour = tmp.0;
                 tz_minute = tmp.1;
             }
-            ((tz_hour.len() == 2 || tz_hour.is_empty())
-                && (tz_minute.len() == 2 || tz_minute.is_empty()))
-            .as_option()?;
-            let delta_hour = bytes_to_u32(tz_hour)? as i32;
-            let delta_minute = bytes_to_u32(tz_minute)? as i32;
-            (!(delta_hour > 14
-                || delta_minute > 59
-                || (delta_hour == 14 && delta_minute != 0)
-                || (tz_sign == b"-" && delta_hour == 0 && delta_minute == 0)))
-                .as_option()?;
+            ensure(
+                (tz_hour.len() == 2 || tz_hour.is_empty())
+                    && (tz_minute.len() == 2 || tz_minute.is_empty()),
+                InvalidTzOffset,
+            )?;
+            let delta_hour = bytes_to_u32(tz_hour).ok_or(InvalidTzOffset)? as i32;
+            let delta_minute = bytes_to_u32(tz_minute).ok_or(InvalidTzOffset)? as i32;
+            ensure(
+                !(delta_hour > 14
+                    || delta_minute > 59
+                    || (delta_hour == 14 && delta_minute != 0)
+                    || (tz_sign == b"-" && delta_hour == 0 && delta_minute == 0)),
+                InvalidTzOffset,
+            )?;
             tz_offset = (delta_hour * 60 + delta_minute) * 60;
             if tz_sign == b"-" {
                 tz_offset = -tz_offset;
             }
         }
         // the following statement checks fsp
-        ((components.len() != 7 && components.len() != 2)
-            || (separators.len() >= components.len() - 1 /* should always true */ && separators[components.len() - 2] == b"."))
-            .as_option()?;
+        ensure(
+            (components.len() != 7 && components.len() != 2)
+                || (separators.len() >= components.len() - 1 /* should always true */
+                    && separators[components.len() - 2] == b"."),
+            BadSeparator,
+        )?;
+
+        Ok((components, if has_tz { Some(tz_offset) } else { None }))
+    }
 
-        Some((components, if has_tz { Some(tz_offset) } else { None }))
+    /// Splits a trailing time zone name (`2021-03-14 02:30:00
+    /// Asia/Shanghai`) off the input: the suffix after the last whitespace
+    /// run, provided it starts with a letter. No accepted datetime grammar
+    /// puts an alphabetic token after a space, so the suffix cannot be
+    /// mistaken for a component. Whether the word names a zone is for the
+    /// caller to decide; `None` merely means there is no candidate.
+    fn split_trailing_tz_name(input: &str) -> Option<(&str, &str)> {
+        let pos = input.rfind(|c: char| c.is_ascii_whitespace())?;
+        let name = &input[pos + 1..];
+        let rest = input[..pos].trim_end();
+        (!rest.is_empty() && name.starts_with(|c: char| c.is_ascii_alphabetic())).as_option()?;
+        Some((rest, name))
     }
 
-    /// If a two-digit year encountered, add an offset to it.
+    /// If a two-digit year encountered, add an offset to it, pivoting on
+    /// `cutoff` (`EvalConfig::two_digit_year_cutoff`, 69 by default).
     /// 99 -> 1999
     /// 20 -> 2020
-    fn adjust_year(year: u32) -> u32 {
-        if year <= 69 {
+    fn adjust_year(cutoff: u32, year: u32) -> u32 {
+        if year <= cutoff {
             2000 + year
-        } else if (70..=99).contains(&year) {
+        } else if year <= 99 {
             1900 + year
         } else {
             year
@@ -514,22 +879,26 @@ mod parser {
     /// Try to parse a datetime string `input` without fractional part and
     /// separators. return an array that stores `[year, month, day, hour,
     /// minute, second, 0]`
-    fn parse_whole(input: &[u8]) -> Option<[u32; 7]> {
+    fn parse_whole(cutoff: u32, input: &[u8]) -> Option<[u32; 7]> {
         let mut parts = [0u32; 7];
 
         // If `input`'s len is 8 or 14, then `input` should be in format like:
         // yyyymmdd/yyyymmddhhmmss which means we have a four-digit year.
-        // Otherwise, we have a two-digit year.
+        // A 13- or 7-digit input can't fit a two-digit year (yymmddhhmmss is
+        // 12 digits and yymmdd is 6), so it carries a three-digit year
+        // (yyymmddhhmmss/yyymmdd), matching the classification of
+        // `parse_from_i64`. Otherwise, we have a two-digit year.
         let year_digits = match input.len() {
             14 | 8 => 4,
-            9..=12 | 5..=7 => 2,
+            13 | 7 => 3,
+            9..=12 | 5..=6 => 2,
             _ => return None,
         };
 
         parts[0] = bytes_to_u32(&input[..year_digits])?;
         // If we encounter a two-digit year, translate it to a four-digit year.
         if year_digits == 2 {
-            parts[0] = adjust_year(parts[0]);
+            parts[0] = adjust_year(cutoff, parts[0]);
         }
 
         for (i, chunk) in input[year_digits..].chunks(2).enumerate() {
@@ -539,27 +908,99 @@ mod parser {
         Some(parts)
     }
 
-    /// Try to parse a fractional part from `input` with `fsp`, round the result
-    /// if `round` is true.
-    /// NOTE: This function assumes that `fsp` is in range: [0, 6].
-    fn parse_frac(input: &[u8], fsp: u8, round: bool) -> Option<(bool, u32)> {
-        debug_assert!(fsp < 7);
-        let fsp = usize::from(fsp);
-        let len = input.len();
+    /// Whether `c` lies in the Unicode fullwidth-forms block that mirrors
+    /// printable ASCII.
+    fn is_fullwidth_ascii(c: char) -> bool {
+        ('\u{ff01}'..='\u{ff5e}').contains(&c)
+    }
 
-        let (input, len) = if fsp >= input.len() {
-            (input, len)
-        } else {
-            (&input[..fsp + round as usize], fsp + round as usize)
+    /// Resolves an ISO 8601 ordinal date (`2020-123`, optionally followed by
+    /// a time part like `2020-123T10:11:12Z`) into its calendar
+    /// `YYYY-MM-DD` spelling, keeping any suffix verbatim. Returns `None`
+    /// when the input is not in ordinal form or the day-of-year does not
+    /// exist in that year (day 366 of a non-leap year lands in the next
+    /// year, so the round trip through the day number rejects it).
+    fn normalize_ordinal_date(input: &str) -> Option<String> {
+        let (rest, year) = digit1(input.as_bytes())?;
+        (year.len() == 4).as_option()?;
+        let rest = match rest {
+            [b'-', tail @ ..] => tail,
+            _ => return None,
         };
+        let (rest, day_of_year) = digit1(rest)?;
+        (day_of_year.len() == 3).as_option()?;
+        // Only a bare date or a date followed by a time part qualifies;
+        // anything else (a punctuation, a fractional part) keeps its
+        // existing meaning.
+        match rest.first() {
+            None => {}
+            Some(&c) if c == b'T' || c.is_ascii_whitespace() => {}
+            _ => return None,
+        }
 
-        let frac = bytes_to_u32(input)? * TEN_POW[MICRO_WIDTH.saturating_sub(len)];
+        let year = bytes_to_u32(year)?;
+        let day_of_year = bytes_to_u32(day_of_year)?;
+        (day_of_year != 0).as_option()?;
+        let jan1 = extension::calc_day_number(year as i32, 1, 1)?;
+        let (y, month, day) =
+            extension::calc_date_from_day_number(jan1 + day_of_year as i32 - 1)?;
+        (y == year).as_option()?;
+
+        // The consumed prefix (`YYYY-DDD`) is pure ASCII, so byte offset 8
+        // is a char boundary.
+        let mut normalized = format!("{:04}-{:02}-{:02}", y, month, day);
+        normalized.push_str(&input[8..]);
+        Some(normalized)
+    }
 
-        Some(if round {
-            round_frac(frac, fsp as u8)
-        } else {
-            (false, frac)
-        })
+    /// Resolves an ISO 8601 week date (`2020-W05-3`, optionally followed by
+    /// a time part) into its calendar `YYYY-MM-DD` spelling, keeping any
+    /// suffix verbatim. Week 1 is the week containing the year's first
+    /// Thursday, built on the same day-number math as `WeekMode` mode 3
+    /// (`%x`/`%v` formatting), so week 53 is only accepted in years that
+    /// have one and the calendar year may differ from the week year at the
+    /// edges.
+    fn normalize_week_date(input: &str) -> Option<String> {
+        let (rest, year) = digit1(input.as_bytes())?;
+        (year.len() == 4).as_option()?;
+        let rest = match rest {
+            [b'-', b'W' | b'w', tail @ ..] => tail,
+            _ => return None,
+        };
+        let (rest, week) = digit1(rest)?;
+        (week.len() == 2).as_option()?;
+        let rest = match rest {
+            [b'-', tail @ ..] => tail,
+            _ => return None,
+        };
+        let (rest, weekday) = digit1(rest)?;
+        (weekday.len() == 1).as_option()?;
+        match rest.first() {
+            None => {}
+            Some(&c) if c == b'T' || c.is_ascii_whitespace() => {}
+            _ => return None,
+        }
+
+        let year = bytes_to_u32(year)?;
+        let week = bytes_to_u32(week)?;
+        let weekday = bytes_to_u32(weekday)?;
+        ((1..=53).contains(&week) && (1..=7).contains(&weekday)).as_option()?;
+
+        // Jan 4 always lies in week 1, so its Monday anchors the grid.
+        let jan4 = extension::calc_day_number(year as i32, 1, 4)?;
+        let monday = jan4 - extension::calc_weekday(jan4, false) + (week as i32 - 1) * 7;
+        // A week belongs to the year containing its Thursday; this rejects
+        // week 53 of years that only have 52 weeks.
+        let (thursday_year, _, _) = extension::calc_date_from_day_number(monday + 3)?;
+        (thursday_year == year).as_option()?;
+        let (y, month, day) =
+            extension::calc_date_from_day_number(monday + weekday as i32 - 1)?;
+
+        // The consumed prefix (`YYYY-Www-D`) is pure ASCII, so byte offset
+        // 10 is a char boundary.
+        let mut normalized = format!("{:04}-{:02}-{:02}", y, month, day);
+        normalized.push_str(&input[10..]);
+        Some(normalized)
     }
 
     pub fn parse(
@@ -568,9 +1009,58 @@ mod parser {
         time_type: TimeType,
         fsp: u8,
         round: bool,
-    ) -> Option<Time> {
+    ) -> ParseResult<Time> {
         let trimmed = input.trim();
-        (!trimmed.is_empty()).as_option()?;
+        ensure(!trimmed.is_empty(), TimeParseErrorKind::Empty)?;
+
+        // Fullwidth forms (U+FF01..=U+FF5E) mirror ASCII 0x21..=0x7E at a
+        // fixed offset, so mapping them back is a plain subtraction. Only
+        // allocate when the input actually contains such a code point.
+        if ctx.cfg.flag.contains(Flag::NORMALIZE_FULLWIDTH_CHARS)
+            && trimmed.chars().any(is_fullwidth_ascii)
+        {
+            let normalized = trimmed
+                .chars()
+                .map(|c| {
+                    if is_fullwidth_ascii(c) {
+                        char::from_u32(c as u32 - 0xfee0).unwrap()
+                    } else {
+                        c
+                    }
+                })
+                .collect::<String>();
+            return parse(ctx, &normalized, time_type, fsp, round);
+        }
+
+        // An ISO 8601 ordinal date (`2020-123`) or week date (`2020-W05-3`)
+        // packs the date into components the splitter would otherwise
+        // mistake for a fractional part or reject at the `W` marker. Spell
+        // it out as the calendar date and reparse, so time and timezone
+        // suffixes compose with the ordinary path below.
+        if let Some(normalized) =
+            normalize_ordinal_date(trimmed).or_else(|| normalize_week_date(trimmed))
+        {
+            return parse(ctx, &normalized, time_type, fsp, round);
+        }
+
+        // A trailing named zone ("2021-03-14 02:30:00 Asia/Shanghai") is
+        // split off before the component splitter runs: the splitter only
+        // knows digits, month names and offset punctuation. A candidate
+        // that names no known zone fails the whole parse instead of
+        // falling through to a misleading separator error.
+        let mut named_tz = None;
+        let trimmed = if ctx.cfg.flag.contains(Flag::PARSE_TZ_NAME) {
+            match split_trailing_tz_name(trimmed) {
+                Some((rest, name)) => {
+                    named_tz =
+                        Some(Tz::from_tz_name(name).ok_or(TimeParseErrorKind::InvalidTzName)?);
+                    rest
+                }
+                None => trimmed,
+            }
+        } else {
+            trimmed
+        };
 
         // to support ISO8601 and MySQL's time zone support, we further parse the
         // following formats 2020-12-17T11:55:55Z
@@ -578,19 +1068,25 @@ mod parser {
         // 2020-12-17T11:55:55-08
         // 2020-12-17T11:55:55+02:00
         let (components, tz) = split_components_with_tz(trimmed)?;
+        let cutoff = ctx.cfg.two_digit_year_cutoff;
         let time_without_tz = match components.len() {
             1 | 2 => {
-                let mut whole = parse_whole(components[0])?;
+                let mut whole =
+                    parse_whole(cutoff, components[0]).ok_or(TimeParseErrorKind::Unrecognized)?;
 
                 let (carry, frac) = if let Some(frac) = components.get(1) {
-                    // If we have a fractional part,
-                    // we expect the `whole` is in format: `yymmddhhmm/yymmddhhmmss/yyyymmddhhmmss`.
+                    // If we have a fractional part, we expect the `whole` is
+                    // in format:
+                    // `yymmddhhmm/yymmddhhmmss/yyymmddhhmmss/yyyymmddhhmmss`.
                     // Otherwise, the fractional part is meaningless.
-                    (components[0].len() == 10
-                        || components[0].len() == 12
-                        || components[0].len() == 14)
-                        .as_option()?;
-                    parse_frac(frac, fsp, round)?
+                    ensure(
+                        components[0].len() == 10
+                            || components[0].len() == 12
+                            || components[0].len() == 13
+                            || components[0].len() == 14,
+                        TimeParseErrorKind::BadSeparator,
+                    )?;
+                    widen_frac_digits(frac, fsp, round).ok_or(TimeParseErrorKind::BadNumber)?
                 } else {
                     (false, 0)
                 };
@@ -598,23 +1094,52 @@ mod parser {
                 whole[6] = frac;
                 let mut parts = whole;
                 if carry {
-                    round_components(&mut parts)?;
+                    round_components(&mut parts).ok_or(TimeParseErrorKind::OutOfRangeComponent {
+                        which: "year",
+                    })?;
                 }
 
-                Time::from_slice(ctx, &parts, time_type, fsp)
+                Time::from_slice(ctx, &parts, time_type, fsp)?
             }
             3..=7 => {
+                // `YYYY-MM-DD HH` and `YYYY-MM-DD HH:MM` zero-fill the
+                // missing minute/second. MySQL warns about such partial time
+                // parts, and strict sql mode rejects them when the statement
+                // opts in via `PARTIAL_DATETIME_AS_ERROR`.
+                if components.len() == 4 || components.len() == 5 {
+                    if ctx.cfg.sql_mode.is_strict()
+                        && ctx.cfg.flag.contains(Flag::PARTIAL_DATETIME_AS_ERROR)
+                    {
+                        return Err(TimeParseErrorKind::Unrecognized);
+                    }
+                    ctx.append_warning(Error::truncated_wrong_val("DATETIME", trimmed));
+                }
                 let whole = std::cmp::min(components.len(), 6);
-                let mut parts: Vec<_> = components[..whole].iter().try_fold(
-                    Vec::with_capacity(MAX_COMPONENTS_LEN),
-                    |mut acc, part| -> Option<_> {
-                        acc.push(bytes_to_u32(part)?);
-                        Some(acc)
-                    },
-                )?;
+                let month_is_name = components[1][0].is_ascii_alphabetic();
+                let mut parts: Vec<_> = components[..whole]
+                    .iter()
+                    .enumerate()
+                    .try_fold(
+                        Vec::with_capacity(MAX_COMPONENTS_LEN),
+                        |mut acc, (i, part)| -> Option<_> {
+                            acc.push(if i == 1 && month_is_name {
+                                month_name_to_number(part)?
+                            } else {
+                                bytes_to_u32(part)?
+                            });
+                            Some(acc)
+                        },
+                    )
+                    .ok_or(TimeParseErrorKind::BadNumber)?;
+                // `DD-Mon-YYYY` ("12-Jan-2020") puts the four-digit year
+                // last; flip it into ymd order. A four-digit leading
+                // component is already the year ("2020-Jan-12").
+                if month_is_name && components[0].len() <= 2 && components[2].len() == 4 {
+                    parts.swap(0, 2);
+                }
 
                 let (carry, frac) = if let Some(frac) = components.get(6) {
-                    parse_frac(frac, fsp, round)?
+                    widen_frac_digits(frac, fsp, round).ok_or(TimeParseErrorKind::BadNumber)?
                 } else {
                     (false, 0)
                 };
@@ -623,37 +1148,47 @@ mod parser {
                 parts.push(frac);
                 // Skip a special case "00-00-00".
                 if components[0].len() == 2 && !parts.iter().all(|x| *x == 0u32) {
-                    parts[0] = adjust_year(parts[0]);
+                    parts[0] = adjust_year(cutoff, parts[0]);
                 }
 
                 if carry {
-                    round_components(&mut parts)?;
+                    round_components(&mut parts).ok_or(TimeParseErrorKind::OutOfRangeComponent {
+                        which: "year",
+                    })?;
                 }
-                Time::from_slice(ctx, &parts, time_type, fsp)
+                Time::from_slice(ctx, &parts, time_type, fsp)?
             }
-            _ => None,
+            _ => return Err(TimeParseErrorKind::TooManyComponents),
         };
-        match (tz, time_without_tz) {
-            (Some(tz_offset), Some(t)) => {
-                let tz_parsed = Tz::from_offset(tz_offset as i64)?;
+        let tz_parsed = match (tz, named_tz) {
+            // "2020-12-23 15:59:23+08:00 UTC" names the zone twice.
+            (Some(_), Some(_)) => return Err(TimeParseErrorKind::InvalidTzName),
+            (Some(tz_offset), None) => Some(
+                Tz::from_offset(tz_offset as i64).ok_or(TimeParseErrorKind::InvalidTzOffset)?,
+            ),
+            (None, named) => named,
+        };
+        match tz_parsed {
+            Some(tz_parsed) => {
+                // A fixed offset is never ambiguous; for a named zone, a
+                // DST-ambiguous local time takes its earliest mapping.
                 let mut ts = chrono_datetime(
                     &tz_parsed,
-                    t.year(),
-                    t.month(),
-                    t.day(),
-                    t.hour(),
-                    t.minute(),
-                    t.second(),
-                    t.micro(),
+                    time_without_tz.year(),
+                    time_without_tz.month(),
+                    time_without_tz.day(),
+                    time_without_tz.hour(),
+                    time_without_tz.minute(),
+                    time_without_tz.second(),
+                    time_without_tz.micro(),
+                    AmbiguityPolicy::Earliest,
                 )
-                .ok()?;
+                .map_err(|_| TimeParseErrorKind::Unrecognized)?;
                 ts = ts.with_timezone(&ctx.cfg.tz);
-                Some(
-                    Time::try_from_chrono_datetime(ctx, ts.naive_local(), time_type, fsp as i8)
-                        .ok()?,
-                )
+                Time::try_from_chrono_datetime(ctx, ts.naive_local(), time_type, fsp as i8)
+                    .map_err(|_| TimeParseErrorKind::TimestampOutOfRange)
             }
-            _ => time_without_tz,
+            None => Ok(time_without_tz),
         }
     }
 
@@ -663,12 +1198,15 @@ mod parser {
         time_type: TimeType,
         fsp: u8,
         round: bool,
-    ) -> Option<Time> {
+    ) -> ParseResult<Time> {
         let (components, _) = split_components_with_tz(input.as_str())?;
         match components.len() {
             1 | 2 => {
-                let result: i64 = components[0].convert(ctx).ok()?;
-                let whole_time = parse_from_i64(ctx, result, time_type, fsp)?;
+                let result: i64 = components[0]
+                    .convert(ctx)
+                    .map_err(|_| TimeParseErrorKind::BadNumber)?;
+                let whole_time = parse_from_i64(ctx, result, time_type, fsp)
+                    .ok_or(TimeParseErrorKind::Unrecognized)?;
                 let mut whole = [
                     whole_time.get_year(),
                     whole_time.get_month(),
@@ -685,7 +1223,7 @@ mod parser {
                     // which match the `whole` part length from 9 to 14.
                     // Otherwise, the fractional part is meaningless.
                     if components[0].len() >= 9 && components[0].len() <= 14 {
-                        parse_frac(frac, fsp, round)?
+                        widen_frac_digits(frac, fsp, round).ok_or(TimeParseErrorKind::BadNumber)?
                     } else {
                         (false, 0)
                     }
@@ -696,12 +1234,14 @@ mod parser {
                 whole[6] = frac;
                 let mut parts = whole;
                 if carry {
-                    round_components(&mut parts)?;
+                    round_components(&mut parts).ok_or(TimeParseErrorKind::OutOfRangeComponent {
+                        which: "year",
+                    })?;
                 }
 
                 Time::from_slice(ctx, &parts, time_type, fsp)
             }
-            _ => None,
+            _ => Err(TimeParseErrorKind::TooManyComponents),
         }
     }
 
@@ -719,12 +1259,32 @@ mod parser {
         // The parser eats two digits each time from the end of string,
         // and fill it into `Time` with reversed order.
         // Port from: https://github.com/pingcap/tidb/blob/b1aad071489619998e4caefd235ed01f179c2db2/types/time.go#L1263
+        //
+        // Two-digit years pivot on the configured cutoff (69 by default):
+        // with the default, YYMMDD up to 691231 reads as 20YY while 700101
+        // and above reads as 19YY, with the unreachable span in between
+        // (e.g. 691232..=700100) rejected, and likewise for YYMMDDHHMMSS.
+        let cutoff = i64::from(ctx.cfg.two_digit_year_cutoff);
         let aligned = match input {
-            101..=691_231 => (input + 20_000_000) * 1_000_000,
-            700_101..=991_231 => (input + 19_000_000) * 1_000_000,
+            101..=991_231 => {
+                if input <= cutoff * 10_000 + 1_231 {
+                    (input + 20_000_000) * 1_000_000
+                } else if input >= (cutoff + 1) * 10_000 + 101 {
+                    (input + 19_000_000) * 1_000_000
+                } else {
+                    return None;
+                }
+            }
             991_232..=99_991_231 => input * 1_000_000,
-            101_000_000..=691_231_235_959 => input + 20_000_000_000_000,
-            700_101_000_000..=991_231_235_959 => input + 19_000_000_000_000,
+            101_000_000..=991_231_235_959 => {
+                if input <= cutoff * 10_000_000_000 + 1_231_235_959 {
+                    input + 20_000_000_000_000
+                } else if input >= (cutoff + 1) * 10_000_000_000 + 101_000_000 {
+                    input + 19_000_000_000_000
+                } else {
+                    return None;
+                }
+            }
             1_000_000_000_000..=i64::MAX => input,
             _ => return None,
         };
@@ -733,6 +1293,108 @@ mod parser {
     }
 }
 
+/// A small thread-local cache for parsed datetime literals. Queries carry
+/// their constant literals into every region request, so coprocessors
+/// re-parse the same short strings over and over; sessions opt in via
+/// `EvalConfig::cache_datetime_literals`. Entries are keyed by the literal
+/// and everything that can change its parse — a fingerprint of the config
+/// (sql mode, flags, time zone, two-digit-year cutoff) plus the fsp,
+/// rounding and target type — so a zone or mode change never returns a
+/// stale value.
+mod literal_cache {
+    use std::{
+        cell::RefCell,
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    use super::{Time, TimeType};
+    use crate::expr::EvalConfig;
+
+    /// Only short inputs qualify: constant literals are short, and the key
+    /// comparison on lookup stays cheap.
+    pub(super) const MAX_LITERAL_LEN: usize = 64;
+    /// A query rarely carries more than a handful of distinct datetime
+    /// literals.
+    const CAPACITY: usize = 16;
+
+    struct Entry {
+        fingerprint: u64,
+        input: String,
+        time_type: TimeType,
+        fsp: u8,
+        round: bool,
+        value: Time,
+    }
+
+    thread_local! {
+        /// Most-recently-used first: hits move to the front, insertion
+        /// evicts the back.
+        static CACHE: RefCell<Vec<Entry>> = RefCell::new(Vec::new());
+    }
+
+    /// Hashes the parts of the config that can change what a datetime
+    /// literal parses to.
+    pub(super) fn config_fingerprint(cfg: &EvalConfig) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        cfg.flag.bits().hash(&mut hasher);
+        cfg.sql_mode.bits().hash(&mut hasher);
+        cfg.two_digit_year_cutoff.hash(&mut hasher);
+        cfg.tz.name().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub(super) fn get(
+        fingerprint: u64,
+        input: &str,
+        time_type: TimeType,
+        fsp: u8,
+        round: bool,
+    ) -> Option<Time> {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let pos = cache.iter().position(|e| {
+                e.fingerprint == fingerprint
+                    && e.fsp == fsp
+                    && e.round == round
+                    && e.time_type == time_type
+                    && e.input == input
+            })?;
+            let entry = cache.remove(pos);
+            let value = entry.value;
+            cache.insert(0, entry);
+            Some(value)
+        })
+    }
+
+    pub(super) fn insert(
+        fingerprint: u64,
+        input: &str,
+        time_type: TimeType,
+        fsp: u8,
+        round: bool,
+        value: Time,
+    ) {
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.len() >= CAPACITY {
+                cache.pop();
+            }
+            cache.insert(
+                0,
+                Entry {
+                    fingerprint,
+                    input: input.to_string(),
+                    time_type,
+                    fsp,
+                    round,
+                    value,
+                },
+            );
+        });
+    }
+}
+
 impl Time {
     pub fn parse(
         ctx: &mut EvalContext,
@@ -741,8 +1403,65 @@ impl Time {
         fsp: i8,
         round: bool,
     ) -> Result<Time> {
-        parser::parse(ctx, input, time_type, check_fsp(fsp)?, round)
-            .ok_or_else(|| Error::incorrect_datetime_value(input))
+        let fsp = check_fsp(fsp)?;
+        // Constant literals reappear for every region request of a query;
+        // sessions opt in to a thread-local cache for them.
+        if ctx.cfg.cache_datetime_literals && input.len() < literal_cache::MAX_LITERAL_LEN {
+            return Self::parse_via_literal_cache(ctx, input, time_type, fsp, round);
+        }
+        parser::parse(ctx, input, time_type, fsp, round)
+            .map_err(|kind| Error::incorrect_datetime_value_with_reason(input, kind))
+    }
+
+    /// [`Time::parse`] through the [`literal_cache`]: a miss parses and
+    /// stores the result under the config fingerprint.
+    fn parse_via_literal_cache(
+        ctx: &mut EvalContext,
+        input: &str,
+        time_type: TimeType,
+        fsp: u8,
+        round: bool,
+    ) -> Result<Time> {
+        let fingerprint = literal_cache::config_fingerprint(&ctx.cfg);
+        if let Some(t) = literal_cache::get(fingerprint, input, time_type, fsp, round) {
+            return Ok(t);
+        }
+        let warnings_before = ctx.warnings.warning_cnt;
+        let t = parser::parse(ctx, input, time_type, fsp, round)
+            .map_err(|kind| Error::incorrect_datetime_value_with_reason(input, kind))?;
+        // A cache hit skips the parse and with it any warning the parse
+        // would append, so only warning-free results are stored.
+        if ctx.warnings.warning_cnt == warnings_before {
+            literal_cache::insert(fingerprint, input, time_type, fsp, round, t);
+        }
+        Ok(t)
+    }
+
+    /// Like [`Time::parse`], with the fractional-part handling spelled out:
+    /// `FspRounding::Round` matches `parse` with `round = true`, while
+    /// `FspRounding::Truncate` cuts the part at `fsp` digits without ever
+    /// carrying into the seconds and warns with [`Error::truncated`] when a
+    /// non-zero digit was dropped. Digits beyond the microsecond precision
+    /// are dropped by the parser either way.
+    pub fn parse_with_rounding(
+        ctx: &mut EvalContext,
+        input: &str,
+        time_type: TimeType,
+        fsp: i8,
+        rounding: FspRounding,
+    ) -> Result<Time> {
+        match rounding {
+            FspRounding::Round => Self::parse(ctx, input, time_type, fsp, true),
+            FspRounding::Truncate => {
+                let fsp = check_fsp(fsp)?;
+                // Parse at full precision so the dropped digits are still
+                // around to decide whether the truncation deserves a
+                // warning.
+                parser::parse(ctx, input, time_type, MAX_FSP as u8, false)
+                    .map_err(|kind| Error::incorrect_datetime_value_with_reason(input, kind))?
+                    .round_frac_with(ctx, fsp as i8, FspRounding::Truncate)
+            }
+        }
     }
     pub fn parse_datetime(
         ctx: &mut EvalContext,
@@ -763,6 +1482,32 @@ impl Time {
     ) -> Result<Time> {
         Self::parse(ctx, input, TimeType::Timestamp, fsp, round)
     }
+    /// Parses a batch of strings with the fsp checked once, which the
+    /// scalar [`Time::parse`] pays per call. Intended for the vectorized
+    /// CAST implementations. A row that fails to parse yields `None` and
+    /// appends a warning carrying the same message as the error the scalar
+    /// path returns for it, so per-row diagnostics are identical.
+    pub fn parse_batch(
+        ctx: &mut EvalContext,
+        inputs: &[&str],
+        time_type: TimeType,
+        fsp: i8,
+        round: bool,
+    ) -> Result<Vec<Option<Time>>> {
+        let fsp = check_fsp(fsp)?;
+        let mut results = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let parsed = match parser::parse(ctx, input, time_type, fsp, round) {
+                Ok(t) => Some(t),
+                Err(kind) => {
+                    ctx.append_warning(Error::incorrect_datetime_value_with_reason(input, kind));
+                    None
+                }
+            };
+            results.push(parsed);
+        }
+        Ok(results)
+    }
     pub fn parse_from_i64(
         ctx: &mut EvalContext,
         input: i64,
@@ -780,7 +1525,33 @@ impl Time {
         round: bool,
     ) -> Result<Time> {
         parser::parse_from_float_string(ctx, input.to_string(), time_type, check_fsp(fsp)?, round)
-            .ok_or_else(|| Error::incorrect_datetime_value(input))
+            .map_err(|kind| Error::incorrect_datetime_value_with_reason(input, kind))
+    }
+
+    /// [`Time::parse_from_decimal`] with the fractional-part handling
+    /// spelled out, like [`Time::parse_with_rounding`].
+    pub fn parse_from_decimal_with_rounding(
+        ctx: &mut EvalContext,
+        input: &Decimal,
+        time_type: TimeType,
+        fsp: i8,
+        rounding: FspRounding,
+    ) -> Result<Time> {
+        match rounding {
+            FspRounding::Round => Self::parse_from_decimal(ctx, input, time_type, fsp, true),
+            FspRounding::Truncate => {
+                let fsp = check_fsp(fsp)?;
+                parser::parse_from_float_string(
+                    ctx,
+                    input.to_string(),
+                    time_type,
+                    MAX_FSP as u8,
+                    false,
+                )
+                .map_err(|kind| Error::incorrect_datetime_value_with_reason(input, kind))?
+                .round_frac_with(ctx, fsp as i8, FspRounding::Truncate)
+            }
+        }
     }
 
     pub fn parse_from_real(
@@ -791,7 +1562,7 @@ impl Time {
         round: bool,
     ) -> Result<Time> {
         parser::parse_from_float_string(ctx, input.to_string(), time_type, check_fsp(fsp)?, round)
-            .ok_or_else(|| Error::incorrect_datetime_value(input))
+            .map_err(|kind| Error::incorrect_datetime_value_with_reason(input, kind))
     }
 }
 
@@ -803,7 +1574,7 @@ fn handle_zero_date(ctx: &mut EvalContext, mut args: TimeArgs) -> Result<Option<
     let no_zero_date = sql_mode.contains(SqlMode::NO_ZERO_DATE);
     let ignore_truncate = flags.contains(Flag::IGNORE_TRUNCATE);
 
-    debug_assert!(args.is_zero());
+    debug_assert!(args.is_zero_date());
 
     if no_zero_date {
         (!strict_mode || ignore_truncate).ok_or(Error::truncated())?;
@@ -881,15 +1652,49 @@ impl Default for TimeArgs {
 }
 
 impl TimeArgs {
-    fn check(mut self, ctx: &mut EvalContext) -> Option<TimeArgs> {
-        self.fsp = check_fsp(self.fsp).ok()? as i8;
+    fn check(mut self, ctx: &mut EvalContext) -> parser::ParseResult<TimeArgs> {
+        self.fsp = check_fsp(self.fsp)
+            .map_err(|_| TimeParseErrorKind::FspTooLarge)? as i8;
         let (fsp, time_type) = (self.fsp, self.time_type);
+        // Computed up front: the checks below funnel every rejection through
+        // the sql-mode handlers, which only know how to say "truncated".
+        let kind = self.rejection_kind();
         match self.time_type {
             TimeType::Date | TimeType::DateTime => self.check_datetime(ctx),
             TimeType::Timestamp => self.check_timestamp(ctx),
         }
         .map(|datetime| datetime.unwrap_or_else(|| TimeArgs::zero(fsp, time_type)))
-        .ok()
+        .map_err(|_| kind)
+    }
+
+    /// The parse-error variant a rejection of these components reports:
+    /// the first out-of-range field, a forbidden zero date, or — for a
+    /// timestamp whose fields are all individually fine — the type range.
+    fn rejection_kind(&self) -> TimeParseErrorKind {
+        let which = if self.year > 9999 {
+            "year"
+        } else if self.month > 12 {
+            "month"
+        } else if self.day > 31 {
+            "day"
+        } else if self.hour > 23 {
+            "hour"
+        } else if self.minute > 59 {
+            "minute"
+        } else if self.second > 59 {
+            "second"
+        } else if self.micro > 999_999 {
+            "microsecond"
+        } else if self.month != 0 && self.day > last_day_of_month(self.year, self.month) {
+            "day"
+        } else if self.is_zero_date() || self.month == 0 || self.day == 0 {
+            return TimeParseErrorKind::ZeroDateForbidden;
+        } else if self.time_type == TimeType::Timestamp {
+            return TimeParseErrorKind::TimestampOutOfRange;
+        } else {
+            return TimeParseErrorKind::Unrecognized;
+        };
+        TimeParseErrorKind::OutOfRangeComponent { which }
     }
 
     pub fn zero(fsp: i8, time_type: TimeType) -> TimeArgs {
@@ -917,13 +1722,20 @@ impl TimeArgs {
     }
 
     pub fn is_zero(&self) -> bool {
+        self.is_zero_date() && self.micro == 0
+    }
+
+    /// Returns whether the date-and-time part is all-zero, ignoring the
+    /// fractional part. `"0000-00-00 00:00:00.000000"` is no less a zero date
+    /// for carrying explicit fractional digits, so the NO_ZERO_DATE checks
+    /// must key off this instead of [`is_zero`](TimeArgs::is_zero).
+    pub fn is_zero_date(&self) -> bool {
         self.year == 0
             && self.month == 0
             && self.day == 0
             && self.hour == 0
             && self.minute == 0
             && self.second == 0
-            && self.micro == 0
     }
 
     fn check_date(mut self, ctx: &mut EvalContext) -> Result<Option<Self>> {
@@ -933,8 +1745,11 @@ impl TimeArgs {
 
         let is_relaxed = ctx.cfg.sql_mode.contains(SqlMode::INVALID_DATES);
 
-        if self.is_zero() {
+        if self.is_zero_date() {
             self = try_opt!(handle_zero_date(ctx, self));
+            // A zero date is stored as the canonical zero value: a leftover
+            // fraction would survive rounding with fsp > 0 otherwise.
+            self.micro = 0;
         }
 
         if month == 0 || day == 0 {
@@ -966,9 +1781,11 @@ impl TimeArgs {
         Ok(Some(datetime))
     }
 
-    fn check_timestamp(self, ctx: &mut EvalContext) -> Result<Option<Self>> {
-        if self.is_zero() {
-            return handle_zero_date(ctx, self);
+    fn check_timestamp(mut self, ctx: &mut EvalContext) -> Result<Option<Self>> {
+        if self.is_zero_date() {
+            self = try_opt!(handle_zero_date(ctx, self));
+            self.micro = 0;
+            return Ok(Some(self));
         }
 
         let datetime = chrono_datetime(
@@ -980,6 +1797,7 @@ impl TimeArgs {
             self.minute,
             self.second,
             self.micro,
+            AmbiguityPolicy::Earliest,
         );
 
         if datetime.is_err() {
@@ -1004,10 +1822,12 @@ impl Time {
         parts: &[u32],
         time_type: TimeType,
         fsp: u8,
-    ) -> Option<Self> {
-        let [year, month, day, hour, minute, second, micro]: [u32; 7] = parts.try_into().ok()?;
+    ) -> parser::ParseResult<Self> {
+        let [year, month, day, hour, minute, second, micro]: [u32; 7] = parts
+            .try_into()
+            .map_err(|_| TimeParseErrorKind::Unrecognized)?;
 
-        Time::new(
+        Time::from_args(
             ctx,
             TimeArgs {
                 year,
@@ -1021,18 +1841,69 @@ impl Time {
                 time_type,
             },
         )
-        .ok()
     }
 
-    /// Construct a `Time` via a number in format: yyyymmddhhmmss
-    fn from_aligned_i64(
+    /// Constructs a `Time` from discrete components, going through the same
+    /// validation as parsing. The SQL modes on `ctx` decide how edge cases
+    /// are treated: `NO_ZERO_DATE`/`NO_ZERO_IN_DATE` reject (or, outside of
+    /// strict mode, warn about) zero dates and zero month/day parts,
+    /// `INVALID_DATES` admits dates like `2007-02-31`, and otherwise invalid
+    /// components are an error. `fsp` may be `UNSPECIFIED_FSP` to get the
+    /// default; `micro` is stored as given (only validated against
+    /// `0..=999_999`), use [`round_frac`](Time::round_frac) to round it to
+    /// `fsp`. For `TimeType::Date` the time-of-day components are zeroed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_ymd_hms_micro(
         ctx: &mut EvalContext,
-        input: i64,
+        year: u32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+        micro: u32,
         time_type: TimeType,
         fsp: i8,
-    ) -> Result<Time> {
-        let ymd = (input / 1_000_000) as u32;
-        let hms = (input % 1_000_000) as u32;
+    ) -> Result<Self> {
+        Time::new(
+            ctx,
+            TimeArgs {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                micro,
+                fsp,
+                time_type,
+            },
+        )
+    }
+
+    /// The discrete components `(year, month, day, hour, minute, second,
+    /// micro)`, the inverse of [`from_ymd_hms_micro`](Time::from_ymd_hms_micro).
+    pub fn components(self) -> (u32, u32, u32, u32, u32, u32, u32) {
+        (
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second(),
+            self.micro(),
+        )
+    }
+
+    /// Construct a `Time` via a number in format: yyyymmddhhmmss
+    fn from_aligned_i64(
+        ctx: &mut EvalContext,
+        input: i64,
+        time_type: TimeType,
+        fsp: i8,
+    ) -> Result<Time> {
+        let ymd = (input / 1_000_000) as u32;
+        let hms = (input % 1_000_000) as u32;
 
         let year = ymd / 10_000;
         let md = ymd % 10_000_u32;
@@ -1094,9 +1965,49 @@ impl Time {
         )
     }
 
-    fn try_into_chrono_datetime(self, ctx: &mut EvalContext) -> Result<DateTime<Tz>> {
+    /// Ambiguous local times resolve to the earlier instant, keeping the
+    /// historical behavior of timestamp packing; callers which need another
+    /// policy go through `chrono_datetime` directly.
+    fn try_into_chrono_datetime<'a>(self, tz: impl Into<TzRef<'a>>) -> Result<DateTime<Tz>> {
         chrono_datetime(
-            &ctx.cfg.tz,
+            tz.into().0,
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second(),
+            self.micro(),
+            AmbiguityPolicy::Earliest,
+        )
+    }
+
+    /// Converts the wall time from one time zone to another with a single
+    /// chrono conversion, without round-tripping through the packed
+    /// representation.
+    ///
+    /// The clock components are interpreted in `from`, mapped to the same
+    /// instant in `to`, and rebuilt with the original time type and fsp.
+    /// `Date` values have no clock component and are rejected; zero times
+    /// pass through unchanged. A wall time which does not exist in `from`
+    /// (it falls in a DST gap) yields an error under every policy, and
+    /// `policy` decides how one that occurs twice in `from` (clocks set
+    /// back) resolves.
+    pub fn convert_time_zone(
+        self,
+        ctx: &mut EvalContext,
+        from: &Tz,
+        to: &Tz,
+        policy: AmbiguityPolicy,
+    ) -> Result<Self> {
+        if self.get_time_type() == TimeType::Date {
+            return Err(box_err!("cannot convert a date value between time zones"));
+        }
+        if self.is_zero() {
+            return Ok(self);
+        }
+        let converted = chrono_datetime(
+            from,
             self.year(),
             self.month(),
             self.day(),
@@ -1104,6 +2015,14 @@ impl Time {
             self.minute(),
             self.second(),
             self.micro(),
+            policy,
+        )?
+        .with_timezone(to);
+        Time::try_from_chrono_datetime(
+            ctx,
+            converted.naive_local(),
+            self.get_time_type(),
+            self.fsp() as i8,
         )
     }
 
@@ -1146,9 +2065,16 @@ impl Time {
 
     fn new(ctx: &mut EvalContext, config: TimeArgs) -> Result<Time> {
         let unchecked_time = Self::unchecked_new(config.clone());
-        let mut checked = config
-            .check(ctx)
-            .ok_or_else(|| Error::incorrect_datetime_value(unchecked_time))?;
+        Self::from_args(ctx, config)
+            .map_err(|kind| Error::incorrect_datetime_value_with_reason(unchecked_time, kind))
+    }
+
+    /// The body of [`Time::new`], reporting a failure as the
+    /// [`TimeParseErrorKind`] it stems from rather than a ready-made error,
+    /// so the parser can attach the original input instead of the packed
+    /// components.
+    fn from_args(ctx: &mut EvalContext, config: TimeArgs) -> parser::ParseResult<Time> {
+        let mut checked = config.check(ctx)?;
         if checked.time_type == TimeType::Date {
             checked.hour = 0;
             checked.minute = 0;
@@ -1173,7 +2099,7 @@ impl Time {
             return Ok(());
         }
 
-        if day > last_day_of_month(year, month) {
+        if day > cached_last_day_of_month(year, month) {
             return Err(Error::truncated());
         }
 
@@ -1211,6 +2137,162 @@ impl Time {
         Some(self)
     }
 
+    /// The bounds of a calendar month as a half-open range: the first
+    /// instant of `(year, month)` and the first instant of the month after
+    /// it, for pruning RANGE COLUMNS partitions over datetime columns.
+    /// December rolls over into January of the next year. December 9999
+    /// has no following month, so its end clamps to the maximum datetime
+    /// (`9999-12-31 23:59:59.999999`) and the range is closed there.
+    ///
+    /// `time_type` picks the representation of the bounds (`Date` clamps
+    /// to `9999-12-31`); they carry fsp 0, except the clamped end, which
+    /// needs the full fractional digits to stay an upper bound.
+    pub fn month_bounds(
+        ctx: &mut EvalContext,
+        year: u32,
+        month: u32,
+        time_type: TimeType,
+    ) -> Result<(Self, Self)> {
+        if year > 9999 || !(1..=12).contains(&month) {
+            return Err(Error::incorrect_datetime_value(format!(
+                "{:04}-{:02}",
+                year, month
+            )));
+        }
+        let start = Time::from_ymd_hms_micro(ctx, year, month, 1, 0, 0, 0, 0, time_type, 0)?;
+        let end = if year == 9999 && month == 12 {
+            Time::from_ymd_hms_micro(
+                ctx,
+                year,
+                month,
+                last_day_of_month(year, month),
+                23,
+                59,
+                59,
+                999_999,
+                time_type,
+                MAX_FSP,
+            )?
+        } else {
+            let (next_year, next_month) = if month == 12 {
+                (year + 1, 1)
+            } else {
+                (year, month + 1)
+            };
+            Time::from_ymd_hms_micro(ctx, next_year, next_month, 1, 0, 0, 0, 0, time_type, 0)?
+        };
+        Ok((start, end))
+    }
+
+    /// Truncates the time down to the start of `unit`, in the style of
+    /// `date_trunc('hour', ts)`: every component finer than the unit is
+    /// zeroed, `Quarter` snaps the month to the first month of its quarter,
+    /// and `Week` steps back to the most recent Sunday (mode-0 weeks).
+    ///
+    /// Zero dates pass through unchanged. The fractional part is dropped for
+    /// every supported unit, so the result always carries fsp 0.
+    pub fn truncate_to(self, ctx: &mut EvalContext, unit: IntervalUnit) -> Result<Self> {
+        use IntervalUnit::*;
+        // Sub-second and compound units name a component to read out, not a
+        // boundary to snap to; only the plain calendar/clock units truncate.
+        if unit > Second {
+            return Err(box_err!("cannot truncate a time to unit {:?}", unit));
+        }
+        if self.is_zero() {
+            return Ok(self);
+        }
+        let mut args = TimeArgs {
+            year: self.year(),
+            month: self.month(),
+            day: self.day(),
+            hour: self.hour(),
+            minute: self.minute(),
+            second: self.second(),
+            micro: 0,
+            fsp: 0,
+            time_type: self.get_time_type(),
+        };
+        if unit < Second {
+            args.second = 0;
+        }
+        if unit < Minute {
+            args.minute = 0;
+        }
+        if unit < Hour {
+            args.hour = 0;
+        }
+        if matches!(unit, Year | Quarter | Month) {
+            args.day = 1;
+        }
+        match unit {
+            Year => args.month = 1,
+            // With `allow_invalid_date` the month can be 0; snap that to the
+            // first quarter instead of underflowing.
+            Quarter => args.month = (args.month.max(1) - 1) / 3 * 3 + 1,
+            Week => {
+                // The week start is computed on a calendar date since it may
+                // sit in the previous month or year, so zero-in-date and
+                // invalid days have no meaningful week to land in.
+                if args.month == 0
+                    || args.day == 0
+                    || args.day > last_day_of_month(args.year, args.month)
+                {
+                    return Err(Error::incorrect_datetime_value(self));
+                }
+                let date = NaiveDate::from_ymd(args.year as i32, args.month, args.day)
+                    - chrono::Duration::days(i64::from(self.weekday().num_days_from_sunday()));
+                args.year = date.year() as u32;
+                args.month = date.month();
+                args.day = date.day();
+            }
+            _ => {}
+        }
+        Time::new(ctx, args)
+    }
+
+    /// Returns the MySQL `EXTRACT(unit FROM self)` value.
+    ///
+    /// The raw stored fields are read back without normalization, so invalid
+    /// dates kept under `allow_invalid_date` extract exactly what was stored
+    /// (`2019-00-00` has month 0) and zero dates yield 0 for every unit.
+    /// Compound units pack their components decimally the way MySQL prints
+    /// them, e.g. `DayMicrosecond` is `ddhhmmss` * 1000000 + microseconds.
+    pub fn extract(self, unit: IntervalUnit) -> i64 {
+        use IntervalUnit::*;
+        let year = i64::from(self.year());
+        let month = i64::from(self.month());
+        let day = i64::from(self.day());
+        let hour = i64::from(self.hour());
+        let minute = i64::from(self.minute());
+        let second = i64::from(self.second());
+        let micro = i64::from(self.micro());
+        match unit {
+            Year => year,
+            // A zero month maps to quarter 0 rather than underflowing.
+            Quarter => (month + 2) / 3,
+            Month => month,
+            Week => i64::from(self.week(WeekMode::from_bits_truncate(0))),
+            Day => day,
+            Hour => hour,
+            Minute => minute,
+            Second => second,
+            Microsecond => micro,
+            SecondMicrosecond => second * 1_000_000 + micro,
+            MinuteMicrosecond => (minute * 100 + second) * 1_000_000 + micro,
+            MinuteSecond => minute * 100 + second,
+            HourMicrosecond => (hour * 10_000 + minute * 100 + second) * 1_000_000 + micro,
+            HourSecond => hour * 10_000 + minute * 100 + second,
+            HourMinute => hour * 100 + minute,
+            DayMicrosecond => {
+                (day * 1_000_000 + hour * 10_000 + minute * 100 + second) * 1_000_000 + micro
+            }
+            DaySecond => day * 1_000_000 + hour * 10_000 + minute * 100 + second,
+            DayMinute => day * 10_000 + hour * 100 + minute,
+            DayHour => day * 100 + hour,
+            YearMonth => year * 100 + month,
+        }
+    }
+
     #[inline]
     pub fn fsp(self) -> u8 {
         let fsp = self.get_fsp_tt() >> 1;
@@ -1300,7 +2382,18 @@ impl Time {
         let micro = (value & ((1 << 24) - 1)) as u32;
 
         if time_type == TimeType::Timestamp {
-            let utc = chrono_datetime(&Utc, year, month, day, hour, minute, second, micro)?;
+            // UTC has no transitions, so the policy is moot.
+            let utc = chrono_datetime(
+                &Utc,
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                micro,
+                AmbiguityPolicy::Earliest,
+            )?;
             let timestamp = ctx.cfg.tz.from_utc_datetime(&utc.naive_utc());
             Time::try_from_chrono_datetime(ctx, timestamp.naive_local(), time_type, fsp as i8)
         } else {
@@ -1318,42 +2411,87 @@ impl Time {
         }
     }
 
-    pub fn to_packed_u64(mut self, ctx: &mut EvalContext) -> Result<u64> {
+    pub fn to_packed_u64(self, ctx: &EvalContext) -> Result<u64> {
         if self.is_zero() {
             return Ok(0);
         }
 
-        if self.get_time_type() == TimeType::Timestamp {
-            let ts = self.try_into_chrono_datetime(ctx)?;
-            self = Time::try_from_chrono_datetime(
-                ctx,
-                ts.naive_utc(),
-                self.get_time_type(),
-                self.fsp() as i8,
-            )?;
-        }
+        let (year, month, day, hour, minute, second, micro) =
+            if self.get_time_type() == TimeType::Timestamp {
+                // Timestamps are packed in UTC. A valid timestamp stays valid
+                // under the zone conversion, so its fields can be packed
+                // directly without revalidating them through `Time::new`
+                // (which would need a mutable context to report warnings).
+                let ts = self.try_into_chrono_datetime(ctx)?.naive_utc();
+                (
+                    ts.year() as u64,
+                    u64::from(ts.month()),
+                    u64::from(ts.day()),
+                    u64::from(ts.hour()),
+                    u64::from(ts.minute()),
+                    u64::from(ts.second()),
+                    u64::from(ts.nanosecond() / 1000),
+                )
+            } else {
+                (
+                    u64::from(self.year()),
+                    u64::from(self.month()),
+                    u64::from(self.day()),
+                    u64::from(self.hour()),
+                    u64::from(self.minute()),
+                    u64::from(self.second()),
+                    u64::from(self.micro()),
+                )
+            };
 
-        let ymd =
-            ((u64::from(self.year()) * 13 + u64::from(self.month())) << 5) | u64::from(self.day());
-        let hms = (u64::from(self.hour()) << 12)
-            | (u64::from(self.minute()) << 6)
-            | u64::from(self.second());
+        let ymd = ((year * 13 + month) << 5) | day;
+        let hms = (hour << 12) | (minute << 6) | second;
 
-        Ok((((ymd << 17) | hms) << 24) | u64::from(self.micro()))
+        Ok((((ymd << 17) | hms) << 24) | micro)
     }
 
+    /// Composes today's date and `duration` into a `Time`.
+    ///
+    /// `target_fsp` is the fsp of the result: `UNSPECIFIED_FSP` inherits the
+    /// fsp of the duration, and a narrower fsp rounds the fraction here
+    /// instead of leaving that to the caller.
     pub fn from_duration(
         ctx: &mut EvalContext,
         duration: Duration,
         time_type: TimeType,
+        target_fsp: i8,
     ) -> Result<Self> {
         let dur = chrono::Duration::nanoseconds(duration.to_nanos());
 
-        let time = Utc::today().and_hms(0, 0, 0).checked_add_signed(dur);
+        let today = match ctx.cfg.fixed_today {
+            Some(date) => Utc.from_utc_date(&date),
+            None => Utc::today(),
+        };
+        let time = today.and_hms(0, 0, 0).checked_add_signed(dur);
 
         let time = time.ok_or::<Error>(box_err!("parse from duration {} overflows", duration))?;
 
-        Time::try_from_chrono_datetime(ctx, time, time_type, duration.fsp() as i8)
+        // A duration may carry more fractional digits than its fsp claims
+        // (e.g. one built with DEFAULT_FSP from nanoseconds). Widen the fsp
+        // to cover them, so the micros aren't silently dropped before the
+        // rounding below gets a chance to see them.
+        let mut fsp = duration.fsp() as usize;
+        let mut micro = (duration.to_nanos() / 1_000).unsigned_abs() % 1_000_000;
+        if micro != 0 {
+            let mut digits = MICRO_WIDTH;
+            while micro % 10 == 0 {
+                micro /= 10;
+                digits -= 1;
+            }
+            fsp = fsp.max(digits);
+        }
+
+        let time = Time::try_from_chrono_datetime(ctx, time, time_type, fsp as i8)?;
+        if target_fsp == UNSPECIFIED_FSP {
+            Ok(time)
+        } else {
+            time.round_frac(ctx, target_fsp)
+        }
     }
 
     pub fn from_local_time(ctx: &mut EvalContext, time_type: TimeType, fsp: i8) -> Result<Time> {
@@ -1363,6 +2501,88 @@ impl Time {
         Time::try_from_chrono_datetime(ctx, timestamp.naive_local(), time_type, fsp as i8)
     }
 
+    /// Renders this time in RFC 3339 form, e.g.
+    /// `2020-12-24T15:37:50.123456+08:00`, with exactly `fsp` fractional
+    /// digits (none for fsp 0).
+    ///
+    /// A `Timestamp` denotes an instant, so it carries the numeric offset
+    /// the session time zone has at that instant (`Z` when the offset is
+    /// zero). `DateTime` and `Date` carry no zone; they render as the
+    /// ISO 8601 local forms without an offset, a bare `YYYY-MM-DD` for
+    /// dates.
+    ///
+    /// Zero dates, and dates with a zero month or day, have no RFC 3339
+    /// spelling and are rejected instead of being rendered.
+    pub fn to_rfc3339(&self, ctx: &mut EvalContext) -> Result<String> {
+        use std::fmt::Write;
+
+        if self.month() == 0 || self.day() == 0 {
+            return Err(Error::incorrect_datetime_value(self));
+        }
+
+        let mut out = String::with_capacity(35);
+        write!(
+            out,
+            "{:04}-{:02}-{:02}",
+            self.year(),
+            self.month(),
+            self.day()
+        )
+        .unwrap();
+        if self.get_time_type() == TimeType::Date {
+            return Ok(out);
+        }
+        write!(
+            out,
+            "T{:02}:{:02}:{:02}",
+            self.hour(),
+            self.minute(),
+            self.second()
+        )
+        .unwrap();
+        let fsp = usize::from(self.fsp());
+        if fsp > 0 {
+            write!(
+                out,
+                ".{:0width$}",
+                scale_micro_to_fsp(self.micro(), self.fsp()),
+                width = fsp
+            )
+            .unwrap();
+        }
+        if self.get_time_type() == TimeType::Timestamp {
+            // The session zone's offset at this very instant: DST makes it
+            // vary over the year. A local time repeated by a fall-back
+            // transition reports the offset of its earliest occurrence,
+            // like the parsing path does.
+            let offset = chrono_datetime(
+                &ctx.cfg.tz,
+                self.year(),
+                self.month(),
+                self.day(),
+                self.hour(),
+                self.minute(),
+                self.second(),
+                self.micro(),
+                AmbiguityPolicy::Earliest,
+            )?
+            .offset()
+            .fix()
+            .local_minus_utc();
+            if offset == 0 {
+                out.push('Z');
+            } else {
+                let (sign, offset) = if offset < 0 {
+                    ('-', -offset)
+                } else {
+                    ('+', offset)
+                };
+                write!(out, "{}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60).unwrap();
+            }
+        }
+        Ok(out)
+    }
+
     pub fn from_year(
         ctx: &mut EvalContext,
         year: u32,
@@ -1409,7 +2629,44 @@ impl Time {
         }
 
         Time::from_slice(ctx, &slice, time_type, fsp)
-            .ok_or_else(|| Error::incorrect_datetime_value(self))
+            .map_err(|kind| Error::incorrect_datetime_value_with_reason(self, kind))
+    }
+
+    /// [`Time::round_frac`] with the fractional-part handling spelled out:
+    /// `FspRounding::Round` rounds like `round_frac`, while
+    /// `FspRounding::Truncate` cuts the microsecond part at `fsp` digits
+    /// without ever carrying into the seconds, appending [`Error::truncated`]
+    /// as a warning when a non-zero digit was dropped.
+    pub fn round_frac_with(
+        mut self,
+        ctx: &mut EvalContext,
+        fsp: i8,
+        rounding: FspRounding,
+    ) -> Result<Self> {
+        if rounding == FspRounding::Round {
+            return self.round_frac(ctx, fsp);
+        }
+        let time_type = self.get_time_type();
+        if time_type == TimeType::Date || self.is_zero() {
+            return Ok(self);
+        }
+
+        let fsp = check_fsp(fsp)?;
+        if fsp >= self.fsp() {
+            self.set_fsp(fsp);
+            return Ok(self);
+        }
+        // Truncation only ever shrinks the microsecond part, so the result
+        // needs no re-validation and never carries into the seconds.
+        let mask = TEN_POW[MICRO_WIDTH - usize::from(fsp)];
+        let micro = self.micro();
+        let kept = micro / mask * mask;
+        if kept != micro {
+            ctx.append_warning(Error::truncated());
+        }
+        self.set_micro(kept);
+        self.set_fsp(fsp);
+        Ok(self)
     }
 
     pub fn normalized(self, ctx: &mut EvalContext) -> Result<Self> {
@@ -1440,40 +2697,64 @@ impl Time {
     }
 
     pub fn checked_add(self, ctx: &mut EvalContext, rhs: Duration) -> Option<Time> {
-        let normalized = self.normalized(ctx).ok()?;
-        let duration = chrono::Duration::nanoseconds(rhs.to_nanos());
-        if self.get_time_type() == TimeType::Timestamp {
-            let datetime = normalized
-                .try_into_chrono_datetime(ctx)
-                .ok()
-                .and_then(|datetime| datetime.checked_add_signed(duration))?;
-            Time::try_from_chrono_datetime(ctx, datetime, TimeType::Timestamp, self.fsp() as i8)
-        } else {
-            let naive = normalized
-                .try_into_chrono_naive_datetime()
-                .ok()
-                .and_then(|datetime| datetime.checked_add_signed(duration))?;
-            Time::try_from_chrono_datetime(ctx, naive, TimeType::Timestamp, self.fsp() as i8)
-        }
-        .ok()
+        self.checked_add_nanos(ctx, rhs.to_nanos())
     }
 
     pub fn checked_sub(self, ctx: &mut EvalContext, rhs: Duration) -> Option<Time> {
+        self.checked_add_nanos(ctx, -rhs.to_nanos())
+    }
+
+    /// Shifts the wall time by `nanos` nanoseconds. `Timestamp` values go
+    /// through chrono, as the shift may cross a DST transition in the session
+    /// time zone; everything else is calendar arithmetic on the unpacked
+    /// fields via the day number, keeping `DATE_ADD` on datetime columns off
+    /// the chrono construction path. Non-timestamp results carry the
+    /// `DateTime` type regardless of `self`, as a shifted date gains a clock
+    /// part; results outside of `0000-01-01..=9999-12-31` yield `None`.
+    fn checked_add_nanos(self, ctx: &mut EvalContext, nanos: i64) -> Option<Time> {
         let normalized = self.normalized(ctx).ok()?;
-        let duration = chrono::Duration::nanoseconds(rhs.to_nanos());
         if self.get_time_type() == TimeType::Timestamp {
             let datetime = normalized
-                .try_into_chrono_datetime(ctx)
-                .ok()
-                .and_then(|datetime| datetime.checked_sub_signed(duration))?;
-            Time::try_from_chrono_datetime(ctx, datetime, TimeType::Timestamp, self.fsp() as i8)
-        } else {
-            let naive = normalized
-                .try_into_chrono_naive_datetime()
+                .try_into_chrono_datetime(&*ctx)
                 .ok()
-                .and_then(|datetime| datetime.checked_sub_signed(duration))?;
-            Time::try_from_chrono_datetime(ctx, naive, TimeType::Timestamp, self.fsp() as i8)
+                .and_then(|datetime| {
+                    datetime.checked_add_signed(chrono::Duration::nanoseconds(nanos))
+                })?;
+            return Time::try_from_chrono_datetime(
+                ctx,
+                datetime,
+                TimeType::Timestamp,
+                self.fsp() as i8,
+            )
+            .ok();
         }
+
+        const MICROS_PER_DAY: i64 = 24 * 60 * 60 * MICROS_PER_SEC;
+        // `second_number` counts from 0000-00-00, so everything below one
+        // day's worth of microseconds predates 0000-01-01 and has no
+        // representable date; `calc_date_from_day_number` rejects it along
+        // with the overflows past 9999-12-31. Durations carry at most
+        // microsecond precision, so dividing the nanoseconds loses nothing.
+        let micros = (normalized.second_number()? * MICROS_PER_SEC
+            + i64::from(normalized.micro()))
+        .checked_add(nanos / 1000)?;
+        let (year, month, day) = calc_date_from_day_number((micros / MICROS_PER_DAY) as i32)?;
+        let micros_of_day = micros % MICROS_PER_DAY;
+        let secs = micros_of_day / MICROS_PER_SEC;
+        Time::new(
+            ctx,
+            TimeArgs {
+                year,
+                month,
+                day,
+                hour: (secs / 3600) as u32,
+                minute: (secs / 60 % 60) as u32,
+                second: (secs % 60) as u32,
+                micro: (micros_of_day % MICROS_PER_SEC) as u32,
+                fsp: self.fsp() as i8,
+                time_type: TimeType::DateTime,
+            },
+        )
         .ok()
     }
 
@@ -1489,12 +2770,16 @@ impl Time {
         Some(lhs.signed_duration_since(rhs).num_days())
     }
 
-    pub fn ordinal(self) -> i32 {
-        if self.month() == 0 {
-            return self.day() as i32 - 32;
+    /// Returns the day of the year starting from 1, or `None` for
+    /// zero-in-date values, which have no meaningful ordinal.
+    pub fn ordinal(self) -> Option<i32> {
+        if self.month() == 0 || self.day() == 0 {
+            return None;
         }
-        ((1..self.month()).fold(0, |acc, month| acc + last_day_of_month(self.year(), month))
-            + self.day()) as i32
+        Some(
+            ((1..self.month()).fold(0, |acc, month| acc + last_day_of_month(self.year(), month))
+                + self.day()) as i32,
+        )
     }
 
     pub fn weekday(self) -> Weekday {
@@ -1539,9 +2824,10 @@ impl Time {
             'e' => {
                 write!(output, "{}", self.day()).unwrap();
             }
-            'j' => {
-                write!(output, "{:03}", self.days()).unwrap();
-            }
+            'j' => match self.days() {
+                Some(days) => write!(output, "{:03}", days).unwrap(),
+                None => return Err(box_err!("invalid time format")),
+            },
             'H' => {
                 write!(output, "{:02}", self.hour()).unwrap();
             }
@@ -1672,6 +2958,22 @@ impl Time {
                     write!(output, "{:04}", year).unwrap();
                 }
             }
+            // `%G` and `%g` are TiKV extensions, not defined by MySQL: the
+            // strftime ISO 8601 week-based year (the year `%v` belongs to) in
+            // four and two digits. They ease migrating PostgreSQL-flavored
+            // format strings and behave exactly like `%x` / `%x mod 100`.
+            'G' => {
+                let (year, _) = self.year_week(WeekMode::from_bits_truncate(3));
+                if year < 0 {
+                    write!(output, "{}", u32::max_value()).unwrap();
+                } else {
+                    write!(output, "{:04}", year).unwrap();
+                }
+            }
+            'g' => {
+                let (year, _) = self.year_week(WeekMode::from_bits_truncate(3));
+                write!(output, "{:02}", year % 100).unwrap();
+            }
             'Y' => {
                 write!(output, "{:04}", self.year()).unwrap();
             }
@@ -1683,21 +2985,38 @@ impl Time {
         Ok(())
     }
 
-    pub fn date_format(self, layout: &str) -> Result<String> {
-        let mut ret = String::new();
+    /// An upper bound on the bytes `layout` formats into: literals cost
+    /// their own length and no specifier expands by more than the 9 extra
+    /// bytes of `%r` (`12:00:00 AM`).
+    fn date_format_estimated_len(layout: &str) -> usize {
+        layout.len() + layout.matches('%').count() * 9
+    }
+
+    /// Formats `self` per `layout` (as MySQL `DATE_FORMAT`), appending to
+    /// `out` without clearing it, so a vectorized caller can reuse one
+    /// buffer across a batch instead of allocating per row. The needed
+    /// capacity is reserved up front from a pre-scan of the layout.
+    pub fn date_format_into(self, layout: &str, out: &mut String) -> Result<()> {
+        out.reserve(Self::date_format_estimated_len(layout));
         let mut pattern_match = false;
         for b in layout.chars() {
             if pattern_match {
-                self.write_date_format_segment(b, &mut ret)?;
+                self.write_date_format_segment(b, out)?;
                 pattern_match = false;
                 continue;
             }
             if b == '%' {
                 pattern_match = true;
             } else {
-                ret.push(b);
+                out.push(b);
             }
         }
+        Ok(())
+    }
+
+    pub fn date_format(self, layout: &str) -> Result<String> {
+        let mut ret = String::new();
+        self.date_format_into(layout, &mut ret)?;
         Ok(ret)
     }
 
@@ -1714,7 +3033,7 @@ impl Time {
             write!(
                 buffer,
                 ".{:0width$}",
-                self.micro() / TEN_POW[MICRO_WIDTH - fsp],
+                scale_micro_to_fsp(self.micro(), self.fsp()),
                 width = fsp
             )
             .unwrap();
@@ -1722,6 +3041,41 @@ impl Time {
         buffer
     }
 
+    /// Packs the time into its numeric `yyyymmddhhmmss` representation,
+    /// computed from the bitfield accessors directly instead of printing a
+    /// string and reparsing it. `Date` values carry no hms part and pack as
+    /// `yyyymmdd`; zero dates pack to 0.
+    #[inline]
+    pub fn to_numeric_i64(self) -> i64 {
+        let date =
+            i64::from(self.year()) * 10_000 + i64::from(self.month()) * 100 + i64::from(self.day());
+        if self.get_time_type() == TimeType::Date {
+            return date;
+        }
+        date * 1_000_000
+            + i64::from(self.hour()) * 10_000
+            + i64::from(self.minute()) * 100
+            + i64::from(self.second())
+    }
+
+    /// `to_numeric_i64` as an unsigned value; the packed form is never
+    /// negative.
+    #[inline]
+    pub fn to_numeric_u64(self) -> u64 {
+        self.to_numeric_i64() as u64
+    }
+
+    /// Splits the numeric representation into the whole `yyyymmddhhmmss`
+    /// part and a microsecond part truncated to the time's fsp — exactly
+    /// the fractional digits `to_numeric_string` prints, widened back to
+    /// microseconds.
+    #[inline]
+    pub fn to_numeric_parts(self) -> (i64, u32) {
+        let fsp = usize::from(self.fsp());
+        let micro = scale_micro_to_fsp(self.micro(), self.fsp()) * TEN_POW[MICRO_WIDTH - fsp];
+        (self.to_numeric_i64(), micro)
+    }
+
     pub fn parse_fsp(s: &str) -> i8 {
         s.rfind('.').map_or(super::DEFAULT_FSP, |idx| {
             std::cmp::min((s.len() - idx - 1) as i8, super::MAX_FSP)
@@ -1785,9 +3139,13 @@ impl ConvertTo<f64> for Time {
         if self.is_zero() {
             return Ok(0f64);
         }
-        let r = self.to_numeric_string().parse::<f64>();
-        debug_assert!(r.is_ok());
-        Ok(r?)
+        // Matches what parsing `to_numeric_string()` used to produce: the
+        // fractional part is truncated to fsp digits first (fsp=4 keeps
+        // `.123456` as `.1235`, since the stored micro was already rounded
+        // to fsp on construction) and only then widened to a float. The
+        // exact expected values are pinned by `test_convert_to_f64`.
+        let (whole, micro) = self.to_numeric_parts();
+        Ok(whole as f64 + f64::from(micro) / 1e6)
     }
 }
 
@@ -1798,8 +3156,23 @@ impl ConvertTo<Decimal> for Time {
         if self.is_zero() {
             return Ok(0.into());
         }
-
-        self.to_numeric_string().parse()
+        let (whole, micro) = self.to_numeric_parts();
+        let fsp = self.fsp();
+        if fsp == 0 {
+            return Ok(whole.into());
+        }
+        // At most 14 whole and 6 fractional digits, so none of the shifts
+        // or the addition can overflow the decimal word buffer.
+        let frac = i64::from(micro / TEN_POW[MICRO_WIDTH - usize::from(fsp)]);
+        let shifted = Decimal::from(whole).shift(fsp as isize).unwrap();
+        let packed = (&shifted + &Decimal::from(frac)).unwrap();
+        // The final rounding only pads the fraction back to `fsp` digits,
+        // matching the trailing zeros of the string representation.
+        Ok(packed
+            .shift(-(fsp as isize))
+            .unwrap()
+            .round(fsp as i8, RoundMode::HalfEven)
+            .unwrap())
     }
 }
 
@@ -1819,13 +3192,16 @@ impl ConvertTo<Duration> for Time {
     }
 }
 
+/// Clears the low `fsp_tt` bits (see the bitfield definition), which carry
+/// the fsp and the time type and take no part in equality, ordering or
+/// hashing. Masking directly keeps these impls branch-free;
+/// `test_fsp_tt_mask_matches_layout` guards the constant against layout
+/// changes.
+const FSP_TT_MASK: u64 = !0xF;
+
 impl PartialEq for Time {
     fn eq(&self, other: &Self) -> bool {
-        let mut a = *self;
-        let mut b = *other;
-        a.set_fsp_tt(0);
-        b.set_fsp_tt(0);
-        a.0 == b.0
+        self.0 & FSP_TT_MASK == other.0 & FSP_TT_MASK
     }
 }
 
@@ -1839,19 +3215,13 @@ impl PartialOrd for Time {
 
 impl Ord for Time {
     fn cmp(&self, right: &Self) -> Ordering {
-        let mut a = *self;
-        let mut b = *right;
-        a.set_fsp_tt(0);
-        b.set_fsp_tt(0);
-        a.0.cmp(&b.0)
+        (self.0 & FSP_TT_MASK).cmp(&(right.0 & FSP_TT_MASK))
     }
 }
 
 impl Hash for Time {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let mut a = *self;
-        a.set_fsp_tt(0);
-        a.0.hash(state);
+        (self.0 & FSP_TT_MASK).hash(state);
     }
 }
 
@@ -1879,7 +3249,7 @@ impl std::fmt::Display for Time {
                 write!(
                     f,
                     ".{:0width$}",
-                    self.micro() / TEN_POW[MICRO_WIDTH - fsp],
+                    scale_micro_to_fsp(self.micro(), self.fsp()),
                     width = fsp
                 )?;
             }
@@ -1993,7 +3363,11 @@ mod tests {
         no_zero_date: bool,
         allow_invalid_date: bool,
         ignore_truncate: bool,
+        partial_datetime_as_error: bool,
+        normalize_fullwidth_chars: bool,
+        parse_tz_name: bool,
         time_zone: Option<Tz>,
+        two_digit_year_cutoff: Option<u32>,
     }
 
     impl From<TimeEnv> for EvalContext {
@@ -2018,10 +3392,23 @@ mod tests {
             if config.ignore_truncate {
                 flags |= Flag::IGNORE_TRUNCATE;
             }
+            if config.partial_datetime_as_error {
+                flags |= Flag::PARTIAL_DATETIME_AS_ERROR;
+            }
+            if config.normalize_fullwidth_chars {
+                flags |= Flag::NORMALIZE_FULLWIDTH_CHARS;
+            }
+            if config.parse_tz_name {
+                flags |= Flag::PARSE_TZ_NAME;
+            }
 
             eval_config.set_sql_mode(sql_mode).set_flag(flags).tz =
                 config.time_zone.unwrap_or_else(Tz::utc);
 
+            if let Some(cutoff) = config.two_digit_year_cutoff {
+                eval_config.set_two_digit_year_cutoff(cutoff);
+            }
+
             EvalContext::new(Arc::new(eval_config))
         }
     }
@@ -2058,35 +3445,269 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_from_real() -> Result<()> {
+    fn test_two_digit_year_cutoff() -> Result<()> {
+        // The default cutoff maps 69 and below into 20yy.
+        let mut ctx = EvalContext::default();
         let cases = vec![
-            ("2000-03-05 00:00:00", "305", 0),
-            ("2000-12-03 00:00:00", "1203", 0),
-            ("2003-12-05 00:00:00.0", "31205", 1),
-            ("2007-01-18 00:00:00.00", "070118", 2),
-            ("0101-12-09 00:00:00.000", "1011209.333", 3),
-            ("2017-01-18 00:00:00.0000", "20170118.123", 4),
-            ("2012-12-31 11:30:45.12335", "121231113045.123345", 5),
-            ("2012-12-31 11:30:45.125000", "20121231113045.123345", 6),
-            ("2012-12-31 11:30:46.00000", "121231113045.9999999", 5),
-            ("2017-01-05 08:40:59.5756", "170105084059.575601", 4),
+            ("49-01-01", "2049-01-01"),
+            ("70-01-01", "1970-01-01"),
+            ("490101", "2049-01-01"),
         ];
-        let mut ctx = EvalContext::default();
-        for (expected, input, fsp) in cases {
-            let input: Real = input.parse().unwrap();
-            let actual_real =
-                Time::parse_from_real(&mut ctx, &input, TimeType::DateTime, fsp, true)?;
-            assert_eq!(actual_real.to_string(), expected);
+        for (input, expected) in cases {
+            assert_eq!(Time::parse_date(&mut ctx, input)?.to_string(), expected);
         }
 
-        let should_fail = vec![
-            "201705051315111.22",
-            "2011110859.1111",
-            "2011110859.1111",
-            "191203081.1111",
-            "43128.121105",
-        ];
-
+        // A session-level cutoff moves the century window consistently for
+        // separated strings, compact strings and numeric input.
+        let mut ctx: EvalContext = TimeEnv {
+            two_digit_year_cutoff: Some(49),
+            ..TimeEnv::default()
+        }
+        .into();
+        let cases = vec![
+            ("49-01-01", "2049-01-01"),
+            ("50-01-01", "1950-01-01"),
+            ("490101", "2049-01-01"),
+            ("500101", "1950-01-01"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(Time::parse_date(&mut ctx, input)?.to_string(), expected);
+        }
+        assert_eq!(
+            Time::parse_from_i64(&mut ctx, 491_231_235_959, TimeType::DateTime, 0)?.to_string(),
+            "2049-12-31 23:59:59"
+        );
+        assert_eq!(
+            Time::parse_from_i64(&mut ctx, 500_101_000_000, TimeType::DateTime, 0)?.to_string(),
+            "1950-01-01 00:00:00"
+        );
+        // The span between the two century windows stays unreachable.
+        Time::parse_from_i64(&mut ctx, 495_000, TimeType::DateTime, 0).unwrap_err();
+
+        // With a cutoff of 99, every two-digit year lands in 20yy.
+        let mut ctx: EvalContext = TimeEnv {
+            two_digit_year_cutoff: Some(99),
+            ..TimeEnv::default()
+        }
+        .into();
+        assert_eq!(
+            Time::parse_date(&mut ctx, "99-12-31")?.to_string(),
+            "2099-12-31"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_batch() -> Result<()> {
+        let inputs = vec![
+            "2020-02-29 10:30:45.123456",
+            "not-a-date",
+            "700101",
+            "2021-02-29",
+            "2020-12-23 15:59:23Z",
+            "  2012-12-31 11:30:45.9999999  ",
+        ];
+
+        let mut batch_ctx = EvalContext::default();
+        let batched =
+            Time::parse_batch(&mut batch_ctx, &inputs, TimeType::DateTime, MAX_FSP, true)?;
+
+        // Row for row, the batch agrees with the scalar parser.
+        let mut scalar_ctx = EvalContext::default();
+        for (input, batched) in inputs.iter().zip(batched) {
+            let scalar = Time::parse_datetime(&mut scalar_ctx, input, MAX_FSP, true).ok();
+            assert_eq!(scalar, batched, "input: {}", input);
+        }
+        // Each failed row warned exactly once.
+        assert_eq!(batch_ctx.take_warnings().warning_cnt, 2);
+
+        // An invalid fsp fails the whole batch up front, like the scalar
+        // path fails every row.
+        Time::parse_batch(&mut batch_ctx, &inputs, TimeType::DateTime, 10, true).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_kind() {
+        use TimeParseErrorKind::*;
+
+        let mut ctx = EvalContext::default();
+        let cases: Vec<(&str, TimeParseErrorKind)> = vec![
+            ("", Empty),
+            ("   ", Empty),
+            ("2020-12-23x15:59:23", BadSeparator),
+            ("2020-12-23 15:59:23x", BadSeparator),
+            // A space may only stand after the day.
+            ("2020-12 15:59:23", BadSeparator),
+            ("1-2-3-4-5-6-7-8-9-10", TooManyComponents),
+            ("2020-13-23 15:59:23", OutOfRangeComponent { which: "month" }),
+            ("2020-02-30 15:59:23", OutOfRangeComponent { which: "day" }),
+            ("2020-12-23 25:59:23", OutOfRangeComponent { which: "hour" }),
+            ("2020-12-23 15:61:23", OutOfRangeComponent { which: "minute" }),
+            ("2020-12-23 15:59:61", OutOfRangeComponent { which: "second" }),
+            ("2020-12-23 15:59:23+15:00", InvalidTzOffset),
+            ("2020-12-23 15:59:23-00:00", InvalidTzOffset),
+            ("2020-12-23 15:59:23+08:0", InvalidTzOffset),
+            // 'Z' may only follow a complete datetime.
+            ("2020-12Z", InvalidTzOffset),
+        ];
+        for (input, kind) in cases {
+            assert_eq!(
+                parser::parse(&mut ctx, input, TimeType::DateTime, MAX_FSP as u8, true),
+                Err(kind),
+                "input: {}",
+                input
+            );
+            // The error surfaced to the user keeps the old code but carries
+            // the detail in its message.
+            let err = Time::parse_datetime(&mut ctx, input, MAX_FSP, true).unwrap_err();
+            assert!(err.is_truncated(), "input: {}", input);
+            assert!(
+                err.to_string().contains(&kind.to_string()),
+                "input: {}, err: {}",
+                input,
+                err
+            );
+        }
+
+        // A zero date is only an error when the sql mode forbids it.
+        let mut ctx = EvalContext::from(TimeEnv {
+            strict_mode: true,
+            no_zero_date: true,
+            ..TimeEnv::default()
+        });
+        assert_eq!(
+            parser::parse(&mut ctx, "0000-00-00", TimeType::DateTime, 0, true),
+            Err(ZeroDateForbidden)
+        );
+
+        // A timestamp whose fields are individually fine can still lie
+        // outside the type's range.
+        let mut ctx = EvalContext::default();
+        assert_eq!(
+            parser::parse(&mut ctx, "1888-01-01 10:00:00", TimeType::Timestamp, 0, true),
+            Err(TimestampOutOfRange)
+        );
+
+        // An unsupported fsp is reported by the component checks.
+        let args = TimeArgs {
+            year: 2020,
+            month: 12,
+            day: 23,
+            fsp: 10,
+            time_type: TimeType::DateTime,
+            ..TimeArgs::default()
+        };
+        assert_eq!(args.check(&mut ctx).unwrap_err(), FspTooLarge);
+    }
+
+    #[test]
+    fn test_datetime_literal_cache() {
+        fn ctx_with_cache(offset_hours: i64) -> EvalContext {
+            let mut cfg = EvalConfig::new();
+            cfg.tz = Tz::from_offset(offset_hours * 3600).unwrap();
+            cfg.set_cache_datetime_literals(true);
+            EvalContext::new(Arc::new(cfg))
+        }
+
+        // A warm hit returns the same value as the cold parse.
+        let mut ctx = ctx_with_cache(8);
+        let cold = Time::parse_datetime(&mut ctx, "2020-10-10T10:10:10Z", MAX_FSP, true).unwrap();
+        assert_eq!(cold.to_string(), "2020-10-10 18:10:10.000000");
+        let warm = Time::parse_datetime(&mut ctx, "2020-10-10T10:10:10Z", MAX_FSP, true).unwrap();
+        assert_eq!(cold, warm);
+
+        // A zone change re-parses the literal despite the warm cache.
+        let mut ctx = ctx_with_cache(-8);
+        let moved = Time::parse_datetime(&mut ctx, "2020-10-10T10:10:10Z", MAX_FSP, true).unwrap();
+        assert_eq!(moved.to_string(), "2020-10-10 02:10:10.000000");
+
+        // So does an sql-mode change: the zero date the cache has seen in
+        // the permissive mode is still rejected under NO_ZERO_DATE.
+        let mut ctx = ctx_with_cache(8);
+        Time::parse_datetime(&mut ctx, "0000-00-00", 0, true).unwrap();
+        let mut cfg = EvalConfig::new();
+        cfg.set_sql_mode(SqlMode::STRICT_ALL_TABLES | SqlMode::NO_ZERO_DATE);
+        cfg.set_cache_datetime_literals(true);
+        let mut ctx = EvalContext::new(Arc::new(cfg));
+        Time::parse_datetime(&mut ctx, "0000-00-00", 0, true).unwrap_err();
+
+        // The fsp takes part in the key.
+        let mut ctx = ctx_with_cache(8);
+        let wide = Time::parse_datetime(&mut ctx, "2020-01-01 00:00:00.555", 2, true).unwrap();
+        assert_eq!(wide.to_string(), "2020-01-01 00:00:00.56");
+        let narrow = Time::parse_datetime(&mut ctx, "2020-01-01 00:00:00.555", 0, true).unwrap();
+        assert_eq!(narrow.to_string(), "2020-01-01 00:00:01");
+
+        // Parses that warn are never cached, so a hit cannot swallow the
+        // warning: the partial datetime warns on every parse.
+        let mut ctx = ctx_with_cache(8);
+        Time::parse_datetime(&mut ctx, "2020-01-01 10", 0, true).unwrap();
+        Time::parse_datetime(&mut ctx, "2020-01-01 10", 0, true).unwrap();
+        assert_eq!(ctx.take_warnings().warning_cnt, 2);
+    }
+
+    #[test]
+    fn test_last_day_of_month_cache() {
+        // Alternating years with different February lengths must not leak
+        // stale answers out of the two-entry cache.
+        for _ in 0..4 {
+            assert_eq!(cached_last_day_of_month(2020, 2), 29);
+            assert_eq!(cached_last_day_of_month(2021, 2), 28);
+            assert_eq!(cached_last_day_of_month(2000, 2), 29);
+            assert_eq!(cached_last_day_of_month(1900, 2), 28);
+        }
+        // Every (year, month) agrees with the uncached function whatever the
+        // interleaving, including the out-of-range months rounding may probe.
+        for year in [0, 1900, 1999, 2000, 2020, 2021, 9999] {
+            for month in 0..=13 {
+                assert_eq!(
+                    cached_last_day_of_month(year, month),
+                    last_day_of_month(year, month),
+                    "year: {}, month: {}",
+                    year,
+                    month
+                );
+            }
+        }
+        // And through the public check path: a leap day parses in a leap
+        // year, fails right afterwards in a common year, and parses again.
+        let mut ctx = EvalContext::default();
+        Time::parse_date(&mut ctx, "2020-02-29").unwrap();
+        Time::parse_date(&mut ctx, "2021-02-29").unwrap_err();
+        Time::parse_date(&mut ctx, "2020-02-29").unwrap();
+    }
+
+    #[test]
+    fn test_parse_from_real() -> Result<()> {
+        let cases = vec![
+            ("2000-03-05 00:00:00", "305", 0),
+            ("2000-12-03 00:00:00", "1203", 0),
+            ("2003-12-05 00:00:00.0", "31205", 1),
+            ("2007-01-18 00:00:00.00", "070118", 2),
+            ("0101-12-09 00:00:00.000", "1011209.333", 3),
+            ("2017-01-18 00:00:00.0000", "20170118.123", 4),
+            ("2012-12-31 11:30:45.12335", "121231113045.123345", 5),
+            ("2012-12-31 11:30:45.125000", "20121231113045.123345", 6),
+            ("2012-12-31 11:30:46.00000", "121231113045.9999999", 5),
+            ("2017-01-05 08:40:59.5756", "170105084059.575601", 4),
+        ];
+        let mut ctx = EvalContext::default();
+        for (expected, input, fsp) in cases {
+            let input: Real = input.parse().unwrap();
+            let actual_real =
+                Time::parse_from_real(&mut ctx, &input, TimeType::DateTime, fsp, true)?;
+            assert_eq!(actual_real.to_string(), expected);
+        }
+
+        let should_fail = vec![
+            "201705051315111.22",
+            "2011110859.1111",
+            "2011110859.1111",
+            "191203081.1111",
+            "43128.121105",
+        ];
+
         for case in should_fail {
             let case: Real = case.parse().unwrap();
             Time::parse_from_real(&mut ctx, &case, TimeType::DateTime, 0, true).unwrap_err();
@@ -2094,6 +3715,89 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_from_ymd_hms_micro() -> Result<()> {
+        let mut ctx = EvalContext::default();
+
+        let t = Time::from_ymd_hms_micro(
+            &mut ctx,
+            2020,
+            2,
+            29,
+            10,
+            11,
+            12,
+            123400,
+            TimeType::DateTime,
+            4,
+        )?;
+        assert_eq!(t.to_string(), "2020-02-29 10:11:12.1234");
+        assert_eq!(t.components(), (2020, 2, 29, 10, 11, 12, 123400));
+
+        // `UNSPECIFIED_FSP` falls back to the default.
+        let t = Time::from_ymd_hms_micro(
+            &mut ctx,
+            2020,
+            1,
+            1,
+            0,
+            0,
+            0,
+            0,
+            TimeType::DateTime,
+            UNSPECIFIED_FSP,
+        )?;
+        assert_eq!(t.fsp(), crate::codec::mysql::DEFAULT_FSP as u8);
+
+        // Dates drop the time-of-day components.
+        let t = Time::from_ymd_hms_micro(
+            &mut ctx,
+            2020,
+            2,
+            29,
+            10,
+            11,
+            12,
+            123400,
+            TimeType::Date,
+            4,
+        )?;
+        assert_eq!(t.components(), (2020, 2, 29, 0, 0, 0, 0));
+
+        // Zero dates are admitted outside of NO_ZERO_DATE mode.
+        let t = Time::from_ymd_hms_micro(&mut ctx, 0, 0, 0, 0, 0, 0, 0, TimeType::DateTime, 0)?;
+        assert!(t.is_zero());
+
+        // (year, month, day, hour, minute, second, micro, fsp)
+        let invalid = vec![
+            (2020, 13, 1, 0, 0, 0, 0, 0),
+            (2019, 2, 29, 0, 0, 0, 0, 0),
+            (2020, 1, 1, 24, 0, 0, 0, 0),
+            (2020, 1, 1, 0, 60, 0, 0, 0),
+            (2020, 1, 1, 0, 0, 60, 0, 0),
+            (2020, 1, 1, 0, 0, 0, 1_000_000, 6),
+            (10000, 1, 1, 0, 0, 0, 0, 0),
+            // Out-of-range fsp.
+            (2020, 1, 1, 0, 0, 0, 0, 7),
+        ];
+        for (year, month, day, hour, minute, second, micro, fsp) in invalid {
+            Time::from_ymd_hms_micro(
+                &mut ctx,
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                micro,
+                TimeType::DateTime,
+                fsp,
+            )
+            .unwrap_err();
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_parse_from_decimal() -> Result<()> {
         let cases = vec![
@@ -2139,7 +3843,7 @@ mod tests {
             ("2019-09-16", "1909161011"),
             ("2019-09-16", "190916101"),
             ("1909-12-10", "19091210"),
-            ("2019-09-16", "1909161"),
+            ("0190-12-16", "1901216"),
             ("2019-09-16", "190916"),
             ("2019-09-01", "19091"),
             ("2019-09-16", "190916101112.111"),
@@ -2188,6 +3892,8 @@ mod tests {
             ("11121311121.1"),
             ("1201012736"),
             ("1201012736.0"),
+            // A 7-digit string carries a three-digit year: 0190-91-61.
+            ("1909161"),
             ("111213111.1"),
             ("11121311.1"),
             ("1112131.1"),
@@ -2203,6 +3909,129 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_english_month_name() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let datetime_cases = vec![
+            ("12-Jan-2020", "2020-01-12 00:00:00"),
+            ("12-jan-2020", "2020-01-12 00:00:00"),
+            ("12-JANUARY-2020", "2020-01-12 00:00:00"),
+            ("2020-Jan-12 10:11:12", "2020-01-12 10:11:12"),
+            ("12-Dec-2020 10:11:12", "2020-12-12 10:11:12"),
+            ("2020-September-12", "2020-09-12 00:00:00"),
+            // Trailing punctuation after the name is consumed like any
+            // other separator run.
+            ("12-Jan.-2020", "2020-01-12 00:00:00"),
+            // Without a four-digit component the order stays positional
+            // and the two-digit year is adjusted as usual.
+            ("12-Jan-20", "2012-01-20 00:00:00"),
+        ];
+        for (input, expected) in datetime_cases {
+            let t = Time::parse_datetime(&mut ctx, input, 0, false)?;
+            assert_eq!(t.to_string(), expected, "input: {}", input);
+        }
+
+        let date = Time::parse_date(&mut ctx, "12-Jan-2020")?;
+        assert_eq!(date.to_string(), "2020-01-12");
+
+        let should_fail = vec![
+            // Not a month name.
+            "12-Foo-2020",
+            // A name is only accepted in the month position.
+            "Jan-12-2020",
+            "2020-01-Jan",
+        ];
+        for case in should_fail {
+            Time::parse_datetime(&mut ctx, case, 0, false).unwrap_err();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ordinal_date() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let cases = vec![
+            ("2020-001", "2020-01-01 00:00:00"),
+            // Day-of-year counts through the leap day.
+            ("2020-123", "2020-05-02 00:00:00"),
+            ("2019-123", "2019-05-03 00:00:00"),
+            ("2020-366", "2020-12-31 00:00:00"),
+            // Time and timezone suffixes compose with the ordinary path.
+            ("2020-123T10:11:12", "2020-05-02 10:11:12"),
+            ("2020-123 10:11:12", "2020-05-02 10:11:12"),
+            ("2020-123T10:11:12Z", "2020-05-02 10:11:12"),
+        ];
+        for (input, expected) in cases {
+            let t = Time::parse_datetime(&mut ctx, input, 0, false)?;
+            assert_eq!(t.to_string(), expected, "input: {}", input);
+        }
+
+        let date = Time::parse_date(&mut ctx, "2019-365")?;
+        assert_eq!(date.to_string(), "2019-12-31");
+
+        let should_fail = vec![
+            // Day 366 only exists in leap years.
+            "2019-366",
+            "2020-000",
+            "2020-367",
+            // An ordinal date needs a four-digit year.
+            "20-123",
+            "2020-1234",
+        ];
+        for case in should_fail {
+            Time::parse_datetime(&mut ctx, case, 0, false).unwrap_err();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_week_date() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let cases = vec![
+            // Jan 1 2021 is a Friday: week 1 starts on Jan 4 and the first
+            // days of January belong to 2020-W53.
+            ("2021-W01-1", "2021-01-04 00:00:00"),
+            ("2021-W01-5", "2021-01-08 00:00:00"),
+            ("2020-W53-5", "2021-01-01 00:00:00"),
+            // Jan 1 2022 is a Saturday.
+            ("2022-W01-1", "2022-01-03 00:00:00"),
+            ("2021-W52-6", "2022-01-01 00:00:00"),
+            // Jan 1 2023 is a Sunday.
+            ("2023-W01-1", "2023-01-02 00:00:00"),
+            ("2022-W52-7", "2023-01-01 00:00:00"),
+            // The week year reaches back into the previous calendar year.
+            ("2020-W01-1", "2019-12-30 00:00:00"),
+            // Time and timezone suffixes compose with the ordinary path.
+            ("2020-W05-3T10:11:12", "2020-01-29 10:11:12"),
+            ("2020-W05-3 10:11:12", "2020-01-29 10:11:12"),
+            ("2020-W05-3T10:11:12Z", "2020-01-29 10:11:12"),
+        ];
+        for (input, expected) in cases {
+            let t = Time::parse_datetime(&mut ctx, input, 0, false)?;
+            assert_eq!(t.to_string(), expected, "input: {}", input);
+        }
+
+        // The round trip agrees with the mode-3 (`%x`/`%v`) week math.
+        let t = Time::parse_datetime(&mut ctx, "2020-W53-5", 0, false)?;
+        assert_eq!(t.year_week(WeekMode::from_bits_truncate(3)), (2020, 53));
+
+        let should_fail = vec![
+            // 2021 has only 52 ISO weeks.
+            "2021-W53-1",
+            "2020-W54-1",
+            "2020-W00-1",
+            // Weekdays run from 1 (Monday) to 7 (Sunday).
+            "2020-W05-0",
+            "2020-W05-8",
+            // A week date needs a four-digit year.
+            "20-W05-3",
+        ];
+        for case in should_fail {
+            Time::parse_datetime(&mut ctx, case, 0, false).unwrap_err();
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_parse_valid_datetime() -> Result<()> {
         let mut ctx = EvalContext::default();
@@ -2214,7 +4043,8 @@ mod tests {
             ("2019-09-16 10:01:00", "190916101", 0, false),
             ("1909-12-10 00:00:00", "19091210", 0, false),
             ("2020-02-29 10:00:00", "20200229100000", 0, false),
-            ("2019-09-16 01:00:00", "1909161", 0, false),
+            ("0190-12-16 00:00:00", "1901216", 0, false),
+            ("0190-12-16 10:11:12.5", "1901216101112.5", 1, false),
             ("2019-09-16 00:00:00", "190916", 0, false),
             ("2019-09-01 00:00:00", "19091", 0, false),
             ("2019-09-16 10:11:12.111", "190916101112.111", 3, false),
@@ -2320,6 +4150,8 @@ mod tests {
             ("11121311121.1", 2),
             ("1201012736", 2),
             ("1201012736.0", 2),
+            // A 7-digit string carries a three-digit year: 0190-91-61.
+            ("1909161", 0),
             ("111213111.1", 2),
             ("11121311.1", 2),
             ("1112131.1", 2),
@@ -2335,6 +4167,110 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_partial_datetime() -> Result<()> {
+        // A partially-specified time part zero-fills the missing components
+        // and emits a truncation warning.
+        let partial = vec![
+            ("2020-01-01 10:00:00", "2020-01-01 10"),
+            ("2020-01-01 10:20:00", "2020-01-01 10:20"),
+        ];
+        for &(expected, actual) in partial.iter() {
+            let mut ctx = EvalContext::default();
+            assert_eq!(
+                expected,
+                Time::parse_datetime(&mut ctx, actual, 0, false)?.to_string()
+            );
+            assert_eq!(ctx.warnings.warning_cnt, 1, "{}", actual);
+        }
+
+        // Fully-specified forms emit no warning.
+        let complete = vec!["2020-01-01 10:20:30", "2020-01-01", "20200101102030"];
+        for &case in complete.iter() {
+            let mut ctx = EvalContext::default();
+            Time::parse_datetime(&mut ctx, case, 0, false)?;
+            assert_eq!(ctx.warnings.warning_cnt, 0, "{}", case);
+        }
+
+        // Strict mode alone keeps the lenient zero-filling, only warning.
+        let mut ctx = EvalContext::from(TimeEnv {
+            strict_mode: true,
+            ..TimeEnv::default()
+        });
+        Time::parse_datetime(&mut ctx, "2020-01-01 10", 0, false)?;
+        assert_eq!(ctx.warnings.warning_cnt, 1);
+
+        // So does the flag without strict mode.
+        let mut ctx = EvalContext::from(TimeEnv {
+            partial_datetime_as_error: true,
+            ..TimeEnv::default()
+        });
+        Time::parse_datetime(&mut ctx, "2020-01-01 10:20", 0, false)?;
+        assert_eq!(ctx.warnings.warning_cnt, 1);
+
+        // Strict mode plus the flag upgrades the warning to an error.
+        for &case in &["2020-01-01 10", "2020-01-01 10:20"] {
+            let mut ctx = EvalContext::from(TimeEnv {
+                strict_mode: true,
+                partial_datetime_as_error: true,
+                ..TimeEnv::default()
+            });
+            Time::parse_datetime(&mut ctx, case, 0, false).unwrap_err();
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fsp_tt_mask_matches_layout() {
+        // Clearing `fsp_tt` through the bitfield setter must agree with the
+        // constant mask used by the comparison and hash impls.
+        let mut t = Time(u64::MAX);
+        t.set_fsp_tt(0);
+        assert_eq!(t.0, FSP_TT_MASK);
+
+        let mut t = Time(0);
+        t.set_fsp_tt(0b1111);
+        assert_eq!(t.0, !FSP_TT_MASK);
+    }
+
+    #[test]
+    fn test_parse_fullwidth_datetime() -> Result<()> {
+        let cases = vec![
+            ("2024-12-17 11:55:55", "２０２４-12-17 11：55：55"),
+            ("2024-12-17 11:55:55", "２０２４－１２－１７ １１：５５：５５"),
+            ("2024-12-17 00:00:00", "２０２４１２１７"),
+        ];
+        for &(expected, fullwidth) in cases.iter() {
+            // With the flag set, fullwidth forms are mapped to their
+            // halfwidth equivalents before parsing.
+            let mut ctx = EvalContext::from(TimeEnv {
+                normalize_fullwidth_chars: true,
+                ..TimeEnv::default()
+            });
+            assert_eq!(
+                expected,
+                Time::parse_datetime(&mut ctx, fullwidth, 0, false)?.to_string()
+            );
+
+            // Without it, such inputs are rejected as before.
+            let mut ctx = EvalContext::default();
+            Time::parse_datetime(&mut ctx, fullwidth, 0, false).unwrap_err();
+        }
+
+        // Halfwidth inputs are untouched by the flag.
+        let mut ctx = EvalContext::from(TimeEnv {
+            normalize_fullwidth_chars: true,
+            ..TimeEnv::default()
+        });
+        assert_eq!(
+            "2024-12-17 11:55:55",
+            Time::parse_datetime(&mut ctx, "2024-12-17 11:55:55", 0, false)?.to_string()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_valid_timestamp() -> Result<()> {
         let mut ctx = EvalContext::default();
@@ -2345,7 +4281,6 @@ mod tests {
             ("2019-09-16 10:11:00", "1909161011", 0, false),
             ("2019-09-16 10:01:00", "190916101", 0, false),
             ("2019-12-10 00:00:00", "20191210", 0, false),
-            ("2019-09-16 01:00:00", "1909161", 0, false),
             ("2019-09-16 00:00:00", "190916", 0, false),
             ("2019-09-01 00:00:00", "19091", 0, false),
             ("2019-09-16 10:11:12.111", "190916101112.111", 3, false),
@@ -2558,6 +4493,321 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_time_with_tz_name() -> Result<()> {
+        let ctx_with_tz = |tz: &str, parse_tz_name: bool| {
+            EvalContext::from(TimeEnv {
+                time_zone: Some(Tz::from_tz_name(tz).unwrap()),
+                parse_tz_name,
+                ..TimeEnv::default()
+            })
+        };
+        // (session time zone, input, expected)
+        let cases = vec![
+            // A named zone converts into the session zone like an offset
+            // suffix does.
+            (
+                "UTC",
+                "2021-03-14 02:30:00 Asia/Shanghai",
+                Some("2021-03-13 18:30:00.000000"),
+            ),
+            (
+                "Asia/Shanghai",
+                "2021-03-14 02:30:00 UTC",
+                Some("2021-03-14 10:30:00.000000"),
+            ),
+            // It composes with the ISO time separator and a fraction.
+            (
+                "UTC",
+                "2020-10-10T10:10:10.123 America/New_York",
+                Some("2020-10-10 14:10:10.123000"),
+            ),
+            // The UTC/GMT words go through the same path.
+            (
+                "UTC",
+                "2020-01-01 00:00:00 GMT",
+                Some("2020-01-01 00:00:00.000000"),
+            ),
+            // 2020-11-01 01:30:00 occurs twice in America/New_York; the
+            // earliest mapping (EDT, UTC-4) is taken.
+            (
+                "UTC",
+                "2020-11-01 01:30:00 America/New_York",
+                Some("2020-11-01 05:30:00.000000"),
+            ),
+            // An unknown name fails the whole parse.
+            ("UTC", "2020-01-01 00:00:00 Mars/Olympus_Mons", None),
+            // So does naming the zone twice.
+            ("UTC", "2020-01-01 00:00:00+08:00 UTC", None),
+        ];
+        for (tz, input, expected) in cases {
+            let mut ctx = ctx_with_tz(tz, true);
+            let parsed = Time::parse(&mut ctx, input, TimeType::DateTime, 6, true);
+            match expected {
+                Some(expected) => assert_eq!(parsed?.to_string(), expected, "{}", input),
+                None => {
+                    parsed.unwrap_err();
+                }
+            }
+        }
+
+        // Without the flag the suffix stays an ordinary parse error.
+        let mut ctx = ctx_with_tz("UTC", false);
+        Time::parse(
+            &mut ctx,
+            "2021-03-14 02:30:00 Asia/Shanghai",
+            TimeType::DateTime,
+            6,
+            true,
+        )
+        .unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_rfc3339() -> Result<()> {
+        let ctx_with_tz = |tz: &str| {
+            EvalContext::from(TimeEnv {
+                time_zone: Some(Tz::from_tz_name(tz).unwrap()),
+                ..TimeEnv::default()
+            })
+        };
+
+        // (session time zone, input, fsp, time type, expected)
+        let cases = vec![
+            // A timestamp carries the session zone's offset at the instant.
+            (
+                "Asia/Shanghai",
+                "2020-12-24 15:37:50.123456",
+                6,
+                TimeType::Timestamp,
+                "2020-12-24T15:37:50.123456+08:00",
+            ),
+            // DST: New York is UTC-5 in winter, UTC-4 in summer.
+            (
+                "America/New_York",
+                "2020-01-10 12:00:00",
+                0,
+                TimeType::Timestamp,
+                "2020-01-10T12:00:00-05:00",
+            ),
+            (
+                "America/New_York",
+                "2020-07-10 12:00:00",
+                0,
+                TimeType::Timestamp,
+                "2020-07-10T12:00:00-04:00",
+            ),
+            // A zero offset renders as Z.
+            (
+                "UTC",
+                "2020-12-24 15:37:50.12",
+                2,
+                TimeType::Timestamp,
+                "2020-12-24T15:37:50.12Z",
+            ),
+            // DateTime and Date carry no zone, so no offset is emitted; fsp
+            // decides the fractional digits.
+            (
+                "Asia/Shanghai",
+                "2020-12-24 15:37:50.1",
+                3,
+                TimeType::DateTime,
+                "2020-12-24T15:37:50.100",
+            ),
+            (
+                "Asia/Shanghai",
+                "2020-12-24 15:37:50",
+                0,
+                TimeType::DateTime,
+                "2020-12-24T15:37:50",
+            ),
+            (
+                "Asia/Shanghai",
+                "2020-12-24",
+                0,
+                TimeType::Date,
+                "2020-12-24",
+            ),
+        ];
+        for (tz, input, fsp, time_type, expected) in cases {
+            let mut ctx = ctx_with_tz(tz);
+            let time = Time::parse(&mut ctx, input, time_type, fsp, false)?;
+            assert_eq!(time.to_rfc3339(&mut ctx)?.as_str(), expected, "{}", input);
+        }
+
+        // Zero dates, and zero months or days, have no RFC 3339 spelling.
+        let mut ctx = ctx_with_tz("UTC");
+        let zero = Time::zero(&mut ctx, 0, TimeType::DateTime)?;
+        zero.to_rfc3339(&mut ctx).unwrap_err();
+        let zero_day = Time::parse(&mut ctx, "2020-12-00", TimeType::Date, 0, false)?;
+        zero_day.to_rfc3339(&mut ctx).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_month_bounds() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        // (year, month, time type, start, end)
+        let cases = vec![
+            // Leap February ends on the 29th, but the bound is March 1st
+            // either way.
+            (
+                2020,
+                2,
+                TimeType::DateTime,
+                "2020-02-01 00:00:00",
+                "2020-03-01 00:00:00",
+            ),
+            (
+                2019,
+                2,
+                TimeType::DateTime,
+                "2019-02-01 00:00:00",
+                "2019-03-01 00:00:00",
+            ),
+            // December rolls over into January of the next year.
+            (
+                2020,
+                12,
+                TimeType::DateTime,
+                "2020-12-01 00:00:00",
+                "2021-01-01 00:00:00",
+            ),
+            (2020, 12, TimeType::Date, "2020-12-01", "2021-01-01"),
+            // December 9999 has no following month: the end clamps to the
+            // maximum datetime and the range is closed.
+            (
+                9999,
+                12,
+                TimeType::DateTime,
+                "9999-12-01 00:00:00",
+                "9999-12-31 23:59:59.999999",
+            ),
+            (9999, 12, TimeType::Date, "9999-12-01", "9999-12-31"),
+        ];
+        for (year, month, time_type, start, end) in cases {
+            let (got_start, got_end) = Time::month_bounds(&mut ctx, year, month, time_type)?;
+            assert_eq!(got_start.to_string(), start, "{}-{}", year, month);
+            assert_eq!(got_end.to_string(), end, "{}-{}", year, month);
+            assert!(got_start < got_end, "{}-{}", year, month);
+        }
+
+        // Only real months have bounds.
+        Time::month_bounds(&mut ctx, 2020, 0, TimeType::DateTime).unwrap_err();
+        Time::month_bounds(&mut ctx, 2020, 13, TimeType::DateTime).unwrap_err();
+        Time::month_bounds(&mut ctx, 10000, 1, TimeType::DateTime).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_time_zone_ambiguity() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let new_york = Tz::from_tz_name("America/New_York").unwrap();
+        let utc = Tz::utc();
+        let all_policies = [
+            AmbiguityPolicy::Earliest,
+            AmbiguityPolicy::Latest,
+            AmbiguityPolicy::Error,
+        ];
+
+        // 2020-11-01 01:30:00 occurs twice in America/New_York: clocks fall
+        // back from 02:00 EDT to 01:00 EST.
+        let ambiguous = Time::parse_datetime(&mut ctx, "2020-11-01 01:30:00", 0, false)?;
+        let cases = vec![
+            // EDT, UTC-4.
+            (AmbiguityPolicy::Earliest, "2020-11-01 05:30:00"),
+            // EST, UTC-5.
+            (AmbiguityPolicy::Latest, "2020-11-01 06:30:00"),
+        ];
+        for (policy, expected) in cases {
+            let converted = ambiguous.convert_time_zone(&mut ctx, &new_york, &utc, policy)?;
+            assert_eq!(converted.to_string(), expected, "{:?}", policy);
+        }
+        ambiguous
+            .convert_time_zone(&mut ctx, &new_york, &utc, AmbiguityPolicy::Error)
+            .unwrap_err();
+
+        // An unambiguous time converts the same way under every policy.
+        let plain = Time::parse_datetime(&mut ctx, "2020-11-01 12:00:00", 0, false)?;
+        for policy in all_policies {
+            let converted = plain.convert_time_zone(&mut ctx, &new_york, &utc, policy)?;
+            assert_eq!(converted.to_string(), "2020-11-01 17:00:00", "{:?}", policy);
+        }
+
+        // 2020-03-08 02:30:00 does not exist in America/New_York: clocks
+        // spring forward from 02:00 EST to 03:00 EDT.
+        let skipped = Time::parse_datetime(&mut ctx, "2020-03-08 02:30:00", 0, false)?;
+        for policy in all_policies {
+            skipped
+                .convert_time_zone(&mut ctx, &new_york, &utc, policy)
+                .unwrap_err();
+        }
+
+        // A zero time converts to itself.
+        let zero = Time::zero(&mut ctx, 0, TimeType::DateTime)?;
+        assert_eq!(
+            zero.convert_time_zone(&mut ctx, &new_york, &utc, AmbiguityPolicy::Error)?,
+            zero
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_set_components() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let mut t = Time::parse_datetime(&mut ctx, "2020-02-10 11:22:33.444", 6, false)?;
+
+        // Each setter accepts its boundary and rejects boundary + 1, leaving
+        // the value untouched on failure.
+        t.try_set_year(9999)?;
+        assert_eq!(t.year(), 9999);
+        t.try_set_year(10000).unwrap_err();
+        assert_eq!(t.year(), 9999);
+
+        t.try_set_month(12)?;
+        assert_eq!(t.month(), 12);
+        t.try_set_month(13).unwrap_err();
+        assert_eq!(t.month(), 12);
+
+        t.try_set_day(31)?;
+        assert_eq!(t.day(), 31);
+        t.try_set_day(32).unwrap_err();
+
+        t.try_set_hour(23)?;
+        assert_eq!(t.hour(), 23);
+        t.try_set_hour(24).unwrap_err();
+
+        t.try_set_minute(59)?;
+        assert_eq!(t.minute(), 59);
+        t.try_set_minute(60).unwrap_err();
+
+        t.try_set_second(59)?;
+        assert_eq!(t.second(), 59);
+        t.try_set_second(60).unwrap_err();
+
+        t.try_set_micro(999_999)?;
+        assert_eq!(t.micro(), 999_999);
+        t.try_set_micro(1_000_000).unwrap_err();
+
+        // When the month is known, the day is validated against the length
+        // of that month, leap years included.
+        t.try_set_year(2020)?;
+        t.try_set_month(2)?;
+        t.try_set_day(29)?;
+        assert_eq!(t.day(), 29);
+        t.try_set_day(30).unwrap_err();
+        t.try_set_year(2021)?;
+        t.try_set_day(29).unwrap_err();
+        t.try_set_day(28)?;
+
+        // Month 0 (a zero date) caps the day at 31 only.
+        t.try_set_month(0)?;
+        t.try_set_day(31)?;
+        t.try_set_day(32).unwrap_err();
+        Ok(())
+    }
+
     #[test]
     fn test_allow_invalid_date() -> Result<()> {
         let cases = vec![
@@ -2699,6 +4949,58 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_no_zero_date_with_fsp() -> Result<()> {
+        // A zero date stays a zero date no matter how many fractional digits
+        // it carries: "0000-00-00 00:00:00.000000" with fsp > 0 must go
+        // through the same NO_ZERO_DATE handling as the fsp 0 form.
+        let cases = vec![
+            ("0000-00-00 00:00:00", 0),
+            ("0000-00-00 00:00:00.000000", 6),
+            ("0000-00-00 00:00:00.000000", 0),
+            ("0000-00-00 00:00:00", 6),
+        ];
+
+        for &(input, fsp) in cases.iter() {
+            for &strict_mode in &[false, true] {
+                for &no_zero_date in &[false, true] {
+                    let mut ctx = EvalContext::from(TimeEnv {
+                        strict_mode,
+                        no_zero_date,
+                        ..TimeEnv::default()
+                    });
+                    let result = Time::parse_datetime(&mut ctx, input, fsp, true);
+                    if no_zero_date && strict_mode {
+                        // NO_ZERO_DATE + strict mode requires an error.
+                        result.unwrap_err();
+                    } else {
+                        // Otherwise the zero value is returned, with a
+                        // warning if NO_ZERO_DATE is set.
+                        assert!(result?.is_zero());
+                        assert_eq!(ctx.warnings.warning_cnt > 0, no_zero_date);
+                    }
+                }
+            }
+        }
+
+        // The same holds for the Date type.
+        let mut ctx = EvalContext::from(TimeEnv {
+            strict_mode: true,
+            no_zero_date: true,
+            ..TimeEnv::default()
+        });
+        Time::parse(
+            &mut ctx,
+            "0000-00-00 00:00:00.000000",
+            TimeType::Date,
+            6,
+            true,
+        )
+        .unwrap_err();
+
+        Ok(())
+    }
+
     #[test]
     fn test_no_zero_in_date() -> Result<()> {
         let cases = ["2019-01-00", "2019-00-01"];
@@ -2795,6 +5097,8 @@ mod tests {
             ("2019-04-01 00:00:00.123456", MAX_FSP),
             ("2001-01-01 00:00:00.123456", MAX_FSP),
             ("2007-08-01 00:00:00.999999", MAX_FSP),
+            ("2040-01-01 00:00:00", 0),
+            ("2999-12-31 12:13:14.999999", MAX_FSP),
         ];
 
         for tz in tz_table {
@@ -2817,6 +5121,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_timestamp_upper_bound() -> Result<()> {
+        // The bound is defined in UTC, so check it in UTC first.
+        let mut ctx = EvalContext::default();
+        let time = Time::parse_timestamp(&mut ctx, "3001-01-18 23:59:59.999999", MAX_FSP, false)?;
+        let packed = time.to_packed_u64(&mut ctx)?;
+        assert_eq!(
+            time,
+            Time::from_packed_u64(&mut ctx, packed, TimeType::Timestamp, MAX_FSP)?
+        );
+        // One second past the bound is rejected.
+        assert!(Time::parse_timestamp(&mut ctx, "3001-01-19 00:00:00", 0, false).is_err());
+
+        // In an eastern zone the same wall time is before the bound, while
+        // a wall time past it is still caught by the conversion.
+        let mut ctx = EvalContext::from(TimeEnv {
+            time_zone: Tz::from_tz_name("Etc/GMT-5"),
+            ..TimeEnv::default()
+        });
+        let time = Time::parse_timestamp(&mut ctx, "3001-01-19 00:00:00", 0, false)?;
+        let packed = time.to_packed_u64(&mut ctx)?;
+        assert_eq!(
+            time,
+            Time::from_packed_u64(&mut ctx, packed, TimeType::Timestamp, 0)?
+        );
+        assert!(Time::parse_timestamp(&mut ctx, "3001-01-19 05:00:00", 0, false).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_compare() -> Result<()> {
         let cases = vec![
@@ -2841,25 +5175,67 @@ mod tests {
 
     #[test]
     fn test_from_duration() -> Result<()> {
-        let cases = vec!["11:30:45.123456", "-35:30:46"];
-        for case in cases {
-            let mut ctx = EvalContext::default();
-            let duration = Duration::parse(&mut ctx, case, MAX_FSP)?;
+        // A fixed anchor makes the composed date deterministic.
+        let cases = vec![
+            ((2020, 2, 2), "11:30:45.123456", "2020-02-02 11:30:45.123456"),
+            ((2020, 2, 2), "-35:30:46", "2020-01-31 12:29:14"),
+            ((2019, 12, 31), "25:00:00", "2020-01-01 01:00:00"),
+        ];
+        for ((year, month, day), duration, expected) in cases {
+            let mut cfg = EvalConfig::new();
+            cfg.set_fixed_today(Some(NaiveDate::from_ymd(year, month, day)));
+            let mut ctx = EvalContext::new(Arc::new(cfg));
+            let duration = Duration::parse(&mut ctx, duration, MAX_FSP)?;
+
+            let actual =
+                Time::from_duration(&mut ctx, duration, TimeType::DateTime, UNSPECIFIED_FSP)?;
+            assert_eq!(actual.to_string(), expected);
+        }
 
-            let actual = Time::from_duration(&mut ctx, duration, TimeType::DateTime)?;
-            let today = actual
-                .try_into_chrono_datetime(&mut ctx)?
-                .checked_sub_signed(chrono::Duration::nanoseconds(duration.to_nanos()))
-                .unwrap();
+        // Without `fixed_today`, the anchor is the wall-clock date.
+        let mut ctx = EvalContext::default();
+        let duration = Duration::parse(&mut ctx, "11:30:45.123456", MAX_FSP)?;
+        let actual = Time::from_duration(&mut ctx, duration, TimeType::DateTime, UNSPECIFIED_FSP)?;
+        let today = actual
+            .try_into_chrono_datetime(&mut ctx)?
+            .checked_sub_signed(chrono::Duration::nanoseconds(duration.to_nanos()))
+            .unwrap();
 
-            let now = Utc::now();
-            assert_eq!(today.year(), now.year());
-            assert_eq!(today.month(), now.month());
-            assert_eq!(today.day(), now.day());
-            assert_eq!(today.hour(), 0);
-            assert_eq!(today.minute(), 0);
-            assert_eq!(today.second(), 0);
+        let now = Utc::now();
+        assert_eq!(today.year(), now.year());
+        assert_eq!(today.month(), now.month());
+        assert_eq!(today.day(), now.day());
+        assert_eq!(today.hour(), 0);
+        assert_eq!(today.minute(), 0);
+        assert_eq!(today.second(), 0);
+
+        // The fraction must survive a duration whose fsp understates the
+        // digits it carries, and an explicit `target_fsp` rounds here.
+        let mut ctx = EvalContext::default();
+        let duration = Duration::parse(&mut ctx, "11:30:45.999999", MAX_FSP)?;
+        let cases = vec![
+            (UNSPECIFIED_FSP, "45.999999"),
+            (6, "45.999999"),
+            (2, "46.00"),
+            (0, "46"),
+        ];
+        for (target_fsp, expected_tail) in cases {
+            let actual = Time::from_duration(&mut ctx, duration, TimeType::DateTime, target_fsp)?;
+            assert!(
+                actual.to_string().ends_with(expected_tail),
+                "fsp {}: {}",
+                target_fsp,
+                actual
+            );
         }
+
+        // A duration whose fsp understates its digits (e.g. after
+        // `minimize_fsp`) still carries its micros into the composed time
+        // when the target inherits the fsp.
+        let duration = Duration::parse(&mut ctx, "11:30:45.123456", MAX_FSP)?.minimize_fsp();
+        let actual = Time::from_duration(&mut ctx, duration, TimeType::DateTime, UNSPECIFIED_FSP)?;
+        assert!(actual.to_string().ends_with("45.123456"), "{}", actual);
+
         Ok(())
     }
 
@@ -2917,6 +5293,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fsp_truncate() -> Result<()> {
+        // (input, fsp, expected, warnings)
+        let cases = vec![
+            // Non-zero digits dropped: truncated, one warning, no carry.
+            ("2012-12-31 11:30:45.123456", 3, "2012-12-31 11:30:45.123", 1),
+            ("2012-12-31 11:30:45.999999", 0, "2012-12-31 11:30:45", 1),
+            ("2012-12-31 23:59:59.999999", 3, "2012-12-31 23:59:59.999", 1),
+            // Only zero digits dropped: no warning.
+            ("2012-12-31 11:30:45.123000", 3, "2012-12-31 11:30:45.123", 0),
+            ("2012-12-31 11:30:45", 2, "2012-12-31 11:30:45.00", 0),
+            // Nothing to drop: the fsp only widens.
+            ("2012-12-31 11:30:45.12", 4, "2012-12-31 11:30:45.1200", 0),
+        ];
+        for (input, fsp, expected, warnings) in cases {
+            let mut ctx = EvalContext::default();
+            let t = Time::parse_with_rounding(
+                &mut ctx,
+                input,
+                TimeType::DateTime,
+                fsp,
+                FspRounding::Truncate,
+            )?;
+            assert_eq!(t.to_string(), expected, "{}", input);
+            assert_eq!(ctx.warnings.warning_cnt, warnings, "{}", input);
+
+            // `round_frac_with` applies the same truncation to an already
+            // parsed value.
+            let mut ctx = EvalContext::default();
+            let t = Time::parse_datetime(&mut ctx, input, MAX_FSP, false)?
+                .round_frac_with(&mut ctx, fsp, FspRounding::Truncate)?;
+            assert_eq!(t.to_string(), expected, "{}", input);
+            assert_eq!(ctx.warnings.warning_cnt, warnings, "{}", input);
+        }
+
+        // `Round` keeps the behavior of the boolean entry points.
+        let mut ctx = EvalContext::default();
+        let t = Time::parse_with_rounding(
+            &mut ctx,
+            "2012-12-31 23:59:59.999999",
+            TimeType::DateTime,
+            3,
+            FspRounding::Round,
+        )?;
+        assert_eq!(t.to_string(), "2013-01-01 00:00:00.000");
+        assert_eq!(ctx.warnings.warning_cnt, 0);
+
+        // Decimal inputs truncate the same way.
+        let mut ctx = EvalContext::default();
+        let input: Decimal = "121231113045.9999999".parse().unwrap();
+        let t = Time::parse_from_decimal_with_rounding(
+            &mut ctx,
+            &input,
+            TimeType::DateTime,
+            5,
+            FspRounding::Truncate,
+        )?;
+        assert_eq!(t.to_string(), "2012-12-31 11:30:45.99999");
+        assert_eq!(ctx.warnings.warning_cnt, 1);
+        Ok(())
+    }
+
     #[test]
     fn test_normalized() -> Result<()> {
         let should_pass = vec![
@@ -2966,6 +5404,22 @@ mod tests {
                 "1 12:30:00",
                 "2019-01-01 00:00:45.123456",
             ),
+            (
+                "2020-02-28 23:30:00.000000",
+                "01:00:00",
+                "2020-02-29 00:30:00.000000",
+            ),
+            // Results outside of the timestamp range are fine for datetimes.
+            (
+                "9999-12-31 22:59:59.000000",
+                "01:00:00",
+                "9999-12-31 23:59:59.000000",
+            ),
+            (
+                "0000-01-01 00:00:00.000000",
+                "00:00:00.000001",
+                "0000-01-01 00:00:00.000001",
+            ),
         ];
 
         for (lhs, rhs, expected) in normal_cases.clone() {
@@ -3059,6 +5513,222 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_truncate_to() -> Result<()> {
+        use IntervalUnit::*;
+        let mut ctx = EvalContext::default();
+        let cases = vec![
+            // Every unit on one value, across the fractional part.
+            ("2019-11-27 15:45:12.5", Year, "2019-01-01 00:00:00"),
+            ("2019-11-27 15:45:12.5", Quarter, "2019-10-01 00:00:00"),
+            ("2019-11-27 15:45:12.5", Month, "2019-11-01 00:00:00"),
+            // 2019-11-27 is a Wednesday.
+            ("2019-11-27 15:45:12.5", Week, "2019-11-24 00:00:00"),
+            ("2019-11-27 15:45:12.5", Day, "2019-11-27 00:00:00"),
+            ("2019-11-27 15:45:12.5", Hour, "2019-11-27 15:00:00"),
+            ("2019-11-27 15:45:12.5", Minute, "2019-11-27 15:45:00"),
+            ("2019-11-27 15:45:12.5", Second, "2019-11-27 15:45:12"),
+            // Quarter boundaries.
+            ("2020-01-15 00:00:01", Quarter, "2020-01-01 00:00:00"),
+            ("2020-06-30 23:59:59", Quarter, "2020-04-01 00:00:00"),
+            ("2020-07-01 00:00:00", Quarter, "2020-07-01 00:00:00"),
+            // Weeks crossing a month and a year boundary.
+            ("2020-03-03 12:00:00", Week, "2020-03-01 00:00:00"),
+            ("2020-05-01 12:00:00", Week, "2020-04-26 00:00:00"),
+            ("2021-01-02 12:00:00", Week, "2020-12-27 00:00:00"),
+            // Leap day: 2020-02-29 is a Saturday.
+            ("2020-02-29 10:11:12", Day, "2020-02-29 00:00:00"),
+            ("2020-02-29 10:11:12", Week, "2020-02-23 00:00:00"),
+            ("2020-02-29 10:11:12", Month, "2020-02-01 00:00:00"),
+        ];
+        for (s, unit, expected) in cases {
+            let t = Time::parse_datetime(&mut ctx, s, 1, false)?;
+            let truncated = t.truncate_to(&mut ctx, unit)?;
+            assert_eq!(truncated.to_string(), expected, "{} {:?}", s, unit);
+            assert_eq!(truncated.fsp(), 0, "{} {:?}", s, unit);
+        }
+
+        // Zero dates pass through unchanged.
+        let mut ctx = EvalContext::from(TimeEnv {
+            allow_invalid_date: true,
+            ..TimeEnv::default()
+        });
+        let zero = Time::parse_datetime(&mut ctx, "0000-00-00 00:00:00", 0, false)?;
+        for unit in [Year, Quarter, Month, Week, Day, Hour, Minute, Second] {
+            assert_eq!(zero.truncate_to(&mut ctx, unit)?, zero, "{:?}", unit);
+        }
+        // Zero-in-date values have no week to land in.
+        let t = Time::parse_datetime(&mut ctx, "2019-11-00 10:00:00", 0, false)?;
+        t.truncate_to(&mut ctx, Week).unwrap_err();
+        // Extraction-only units have no truncation semantics.
+        let t = Time::parse_datetime(&mut ctx, "2019-11-27 10:00:00", 0, false)?;
+        t.truncate_to(&mut ctx, Microsecond).unwrap_err();
+        t.truncate_to(&mut ctx, DayHour).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract() -> Result<()> {
+        use IntervalUnit::*;
+        let mut ctx = EvalContext::default();
+        let cases = vec![
+            // Ported from TiDB's extract test table.
+            ("2011-11-11 10:10:10.123456", Year, 2011),
+            ("2011-11-11 10:10:10.123456", Quarter, 4),
+            ("2011-11-11 10:10:10.123456", Month, 11),
+            ("2011-11-11 10:10:10.123456", Week, 45),
+            ("2011-11-11 10:10:10.123456", Day, 11),
+            ("2011-11-11 10:10:10.123456", Hour, 10),
+            ("2011-11-11 10:10:10.123456", Minute, 10),
+            ("2011-11-11 10:10:10.123456", Second, 10),
+            ("2011-11-11 10:10:10.123456", Microsecond, 123456),
+            ("2011-11-11 10:10:10.123456", SecondMicrosecond, 10123456),
+            ("2011-11-11 10:10:10.123456", MinuteMicrosecond, 1010123456),
+            ("2011-11-11 10:10:10.123456", MinuteSecond, 1010),
+            ("2011-11-11 10:10:10.123456", HourMicrosecond, 101010123456),
+            ("2011-11-11 10:10:10.123456", HourSecond, 101010),
+            ("2011-11-11 10:10:10.123456", HourMinute, 1010),
+            ("2011-11-11 10:10:10.123456", DayMicrosecond, 11101010123456),
+            ("2011-11-11 10:10:10.123456", DaySecond, 11101010),
+            ("2011-11-11 10:10:10.123456", DayMinute, 111010),
+            ("2011-11-11 10:10:10.123456", DayHour, 1110),
+            ("2011-11-11 10:10:10.123456", YearMonth, 201111),
+            // Quarter boundaries.
+            ("2020-01-01 00:00:00", Quarter, 1),
+            ("2020-03-31 23:59:59", Quarter, 1),
+            ("2020-04-01 00:00:00", Quarter, 2),
+            ("2020-12-31 23:59:59", Quarter, 4),
+            // Mode-0 weeks run Sunday-first; days before the first Sunday
+            // are week 0.
+            ("2020-01-01 00:00:00", Week, 0),
+            ("2020-01-05 00:00:00", Week, 1),
+        ];
+        for (s, unit, expected) in cases {
+            let t = Time::parse_datetime(&mut ctx, s, MAX_FSP, false)?;
+            assert_eq!(t.extract(unit), expected, "{} {:?}", s, unit);
+        }
+
+        let mut ctx = EvalContext::from(TimeEnv {
+            allow_invalid_date: true,
+            ..TimeEnv::default()
+        });
+        // Zero dates extract 0 for every unit.
+        let zero = Time::parse_datetime(&mut ctx, "0000-00-00 00:00:00", 0, false)?;
+        for unit in [
+            Year,
+            Quarter,
+            Month,
+            Week,
+            Day,
+            Hour,
+            Minute,
+            Second,
+            Microsecond,
+            SecondMicrosecond,
+            MinuteMicrosecond,
+            MinuteSecond,
+            HourMicrosecond,
+            HourSecond,
+            HourMinute,
+            DayMicrosecond,
+            DaySecond,
+            DayMinute,
+            DayHour,
+            YearMonth,
+        ] {
+            assert_eq!(zero.extract(unit), 0, "{:?}", unit);
+        }
+        // Invalid dates extract the raw stored fields without normalization.
+        let t = Time::parse_datetime(&mut ctx, "2019-00-00 10:11:12", 0, false)?;
+        assert_eq!(t.extract(Year), 2019);
+        assert_eq!(t.extract(Quarter), 0);
+        assert_eq!(t.extract(Month), 0);
+        assert_eq!(t.extract(Week), 0);
+        assert_eq!(t.extract(Day), 0);
+        assert_eq!(t.extract(YearMonth), 201900);
+        assert_eq!(t.extract(DayHour), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_time_zone() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let utc = Tz::utc();
+        let offset = Tz::from_offset(8 * 3600).unwrap();
+        let new_york = Tz::from_tz_name("America/New_York").unwrap();
+
+        let cases = vec![
+            // Fixed offset, fractional part intact.
+            ("2020-01-01 12:00:00.123456", &utc, &offset, "2020-01-01 20:00:00.123456"),
+            ("2020-01-01 20:00:00.123456", &offset, &utc, "2020-01-01 12:00:00.123456"),
+            // Named zone on both sides of DST: UTC-5 in winter, UTC-4 in
+            // summer.
+            ("2020-01-01 12:00:00.000000", &utc, &new_york, "2020-01-01 07:00:00.000000"),
+            ("2020-06-01 12:00:00.000000", &utc, &new_york, "2020-06-01 08:00:00.000000"),
+            ("2020-06-01 08:00:00.000000", &new_york, &utc, "2020-06-01 12:00:00.000000"),
+        ];
+        for (s, from, to, expected) in cases {
+            let t = Time::parse_datetime(&mut ctx, s, MAX_FSP, false)?;
+            let converted = t.convert_time_zone(&mut ctx, from, to, AmbiguityPolicy::Earliest)?;
+            assert_eq!(converted.to_string(), expected, "{}", s);
+            assert_eq!(converted.fsp(), t.fsp(), "{}", s);
+        }
+
+        // 02:30 on 2020-03-08 does not exist in America/New_York: the clock
+        // jumps from 02:00 to 03:00.
+        let gap = Time::parse_datetime(&mut ctx, "2020-03-08 02:30:00", 0, false)?;
+        gap.convert_time_zone(&mut ctx, &new_york, &utc, AmbiguityPolicy::Earliest)
+            .unwrap_err();
+
+        // Dates have no clock component to convert.
+        let date = Time::parse_date(&mut ctx, "2020-01-01")?;
+        date.convert_time_zone(&mut ctx, &utc, &offset, AmbiguityPolicy::Earliest)
+            .unwrap_err();
+
+        // Zero times pass through unchanged.
+        let zero = Time::parse_datetime(&mut ctx, "0000-00-00 00:00:00", 0, true)?;
+        assert_eq!(
+            zero.convert_time_zone(&mut ctx, &utc, &new_york, AmbiguityPolicy::Earliest)?,
+            zero
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_in_date_day_number() -> Result<()> {
+        let mut ctx = EvalContext::from(TimeEnv {
+            allow_invalid_date: true,
+            ..TimeEnv::default()
+        });
+
+        // `ordinal()` and `day_number()` must agree on zero-in-date values:
+        // neither has an answer when the month or the day is 0.
+        for s in ["0000-00-00", "2019-00-12", "2019-10-00", "0000-00-12"] {
+            let t = Time::parse_date(&mut ctx, s)?;
+            assert_eq!(t.ordinal(), None, "{}", s);
+            assert_eq!(t.day_number(), None, "{}", s);
+            assert_eq!(t.second_number(), None, "{}", s);
+        }
+
+        let cases = vec![
+            ("0000-01-01", 1, 1),
+            ("2007-10-07", 280, 733321),
+            ("2008-10-07", 281, 733687),
+        ];
+        for (s, ordinal, day_number) in cases {
+            let t = Time::parse_date(&mut ctx, s)?;
+            assert_eq!(t.ordinal(), Some(ordinal), "{}", s);
+            assert_eq!(t.day_number(), Some(day_number), "{}", s);
+            assert_eq!(
+                t.second_number(),
+                Some(i64::from(day_number) * 86400),
+                "{}",
+                s
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_date_format() -> Result<()> {
         let cases = vec![
@@ -3078,12 +5748,12 @@ mod tests {
             ),
             (
                 "0000-01-01 00:00:00.123456",
-                // Functions week() and yearweek() don't support multi mode,
-                // so the result of "%U %u %V %Y" is different from MySQL.
-                "%b %M %m %c %D %d %e %j %k %h %i %p %r %T %s %f %v %Y
-                %y %%",
-                "Jan January 01 1 1st 01 1 001 0 12 00 AM 12:00:00 AM 00:00:00 00 123456 52 0000
-                00 %",
+                // The week year of `%x` underflows to -1 here, which MySQL
+                // prints as u32::MAX.
+                "%b %M %m %c %D %d %e %j %k %h %i %p %r %T %s %f %U %u %V
+                %v %X %x %Y %y %%",
+                "Jan January 01 1 1st 01 1 001 0 12 00 AM 12:00:00 AM 00:00:00 00 123456 01 00 01
+                52 0000 4294967295 0000 00 %",
             ),
             (
                 "2016-09-3 00:59:59.123456",
@@ -3099,6 +5769,32 @@ mod tests {
                 "Oct October 10 10 1st 01 1 275 0 00 00 AM 12:00:00 AM 00:00:00 00 000000 40
                 2012 2012 12 %",
             ),
+            // `%G`/`%g` at the ISO-year boundaries: Jan 1 can still belong to
+            // the previous ISO year and Dec 31 to the next one.
+            (
+                "2016-01-01 00:00:00",
+                "%G %g %x %v %Y %y",
+                "2015 15 2015 53 2016 16",
+            ),
+            (
+                "2012-12-31 00:00:00",
+                "%G %g %x %v %Y %y",
+                "2013 13 2013 01 2012 12",
+            ),
+            // First week of the year with Jan 1 falling on each weekday: the
+            // four specifiers disagree on whether that week counts as week 0,
+            // week 1, or the last week of the previous year.
+            ("2018-01-01", "%U %u %V %v %X %x", "00 01 53 01 2017 2018"), // Monday
+            ("2019-01-01", "%U %u %V %v %X %x", "00 01 52 01 2018 2019"), // Tuesday
+            ("2020-01-01", "%U %u %V %v %X %x", "00 01 52 01 2019 2020"), // Wednesday
+            ("2015-01-01", "%U %u %V %v %X %x", "00 01 52 01 2014 2015"), // Thursday
+            ("2016-01-01", "%U %u %V %v %X %x", "00 00 52 53 2015 2015"), // Friday
+            ("2022-01-01", "%U %u %V %v %X %x", "00 00 52 52 2021 2021"), // Saturday
+            ("2017-01-01", "%U %u %V %v %X %x", "01 00 01 52 2017 2016"), // Sunday
+            // The supported range ends at year 9999; no week year overflow
+            // is possible there since 9999-12-31 is a Friday.
+            ("9999-01-01", "%U %u %V %v %X %x", "00 00 52 53 9998 9998"),
+            ("9999-12-31", "%U %u %V %v %X %x", "52 52 52 52 9999 9999"),
         ];
         for (s, layout, expect) in cases {
             let mut ctx = EvalContext::default();
@@ -3109,6 +5805,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_date_format_into() -> Result<()> {
+        let mut ctx = EvalContext::default();
+        let time = Time::parse_datetime(&mut ctx, "2010-01-07 23:12:34.12345", 6, false)?;
+        let layouts = vec![
+            "%b %M %m %c %D %d %e %j %k %h %i %p %r %T %s %f %U %u %V %v %a %W %w %X %x %Y %y %%",
+            // Unknown specifiers are emitted verbatim, a trailing `%` is
+            // dropped, both exactly like the allocating method.
+            "%Q %q unknown",
+            "trailing percent %",
+            "%",
+            "plain literal text",
+            "",
+        ];
+        let mut buf = String::new();
+        for layout in layouts {
+            buf.clear();
+            time.date_format_into(layout, &mut buf)?;
+            assert_eq!(buf, time.date_format(layout)?, "layout: {}", layout);
+        }
+
+        // Appends without clearing, so a caller can build row output in
+        // place.
+        let mut buf = String::from("row: ");
+        time.date_format_into("%Y-%m-%d", &mut buf)?;
+        assert_eq!(buf, "row: 2010-01-07");
+        Ok(())
+    }
+
     #[test]
     fn test_to_numeric_string() {
         let cases = vec![
@@ -3128,6 +5853,85 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_to_numeric_i64() {
+        let mut ctx = EvalContext::default();
+        // Mirrors `test_to_numeric_string`: the packed integer is the whole
+        // part of the numeric string.
+        let cases = vec![
+            ("2012-12-31 11:30:45.123456", 4, 20121231113045i64),
+            ("2012-12-31 11:30:45.999999", 0, 20121231113046),
+            ("2017-01-05 23:59:59.575601", 0, 20170106000000),
+            ("0000-00-00 00:00:00", 6, 0),
+        ];
+        for (s, fsp, expect) in cases {
+            let t = Time::parse_datetime(&mut ctx, s, fsp, true).unwrap();
+            assert_eq!(t.to_numeric_i64(), expect);
+            assert_eq!(t.to_numeric_u64(), expect as u64);
+        }
+
+        // A `Date` carries no hms part and packs as `yyyymmdd`.
+        let d = Time::parse_date(&mut ctx, "2012-12-31").unwrap();
+        assert_eq!(d.to_numeric_i64(), 20121231);
+
+        // The fractional variant returns exactly the microseconds the
+        // numeric string prints for the time's fsp.
+        let t = Time::parse_datetime(&mut ctx, "2012-12-31 11:30:45.123456", 4, true).unwrap();
+        assert_eq!(t.to_numeric_parts(), (20121231113045, 123500));
+        let t = Time::parse_datetime(&mut ctx, "2012-12-31 11:30:45.123456", 6, true).unwrap();
+        assert_eq!(t.to_numeric_parts(), (20121231113045, 123456));
+    }
+
+    #[test]
+    fn test_numeric_string_round_trip() {
+        let mut ctx = EvalContext::default();
+
+        // Unpadded 7- and 13-digit numeric strings carry a three-digit year,
+        // matching the classification of the numeric parser.
+        let t = Time::parse_datetime(&mut ctx, "1011209", 0, false).unwrap();
+        assert_eq!(t.to_string(), "0101-12-09 00:00:00");
+        let t = Time::parse_datetime(&mut ctx, "1011209101112.5", 1, false).unwrap();
+        assert_eq!(t.to_string(), "0101-12-09 10:11:12.5");
+
+        // `parse_datetime(&t.to_numeric_string())` reproduces `t` for random
+        // valid times over the full year range 1..=9999. A small xorshift
+        // generator keeps the test deterministic without a randomness
+        // dependency.
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+        for _ in 0..2000 {
+            let year = next(9999) as u32 + 1;
+            let month = next(12) as u32 + 1;
+            let day = next(u64::from(last_day_of_month(year, month))) as u32 + 1;
+            let fsp = next(7) as i8;
+            let mut s = format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                year,
+                month,
+                day,
+                next(24),
+                next(60),
+                next(60)
+            );
+            if fsp > 0 {
+                s.push_str(&format!(
+                    ".{:0width$}",
+                    next(10u64.pow(fsp as u32)),
+                    width = fsp as usize
+                ));
+            }
+            let t = Time::parse_datetime(&mut ctx, &s, fsp, false).unwrap();
+            let numeric = t.to_numeric_string();
+            let round = Time::parse_datetime(&mut ctx, &numeric, fsp, false).unwrap();
+            assert_eq!(t, round, "numeric string: {}", numeric);
+        }
+    }
+
     #[test]
     fn test_to_decimal() {
         let cases = vec![
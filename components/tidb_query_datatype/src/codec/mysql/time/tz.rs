@@ -1,6 +1,6 @@
 // Copyright 2018 TiKV Project Authors. Licensed under Apache-2.0.
 
-use std::{fmt, str::FromStr};
+use std::{borrow::Cow, fmt, str::FromStr};
 
 use chrono::*;
 
@@ -26,16 +26,50 @@ impl Tz {
         FixedOffset::east_opt(secs as i32).map(Tz::Offset)
     }
 
+    /// The well-known aliases [`Tz::from_tz_name`] accepts: `SYSTEM` (in any
+    /// case) denotes the local time zone, while `UTC`, `Universal` and `GMT`
+    /// are the links the IANA database keeps for UTC. Listed explicitly so
+    /// status APIs can report them without scanning the whole database.
+    pub const COMMON_ALIASES: [&'static str; 4] = ["SYSTEM", "UTC", "Universal", "GMT"];
+
     /// Constructs a time zone from the name. If the specified time zone name is
     /// `system`, a local time zone will be constructed.
     pub fn from_tz_name(name: &str) -> Option<Self> {
-        if name.to_lowercase() == "system" {
+        Self::from_alias(name).or_else(|| chrono_tz::Tz::from_str(name).ok().map(Tz::Name))
+    }
+
+    /// Resolves the [`Tz::COMMON_ALIASES`]: the local time zone for `SYSTEM`
+    /// and the linked database zone for the UTC aliases. Returns `None` for
+    /// every other name, including plain database names.
+    pub fn from_alias(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("system") {
             Some(Tz::local())
-        } else {
+        } else if Self::COMMON_ALIASES.contains(&name) {
             chrono_tz::Tz::from_str(name).ok().map(Tz::Name)
+        } else {
+            None
         }
     }
 
+    /// Iterates over every time zone name [`Tz::from_tz_name`] accepts: the
+    /// common aliases first, then the rest of the IANA database. The UTC
+    /// aliases are database names themselves and are not listed twice.
+    pub fn list_names() -> impl Iterator<Item = &'static str> {
+        Self::COMMON_ALIASES.iter().copied().chain(
+            chrono_tz::TZ_VARIANTS
+                .iter()
+                .map(|tz| tz.name())
+                .filter(|name| !Self::COMMON_ALIASES.contains(name)),
+        )
+    }
+
+    /// Whether [`Tz::from_tz_name`] accepts the name. Defined in terms of
+    /// `from_tz_name` itself, so the validation can never drift from what
+    /// parsing accepts.
+    pub fn is_valid_name(name: &str) -> bool {
+        Self::from_tz_name(name).is_some()
+    }
+
     /// Constructs a UTC time zone.
     pub fn utc() -> Self {
         Tz::Name(chrono_tz::UTC)
@@ -45,6 +79,24 @@ impl Tz {
     pub fn local() -> Self {
         Tz::Local(Local)
     }
+
+    /// Returns the UTC offset, in seconds, this zone applies at the given
+    /// instant (seconds since the Unix epoch). Named zones answer according
+    /// to their DST rules at that instant; fixed offsets are constant.
+    pub fn offset_seconds_at(&self, utc_seconds: i64) -> i32 {
+        let utc = NaiveDateTime::from_timestamp(utc_seconds, 0);
+        self.offset_from_utc_datetime(&utc).fix().local_minus_utc()
+    }
+
+    /// Returns the IANA name of a named zone, the `[+-]HH:MM` rendering of a
+    /// fixed offset, or `SYSTEM` for the local time zone.
+    pub fn name(&self) -> Cow<'_, str> {
+        match self {
+            Tz::Offset(offset) => Cow::Owned(offset.to_string()),
+            Tz::Name(tz) => Cow::Borrowed(tz.name()),
+            Tz::Local(_) => Cow::Borrowed("SYSTEM"),
+        }
+    }
 }
 
 impl fmt::Debug for Tz {
@@ -210,3 +262,82 @@ impl fmt::Display for TzOffset {
         fmt::Debug::fmt(self, f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_seconds_at() {
+        // America/New_York switches from EST (-05:00) to EDT (-04:00) at
+        // 2020-03-08 07:00:00 UTC, and back at 2020-11-01 06:00:00 UTC.
+        let new_york = Tz::from_tz_name("America/New_York").unwrap();
+        let spring_forward = 1583650800;
+        assert_eq!(new_york.offset_seconds_at(spring_forward - 1), -5 * 3600);
+        assert_eq!(new_york.offset_seconds_at(spring_forward), -4 * 3600);
+        let fall_back = 1604210400;
+        assert_eq!(new_york.offset_seconds_at(fall_back - 1), -4 * 3600);
+        assert_eq!(new_york.offset_seconds_at(fall_back), -5 * 3600);
+
+        // Fixed offsets and UTC never change.
+        let offset = Tz::from_offset(8 * 3600).unwrap();
+        assert_eq!(offset.offset_seconds_at(spring_forward), 8 * 3600);
+        assert_eq!(offset.offset_seconds_at(0), 8 * 3600);
+        assert_eq!(Tz::utc().offset_seconds_at(spring_forward), 0);
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(Tz::utc().name(), "UTC");
+        assert_eq!(
+            Tz::from_tz_name("America/New_York").unwrap().name(),
+            "America/New_York"
+        );
+        assert_eq!(Tz::from_offset(8 * 3600).unwrap().name(), "+08:00");
+        assert_eq!(Tz::from_offset(-3600 / 2).unwrap().name(), "-00:30");
+        assert_eq!(Tz::local().name(), "SYSTEM");
+    }
+
+    #[test]
+    fn test_list_and_validate_names() {
+        // Every listed name round-trips through `from_tz_name`, and the
+        // validation agrees.
+        let names: Vec<_> = Tz::list_names().collect();
+        for &name in &names {
+            assert!(Tz::is_valid_name(name), "{}", name);
+            let tz = Tz::from_tz_name(name).unwrap();
+            if name == "SYSTEM" {
+                assert!(matches!(tz, Tz::Local(_)));
+            } else {
+                // Database names parse to the named zone reporting the same
+                // name back.
+                assert_eq!(tz.name(), name);
+            }
+        }
+        // The database carries several hundred zones, and the aliases are
+        // not listed twice.
+        assert!(names.len() > 400, "{}", names.len());
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), names.len());
+
+        // The aliases resolve as documented, whatever the case of `system`.
+        assert!(matches!(Tz::from_alias("SYSTEM"), Some(Tz::Local(_))));
+        assert!(matches!(Tz::from_alias("system"), Some(Tz::Local(_))));
+        for alias in ["UTC", "Universal", "GMT"] {
+            let tz = Tz::from_alias(alias).unwrap();
+            assert_eq!(tz.offset_seconds_at(0), 0, "{}", alias);
+            assert_eq!(tz.name(), alias);
+        }
+        // Plain database names are not aliases.
+        assert!(Tz::from_alias("Asia/Shanghai").is_none());
+
+        // Rejections agree with `from_tz_name` too: offsets and unknown
+        // names go through `from_offset`, not the name lookup.
+        for name in ["", "+08:00", "Mars/Olympus_Mons", "utc"] {
+            assert!(!Tz::is_valid_name(name), "{}", name);
+            assert!(Tz::from_tz_name(name).is_none(), "{}", name);
+        }
+    }
+}
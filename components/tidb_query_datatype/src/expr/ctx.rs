@@ -3,6 +3,7 @@
 use std::{i64, mem, sync::Arc, u64};
 
 use bitflags::bitflags;
+use chrono::NaiveDate;
 use tipb::DagRequest;
 
 use super::{Error, Result};
@@ -49,6 +50,23 @@ bitflags! {
         const DIVIDED_BY_ZERO_AS_WARNING = 1 << 8;
         /// `IN_LOAD_DATA_STMT` indicates if this is a LOAD DATA statement.
         const IN_LOAD_DATA_STMT = 1 << 10;
+        /// `PARTIAL_DATETIME_AS_ERROR` indicates that a datetime string whose
+        /// time part is only partially specified (e.g. `2020-01-01 10`) should
+        /// be rejected instead of merely warned about when strict sql mode is
+        /// set. Without this flag such strings keep parsing with the missing
+        /// parts zero-filled.
+        const PARTIAL_DATETIME_AS_ERROR = 1 << 11;
+        /// `NORMALIZE_FULLWIDTH_CHARS` indicates that Unicode fullwidth forms
+        /// in datetime strings (e.g. `２０２４：１２`) should be mapped to
+        /// their halfwidth ASCII equivalents before parsing, instead of being
+        /// rejected. Common in data imported from CJK sources.
+        const NORMALIZE_FULLWIDTH_CHARS = 1 << 12;
+        /// `PARSE_TZ_NAME` indicates that a datetime string may end with a
+        /// named time zone (e.g. `2021-03-14 02:30:00 Asia/Shanghai`); the
+        /// value is converted into the session time zone the way an explicit
+        /// `+08:00` suffix is. MySQL rejects such strings, so this is opt-in
+        /// for data imported from sources that emit them.
+        const PARSE_TZ_NAME = 1 << 13;
     }
 }
 
@@ -60,6 +78,8 @@ impl SqlMode {
 }
 
 const DEFAULT_MAX_WARNING_CNT: usize = 64;
+/// MySQL's fixed pivot for two-digit years.
+const DEFAULT_TWO_DIGIT_YEAR_CUTOFF: u32 = 69;
 
 #[derive(Clone, Debug)]
 pub struct EvalConfig {
@@ -73,6 +93,23 @@ pub struct EvalConfig {
 
     pub paging_size: Option<u64>,
     pub div_precision_increment: u8,
+
+    /// When set, functions which need a "today" anchor (e.g.
+    /// `Time::from_duration`) use this date instead of the wall clock, so
+    /// their results are deterministic. Mainly for tests.
+    pub fixed_today: Option<NaiveDate>,
+
+    /// The pivot for two-digit years in datetime parsing: `yy <= cutoff`
+    /// reads as `20yy`, while larger two-digit years read as `19yy`. MySQL
+    /// documents the pivot as a fixed 69; sessions whose data expects a
+    /// different century window can move it.
+    pub two_digit_year_cutoff: u32,
+
+    /// Opts in to the thread-local cache of parsed datetime literals that
+    /// `Time::parse` consults for short inputs, so constant literals are
+    /// not re-parsed for every region request of a query. Off by default:
+    /// tests and one-shot parses should keep exercising the parser.
+    pub cache_datetime_literals: bool,
 }
 
 impl Default for EvalConfig {
@@ -113,6 +150,9 @@ impl EvalConfig {
             sql_mode: SqlMode::empty(),
             paging_size: None,
             div_precision_increment: DEFAULT_DIV_FRAC_INCR,
+            fixed_today: None,
+            two_digit_year_cutoff: DEFAULT_TWO_DIGIT_YEAR_CUTOFF,
+            cache_datetime_literals: false,
         }
     }
 
@@ -162,6 +202,22 @@ impl EvalConfig {
         self
     }
 
+    pub fn set_two_digit_year_cutoff(&mut self, new_value: u32) -> &mut Self {
+        // A cutoff of 99 or more maps every two-digit year into 20yy.
+        self.two_digit_year_cutoff = new_value.min(99);
+        self
+    }
+
+    pub fn set_cache_datetime_literals(&mut self, new_value: bool) -> &mut Self {
+        self.cache_datetime_literals = new_value;
+        self
+    }
+
+    pub fn set_fixed_today(&mut self, new_value: Option<NaiveDate>) -> &mut Self {
+        self.fixed_today = new_value;
+        self
+    }
+
     pub fn new_eval_warnings(&self) -> EvalWarnings {
         EvalWarnings::new(self.max_warning_cnt)
     }
@@ -1299,8 +1299,8 @@ fn cast_duration_as_time(
             ctx,
             *val,
             extra.ret_field_type.as_accessor().tp().try_into()?,
+            extra.ret_field_type.get_decimal() as i8,
         )
-        .and_then(|now| now.round_frac(ctx, extra.ret_field_type.get_decimal() as i8))
         .map(Some)
         .or_else(|e| Ok(ctx.handle_invalid_time_error(e).map(|_| None)?))
     } else {
@@ -1329,10 +1329,9 @@ pub fn cast_json_as_time(
                 ctx,
                 duration,
                 extra.ret_field_type.as_accessor().tp().try_into()?,
+                extra.ret_field_type.decimal() as i8,
             )?;
-            Ok(Some(
-                time.round_frac(ctx, extra.ret_field_type.decimal() as i8)?,
-            ))
+            Ok(Some(time))
         }
         JsonType::String => cast_bytes_like_as_time(ctx, extra, v.unquote()?.as_bytes()),
         _ => {
@@ -1398,10 +1397,7 @@ fn cast_string_as_json(
                 .flag()
                 .contains(FieldTypeFlag::PARSE_TO_JSON)
             {
-                // if failed, is it because of bug?
-                let s: String = box_try!(String::from_utf8(val.to_owned()));
-                let val: Json = s.parse()?;
-                Ok(Some(val))
+                Ok(Some(Json::parse_text(val)?))
             } else {
                 // FIXME: port `JSONBinary` from TiDB to adapt if the bytes is not a valid utf8
                 // string
@@ -430,7 +430,7 @@ fn json_contains(args: &[ScalarValueRef]) -> Result<Option<i64>> {
     let j: Option<JsonRef> = args[0].as_json();
     let mut j = match j {
         None => return Ok(None),
-        Some(j) => j.to_owned(),
+        Some(j) => j,
     };
     let target: Option<JsonRef> = args[1].as_json();
     let target = match target {
@@ -438,23 +438,38 @@ fn json_contains(args: &[ScalarValueRef]) -> Result<Option<i64>> {
         Some(target) => target,
     };
 
+    // Keeps an autowrapped extraction alive for the containment check below.
+    let extracted: Json;
     if args.len() == 3 {
         match parse_json_path_list(&args[2..])? {
             Some(path_expr_list) => {
                 if path_expr_list.len() == 1 && path_expr_list[0].contains_any_asterisk() {
                     return Ok(None);
                 }
-                match j.as_ref().extract(&path_expr_list)? {
-                    Some(json) => {
-                        j = json;
+                if path_expr_list.iter().any(|p| p.contains_any_range()) {
+                    // Multiple matches are autowrapped into a fresh array, so
+                    // an owned value is unavoidable here.
+                    match j.extract(&path_expr_list)? {
+                        Some(json) => {
+                            extracted = json;
+                            j = extracted.as_ref();
+                        }
+                        _ => return Ok(None),
+                    }
+                } else {
+                    // A single path without asterisk or range matches at most
+                    // once, so the sub-document can be borrowed directly.
+                    let mut elem_list = j.extract_ref(&path_expr_list)?;
+                    if elem_list.is_empty() {
+                        return Ok(None);
                     }
-                    _ => return Ok(None),
+                    j = elem_list.remove(0);
                 }
             }
             None => return Ok(None),
         };
     }
-    Ok(Some(j.as_ref().json_contains(target)? as i64))
+    Ok(Some(j.json_contains(target)? as i64))
 }
 
 // Args should be like `(Option<JsonRef> , Option<JsonRef>)`
@@ -151,12 +151,15 @@ pub fn day_of_year(ctx: &mut EvalContext, t: Option<&DateTime>) -> Result<Option
         return Ok(None);
     }
     let t = t.as_ref().unwrap();
-    if t.invalid_zero() {
-        return ctx
-            .handle_invalid_time_error(Error::incorrect_datetime_value(t))
-            .map(|_| Ok(None))?;
-    }
-    let day = t.days();
+    let day = match t.days() {
+        Some(day) => day,
+        // Zero-in-date values have no day of year.
+        None => {
+            return ctx
+                .handle_invalid_time_error(Error::incorrect_datetime_value(t))
+                .map(|_| Ok(None))?;
+        }
+    };
     Ok(Some(Int::from(day)))
 }
 
@@ -224,23 +227,31 @@ pub fn to_days(ctx: &mut EvalContext, t: Option<&DateTime>) -> Result<Option<Int
         Some(v) => v,
         _ => return Ok(None),
     };
-    if t.invalid_zero() {
-        return ctx
-            .handle_invalid_time_error(Error::incorrect_datetime_value(t))
-            .map(|_| Ok(None))?;
-    }
-    Ok(Some(Int::from(t.day_number())))
+    let day_number = match t.day_number() {
+        Some(day_number) => day_number,
+        // Zero-in-date values have no day number.
+        None => {
+            return ctx
+                .handle_invalid_time_error(Error::incorrect_datetime_value(t))
+                .map(|_| Ok(None))?;
+        }
+    };
+    Ok(Some(Int::from(day_number)))
 }
 
 #[rpn_fn(capture = [ctx])]
 #[inline]
 pub fn to_seconds(ctx: &mut EvalContext, t: &DateTime) -> Result<Option<Int>> {
-    if t.invalid_zero() {
-        return ctx
-            .handle_invalid_time_error(Error::incorrect_datetime_value(t))
-            .map(|_| Ok(None))?;
-    }
-    Ok(Some(t.second_number()))
+    let second_number = match t.second_number() {
+        Some(second_number) => second_number,
+        // Zero-in-date values have no second number.
+        None => {
+            return ctx
+                .handle_invalid_time_error(Error::incorrect_datetime_value(t))
+                .map(|_| Ok(None))?;
+        }
+    };
+    Ok(Some(second_number))
 }
 
 #[rpn_fn(writer, capture = [ctx])]
@@ -1040,12 +1051,12 @@ mod tests {
             ),
             (
                 "0000-01-01 00:00:00.123456",
-                // Functions week() and yearweek() don't support multi mode,
-                // so the result of "%U %u %V %Y" is different from MySQL.
-                "%b %M %m %c %D %d %e %j %k %h %i %p %r %T %s %f %v
-                %x %Y %y %%",
-                "Jan January 01 1 1st 01 1 001 0 12 00 AM 12:00:00 AM 00:00:00 00 123456 52
-                4294967295 0000 00 %",
+                // The week year of `%x` underflows to -1 here, which MySQL
+                // prints as u32::MAX.
+                "%b %M %m %c %D %d %e %j %k %h %i %p %r %T %s %f %U %u %V %v
+                %X %x %Y %y %%",
+                "Jan January 01 1 1st 01 1 001 0 12 00 AM 12:00:00 AM 00:00:00 00 123456 01 00 01 52
+                0000 4294967295 0000 00 %",
             ),
             (
                 "2016-09-3 00:59:59.123456",
@@ -1408,6 +1419,8 @@ mod tests {
             (Some("0000-01-01"), Some(1)),
             (Some("2007-10-07 00:00:59"), Some(733321)),
             (Some("0000-00-00 00:00:00"), None),
+            (Some("2018-11-00 00:00:00"), None),
+            (Some("2018-00-11 00:00:00"), None),
             (None, None),
         ];
 
@@ -1431,6 +1444,8 @@ mod tests {
             ("09-11-29 13:43:32", Some(63426721412)),
             ("99-11-29 13:43:32", Some(63111102212)),
             ("0000-00-00 00:00:00", None),
+            ("2018-11-00 00:00:00", None),
+            ("2018-00-11 00:00:00", None),
         ];
 
         let mut ctx = EvalContext::default();
@@ -1553,6 +1568,21 @@ mod tests {
                 "2018-02-02 00:00:00.000000",
                 Some(-1),
             ),
+            (
+                "0000-00-00 00:00:00.000000",
+                "2018-02-01 00:00:00.000000",
+                None,
+            ),
+            (
+                "2018-02-01 00:00:00.000000",
+                "2018-00-01 00:00:00.000000",
+                None,
+            ),
+            (
+                "2018-02-00 00:00:00.000000",
+                "2018-02-01 00:00:00.000000",
+                None,
+            ),
         ];
         let mut ctx = EvalContext::default();
         for (arg1, arg2, exp) in cases {
@@ -87,6 +87,19 @@ pub fn block_on_external_io<F: Future>(f: F) -> F::Output {
 pub trait RetryError {
     /// Returns whether this error can be retried.
     fn is_retryable(&self) -> bool;
+
+    /// The delay the server asked us to wait before retrying (e.g. the
+    /// `Retry-After` header of an S3 503 SlowDown or a GCS 429), if any.
+    /// The retry loop prefers it over its own exponential backoff.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The server-side request id attached to the failed response, if any,
+    /// so it can be handed over to the storage provider's support.
+    fn request_id(&self) -> Option<String> {
+        None
+    }
 }
 
 /// Retries a future execution.
@@ -154,7 +167,7 @@ where
     let mut retry_wait_dur = Duration::from_secs(1);
     let mut retry_time = 0;
     loop {
-        match action().await {
+        let server_delay = match action().await {
             Ok(r) => return Ok(r),
             Err(e) => {
                 if let Some(ref mut f) = ext.on_failure {
@@ -165,13 +178,22 @@ where
                 }
                 retry_time += 1;
                 if retry_time > max_retry_times {
+                    if let Some(id) = e.request_id() {
+                        warn!("retries exhausted"; "request_id" => id);
+                    }
                     return Err(e);
                 }
+                e.retry_after()
             }
-        }
+        };
 
-        let backoff = thread_rng().gen_range(0..1000);
-        sleep(retry_wait_dur + Duration::from_millis(backoff)).await;
+        // Prefer the delay the server asked for over our own backoff guess;
+        // without one, use truncated exponential backoff with jitter.
+        let wait = match server_delay {
+            Some(delay) => MAX_RETRY_DELAY.min(delay),
+            None => retry_wait_dur + Duration::from_millis(thread_rng().gen_range(0..1000)),
+        };
+        sleep(wait).await;
         retry_wait_dur = MAX_RETRY_DELAY.min(retry_wait_dur * 2);
     }
 }
@@ -195,6 +217,16 @@ pub fn http_retriable(status: StatusCode) -> bool {
     status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT
 }
 
+/// Parses a `Retry-After` value in its delay-seconds form. The HTTP-date
+/// form is not produced by the blob storages we talk to and is ignored.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// The response headers which may carry a server-side request id, in the
+/// order we look them up.
+const REQUEST_ID_HEADERS: &[&str] = &["x-amz-request-id", "x-amz-id-2", "x-guploader-uuid"];
+
 impl<E> RetryError for RusotoError<E> {
     fn is_retryable(&self) -> bool {
         match self {
@@ -203,6 +235,23 @@ impl<E> RetryError for RusotoError<E> {
             _ => false,
         }
     }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Unknown(resp) => parse_retry_after(resp.headers.get("retry-after")?),
+            _ => None,
+        }
+    }
+
+    fn request_id(&self) -> Option<String> {
+        match self {
+            Self::Unknown(resp) => REQUEST_ID_HEADERS
+                .iter()
+                .find_map(|h| resp.headers.get(*h))
+                .cloned(),
+            _ => None,
+        }
+    }
 }
 
 impl RetryError for HttpDispatchError {
@@ -216,10 +265,9 @@ mod tests {
     use std::{cell::RefCell, pin::Pin};
 
     use futures::{Future, FutureExt};
-    use rusoto_core::HttpDispatchError;
+    use rusoto_core::{request::BufferedHttpResponse, HttpDispatchError};
 
-    use super::RetryError;
-    use crate::stream::retry;
+    use super::*;
 
     #[derive(Debug)]
     struct TriviallyRetry;
@@ -261,4 +309,72 @@ mod tests {
         let r = retry(gen_action_fail_for(1)).await;
         assert!(r.is_ok(), "{:?}", r);
     }
+
+    #[derive(Debug)]
+    struct Throttled;
+
+    impl RetryError for Throttled {
+        fn is_retryable(&self) -> bool {
+            true
+        }
+        fn retry_after(&self) -> Option<Duration> {
+            Some(Duration::from_millis(20))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_prefers_server_delay() {
+        let mut n = 0;
+        let begin = std::time::Instant::now();
+        let r = retry(move || {
+            n += 1;
+            if n <= 2 {
+                futures::future::err(Throttled).boxed()
+            } else {
+                futures::future::ok(()).boxed()
+            }
+        })
+        .await;
+        assert!(r.is_ok(), "{:?}", r);
+        // Two failures slept on the server-provided 20ms delay; the default
+        // exponential backoff would have slept at least a second each time.
+        assert!(
+            begin.elapsed() < Duration::from_secs(1),
+            "took {:?}",
+            begin.elapsed()
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("7"), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after(" 30 "), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("-1"), None);
+        // The HTTP-date form is not supported.
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+
+    #[test]
+    fn test_rusoto_throttle_metadata() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("retry-after", "7".to_string());
+        headers.insert("x-amz-request-id", "REQUEST-ID-1".to_string());
+        let resp = BufferedHttpResponse {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            headers,
+            body: Bytes::new(),
+        };
+        let err = RusotoError::<()>::Unknown(resp);
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+        assert_eq!(err.request_id().as_deref(), Some("REQUEST-ID-1"));
+
+        let err = RusotoError::<()>::Unknown(BufferedHttpResponse {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            headers: http::HeaderMap::new(),
+            body: Bytes::new(),
+        });
+        assert_eq!(err.retry_after(), None);
+        assert_eq!(err.request_id(), None);
+    }
 }
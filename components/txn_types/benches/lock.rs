@@ -0,0 +1,107 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A shared criterion harness for the lock hot paths: `to_bytes`, `parse`
+//! and `check_ts_conflict`. Micro-changes to the codec keep landing without
+//! a common yardstick (the `rollback_ts` capacity tweak was never measured),
+//! so this pins down the shapes to compare against: small optimistic locks,
+//! pessimistic locks, an async-commit lock with many secondaries, and a
+//! conflict scan over a large `TsSet`.
+
+use std::{borrow::Cow, hint::black_box};
+
+use criterion::*;
+use kvproto::kvrpcpb::IsolationLevel;
+use rand::prelude::*;
+use txn_types::{Key, Lock, LockType, TimeStamp, TsSet};
+
+const LOCK_COUNT: usize = 10_000;
+const SECONDARY_COUNT: usize = 100;
+
+fn small_optimistic_lock() -> Lock {
+    Lock::new_optimistic(
+        LockType::Put,
+        b"primary_key".to_vec(),
+        10.into(),
+        3_000,
+        Some(b"short_value".to_vec()),
+    )
+}
+
+fn pessimistic_lock() -> Lock {
+    Lock::new_pessimistic(b"primary_key".to_vec(), 10.into(), 3_000, 11.into())
+}
+
+fn async_commit_lock() -> Lock {
+    let mut rng = StdRng::seed_from_u64(0x5ca1_ab1e);
+    let secondaries = (0..SECONDARY_COUNT)
+        .map(|_| {
+            let mut key = [0u8; 24];
+            rng.fill_bytes(&mut key);
+            key.to_vec()
+        })
+        .collect();
+    Lock::new_async_commit(
+        LockType::Put,
+        b"primary_key".to_vec(),
+        10.into(),
+        3_000,
+        None,
+        20.into(),
+        secondaries,
+    )
+}
+
+fn bench_encode_decode(c: &mut Criterion, name: &str, lock: Lock) {
+    let encoded = lock.to_bytes();
+    c.bench_function(&format!("encode_{}", name), |b| {
+        b.iter(|| black_box(lock.to_bytes()))
+    });
+    c.bench_function(&format!("decode_{}", name), |b| {
+        b.iter(|| black_box(Lock::parse(&encoded).unwrap()))
+    });
+}
+
+fn bench_codec(c: &mut Criterion) {
+    bench_encode_decode(c, "small_optimistic_lock", small_optimistic_lock());
+    bench_encode_decode(c, "pessimistic_lock", pessimistic_lock());
+    bench_encode_decode(c, "async_commit_lock_100_secondaries", async_commit_lock());
+}
+
+fn bench_check_ts_conflict(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0xdead_beef);
+    let locks: Vec<(Key, Lock)> = (0..LOCK_COUNT)
+        .map(|i| {
+            let mut raw = [0u8; 24];
+            rng.fill_bytes(&mut raw);
+            let lock = Lock::new_optimistic(
+                LockType::Put,
+                raw.to_vec(),
+                TimeStamp::compose(1_000 + i as u64, 0),
+                3_000,
+                None,
+            );
+            (Key::from_raw(&raw), lock)
+        })
+        .collect();
+    // Every lock's start_ts is in the set, so each check pays for one lookup
+    // in the `Set` variant and then passes.
+    let bypass_locks = TsSet::new(locks.iter().map(|(_, lock)| lock.ts).collect());
+    let read_ts = TimeStamp::compose(1 << 20, 0);
+    c.bench_function("check_ts_conflict_10k_locks_large_ts_set", |b| {
+        b.iter(|| {
+            for (key, lock) in &locks {
+                black_box(Lock::check_ts_conflict(
+                    Cow::Borrowed(lock),
+                    key,
+                    read_ts,
+                    &bypass_locks,
+                    IsolationLevel::Si,
+                ))
+                .unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_codec, bench_check_ts_conflict);
+criterion_main!(benches);
@@ -0,0 +1,163 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Benchmarks `Lock::parse` against the scratch-buffer `Lock::parse_in`,
+//! reporting both wall time and the number of allocations per batch of
+//! locks, which is what the scratch parser is meant to shave off.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use criterion::*;
+use rand::prelude::*;
+use txn_types::{Lock, LockBuffers, LockType, TimeStamp};
+
+const LOCK_COUNT: usize = 10_000;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Counts allocations so the report below can show allocations per batch.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Encodes a mix of lock shapes resembling what a resolve-lock scan sees:
+/// plain optimistic locks, locks with short values, pessimistic locks and
+/// the occasional async-commit primary with secondaries.
+fn prepare_locks(count: usize) -> Vec<Vec<u8>> {
+    let mut rng = StdRng::seed_from_u64(0xdead_beef);
+    (0..count)
+        .map(|i| {
+            let mut primary = [0u8; 24];
+            rng.fill_bytes(&mut primary);
+            let short_value = (i % 4 == 0).then(|| primary.to_vec());
+            let lock = Lock::new(
+                if i % 5 == 0 {
+                    LockType::Pessimistic
+                } else {
+                    LockType::Put
+                },
+                primary.to_vec(),
+                TimeStamp::compose(1_000 + i as u64, 0),
+                3_000,
+                short_value,
+                TimeStamp::zero(),
+                1,
+                TimeStamp::zero(),
+                false,
+            );
+            if i % 50 == 0 {
+                lock.use_async_commit(vec![b"sk1".to_vec(), b"sk2".to_vec(), b"sk3".to_vec()])
+                    .to_bytes()
+            } else {
+                lock.to_bytes()
+            }
+        })
+        .collect()
+}
+
+fn count_allocations(f: impl FnOnce()) -> u64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Prints the allocation counts per `LOCK_COUNT` locks for both parsers.
+fn report_allocation_counts(values: &[Vec<u8>]) {
+    let parse_allocs = count_allocations(|| {
+        for v in values {
+            black_box(Lock::parse(v).unwrap());
+        }
+    });
+    let parse_in_allocs = count_allocations(|| {
+        let mut buffers = LockBuffers::default();
+        for v in values {
+            let lock = black_box(Lock::parse_in(v, &mut buffers).unwrap());
+            buffers.recycle(lock);
+        }
+    });
+    println!(
+        "allocations per {} locks: parse={} parse_in={}",
+        values.len(),
+        parse_allocs,
+        parse_in_allocs
+    );
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let values = prepare_locks(LOCK_COUNT);
+    c.bench_function("parse_10k_locks", |b| {
+        b.iter(|| {
+            for v in &values {
+                black_box(Lock::parse(v).unwrap());
+            }
+        })
+    });
+}
+
+fn bench_parse_in(c: &mut Criterion) {
+    let values = prepare_locks(LOCK_COUNT);
+    let mut buffers = LockBuffers::default();
+    c.bench_function("parse_in_10k_locks", |b| {
+        b.iter(|| {
+            for v in &values {
+                let lock = black_box(Lock::parse_in(v, &mut buffers).unwrap());
+                buffers.recycle(lock);
+            }
+        })
+    });
+}
+
+/// Encodes and parses a single async-commit primary carrying 10k
+/// secondaries, the shape the flat `SecondaryKeys` buffer is built for.
+fn bench_10k_secondaries(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0x5ec0_4da2);
+    let secondaries: Vec<Vec<u8>> = (0..10_000)
+        .map(|_| {
+            let mut key = [0u8; 24];
+            rng.fill_bytes(&mut key);
+            key.to_vec()
+        })
+        .collect();
+    let lock = Lock::new(
+        LockType::Put,
+        b"primary".to_vec(),
+        TimeStamp::compose(1_000, 0),
+        3_000,
+        None,
+        TimeStamp::zero(),
+        1,
+        TimeStamp::zero(),
+        false,
+    )
+    .use_async_commit(secondaries);
+    c.bench_function("encode_lock_10k_secondaries", |b| {
+        b.iter(|| black_box(lock.to_bytes()))
+    });
+    let value = lock.to_bytes();
+    c.bench_function("parse_lock_10k_secondaries", |b| {
+        b.iter(|| black_box(Lock::parse(&value).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_parse, bench_parse_in, bench_10k_secondaries);
+
+fn main() {
+    report_allocation_counts(&prepare_locks(LOCK_COUNT));
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
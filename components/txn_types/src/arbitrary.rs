@@ -0,0 +1,189 @@
+// Copyright 2026 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Proptest strategies generating valid locks, so downstream test crates
+//! (mvcc, scheduler, cdc, ...) can share one generator instead of
+//! hand-rolling their own. Only compiled for tests or with the `testexport`
+//! feature.
+//!
+//! "Valid" means the invariants the rest of the crate relies on hold:
+//! `secondaries` are only set together with `use_async_commit`, short values
+//! stay within [`SHORT_VALUE_MAX_LEN`], and `LastChange::Exist` always
+//! carries a non-zero ts and a positive version estimation. Every generated
+//! lock round-trips through `to_bytes`/`parse`.
+
+use proptest::{collection::vec, option, prelude::*, strategy::BoxedStrategy};
+
+use crate::{LastChange, Lock, LockType, PessimisticLock, TimeStamp, SHORT_VALUE_MAX_LEN};
+
+/// A strategy over all [`LockType`] variants.
+pub fn arb_lock_type() -> impl Strategy<Value = LockType> {
+    prop_oneof![
+        Just(LockType::Put),
+        Just(LockType::Delete),
+        Just(LockType::Lock),
+        Just(LockType::Pessimistic),
+    ]
+}
+
+// The lock codec only cares about length framing, not key contents, so
+// short keys explore it just as well.
+fn arb_key() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..32)
+}
+
+fn arb_ts() -> impl Strategy<Value = TimeStamp> {
+    any::<u64>().prop_map(TimeStamp::from)
+}
+
+/// A strategy over the valid [`LastChange`] values.
+pub fn arb_last_change() -> impl Strategy<Value = LastChange> {
+    prop_oneof![
+        Just(LastChange::Unknown),
+        Just(LastChange::NotExist),
+        (1..=u64::MAX, 1..=u64::MAX)
+            .prop_map(|(ts, versions)| LastChange::make_exist(ts.into(), versions)),
+    ]
+}
+
+/// A strategy over valid [`Lock`]s.
+///
+/// `allow_long_short_value` is kept unset and short values within
+/// [`SHORT_VALUE_MAX_LEN`], matching what the write paths produce today.
+pub fn arb_lock() -> impl Strategy<Value = Lock> {
+    let base = (
+        arb_lock_type(),
+        arb_key(),
+        arb_ts(),
+        any::<u64>(),
+        option::of(vec(any::<u8>(), 0..=SHORT_VALUE_MAX_LEN)),
+        arb_ts(),
+        any::<u64>(),
+        arb_ts(),
+    );
+    let extra = (
+        // `Some` turns on async commit; secondaries must not appear alone.
+        option::of(vec(arb_key(), 0..4)),
+        vec(arb_ts(), 0..4),
+        arb_last_change(),
+        // The upper application is limited to sources under 0x80.
+        0..0x80u64,
+        any::<bool>(),
+        any::<u64>(),
+    );
+    (base, extra).prop_map(
+        |(
+            (lock_type, primary, ts, ttl, short_value, for_update_ts, txn_size, min_commit_ts),
+            (secondaries, rollback_ts, last_change, txn_source, is_locked_with_conflict, generation),
+        )| {
+            let mut lock = Lock::new(
+                lock_type,
+                primary,
+                ts,
+                ttl,
+                short_value,
+                for_update_ts,
+                txn_size,
+                min_commit_ts,
+                is_locked_with_conflict,
+            )
+            .with_rollback_ts(rollback_ts)
+            .set_last_change(last_change)
+            .set_txn_source(txn_source)
+            .with_generation(generation);
+            if let Some(secondaries) = secondaries {
+                lock = lock.use_async_commit(secondaries);
+            }
+            lock
+        },
+    )
+}
+
+/// A strategy over valid [`PessimisticLock`]s.
+pub fn arb_pessimistic_lock() -> impl Strategy<Value = PessimisticLock> {
+    (
+        arb_key(),
+        arb_ts(),
+        any::<u64>(),
+        arb_ts(),
+        arb_ts(),
+        arb_last_change(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                primary,
+                start_ts,
+                ttl,
+                for_update_ts,
+                min_commit_ts,
+                last_change,
+                is_locked_with_conflict,
+            )| PessimisticLock {
+                primary: primary.into_boxed_slice(),
+                start_ts,
+                ttl,
+                for_update_ts,
+                min_commit_ts,
+                last_change,
+                is_locked_with_conflict,
+            },
+        )
+}
+
+impl Arbitrary for LastChange {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<LastChange>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        arb_last_change().boxed()
+    }
+}
+
+impl Arbitrary for Lock {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Lock>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        arb_lock().boxed()
+    }
+}
+
+impl Arbitrary for PessimisticLock {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<PessimisticLock>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        arb_pessimistic_lock().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_lock_roundtrip(lock in any::<Lock>()) {
+            let bytes = lock.to_bytes();
+            let parsed = Lock::parse(&bytes).unwrap();
+            prop_assert_eq!(&parsed, &lock);
+            prop_assert_eq!(parsed.to_bytes(), bytes);
+        }
+
+        #[test]
+        fn test_peek_agrees_with_parse(lock in any::<Lock>()) {
+            let bytes = lock.to_bytes();
+            let parsed = Lock::parse(&bytes).unwrap();
+            prop_assert_eq!(crate::peek_lock_type(&bytes).unwrap(), parsed.lock_type);
+            prop_assert_eq!(crate::peek_start_ts(&bytes).unwrap(), parsed.ts);
+        }
+
+        #[test]
+        fn test_pessimistic_lock_to_lock(lock in any::<PessimisticLock>()) {
+            let as_lock = lock.to_lock();
+            prop_assert_eq!(as_lock.lock_type, LockType::Pessimistic);
+            prop_assert_eq!(&as_lock, &lock.clone().into_lock());
+            prop_assert_eq!(Lock::parse(&as_lock.to_bytes()).unwrap(), as_lock);
+        }
+    }
+}
@@ -10,7 +10,11 @@ use std::io;
 
 use error_code::{self, ErrorCode, ErrorCodeExt};
 use kvproto::kvrpcpb;
-pub use lock::{Lock, LockType, PessimisticLock, TxnLockRef};
+pub use lock::{
+    min_blocking_start_ts, peek_lock_type, peek_start_ts, set_redact_lock_info, summarize, Lock,
+    LockBuffers, LockRef, LockSummary, LockType, PessimisticLock, SecondaryKeys, TxnLockRef,
+    LOCK_AGE_BUCKETS_MS,
+};
 use thiserror::Error;
 pub use timestamp::{TimeStamp, TsSet, TSO_PHYSICAL_SHIFT_BITS};
 pub use types::{
@@ -19,6 +23,8 @@ pub use types::{
 };
 pub use write::{Write, WriteRef, WriteType};
 
+#[cfg(any(test, feature = "testexport"))]
+pub mod arbitrary;
 mod lock;
 mod timestamp;
 mod types;
@@ -37,9 +43,9 @@ pub enum ErrorInner {
     #[error("key is locked (backoff or cleanup) {0:?}")]
     KeyIsLocked(kvproto::kvrpcpb::LockInfo),
     #[error(
-        "write conflict, start_ts: {}, conflict_start_ts: {}, conflict_commit_ts: {}, key: {}, primary: {}, reason: {:?}",
+        "write conflict, start_ts: {}, conflict_start_ts: {}, conflict_commit_ts: {}, key: {}, primary: {}, reason: {:?}, lock_type: {:?}",
         .start_ts, .conflict_start_ts, .conflict_commit_ts,
-        log_wrappers::Value::key(.key), log_wrappers::Value::key(.primary), .reason
+        log_wrappers::Value::key(.key), log_wrappers::Value::key(.primary), .reason, .lock_type
     )]
     WriteConflict {
         start_ts: TimeStamp,
@@ -48,6 +54,9 @@ pub enum ErrorInner {
         key: Vec<u8>,
         primary: Vec<u8>,
         reason: kvrpcpb::WriteConflictReason,
+        /// The type of the conflicting lock when the conflict was raised
+        /// against a pending lock instead of a committed write.
+        lock_type: Option<LockType>,
     },
 }
 
@@ -66,6 +75,7 @@ impl ErrorInner {
                 key,
                 primary,
                 reason,
+                lock_type,
             } => Some(ErrorInner::WriteConflict {
                 start_ts: *start_ts,
                 conflict_start_ts: *conflict_start_ts,
@@ -73,6 +83,7 @@ impl ErrorInner {
                 key: key.to_owned(),
                 primary: primary.to_owned(),
                 reason: reason.to_owned(),
+                lock_type: *lock_type,
             }),
         }
     }
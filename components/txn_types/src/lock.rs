@@ -1,9 +1,17 @@
 // Copyright 2016 TiKV Project Authors. Licensed under Apache-2.0.
 
-use std::{borrow::Cow, mem::size_of};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    mem::size_of,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
 
 use byteorder::ReadBytesExt;
 use kvproto::kvrpcpb::{IsolationLevel, LockInfo, Op, WriteConflictReason};
+#[cfg(feature = "lock-dump")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use tikv_util::{
     codec::{
         bytes::{self, BytesEncoder},
@@ -31,6 +39,10 @@ const FLAG_DELETE: u8 = b'D';
 const FLAG_LOCK: u8 = b'L';
 const FLAG_PESSIMISTIC: u8 = b'S';
 
+// Each optional section must be emitted at most once per lock: the parsers
+// reject a duplicated prefix as `BadFormatLock` instead of letting the later
+// section silently overwrite (or, for the list sections, leak) the earlier
+// one.
 const FOR_UPDATE_TS_PREFIX: u8 = b'f';
 const TXN_SIZE_PREFIX: u8 = b't';
 const MIN_COMMIT_TS_PREFIX: u8 = b'c';
@@ -41,6 +53,12 @@ const TXN_SOURCE_PREFIX: u8 = b's';
 const _RESERVED_PREFIX: u8 = b'T'; // Reserved for future use.
 const PESSIMISTIC_LOCK_WITH_CONFLICT_PREFIX: u8 = b'F';
 const GENERATION_PREFIX: u8 = b'g';
+/// A short value section with a u16 length, lifting the 255-byte cap of
+/// `SHORT_VALUE_PREFIX`. Parsers always accept it, but `to_bytes` only emits
+/// it when the value does not fit the u8 length and the lock opts in via
+/// [`Lock::allow_long_short_value`], so it never reaches disk before every
+/// node in the cluster can read it back.
+const LONG_SHORT_VALUE_PREFIX: u8 = b'V';
 
 impl LockType {
     pub fn from_mutation(mutation: &Mutation) -> Option<LockType> {
@@ -72,6 +90,171 @@ impl LockType {
     }
 }
 
+#[cfg(feature = "lock-dump")]
+impl Serialize for LockType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            LockType::Put => "put",
+            LockType::Delete => "delete",
+            LockType::Lock => "lock",
+            LockType::Pessimistic => "pessimistic",
+        })
+    }
+}
+
+// Whether the errors built by the conflict checks below have their key bytes
+// redacted. Process-level, following `log_wrappers::set_redact_info_log`
+// (which only covers logging, not the errors forwarded to clients).
+static REDACT_LOCK_INFO: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether the errors built by `Lock::check_ts_conflict_*` (the
+/// `LockInfo` of `KeyIsLocked` and the keys of `WriteConflict`) replace key
+/// bytes with a salted, length-preserving hash, for deployments which must
+/// not leak raw user keys to clients. The hash is stable within a process,
+/// so errors for the same key still correlate, but it cannot be recomputed
+/// across restarts.
+pub fn set_redact_lock_info(v: bool) {
+    REDACT_LOCK_INFO.store(v, Ordering::Relaxed);
+}
+
+fn redact_lock_info_enabled() -> bool {
+    REDACT_LOCK_INFO.load(Ordering::Relaxed)
+}
+
+/// The per-process random salt of [`redacted_key`].
+fn redact_salt() -> u64 {
+    static SALT: AtomicU64 = AtomicU64::new(0);
+    let mut salt = SALT.load(Ordering::Relaxed);
+    if salt == 0 {
+        // Seeded from the OS; zero is re-rolled so it can keep serving as
+        // the "uninitialized" sentinel.
+        let seeded = RandomState::new().build_hasher().finish().max(1);
+        salt = match SALT.compare_exchange(0, seeded, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => seeded,
+            Err(raced) => raced,
+        };
+    }
+    salt
+}
+
+/// Replaces `key` with a salted hash of the same length, so clients can
+/// still correlate errors by key without seeing the key bytes.
+fn redacted_key(key: &[u8]) -> Vec<u8> {
+    let salt = redact_salt();
+    let mut out = Vec::with_capacity(key.len());
+    let mut block: u64 = 0;
+    while out.len() < key.len() {
+        let mut buf = Vec::with_capacity(2 * size_of::<u64>() + key.len());
+        buf.extend_from_slice(&salt.to_be_bytes());
+        buf.extend_from_slice(&block.to_be_bytes());
+        buf.extend_from_slice(key);
+        let hash = farmhash::fingerprint64(&buf).to_be_bytes();
+        let left = key.len() - out.len();
+        out.extend_from_slice(&hash[..left.min(hash.len())]);
+        block += 1;
+    }
+    out
+}
+
+/// The hex form of `bytes` for JSON dumps, honoring [`set_redact_lock_info`]:
+/// with redaction on, the same salted hash the conflict errors expose replaces
+/// the raw bytes before encoding, so a dump still correlates by key without
+/// containing it.
+#[cfg(feature = "lock-dump")]
+fn dump_hex(bytes: &[u8]) -> String {
+    if redact_lock_info_enabled() {
+        log_wrappers::hex_encode_upper(redacted_key(bytes))
+    } else {
+        log_wrappers::hex_encode_upper(bytes)
+    }
+}
+
+/// The secondary keys of an async-commit primary lock.
+///
+/// The keys live back to back in one flat byte buffer with per-key offsets,
+/// so a primary carrying thousands of secondaries costs two allocations
+/// instead of one per key, and encoding walks a contiguous buffer instead of
+/// chasing a pointer per key.
+#[derive(PartialEq, Clone, Default)]
+pub struct SecondaryKeys {
+    /// The keys, concatenated.
+    buf: Vec<u8>,
+    /// Where each key starts in `buf`; a key ends where the next one begins.
+    offsets: Vec<usize>,
+}
+
+impl SecondaryKeys {
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Returns the `i`-th key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= self.len()`.
+    pub fn get(&self, i: usize) -> &[u8] {
+        let end = self.offsets.get(i + 1).copied().unwrap_or(self.buf.len());
+        &self.buf[self.offsets[i]..end]
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &[u8]> {
+        (0..self.len()).map(move |i| self.get(i))
+    }
+
+    pub fn push(&mut self, key: &[u8]) {
+        self.offsets.push(self.buf.len());
+        self.buf.extend_from_slice(key);
+    }
+
+    /// Copies the keys out into the nested shape, e.g. for protobuf.
+    pub fn to_vec(&self) -> Vec<Vec<u8>> {
+        self.iter().map(<[u8]>::to_vec).collect()
+    }
+
+    /// The total length of the keys in bytes.
+    fn total_len(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+impl From<Vec<Vec<u8>>> for SecondaryKeys {
+    fn from(keys: Vec<Vec<u8>>) -> Self {
+        let mut res = SecondaryKeys {
+            buf: Vec::with_capacity(keys.iter().map(Vec::len).sum()),
+            offsets: Vec::with_capacity(keys.len()),
+        };
+        for key in &keys {
+            res.push(key);
+        }
+        res
+    }
+}
+
+impl PartialEq<Vec<Vec<u8>>> for SecondaryKeys {
+    fn eq(&self, other: &Vec<Vec<u8>>) -> bool {
+        self.len() == other.len() && self.iter().zip(other).all(|(a, b)| a == b.as_slice())
+    }
+}
+
+impl std::fmt::Debug for SecondaryKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(log_wrappers::Value::key))
+            .finish()
+    }
+}
+
+impl HeapSize for SecondaryKeys {
+    fn approximate_heap_size(&self) -> usize {
+        self.buf.approximate_heap_size() + self.offsets.capacity() * size_of::<usize>()
+    }
+}
+
 #[derive(PartialEq, Clone)]
 pub struct Lock {
     pub lock_type: LockType,
@@ -86,7 +269,7 @@ pub struct Lock {
     pub use_async_commit: bool,
     // Only valid when `use_async_commit` is true, and the lock is primary. Do not set
     // `secondaries` for secondaries.
-    pub secondaries: Vec<Vec<u8>>,
+    pub secondaries: SecondaryKeys,
     // In some rare cases, a protected rollback may happen when there's already another
     // transaction's lock on the key. In this case, if the other transaction uses calculated
     // timestamp as commit_ts, the protected rollback record may be overwritten. Checking Write CF
@@ -109,14 +292,16 @@ pub struct Lock {
     pub is_locked_with_conflict: bool,
     /// The generation of the lock, used in pipelined DML.
     pub generation: u64,
+    /// Permits `to_bytes` to use the u16-length short value encoding
+    /// (`LONG_SHORT_VALUE_PREFIX`) when the value exceeds 255 bytes. Keep it
+    /// unset until the whole cluster parses that encoding; parsers set it on
+    /// locks they decode from the long form, so re-encoding such a lock does
+    /// not truncate it back to the u8 form.
+    pub allow_long_short_value: bool,
 }
 
 impl std::fmt::Debug for Lock {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut secondary_keys = std::vec::Vec::with_capacity(self.secondaries.len());
-        for key in self.secondaries.iter() {
-            secondary_keys.push(log_wrappers::Value::key(key))
-        }
         f.debug_struct("Lock")
             .field("lock_type", &self.lock_type)
             .field("primary_key", &log_wrappers::Value::key(&self.primary))
@@ -130,7 +315,7 @@ impl std::fmt::Debug for Lock {
             .field("txn_size", &self.txn_size)
             .field("min_commit_ts", &self.min_commit_ts)
             .field("use_async_commit", &self.use_async_commit)
-            .field("secondaries", &secondary_keys)
+            .field("secondaries", &self.secondaries)
             .field("rollback_ts", &self.rollback_ts)
             .field("last_change", &self.last_change)
             .field("txn_source", &self.txn_source)
@@ -140,6 +325,53 @@ impl std::fmt::Debug for Lock {
     }
 }
 
+// The field names below are pinned by `test_lock_dump_json`: downstream
+// scripts parse the dumps, so renames here are breaking changes.
+#[cfg(feature = "lock-dump")]
+impl Serialize for Lock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Lock", 15)?;
+        s.serialize_field("lock_type", &self.lock_type)?;
+        s.serialize_field("primary_key", &dump_hex(&self.primary))?;
+        s.serialize_field("start_ts", &self.ts.into_inner())?;
+        s.serialize_field("ttl", &self.ttl)?;
+        s.serialize_field("short_value", &self.short_value.as_deref().map(dump_hex))?;
+        s.serialize_field("for_update_ts", &self.for_update_ts.into_inner())?;
+        s.serialize_field("txn_size", &self.txn_size)?;
+        s.serialize_field("min_commit_ts", &self.min_commit_ts.into_inner())?;
+        s.serialize_field("use_async_commit", &self.use_async_commit)?;
+        s.serialize_field(
+            "secondaries",
+            &self.secondaries.iter().map(dump_hex).collect::<Vec<_>>(),
+        )?;
+        s.serialize_field(
+            "rollback_ts",
+            &self
+                .rollback_ts
+                .iter()
+                .map(|ts| ts.into_inner())
+                .collect::<Vec<_>>(),
+        )?;
+        s.serialize_field("last_change", &self.last_change)?;
+        s.serialize_field("txn_source", &self.txn_source)?;
+        s.serialize_field("is_locked_with_conflict", &self.is_locked_with_conflict)?;
+        s.serialize_field("generation", &self.generation)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "lock-dump")]
+impl Lock {
+    /// Renders the lock as a [`serde_json::Value`], one self-contained object
+    /// per lock, for JSON-lines dumps. Keys and values come out as hex
+    /// strings, redacted per [`set_redact_lock_info`].
+    pub fn to_json_value(&self) -> serde_json::Value {
+        // Serializing into a `Value` has no I/O and none of the fields can
+        // fail to serialize.
+        serde_json::to_value(self).unwrap()
+    }
+}
+
 impl HeapSize for Lock {
     fn approximate_heap_size(&self) -> usize {
         self.primary.approximate_heap_size()
@@ -150,6 +382,13 @@ impl HeapSize for Lock {
 }
 
 impl Lock {
+    /// Builds a lock from every field that has no dedicated setter.
+    ///
+    /// Beware the argument order: `ts`, `for_update_ts` and `min_commit_ts`
+    /// are interleaved with the plain-`u64` `ttl` and `txn_size`, so a swap
+    /// that type-checks can still be wrong. Prefer [`Lock::new_optimistic`],
+    /// [`Lock::new_pessimistic`] or [`Lock::new_async_commit`] when the
+    /// remaining fields are all defaults.
     pub fn new(
         lock_type: LockType,
         primary: Vec<u8>,
@@ -171,17 +410,102 @@ impl Lock {
             txn_size,
             min_commit_ts,
             use_async_commit: false,
-            secondaries: Vec::default(),
+            secondaries: SecondaryKeys::default(),
             rollback_ts: Vec::default(),
             last_change: LastChange::default(),
             txn_source: 0,
             is_locked_with_conflict,
             generation: 0,
+            allow_long_short_value: false,
         }
     }
 
+    /// A plain optimistic prewrite lock: no `for_update_ts`, no `txn_size`
+    /// and no `min_commit_ts`.
+    pub fn new_optimistic(
+        lock_type: LockType,
+        primary: Vec<u8>,
+        start_ts: TimeStamp,
+        ttl: u64,
+        short_value: Option<Value>,
+    ) -> Self {
+        Self::new(
+            lock_type,
+            primary,
+            start_ts,
+            ttl,
+            short_value,
+            TimeStamp::zero(),
+            0,
+            TimeStamp::zero(),
+            false,
+        )
+    }
+
+    /// A pessimistic lock of transaction `start_ts` acquired at
+    /// `for_update_ts`. Pessimistic locks never carry a value.
+    pub fn new_pessimistic(
+        primary: Vec<u8>,
+        start_ts: TimeStamp,
+        ttl: u64,
+        for_update_ts: TimeStamp,
+    ) -> Self {
+        Self::new(
+            LockType::Pessimistic,
+            primary,
+            start_ts,
+            ttl,
+            None,
+            for_update_ts,
+            0,
+            TimeStamp::zero(),
+            false,
+        )
+    }
+
+    /// An async commit prewrite lock carrying its `secondaries` and the
+    /// `min_commit_ts` the commit ts will be calculated from.
+    pub fn new_async_commit(
+        lock_type: LockType,
+        primary: Vec<u8>,
+        start_ts: TimeStamp,
+        ttl: u64,
+        short_value: Option<Value>,
+        min_commit_ts: TimeStamp,
+        secondaries: Vec<Vec<u8>>,
+    ) -> Self {
+        Self::new(
+            lock_type,
+            primary,
+            start_ts,
+            ttl,
+            short_value,
+            TimeStamp::zero(),
+            0,
+            min_commit_ts,
+            false,
+        )
+        .use_async_commit(secondaries)
+    }
+
+    #[must_use]
+    pub fn use_async_commit(self, secondaries: Vec<Vec<u8>>) -> Self {
+        self.with_secondaries(secondaries.into())
+    }
+
+    /// Like [`Lock::use_async_commit`], but takes the secondaries already in
+    /// flat form: `buf` holds the keys back to back and `offsets[i]` is where
+    /// the `i`-th key starts.
+    #[must_use]
+    pub fn use_async_commit_flat(self, buf: Vec<u8>, offsets: Vec<usize>) -> Self {
+        debug_assert!(offsets.first().map_or(true, |o| *o == 0));
+        debug_assert!(offsets.windows(2).all(|w| w[0] <= w[1]));
+        debug_assert!(offsets.last().map_or(true, |o| *o <= buf.len()));
+        self.with_secondaries(SecondaryKeys { buf, offsets })
+    }
+
     #[must_use]
-    pub fn use_async_commit(mut self, secondaries: Vec<Vec<u8>>) -> Self {
+    fn with_secondaries(mut self, secondaries: SecondaryKeys) -> Self {
         self.use_async_commit = true;
         self.secondaries = secondaries;
         self
@@ -213,6 +537,13 @@ impl Lock {
         self
     }
 
+    #[inline]
+    #[must_use]
+    pub fn allow_long_short_value(mut self) -> Self {
+        self.allow_long_short_value = true;
+        self
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut b = Vec::with_capacity(self.pre_allocate_size());
         b.push(self.lock_type.to_u8());
@@ -220,8 +551,13 @@ impl Lock {
         b.encode_var_u64(self.ts.into_inner()).unwrap();
         b.encode_var_u64(self.ttl).unwrap();
         if let Some(ref v) = self.short_value {
-            b.push(SHORT_VALUE_PREFIX);
-            b.push(v.len() as u8);
+            if v.len() > u8::MAX as usize && self.allow_long_short_value {
+                b.push(LONG_SHORT_VALUE_PREFIX);
+                b.encode_u16(v.len() as u16).unwrap();
+            } else {
+                b.push(SHORT_VALUE_PREFIX);
+                b.push(v.len() as u8);
+            }
             b.extend_from_slice(v);
         }
         if !self.for_update_ts.is_zero() {
@@ -239,7 +575,7 @@ impl Lock {
         if self.use_async_commit {
             b.push(ASYNC_COMMIT_PREFIX);
             b.encode_var_u64(self.secondaries.len() as _).unwrap();
-            for k in &self.secondaries {
+            for k in self.secondaries.iter() {
                 b.encode_compact_bytes(k).unwrap();
             }
         }
@@ -276,7 +612,8 @@ impl Lock {
     fn pre_allocate_size(&self) -> usize {
         let mut size = 1 + MAX_VAR_I64_LEN + self.primary.len() + MAX_VAR_U64_LEN * 2;
         if let Some(v) = &self.short_value {
-            size += 2 + v.len();
+            // The long form spends one extra byte on its u16 length.
+            size += 1 + size_of::<u16>() + v.len();
         }
         if !self.for_update_ts.is_zero() {
             size += 1 + size_of::<u64>();
@@ -290,11 +627,8 @@ impl Lock {
         if self.use_async_commit {
             size += 1
                 + MAX_VAR_U64_LEN
-                + self
-                    .secondaries
-                    .iter()
-                    .map(|k| MAX_VAR_I64_LEN + k.len())
-                    .sum::<usize>();
+                + self.secondaries.total_len()
+                + MAX_VAR_I64_LEN * self.secondaries.len();
         }
         if !self.rollback_ts.is_empty() {
             size += 1 + MAX_VAR_U64_LEN + size_of::<u64>() * self.rollback_ts.len();
@@ -349,15 +683,19 @@ impl Lock {
         let mut txn_size: u64 = 0;
         let mut min_commit_ts = TimeStamp::zero();
         let mut use_async_commit = false;
-        let mut secondaries = Vec::new();
+        let mut secondaries = SecondaryKeys::default();
         let mut rollback_ts = Vec::new();
         let mut last_change_ts = TimeStamp::zero();
         let mut estimated_versions_to_last_change = 0;
         let mut txn_source = 0;
         let mut is_locked_with_conflict = false;
         let mut generation = 0;
+        let mut allow_long_short_value = false;
+        let mut seen_sections = 0u16;
         while !b.is_empty() {
-            match b.read_u8()? {
+            let prefix = b.read_u8()?;
+            mark_section_seen(&mut seen_sections, prefix)?;
+            match prefix {
                 SHORT_VALUE_PREFIX => {
                     let len = b.read_u8()?;
                     if b.len() < len as usize {
@@ -370,15 +708,29 @@ impl Lock {
                     short_value = Some(b[..len as usize].to_vec());
                     b = &b[len as usize..];
                 }
+                LONG_SHORT_VALUE_PREFIX => {
+                    let len = number::decode_u16(&mut b)? as usize;
+                    if b.len() < len {
+                        panic!(
+                            "content len [{}] shorter than short value len [{}]",
+                            b.len(),
+                            len,
+                        );
+                    }
+                    short_value = Some(b[..len].to_vec());
+                    b = &b[len..];
+                    allow_long_short_value = true;
+                }
                 FOR_UPDATE_TS_PREFIX => for_update_ts = number::decode_u64(&mut b)?.into(),
                 TXN_SIZE_PREFIX => txn_size = number::decode_u64(&mut b)?,
                 MIN_COMMIT_TS_PREFIX => min_commit_ts = number::decode_u64(&mut b)?.into(),
                 ASYNC_COMMIT_PREFIX => {
                     use_async_commit = true;
-                    let len = number::decode_var_u64(&mut b)? as _;
-                    secondaries = (0..len)
-                        .map(|_| bytes::decode_compact_bytes(&mut b).map_err(Into::into))
-                        .collect::<Result<_>>()?;
+                    let len = number::decode_var_u64(&mut b)? as usize;
+                    for _ in 0..len {
+                        let key_len = number::decode_var_i64(&mut b)? as usize;
+                        secondaries.push(advance(&mut b, key_len)?);
+                    }
                 }
                 ROLLBACK_TS_PREFIX => {
                     let len = number::decode_var_u64(&mut b)? as usize;
@@ -428,16 +780,173 @@ impl Lock {
         .with_rollback_ts(rollback_ts)
         .with_generation(generation);
         if use_async_commit {
-            lock = lock.use_async_commit(secondaries);
+            lock = lock.with_secondaries(secondaries);
+        }
+        if allow_long_short_value {
+            lock = lock.allow_long_short_value();
+        }
+        Ok(lock)
+    }
+
+    /// Like [`Lock::parse`], but draws the allocations behind the parsed
+    /// fields from `buffers` instead of the global allocator. The returned
+    /// lock is field-for-field equal to what `Lock::parse` produces.
+    ///
+    /// Batch paths such as resolve-lock decode tens of thousands of locks and
+    /// immediately drop most of them; parsing through a scratch
+    /// [`LockBuffers`] and [recycling](LockBuffers::recycle) the rejected
+    /// locks turns the per-lock `Vec` allocations into buffer reuse.
+    pub fn parse_in(mut b: &[u8], buffers: &mut LockBuffers) -> Result<Lock> {
+        if b.is_empty() {
+            return Err(Error::from(ErrorInner::BadFormatLock));
+        }
+        let lock_type = LockType::from_u8(b.read_u8()?).ok_or(ErrorInner::BadFormatLock)?;
+        let primary_len = number::decode_var_i64(&mut b)? as usize;
+        let primary = buffers.fill_buf(advance(&mut b, primary_len)?);
+        let ts = number::decode_var_u64(&mut b)?.into();
+        let ttl = if b.is_empty() {
+            0
+        } else {
+            number::decode_var_u64(&mut b)?
+        };
+
+        if b.is_empty() {
+            return Ok(Lock::new(
+                lock_type,
+                primary,
+                ts,
+                ttl,
+                None,
+                TimeStamp::zero(),
+                0,
+                TimeStamp::zero(),
+                false,
+            ));
+        }
+
+        let mut short_value = None;
+        let mut for_update_ts = TimeStamp::zero();
+        let mut txn_size: u64 = 0;
+        let mut min_commit_ts = TimeStamp::zero();
+        let mut use_async_commit = false;
+        let mut secondaries = SecondaryKeys::default();
+        let mut rollback_ts = Vec::new();
+        let mut last_change_ts = TimeStamp::zero();
+        let mut estimated_versions_to_last_change = 0;
+        let mut txn_source = 0;
+        let mut is_locked_with_conflict = false;
+        let mut generation = 0;
+        let mut allow_long_short_value = false;
+        let mut seen_sections = 0u16;
+        while !b.is_empty() {
+            let prefix = b.read_u8()?;
+            mark_section_seen(&mut seen_sections, prefix)?;
+            match prefix {
+                SHORT_VALUE_PREFIX => {
+                    let len = b.read_u8()?;
+                    if b.len() < len as usize {
+                        panic!(
+                            "content len [{}] shorter than short value len [{}]",
+                            b.len(),
+                            len,
+                        );
+                    }
+                    short_value = Some(buffers.fill_buf(&b[..len as usize]));
+                    b = &b[len as usize..];
+                }
+                LONG_SHORT_VALUE_PREFIX => {
+                    let len = number::decode_u16(&mut b)? as usize;
+                    if b.len() < len {
+                        panic!(
+                            "content len [{}] shorter than short value len [{}]",
+                            b.len(),
+                            len,
+                        );
+                    }
+                    short_value = Some(buffers.fill_buf(&b[..len]));
+                    b = &b[len..];
+                    allow_long_short_value = true;
+                }
+                FOR_UPDATE_TS_PREFIX => for_update_ts = number::decode_u64(&mut b)?.into(),
+                TXN_SIZE_PREFIX => txn_size = number::decode_u64(&mut b)?,
+                MIN_COMMIT_TS_PREFIX => min_commit_ts = number::decode_u64(&mut b)?.into(),
+                ASYNC_COMMIT_PREFIX => {
+                    use_async_commit = true;
+                    let len = number::decode_var_u64(&mut b)? as usize;
+                    let mut keys = buffers.take_secondaries();
+                    for _ in 0..len {
+                        let key_len = number::decode_var_i64(&mut b)? as usize;
+                        keys.push(advance(&mut b, key_len)?);
+                    }
+                    secondaries = keys;
+                }
+                ROLLBACK_TS_PREFIX => {
+                    let len = number::decode_var_u64(&mut b)? as usize;
+                    rollback_ts = buffers.take_ts_list();
+                    // Reserve one more place to avoid reallocation when pushing a new
+                    // timestamp to it, keeping parity with `Lock::parse`.
+                    rollback_ts.reserve(len + 1);
+                    for _ in 0..len {
+                        rollback_ts.push(number::decode_u64(&mut b)?.into());
+                    }
+                }
+                LAST_CHANGE_PREFIX => {
+                    last_change_ts = number::decode_u64(&mut b)?.into();
+                    estimated_versions_to_last_change = number::decode_var_u64(&mut b)?;
+                }
+                TXN_SOURCE_PREFIX => {
+                    txn_source = number::decode_var_u64(&mut b)?;
+                }
+                PESSIMISTIC_LOCK_WITH_CONFLICT_PREFIX => {
+                    is_locked_with_conflict = true;
+                }
+                GENERATION_PREFIX => {
+                    generation = number::decode_u64(&mut b)?;
+                }
+                _ => {
+                    // To support forward compatibility, all fields should be serialized in order
+                    // and stop parsing if meets an unknown byte.
+                    break;
+                }
+            }
+        }
+        let mut lock = Lock::new(
+            lock_type,
+            primary,
+            ts,
+            ttl,
+            short_value,
+            for_update_ts,
+            txn_size,
+            min_commit_ts,
+            is_locked_with_conflict,
+        )
+        .set_last_change(LastChange::from_parts(
+            last_change_ts,
+            estimated_versions_to_last_change,
+        ))
+        .set_txn_source(txn_source)
+        .with_rollback_ts(rollback_ts)
+        .with_generation(generation);
+        if use_async_commit {
+            lock = lock.with_secondaries(secondaries);
+        }
+        if allow_long_short_value {
+            lock = lock.allow_long_short_value();
         }
         Ok(lock)
     }
 
     pub fn into_lock_info(self, raw_key: Vec<u8>) -> LockInfo {
         let mut info = LockInfo::default();
-        info.set_primary_lock(self.primary);
+        if redact_lock_info_enabled() {
+            info.set_primary_lock(redacted_key(&self.primary));
+            info.set_key(redacted_key(&raw_key));
+        } else {
+            info.set_primary_lock(self.primary);
+            info.set_key(raw_key);
+        }
         info.set_lock_version(self.ts.into_inner());
-        info.set_key(raw_key);
         info.set_lock_ttl(self.ttl);
         info.set_txn_size(self.txn_size);
         let lock_type = match self.lock_type {
@@ -450,7 +959,7 @@ impl Lock {
         info.set_lock_for_update_ts(self.for_update_ts.into_inner());
         info.set_use_async_commit(self.use_async_commit);
         info.set_min_commit_ts(self.min_commit_ts.into_inner());
-        info.set_secondaries(self.secondaries.into());
+        info.set_secondaries(self.secondaries.to_vec().into());
         // The client does not care about last_change_ts, versions_to_last_version and
         // txn_source.
         info
@@ -465,8 +974,10 @@ impl Lock {
         bypass_locks: &TsSet,
         is_replica_read: bool,
     ) -> Result<()> {
-        if lock.ts > ts || lock.lock_type == LockType::Lock || lock.is_pessimistic_lock() {
-            // Ignore lock when lock.ts > ts or lock's type is Lock or Pessimistic
+        // Ignore lock when lock.ts > ts or lock's type is Lock or Pessimistic. A
+        // zero `resolved_ts` grants no extra guarantee, so this is the plain
+        // ordering rule.
+        if !lock.blocks_stale_read(ts, TimeStamp::zero()) {
             return Ok(());
         }
 
@@ -520,13 +1031,22 @@ impl Lock {
         }
 
         // Return conflict error.
+        let (key, primary) = if redact_lock_info_enabled() {
+            (redacted_key(&key.to_raw()?), redacted_key(&lock.primary))
+        } else {
+            (key.to_raw()?, lock.primary.to_vec())
+        };
         Err(Error::from(ErrorInner::WriteConflict {
             start_ts: ts,
             conflict_start_ts: lock.ts,
-            conflict_commit_ts: Default::default(),
-            key: key.to_raw()?,
-            primary: lock.primary.to_vec(),
+            // The lock is not committed yet, but its `min_commit_ts` is the
+            // lower bound of its eventual commit ts, so report that as the
+            // tentative conflict commit ts when it's known.
+            conflict_commit_ts: lock.min_commit_ts,
+            key,
+            primary,
             reason: WriteConflictReason::RcCheckTs,
+            lock_type: Some(lock.lock_type),
         }))
     }
 
@@ -562,10 +1082,46 @@ impl Lock {
         }
     }
 
+    /// Returns whether this lock prevents a stale read at `read_ts` from
+    /// being served, given that the region's resolved ts has advanced to
+    /// `resolved_ts`. See the module-level `blocks_stale_read` for the rule
+    /// and its reasoning.
+    pub fn blocks_stale_read(&self, read_ts: TimeStamp, resolved_ts: TimeStamp) -> bool {
+        blocks_stale_read(self.lock_type, self.ts, read_ts, resolved_ts)
+    }
+
     pub fn is_pessimistic_txn(&self) -> bool {
         !self.for_update_ts.is_zero()
     }
 
+    /// A stable token identifying the logical request which produced this
+    /// lock. Retries of the same request after a network timeout carry the
+    /// same start_ts, for_update_ts, generation, type and primary, so the
+    /// scheduler can compare fingerprints instead of those fields one by one
+    /// to recognize a duplicate. The token is derived on demand and never
+    /// persisted. See also [`Lock::is_same_request`].
+    pub fn request_fingerprint(&self) -> u64 {
+        request_fingerprint(
+            self.lock_type,
+            &self.primary,
+            self.ts,
+            self.for_update_ts,
+            self.generation,
+        )
+    }
+
+    /// Returns whether `other` stems from the same logical request as this
+    /// lock, i.e. whether the two only differ in fields a retry is allowed to
+    /// change (ttl, min_commit_ts, the cached short value, ...). Unlike
+    /// comparing [`Lock::request_fingerprint`]s, this cannot collide.
+    pub fn is_same_request(&self, other: &Lock) -> bool {
+        self.lock_type == other.lock_type
+            && self.primary == other.primary
+            && self.ts == other.ts
+            && self.for_update_ts == other.for_update_ts
+            && self.generation == other.generation
+    }
+
     pub fn is_pessimistic_lock(&self) -> bool {
         self.lock_type == LockType::Pessimistic
     }
@@ -573,6 +1129,406 @@ impl Lock {
     pub fn is_pessimistic_lock_with_conflict(&self) -> bool {
         self.is_pessimistic_lock() && self.is_locked_with_conflict
     }
+
+    /// Returns whether this lock's transaction might still produce a commit
+    /// record with `commit_ts` at or below `ts`. CDC and resolved-ts use it
+    /// to decide whether `ts` is a safe resolved-ts candidate in the
+    /// presence of this lock: when it returns `false` for every lock, no
+    /// commit at or below `ts` can appear later.
+    ///
+    /// The rule, shared by 2PC and async commit:
+    ///
+    /// - `Lock` and `Pessimistic` locks never hide a committed version (a
+    ///   `Lock`-type commit record carries no data), so they cannot
+    ///   invalidate a candidate and the answer is `false`, mirroring
+    ///   [`min_blocking_start_ts`].
+    /// - The commit ts is strictly greater than the start ts, so a
+    ///   candidate at or below `self.ts` is always safe.
+    /// - Committing below `min_commit_ts` is rejected (`CommitTsExpired`),
+    ///   so a candidate below it is safe. Equality is not: an async-commit
+    ///   transaction computes its commit ts as the maximum of the
+    ///   `min_commit_ts` of its keys and may commit at exactly this value
+    ///   (see the async-commit design in tikv/sig-transaction,
+    ///   `doc/design/async-commit`). For an ordinary 2PC lock
+    ///   `min_commit_ts` merely records how far readers pushed it (possibly
+    ///   zero), and the PD-allocated commit ts obeys the same lower bounds.
+    ///
+    /// A 1PC transaction never writes a lock at all — one that falls back
+    /// to 2PC locks with `use_async_commit` unset — so there is no 1PC flag
+    /// to consult here; its commits are fenced by the locks of the fallback
+    /// path like any other transaction's.
+    pub fn may_commit_before(&self, ts: TimeStamp) -> bool {
+        if matches!(self.lock_type, LockType::Lock | LockType::Pessimistic) {
+            return false;
+        }
+        self.ts < ts && self.min_commit_ts <= ts
+    }
+}
+
+/// Returns whether a lock prevents a read at `read_ts` from being served,
+/// given that the region's resolved ts has advanced to `resolved_ts`.
+///
+/// A reader may ignore the lock when any of the following holds:
+///
+/// - The lock is a `Lock` or `Pessimistic` lock. These never hide a committed
+///   version, so they are invisible to readers.
+/// - `lock.ts > read_ts`. The lock's transaction can only commit at some
+///   `commit_ts > lock.ts`, so its write cannot be visible to the reader.
+/// - `read_ts <= resolved_ts`. Advancing the resolved ts pushes the
+///   `min_commit_ts` of every transaction still holding a lock beyond
+///   `resolved_ts`, so such a transaction commits (if it ever does) above
+///   `resolved_ts` and the data at or below `resolved_ts` is already
+///   consistent. This is what allows a stale read to be served without
+///   checking locks at all once `read_ts` is covered by the safe ts.
+///
+/// Otherwise the reader gets no guarantee from `resolved_ts` (it reads above
+/// it) and the lock may commit at or below `read_ts`, so it blocks the read.
+fn blocks_stale_read(
+    lock_type: LockType,
+    lock_ts: TimeStamp,
+    read_ts: TimeStamp,
+    resolved_ts: TimeStamp,
+) -> bool {
+    if matches!(lock_type, LockType::Lock | LockType::Pessimistic) {
+        return false;
+    }
+    if lock_ts > read_ts {
+        return false;
+    }
+    read_ts > resolved_ts
+}
+
+/// Computes the barrier a set of locks imposes on advancing the resolved ts:
+/// the smallest timestamp at or above which one of the locks may still
+/// commit. The resolved ts must stay strictly below it; an empty or fully
+/// ignorable set imposes no barrier and yields `None`.
+///
+/// A lock is ignored when it is a `Lock`-type or pessimistic lock (including
+/// every in-memory pessimistic lock), since those never hide a committed
+/// version. For the remaining locks the transaction commits, if it ever
+/// does, above both its start ts and its pushed `min_commit_ts`, so each
+/// contributes the larger of the two and the barrier is the minimum over
+/// those.
+pub fn min_blocking_start_ts<'a>(
+    locks: impl Iterator<Item = TxnLockRef<'a>>,
+) -> Option<TimeStamp> {
+    locks
+        .filter_map(|lock| match lock {
+            TxnLockRef::InMemory(_) => None,
+            TxnLockRef::Persisted(lock)
+                if matches!(lock.lock_type, LockType::Lock | LockType::Pessimistic) =>
+            {
+                None
+            }
+            TxnLockRef::Persisted(lock) => Some(std::cmp::max(lock.ts, lock.min_commit_ts)),
+        })
+        .min()
+}
+
+/// Computes the request fingerprint over the identifying fields of a lock.
+/// The fixed-width fields go in front of the variable-length primary, so two
+/// distinct field combinations never serialize to the same bytes.
+fn request_fingerprint(
+    lock_type: LockType,
+    primary: &[u8],
+    start_ts: TimeStamp,
+    for_update_ts: TimeStamp,
+    generation: u64,
+) -> u64 {
+    let mut buf = Vec::with_capacity(1 + 3 * size_of::<u64>() + primary.len());
+    buf.push(lock_type.to_u8());
+    buf.extend_from_slice(&start_ts.into_inner().to_be_bytes());
+    buf.extend_from_slice(&for_update_ts.into_inner().to_be_bytes());
+    buf.extend_from_slice(&generation.to_be_bytes());
+    buf.extend_from_slice(primary);
+    farmhash::fingerprint64(&buf)
+}
+
+/// Marks the optional section introduced by `prefix` as seen in the `seen`
+/// bitmask, failing with `BadFormatLock` when the section already appeared.
+/// Encoders emit each section at most once, so a duplicate means the buffer
+/// is corrupt; without the check the later section would silently win. The
+/// two short-value spellings share a bit since they fill the same field.
+/// Unknown prefixes are left alone: the parsers stop at them for forward
+/// compatibility.
+fn mark_section_seen(seen: &mut u16, prefix: u8) -> Result<()> {
+    let bit = match prefix {
+        SHORT_VALUE_PREFIX | LONG_SHORT_VALUE_PREFIX => 1,
+        FOR_UPDATE_TS_PREFIX => 1 << 1,
+        TXN_SIZE_PREFIX => 1 << 2,
+        MIN_COMMIT_TS_PREFIX => 1 << 3,
+        ASYNC_COMMIT_PREFIX => 1 << 4,
+        ROLLBACK_TS_PREFIX => 1 << 5,
+        LAST_CHANGE_PREFIX => 1 << 6,
+        TXN_SOURCE_PREFIX => 1 << 7,
+        PESSIMISTIC_LOCK_WITH_CONFLICT_PREFIX => 1 << 8,
+        GENERATION_PREFIX => 1 << 9,
+        _ => return Ok(()),
+    };
+    if *seen & bit != 0 {
+        return Err(Error::from(ErrorInner::BadFormatLock));
+    }
+    *seen |= bit;
+    Ok(())
+}
+
+/// Skips over the next `n` bytes of `b` and returns them.
+fn advance<'a>(b: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if b.len() < n {
+        return Err(Error::from(ErrorInner::BadFormatLock));
+    }
+    let (skipped, rest) = b.split_at(n);
+    *b = rest;
+    Ok(skipped)
+}
+
+/// Decodes only the lock type flag from an encoded lock, without parsing or
+/// allocating the rest of the record.
+///
+/// Useful when a caller only needs to know e.g. whether an existing lock is
+/// pessimistic before deciding to overwrite it; agrees with what
+/// [`Lock::parse`] would report in `lock_type`.
+pub fn peek_lock_type(mut b: &[u8]) -> Result<LockType> {
+    if b.is_empty() {
+        return Err(Error::from(ErrorInner::BadFormatLock));
+    }
+    let lock_type = LockType::from_u8(b.read_u8()?).ok_or(ErrorInner::BadFormatLock)?;
+    Ok(lock_type)
+}
+
+/// Decodes only the start ts from an encoded lock, skipping over the lock
+/// type flag and the primary key bytes without allocating.
+///
+/// Agrees with what [`Lock::parse`] would report in `ts`.
+pub fn peek_start_ts(mut b: &[u8]) -> Result<TimeStamp> {
+    if b.is_empty() {
+        return Err(Error::from(ErrorInner::BadFormatLock));
+    }
+    LockType::from_u8(b.read_u8()?).ok_or(ErrorInner::BadFormatLock)?;
+    let primary_len = number::decode_var_i64(&mut b)? as usize;
+    advance(&mut b, primary_len)?;
+    Ok(number::decode_var_u64(&mut b)?.into())
+}
+
+/// Reusable scratch allocations for [`Lock::parse_in`].
+///
+/// The pools are unbounded and hold on to the largest buffers they have seen,
+/// so a `LockBuffers` is meant to be scoped to one batch (e.g. one
+/// resolve-lock scan) rather than kept around indefinitely.
+#[derive(Default)]
+pub struct LockBuffers {
+    /// Byte buffers reused for the primary, the short value and the flat
+    /// `secondaries` buffer.
+    bufs: Vec<Vec<u8>>,
+    /// Offset vectors reused for `secondaries`.
+    offset_lists: Vec<Vec<usize>>,
+    /// Vectors reused for `rollback_ts`.
+    ts_lists: Vec<Vec<TimeStamp>>,
+}
+
+impl LockBuffers {
+    /// Takes a byte buffer from the pool and fills it with `src`.
+    fn fill_buf(&mut self, src: &[u8]) -> Vec<u8> {
+        let mut buf = self.bufs.pop().unwrap_or_default();
+        buf.clear();
+        buf.extend_from_slice(src);
+        buf
+    }
+
+    fn take_secondaries(&mut self) -> SecondaryKeys {
+        let mut buf = self.bufs.pop().unwrap_or_default();
+        buf.clear();
+        let mut offsets = self.offset_lists.pop().unwrap_or_default();
+        offsets.clear();
+        SecondaryKeys { buf, offsets }
+    }
+
+    fn take_ts_list(&mut self) -> Vec<TimeStamp> {
+        self.ts_lists.pop().unwrap_or_default()
+    }
+
+    /// Returns the allocations of a lock produced by [`Lock::parse_in`] to
+    /// the pool so that the next parse can reuse them.
+    pub fn recycle(&mut self, lock: Lock) {
+        self.bufs.push(lock.primary);
+        if let Some(v) = lock.short_value {
+            self.bufs.push(v);
+        }
+        let SecondaryKeys { buf, offsets } = lock.secondaries;
+        self.bufs.push(buf);
+        self.offset_lists.push(offsets);
+        let mut rollback_ts = lock.rollback_ts;
+        rollback_ts.clear();
+        self.ts_lists.push(rollback_ts);
+    }
+}
+
+/// A zero-copy view of the fields of an encoded lock that are needed for
+/// inspection, e.g. the lock report in tikv-ctl. Unlike [`Lock::parse`], no
+/// field is copied out of the input buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LockRef<'a> {
+    pub lock_type: LockType,
+    pub primary: &'a [u8],
+    pub ts: TimeStamp,
+    pub ttl: u64,
+    pub use_async_commit: bool,
+    pub txn_source: u64,
+}
+
+impl<'a> LockRef<'a> {
+    /// Parses the inspection fields from an encoded lock, skipping over the
+    /// sections it does not report without allocating.
+    pub fn parse(mut b: &'a [u8]) -> Result<LockRef<'a>> {
+        if b.is_empty() {
+            return Err(Error::from(ErrorInner::BadFormatLock));
+        }
+        let lock_type = LockType::from_u8(b.read_u8()?).ok_or(ErrorInner::BadFormatLock)?;
+        let primary_len = number::decode_var_i64(&mut b)? as usize;
+        let primary = advance(&mut b, primary_len)?;
+        let ts = number::decode_var_u64(&mut b)?.into();
+        let ttl = if b.is_empty() {
+            0
+        } else {
+            number::decode_var_u64(&mut b)?
+        };
+
+        let mut use_async_commit = false;
+        let mut txn_source = 0;
+        let mut seen_sections = 0u16;
+        while !b.is_empty() {
+            let prefix = b.read_u8()?;
+            mark_section_seen(&mut seen_sections, prefix)?;
+            match prefix {
+                SHORT_VALUE_PREFIX => {
+                    let len = b.read_u8()? as usize;
+                    advance(&mut b, len)?;
+                }
+                LONG_SHORT_VALUE_PREFIX => {
+                    let len = number::decode_u16(&mut b)? as usize;
+                    advance(&mut b, len)?;
+                }
+                FOR_UPDATE_TS_PREFIX | TXN_SIZE_PREFIX | MIN_COMMIT_TS_PREFIX
+                | GENERATION_PREFIX => {
+                    number::decode_u64(&mut b)?;
+                }
+                ASYNC_COMMIT_PREFIX => {
+                    use_async_commit = true;
+                    let len = number::decode_var_u64(&mut b)? as usize;
+                    for _ in 0..len {
+                        let key_len = number::decode_var_i64(&mut b)? as usize;
+                        advance(&mut b, key_len)?;
+                    }
+                }
+                ROLLBACK_TS_PREFIX => {
+                    let len = number::decode_var_u64(&mut b)? as usize;
+                    advance(&mut b, len.saturating_mul(size_of::<u64>()))?;
+                }
+                LAST_CHANGE_PREFIX => {
+                    number::decode_u64(&mut b)?;
+                    number::decode_var_u64(&mut b)?;
+                }
+                TXN_SOURCE_PREFIX => txn_source = number::decode_var_u64(&mut b)?,
+                PESSIMISTIC_LOCK_WITH_CONFLICT_PREFIX => {}
+                _ => {
+                    // Like `Lock::parse`, stop at an unknown byte for forward
+                    // compatibility.
+                    break;
+                }
+            }
+        }
+        Ok(LockRef {
+            lock_type,
+            primary,
+            ts,
+            ttl,
+            use_async_commit,
+            txn_source,
+        })
+    }
+}
+
+/// Upper bounds (in milliseconds) of the age buckets in [`LockSummary`].
+/// Ages beyond the last bound fall into one extra bucket.
+pub const LOCK_AGE_BUCKETS_MS: [u64; 4] = [1_000, 10_000, 60_000, 600_000];
+
+/// How many of the oldest lock primaries a [`LockSummary`] keeps.
+const SUMMARY_TOP_N: usize = 5;
+
+/// Statistics over a set of locks, produced by [`summarize`].
+#[derive(Debug, Default, PartialEq)]
+pub struct LockSummary {
+    /// Locks that parsed successfully.
+    pub total: usize,
+    /// Lock values that failed to parse.
+    pub malformed: usize,
+    pub puts: usize,
+    pub deletes: usize,
+    pub locks: usize,
+    pub pessimistic: usize,
+    pub async_commit: usize,
+    /// Locks whose TTL has expired at the reference timestamp.
+    pub expired: usize,
+    /// Lock counts keyed by their `txn_source`.
+    pub txn_sources: HashMap<u64, usize>,
+    /// Lock counts bucketed by age; see [`LOCK_AGE_BUCKETS_MS`].
+    pub age_buckets: [usize; LOCK_AGE_BUCKETS_MS.len() + 1],
+    /// `(start_ts, primary)` of the oldest locks, ascending by `start_ts`.
+    /// The primary is formatted through `log_wrappers` so it respects the
+    /// redaction settings.
+    pub oldest: Vec<(TimeStamp, String)>,
+}
+
+/// Summarizes an iterator of `(key, encoded lock)` pairs by lock type, age,
+/// TTL expiry, `txn_source` and async-commit usage, without fully
+/// materializing each lock. `now` is the reference timestamp (usually a
+/// fresh TSO timestamp) ages and TTL expiry are derived from.
+pub fn summarize<'a>(
+    iter: impl Iterator<Item = (&'a [u8], &'a [u8])>,
+    now: TimeStamp,
+) -> LockSummary {
+    let mut summary = LockSummary::default();
+    for (_key, value) in iter {
+        let lock = match LockRef::parse(value) {
+            Ok(lock) => lock,
+            Err(_) => {
+                summary.malformed += 1;
+                continue;
+            }
+        };
+        summary.total += 1;
+        match lock.lock_type {
+            LockType::Put => summary.puts += 1,
+            LockType::Delete => summary.deletes += 1,
+            LockType::Lock => summary.locks += 1,
+            LockType::Pessimistic => summary.pessimistic += 1,
+        }
+        if lock.use_async_commit {
+            summary.async_commit += 1;
+        }
+        *summary.txn_sources.entry(lock.txn_source).or_default() += 1;
+
+        let age_ms = now.physical().saturating_sub(lock.ts.physical());
+        let bucket = LOCK_AGE_BUCKETS_MS
+            .iter()
+            .position(|&bound| age_ms < bound)
+            .unwrap_or(LOCK_AGE_BUCKETS_MS.len());
+        summary.age_buckets[bucket] += 1;
+
+        if lock.ttl > 0 && lock.ts.physical().saturating_add(lock.ttl) <= now.physical() {
+            summary.expired += 1;
+        }
+
+        if summary.oldest.len() < SUMMARY_TOP_N
+            || summary.oldest.last().is_some_and(|(ts, _)| lock.ts < *ts)
+        {
+            let primary = format!("{:?}", log_wrappers::Value::key(lock.primary));
+            summary.oldest.push((lock.ts, primary));
+            summary.oldest.sort_by_key(|(ts, _)| *ts);
+            summary.oldest.truncate(SUMMARY_TOP_N);
+        }
+    }
+    summary
 }
 
 /// A specialized lock only for pessimistic lock. This saves memory for cases
@@ -625,6 +1581,19 @@ impl PessimisticLock {
     pub fn memory_size(&self) -> usize {
         self.primary.len() + size_of::<Self>()
     }
+
+    /// Same as [`Lock::request_fingerprint`], without materializing the
+    /// [`Lock`] first. An in-memory pessimistic lock has no generation, so it
+    /// fingerprints like the generation-0 lock `to_lock` produces.
+    pub fn request_fingerprint(&self) -> u64 {
+        request_fingerprint(
+            LockType::Pessimistic,
+            &self.primary,
+            self.start_ts,
+            self.for_update_ts,
+            0,
+        )
+    }
 }
 
 impl std::fmt::Debug for PessimisticLock {
@@ -641,6 +1610,21 @@ impl std::fmt::Debug for PessimisticLock {
     }
 }
 
+#[cfg(feature = "lock-dump")]
+impl Serialize for PessimisticLock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("PessimisticLock", 7)?;
+        s.serialize_field("primary_key", &dump_hex(&self.primary))?;
+        s.serialize_field("start_ts", &self.start_ts.into_inner())?;
+        s.serialize_field("ttl", &self.ttl)?;
+        s.serialize_field("for_update_ts", &self.for_update_ts.into_inner())?;
+        s.serialize_field("min_commit_ts", &self.min_commit_ts.into_inner())?;
+        s.serialize_field("last_change", &self.last_change)?;
+        s.serialize_field("is_locked_with_conflict", &self.is_locked_with_conflict)?;
+        s.end()
+    }
+}
+
 /// TxnLock is a wrapper for in-memory pessimistic locks and storage locks.
 #[derive(PartialEq, Clone, Debug)]
 pub enum TxnLockRef<'a> {
@@ -676,6 +1660,30 @@ impl<'a> TxnLockRef<'a> {
             TxnLockRef::Persisted(lock) => lock.lock_type,
         }
     }
+
+    /// Returns whether this lock prevents a stale read at `read_ts` from
+    /// being served, given that the region's resolved ts has advanced to
+    /// `resolved_ts`. See the module-level `blocks_stale_read` for the rule
+    /// and its reasoning.
+    pub fn blocks_stale_read(&self, read_ts: TimeStamp, resolved_ts: TimeStamp) -> bool {
+        blocks_stale_read(
+            self.get_lock_type(),
+            self.get_start_ts(),
+            read_ts,
+            resolved_ts,
+        )
+    }
+
+    /// Returns whether this lock's transaction might still produce a commit
+    /// record with `commit_ts` at or below `ts`. An in-memory pessimistic
+    /// lock never commits data, so only persisted locks can say yes; see
+    /// [`Lock::may_commit_before`] for the rule.
+    pub fn may_commit_before(&self, ts: TimeStamp) -> bool {
+        match self {
+            TxnLockRef::InMemory(_) => false,
+            TxnLockRef::Persisted(lock) => lock.may_commit_before(ts),
+        }
+    }
 }
 
 impl<'a> From<&'a PessimisticLock> for TxnLockRef<'a> {
@@ -740,27 +1748,13 @@ mod tests {
     fn test_lock() {
         // Test `Lock::to_bytes()` and `Lock::parse()` works as a pair.
         let mut locks = vec![
-            Lock::new(
-                LockType::Put,
-                b"pk".to_vec(),
-                1.into(),
-                10,
-                None,
-                TimeStamp::zero(),
-                0,
-                TimeStamp::zero(),
-                false,
-            ),
-            Lock::new(
+            Lock::new_optimistic(LockType::Put, b"pk".to_vec(), 1.into(), 10, None),
+            Lock::new_optimistic(
                 LockType::Delete,
                 b"pk".to_vec(),
                 1.into(),
                 10,
                 Some(b"short_value".to_vec()),
-                TimeStamp::zero(),
-                0,
-                TimeStamp::zero(),
-                false,
             ),
             Lock::new(
                 LockType::Put,
@@ -947,16 +1941,12 @@ mod tests {
         // Test `Lock::parse()` handles incorrect input.
         Lock::parse(b"").unwrap_err();
 
-        let lock = Lock::new(
+        let lock = Lock::new_optimistic(
             LockType::Lock,
             b"pk".to_vec(),
             1.into(),
             10,
             Some(b"short_value".to_vec()),
-            TimeStamp::zero(),
-            0,
-            TimeStamp::zero(),
-            false,
         );
         let mut v = lock.to_bytes();
         Lock::parse(&v[..4]).unwrap_err();
@@ -966,6 +1956,439 @@ mod tests {
         assert_eq!(l, lock);
     }
 
+    #[test]
+    fn test_use_async_commit_flat() {
+        let keys = vec![
+            b"k1".to_vec(),
+            b"kkkkk2".to_vec(),
+            b"".to_vec(),
+            b"k3k3k3k3k3k3".to_vec(),
+        ];
+        let mut buf = Vec::new();
+        let mut offsets = Vec::new();
+        for k in &keys {
+            offsets.push(buf.len());
+            buf.extend_from_slice(k);
+        }
+        let new_lock = || {
+            Lock::new(
+                LockType::Put,
+                b"pk".to_vec(),
+                111.into(),
+                222,
+                None,
+                333.into(),
+                444,
+                555.into(),
+                false,
+            )
+        };
+        let nested = new_lock().use_async_commit(keys.clone());
+        let flat = new_lock().use_async_commit_flat(buf, offsets);
+        assert_eq!(flat, nested);
+        // The flat form must encode byte-for-byte like the nested one.
+        assert_eq!(flat.to_bytes(), nested.to_bytes());
+
+        let parsed = Lock::parse(&flat.to_bytes()).unwrap();
+        assert_eq!(parsed.secondaries, keys);
+        assert_eq!(parsed.secondaries.len(), keys.len());
+        assert!(!parsed.secondaries.is_empty());
+        for (i, k) in keys.iter().enumerate() {
+            assert_eq!(parsed.secondaries.get(i), k.as_slice());
+        }
+        let collected: Vec<_> = parsed.secondaries.iter().collect();
+        assert_eq!(collected, keys.iter().map(Vec::as_slice).collect::<Vec<_>>());
+        assert_eq!(parsed.secondaries.to_vec(), keys);
+    }
+
+    #[test]
+    fn test_long_short_value() {
+        // With ts = 1 and ttl = 10 every varint before the short value section
+        // is a single byte, so the section's prefix sits at a fixed offset.
+        let new_lock = |value: Vec<u8>| {
+            Lock::new_optimistic(LockType::Put, b"pk".to_vec(), 1.into(), 10, Some(value))
+        };
+        const PREFIX_OFFSET: usize = 6;
+
+        // A value that fits the u8 length keeps the legacy encoding even when
+        // the lock opts in, so old nodes can parse what new nodes write. The
+        // flag is not persisted through the legacy form.
+        let lock = new_lock(b"short_value".to_vec()).allow_long_short_value();
+        let bytes = lock.to_bytes();
+        assert_eq!(bytes[PREFIX_OFFSET], SHORT_VALUE_PREFIX);
+        assert_eq!(Lock::parse(&bytes).unwrap(), new_lock(b"short_value".to_vec()));
+
+        // An oversized value with the opt-in takes the u16-length form, and
+        // all three parsers understand it.
+        let long_value = vec![b'x'; 300];
+        let lock = new_lock(long_value.clone())
+            .allow_long_short_value()
+            .set_txn_source(1);
+        let bytes = lock.to_bytes();
+        assert_eq!(bytes[PREFIX_OFFSET], LONG_SHORT_VALUE_PREFIX);
+        assert_eq!(
+            &bytes[PREFIX_OFFSET + 1..PREFIX_OFFSET + 3],
+            &300u16.to_be_bytes()
+        );
+        assert!(lock.pre_allocate_size() >= bytes.len());
+        let parsed = Lock::parse(&bytes).unwrap();
+        assert_eq!(parsed, lock);
+        let mut buffers = LockBuffers::default();
+        assert_eq!(Lock::parse_in(&bytes, &mut buffers).unwrap(), lock);
+        // `LockRef::parse` skips the section and still reads what follows it.
+        let lock_ref = LockRef::parse(&bytes).unwrap();
+        assert_eq!(lock_ref.primary, &b"pk"[..]);
+        assert_eq!(lock_ref.txn_source, 1);
+
+        // Parsing the long form sets the opt-in on the result, so re-encoding
+        // the parsed lock round-trips through the long form.
+        assert!(parsed.allow_long_short_value);
+        assert_eq!(Lock::parse(&parsed.to_bytes()).unwrap(), parsed);
+
+        // Without the opt-in an oversized value also stays on the legacy
+        // encoding; values this large are normally filtered out by
+        // `is_short_value` before a lock is built.
+        let bytes = new_lock(long_value).to_bytes();
+        assert_eq!(bytes[PREFIX_OFFSET], SHORT_VALUE_PREFIX);
+
+        // Parsers accept the long form for small values too, e.g. from a
+        // writer which always uses it.
+        let mut bytes = new_lock(vec![])
+            .to_bytes()
+            .split_at(PREFIX_OFFSET)
+            .0
+            .to_vec();
+        bytes.push(LONG_SHORT_VALUE_PREFIX);
+        bytes.extend_from_slice(&3u16.to_be_bytes());
+        bytes.extend_from_slice(b"abc");
+        let parsed = Lock::parse(&bytes).unwrap();
+        assert_eq!(parsed.short_value, Some(b"abc".to_vec()));
+        // Re-encoding such a lock falls back to the legacy form, which an old
+        // node can read during a rolling upgrade.
+        let bytes = parsed.to_bytes();
+        assert_eq!(bytes[PREFIX_OFFSET], SHORT_VALUE_PREFIX);
+        assert_eq!(
+            Lock::parse(&bytes).unwrap().short_value,
+            Some(b"abc".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_parse_in_matches_parse() {
+        // `Lock::parse_in` must produce exactly what `Lock::parse` does, for
+        // locks with and without every optional section, while reusing the
+        // recycled allocations across iterations.
+        let locks = vec![
+            Lock::new_optimistic(LockType::Put, b"pk".to_vec(), 1.into(), 10, None),
+            Lock::new(
+                LockType::Delete,
+                b"pk".to_vec(),
+                1.into(),
+                10,
+                Some(b"short_value".to_vec()),
+                10.into(),
+                16,
+                11.into(),
+                true,
+            )
+            .with_rollback_ts(vec![12.into(), 24.into()])
+            .set_last_change(LastChange::make_exist(4.into(), 2))
+            .set_txn_source(3)
+            .with_generation(10),
+            Lock::new(
+                LockType::Put,
+                b"primary".to_vec(),
+                111.into(),
+                222,
+                Some(b"short_value".to_vec()),
+                333.into(),
+                444,
+                555.into(),
+                false,
+            )
+            .use_async_commit(vec![
+                b"k1".to_vec(),
+                b"kkkkk2".to_vec(),
+                b"k3k3k3k3k3k3".to_vec(),
+            ]),
+            Lock::new(
+                LockType::Pessimistic,
+                b"pk".to_vec(),
+                1.into(),
+                10,
+                None,
+                6.into(),
+                0,
+                8.into(),
+                false,
+            )
+            .set_last_change(LastChange::NotExist),
+        ];
+        let mut buffers = LockBuffers::default();
+        // Two rounds: the first starts from empty pools, the second parses
+        // entirely out of recycled buffers.
+        for round in 0..2 {
+            for (i, lock) in locks.iter().enumerate() {
+                let bytes = lock.to_bytes();
+                let expected = Lock::parse(&bytes).unwrap();
+                let got = Lock::parse_in(&bytes, &mut buffers).unwrap();
+                assert_eq!(got, expected, "round {} #{}", round, i);
+                buffers.recycle(got);
+            }
+        }
+
+        // Malformed input must be rejected like `Lock::parse` rejects it.
+        Lock::parse_in(b"", &mut buffers).unwrap_err();
+        let truncated = &locks[0].to_bytes()[..4];
+        Lock::parse_in(truncated, &mut buffers).unwrap_err();
+        // Unknown bytes stop the parse without an error.
+        let mut v = locks[0].to_bytes();
+        v.extend(b"unknown");
+        assert_eq!(Lock::parse_in(&v, &mut buffers).unwrap(), locks[0]);
+    }
+
+    #[test]
+    fn test_parse_duplicated_sections() {
+        // One valid spelling of every optional section.
+        fn section(prefix: u8) -> Vec<u8> {
+            let mut b = vec![prefix];
+            match prefix {
+                SHORT_VALUE_PREFIX => {
+                    b.push(1);
+                    b.push(b'v');
+                }
+                LONG_SHORT_VALUE_PREFIX => {
+                    b.encode_u16(1).unwrap();
+                    b.push(b'v');
+                }
+                FOR_UPDATE_TS_PREFIX | TXN_SIZE_PREFIX | MIN_COMMIT_TS_PREFIX
+                | GENERATION_PREFIX => b.encode_u64(10).unwrap(),
+                ASYNC_COMMIT_PREFIX => {
+                    b.encode_var_u64(1).unwrap();
+                    b.encode_compact_bytes(b"sk").unwrap();
+                }
+                ROLLBACK_TS_PREFIX => {
+                    b.encode_var_u64(1).unwrap();
+                    b.encode_u64(10).unwrap();
+                }
+                LAST_CHANGE_PREFIX => {
+                    b.encode_u64(10).unwrap();
+                    b.encode_var_u64(1).unwrap();
+                }
+                TXN_SOURCE_PREFIX => b.encode_var_u64(1).unwrap(),
+                PESSIMISTIC_LOCK_WITH_CONFLICT_PREFIX => {}
+                _ => unreachable!(),
+            }
+            b
+        }
+
+        let base = Lock::new(
+            LockType::Put,
+            b"pk".to_vec(),
+            1.into(),
+            10,
+            None,
+            TimeStamp::zero(),
+            0,
+            TimeStamp::zero(),
+            false,
+        )
+        .to_bytes();
+        let prefixes = [
+            SHORT_VALUE_PREFIX,
+            LONG_SHORT_VALUE_PREFIX,
+            FOR_UPDATE_TS_PREFIX,
+            TXN_SIZE_PREFIX,
+            MIN_COMMIT_TS_PREFIX,
+            ASYNC_COMMIT_PREFIX,
+            ROLLBACK_TS_PREFIX,
+            LAST_CHANGE_PREFIX,
+            TXN_SOURCE_PREFIX,
+            PESSIMISTIC_LOCK_WITH_CONFLICT_PREFIX,
+            GENERATION_PREFIX,
+        ];
+        let mut buffers = LockBuffers::default();
+        for prefix in prefixes {
+            let mut once = base.clone();
+            once.extend_from_slice(&section(prefix));
+            Lock::parse(&once).unwrap();
+
+            // A duplicated section is rejected by every parser rather than
+            // letting the second occurrence silently win.
+            let mut twice = base.clone();
+            twice.extend_from_slice(&section(prefix));
+            twice.extend_from_slice(&section(prefix));
+            Lock::parse(&twice).unwrap_err();
+            Lock::parse_in(&twice, &mut buffers).unwrap_err();
+            LockRef::parse(&twice).unwrap_err();
+        }
+
+        // The two short-value spellings fill the same field, so mixing them
+        // is also a duplicate.
+        let mut mixed = base;
+        mixed.extend_from_slice(&section(SHORT_VALUE_PREFIX));
+        mixed.extend_from_slice(&section(LONG_SHORT_VALUE_PREFIX));
+        Lock::parse(&mixed).unwrap_err();
+    }
+
+    #[test]
+    fn test_peek() {
+        let locks = vec![
+            Lock::new_optimistic(LockType::Put, b"pk".to_vec(), 1.into(), 10, None),
+            Lock::new_optimistic(
+                LockType::Delete,
+                b"a_rather_long_primary_key".to_vec(),
+                (1 << 60).into(),
+                10,
+                Some(b"short_value".to_vec()),
+            ),
+            Lock::new_pessimistic(b"pk".to_vec(), 5.into(), 100, 8.into()),
+            Lock::new_async_commit(
+                LockType::Put,
+                b"".to_vec(),
+                111.into(),
+                222,
+                None,
+                555.into(),
+                vec![b"k1".to_vec(), b"k2".to_vec()],
+            ),
+        ];
+        for lock in &locks {
+            let bytes = lock.to_bytes();
+            assert_eq!(peek_lock_type(&bytes).unwrap(), lock.lock_type);
+            assert_eq!(peek_start_ts(&bytes).unwrap(), lock.ts);
+        }
+
+        // Corrupted prefixes are rejected without panicking.
+        peek_lock_type(b"").unwrap_err();
+        peek_start_ts(b"").unwrap_err();
+        // Unknown flag byte.
+        peek_lock_type(&[b'?']).unwrap_err();
+        peek_start_ts(&[b'?']).unwrap_err();
+        let bytes = locks[1].to_bytes();
+        // Truncated inside the primary length header or the key bytes.
+        for len in 1..6 {
+            peek_start_ts(&bytes[..len]).unwrap_err();
+        }
+        // Truncated before and inside the varint ts. The primary takes a
+        // one-byte length header plus 25 key bytes, and 1 << 60 needs a
+        // multi-byte varint.
+        peek_start_ts(&bytes[..27]).unwrap_err();
+        peek_start_ts(&bytes[..28]).unwrap_err();
+    }
+
+    #[test]
+    fn test_lock_ref_parse() {
+        // `LockRef::parse` must agree with `Lock::parse` on the fields it
+        // reports, for locks with and without optional sections.
+        let locks = vec![
+            Lock::new_optimistic(LockType::Put, b"pk".to_vec(), 1.into(), 10, None),
+            Lock::new(
+                LockType::Delete,
+                b"pk".to_vec(),
+                1.into(),
+                10,
+                Some(b"short_value".to_vec()),
+                10.into(),
+                16,
+                11.into(),
+                true,
+            )
+            .with_rollback_ts(vec![12.into(), 24.into()])
+            .set_last_change(LastChange::make_exist(4.into(), 2))
+            .set_txn_source(3)
+            .with_generation(10),
+            Lock::new(
+                LockType::Put,
+                b"primary".to_vec(),
+                111.into(),
+                222,
+                None,
+                333.into(),
+                444,
+                555.into(),
+                false,
+            )
+            .use_async_commit(vec![b"k1".to_vec(), b"kkkkk2".to_vec()]),
+        ];
+        for (i, lock) in locks.iter().enumerate() {
+            let bytes = lock.to_bytes();
+            let lock_ref = LockRef::parse(&bytes).unwrap();
+            assert_eq!(lock_ref.lock_type, lock.lock_type, "#{}", i);
+            assert_eq!(lock_ref.primary, lock.primary.as_slice(), "#{}", i);
+            assert_eq!(lock_ref.ts, lock.ts, "#{}", i);
+            assert_eq!(lock_ref.ttl, lock.ttl, "#{}", i);
+            assert_eq!(lock_ref.use_async_commit, lock.use_async_commit, "#{}", i);
+            assert_eq!(lock_ref.txn_source, lock.txn_source, "#{}", i);
+        }
+
+        LockRef::parse(b"").unwrap_err();
+        LockRef::parse(b"Zgarbage").unwrap_err();
+        let truncated = &locks[0].to_bytes()[..4];
+        LockRef::parse(truncated).unwrap_err();
+    }
+
+    #[test]
+    fn test_summarize() {
+        let now = TimeStamp::compose(100_000, 0);
+        // 99s old with ttl 1s: expired, lands in the [60s, 600s) age bucket.
+        let old_ts = TimeStamp::compose(1_000, 0);
+        // 500ms old: alive, lands in the [0, 1s) age bucket.
+        let fresh_ts = TimeStamp::compose(99_500, 0);
+
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = vec![
+            (
+                b"k1".to_vec(),
+                Lock::new_optimistic(LockType::Put, b"old_pk".to_vec(), old_ts, 1_000, None)
+                    .to_bytes(),
+            ),
+            (
+                b"k2".to_vec(),
+                Lock::new_pessimistic(b"fresh_pk".to_vec(), fresh_ts, 100_000, fresh_ts)
+                    .to_bytes(),
+            ),
+            (
+                b"k3".to_vec(),
+                Lock::new_async_commit(
+                    LockType::Put,
+                    b"fresh_pk".to_vec(),
+                    fresh_ts,
+                    100_000,
+                    None,
+                    TimeStamp::zero(),
+                    vec![b"k2".to_vec()],
+                )
+                .set_txn_source(2)
+                .to_bytes(),
+            ),
+            // Malformed values are counted separately.
+            (b"k4".to_vec(), b"".to_vec()),
+            (b"k5".to_vec(), b"Zgarbage".to_vec()),
+        ];
+
+        let summary = summarize(
+            entries.iter().map(|(k, v)| (k.as_slice(), v.as_slice())),
+            now,
+        );
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.malformed, 2);
+        assert_eq!(summary.puts, 2);
+        assert_eq!(summary.deletes, 0);
+        assert_eq!(summary.locks, 0);
+        assert_eq!(summary.pessimistic, 1);
+        assert_eq!(summary.async_commit, 1);
+        assert_eq!(summary.expired, 1);
+        assert_eq!(summary.txn_sources[&0], 2);
+        assert_eq!(summary.txn_sources[&2], 1);
+        assert_eq!(summary.age_buckets, [2, 0, 0, 1, 0]);
+        assert_eq!(summary.oldest.len(), 3);
+        assert_eq!(summary.oldest[0].0, old_ts);
+        assert_eq!(
+            summary.oldest[0].1,
+            format!("{:?}", log_wrappers::Value::key(b"old_pk"))
+        );
+    }
+
     #[test]
     fn test_check_ts_conflict() {
         let key = Key::from_raw(b"foo");
@@ -1155,15 +2578,388 @@ mod tests {
 
         // Report error even if read ts is less than the lock version.
         lock.lock_type = LockType::Put;
-        Lock::check_ts_conflict_rc_check_ts(Cow::Borrowed(&lock), &k1, 50.into(), &empty)
-            .unwrap_err();
-        Lock::check_ts_conflict_rc_check_ts(Cow::Borrowed(&lock), &k1, 110.into(), &empty)
+        lock.min_commit_ts = 101.into();
+        let assert_conflict = |err: Error, expected_lock_type| match *err.0 {
+            ErrorInner::WriteConflict {
+                conflict_start_ts,
+                conflict_commit_ts,
+                reason,
+                lock_type,
+                ..
+            } => {
+                assert_eq!(conflict_start_ts, 100.into());
+                // The lock's min_commit_ts is reported as the tentative
+                // commit ts of the conflicting transaction.
+                assert_eq!(conflict_commit_ts, 101.into());
+                assert_eq!(reason, WriteConflictReason::RcCheckTs);
+                assert_eq!(lock_type, Some(expected_lock_type));
+            }
+            e => panic!("unexpected error: {:?}", e),
+        };
+        let err = Lock::check_ts_conflict_rc_check_ts(Cow::Borrowed(&lock), &k1, 50.into(), &empty)
             .unwrap_err();
+        assert_conflict(err, LockType::Put);
+        let err =
+            Lock::check_ts_conflict_rc_check_ts(Cow::Borrowed(&lock), &k1, 110.into(), &empty)
+                .unwrap_err();
+        assert_conflict(err, LockType::Put);
 
         // Report error if for other lock types.
         lock.lock_type = LockType::Delete;
-        Lock::check_ts_conflict_rc_check_ts(Cow::Borrowed(&lock), &k1, 50.into(), &empty)
+        let err = Lock::check_ts_conflict_rc_check_ts(Cow::Borrowed(&lock), &k1, 50.into(), &empty)
             .unwrap_err();
+        assert_conflict(err, LockType::Delete);
+    }
+
+    #[test]
+    fn test_min_blocking_start_ts() {
+        fn lock(tp: LockType, ts: u64, min_commit_ts: u64) -> Lock {
+            Lock::new(
+                tp,
+                b"pk".to_vec(),
+                ts.into(),
+                3,
+                None,
+                TimeStamp::zero(),
+                0,
+                min_commit_ts.into(),
+                false,
+            )
+        }
+
+        // An empty set imposes no barrier.
+        assert_eq!(min_blocking_start_ts(std::iter::empty()), None);
+
+        // Lock-type and pessimistic locks are ignored, persisted or
+        // in-memory alike.
+        let l_lock = lock(LockType::Lock, 10, 0);
+        let l_pessimistic = lock(LockType::Pessimistic, 20, 0);
+        let in_memory = PessimisticLock {
+            primary: b"pk".to_vec().into_boxed_slice(),
+            start_ts: 5.into(),
+            ttl: 3,
+            for_update_ts: 5.into(),
+            min_commit_ts: 6.into(),
+            last_change: LastChange::Unknown,
+            is_locked_with_conflict: false,
+        };
+        let locks = [
+            TxnLockRef::from(&l_lock),
+            TxnLockRef::from(&l_pessimistic),
+            TxnLockRef::from(&in_memory),
+        ];
+        assert_eq!(min_blocking_start_ts(locks.into_iter()), None);
+
+        // The smallest barrier among the blocking locks wins.
+        let l_put = lock(LockType::Put, 100, 0);
+        let l_delete = lock(LockType::Delete, 90, 0);
+        let locks = [
+            TxnLockRef::from(&l_lock),
+            TxnLockRef::from(&l_put),
+            TxnLockRef::from(&l_delete),
+            TxnLockRef::from(&in_memory),
+        ];
+        assert_eq!(min_blocking_start_ts(locks.into_iter()), Some(90.into()));
+
+        // A pushed min_commit_ts raises the barrier a lock contributes.
+        let l_pushed = lock(LockType::Put, 80, 120);
+        let locks = [TxnLockRef::from(&l_pushed), TxnLockRef::from(&l_put)];
+        assert_eq!(min_blocking_start_ts(locks.into_iter()), Some(100.into()));
+        assert_eq!(
+            min_blocking_start_ts([TxnLockRef::from(&l_pushed)].into_iter()),
+            Some(120.into())
+        );
+    }
+
+    #[test]
+    fn test_may_commit_before() {
+        fn lock(tp: LockType, async_commit: bool, ts: u64, min_commit_ts: u64) -> Lock {
+            let lock = Lock::new(
+                tp,
+                b"pk".to_vec(),
+                ts.into(),
+                3,
+                None,
+                TimeStamp::zero(),
+                0,
+                min_commit_ts.into(),
+                false,
+            );
+            if async_commit {
+                lock.use_async_commit(vec![b"sk".to_vec()])
+            } else {
+                lock
+            }
+        }
+
+        // All locks start at ts 100.
+        let cases = vec![
+            // (lock_type, use_async_commit, min_commit_ts, candidate, expected)
+            // Lock-type and pessimistic locks never commit data, no matter
+            // the candidate.
+            (LockType::Lock, false, 0, 200, false),
+            (LockType::Pessimistic, false, 0, 200, false),
+            (LockType::Pessimistic, false, 101, 200, false),
+            // The commit ts is strictly above the start ts.
+            (LockType::Put, false, 0, 99, false),
+            (LockType::Put, false, 0, 100, false),
+            (LockType::Put, false, 0, 101, true),
+            (LockType::Delete, false, 0, 101, true),
+            // A pushed min_commit_ts fences candidates below it...
+            (LockType::Put, false, 150, 149, false),
+            // ...but committing at exactly min_commit_ts is possible.
+            (LockType::Put, false, 150, 150, true),
+            (LockType::Put, false, 150, 151, true),
+            // Async commit obeys the same bounds; its commit ts may land
+            // exactly on min_commit_ts.
+            (LockType::Put, true, 150, 149, false),
+            (LockType::Put, true, 150, 150, true),
+            (LockType::Put, true, 150, 151, true),
+            (LockType::Delete, true, 101, 100, false),
+            (LockType::Delete, true, 101, 101, true),
+        ];
+        for (tp, async_commit, min_commit_ts, candidate, expected) in cases {
+            let l = lock(tp, async_commit, 100, min_commit_ts);
+            assert_eq!(
+                l.may_commit_before(candidate.into()),
+                expected,
+                "lock_type: {:?}, async_commit: {}, min_commit_ts: {}, candidate: {}",
+                tp,
+                async_commit,
+                min_commit_ts,
+                candidate
+            );
+            // The ref variant agrees with the owned one.
+            assert_eq!(
+                TxnLockRef::from(&l).may_commit_before(candidate.into()),
+                expected
+            );
+        }
+
+        // An in-memory pessimistic lock never commits data.
+        let in_memory = PessimisticLock {
+            primary: b"pk".to_vec().into_boxed_slice(),
+            start_ts: 5.into(),
+            ttl: 3,
+            for_update_ts: 5.into(),
+            min_commit_ts: 6.into(),
+            last_change: LastChange::Unknown,
+            is_locked_with_conflict: false,
+        };
+        assert!(!TxnLockRef::from(&in_memory).may_commit_before(TimeStamp::max()));
+    }
+
+    #[test]
+    fn test_redact_lock_info() {
+        let key = Key::from_raw(b"redacted_key");
+        let lock = Lock::new(
+            LockType::Put,
+            b"redacted_primary".to_vec(),
+            100.into(),
+            3,
+            None,
+            TimeStamp::zero(),
+            1,
+            TimeStamp::zero(),
+            false,
+        );
+        let empty = TsSet::default();
+
+        let key_is_locked = |redact: bool| {
+            set_redact_lock_info(redact);
+            let err = Lock::check_ts_conflict(
+                Cow::Borrowed(&lock),
+                &key,
+                110.into(),
+                &empty,
+                IsolationLevel::Si,
+            )
+            .unwrap_err();
+            set_redact_lock_info(false);
+            match *err.0 {
+                ErrorInner::KeyIsLocked(info) => info,
+                e => panic!("unexpected error: {:?}", e),
+            }
+        };
+        let write_conflict = |redact: bool| {
+            set_redact_lock_info(redact);
+            let err = Lock::check_ts_conflict(
+                Cow::Borrowed(&lock),
+                &key,
+                110.into(),
+                &empty,
+                IsolationLevel::RcCheckTs,
+            )
+            .unwrap_err();
+            set_redact_lock_info(false);
+            match *err.0 {
+                ErrorInner::WriteConflict { key, primary, .. } => (key, primary),
+                e => panic!("unexpected error: {:?}", e),
+            }
+        };
+
+        // With redaction off, the raw keys are embedded as before.
+        let plain = key_is_locked(false);
+        assert_eq!(plain.get_key(), b"redacted_key");
+        assert_eq!(plain.get_primary_lock(), b"redacted_primary");
+        let (plain_key, plain_primary) = write_conflict(false);
+        assert_eq!(plain_key, b"redacted_key");
+        assert_eq!(plain_primary, b"redacted_primary");
+
+        // With redaction on, the key bytes are replaced but the lengths (and
+        // the rest of the lock info) are kept.
+        let redacted = key_is_locked(true);
+        assert_ne!(redacted.get_key(), b"redacted_key");
+        assert_ne!(redacted.get_primary_lock(), b"redacted_primary");
+        assert_eq!(redacted.get_key().len(), b"redacted_key".len());
+        assert_eq!(
+            redacted.get_primary_lock().len(),
+            b"redacted_primary".len()
+        );
+        assert_eq!(redacted.get_lock_version(), plain.get_lock_version());
+        assert_eq!(redacted.get_lock_ttl(), plain.get_lock_ttl());
+
+        // The hash is salted per key and stable within the process.
+        assert_ne!(redacted.get_key(), redacted.get_primary_lock());
+        let again = key_is_locked(true);
+        assert_eq!(again.get_key(), redacted.get_key());
+        assert_eq!(again.get_primary_lock(), redacted.get_primary_lock());
+        let (conflict_key, conflict_primary) = write_conflict(true);
+        assert_eq!(conflict_key, redacted.get_key());
+        assert_eq!(conflict_primary, redacted.get_primary_lock());
+    }
+
+    #[test]
+    fn test_blocks_stale_read() {
+        let mut lock = Lock::new(
+            LockType::Put,
+            b"pk".to_vec(),
+            100.into(),
+            3,
+            None,
+            TimeStamp::zero(),
+            1,
+            TimeStamp::zero(),
+            false,
+        );
+
+        // All orderings of (lock.ts = 100, read_ts, resolved_ts) for a Put lock.
+        let cases = vec![
+            // lock.ts > read_ts: the lock's commit can never be visible.
+            (50, 0, false),
+            (50, 70, false),
+            (50, 200, false),
+            // read_ts <= resolved_ts: data below resolved_ts is consistent.
+            (100, 100, false),
+            (150, 150, false),
+            (150, 200, false),
+            // lock.ts <= read_ts and read_ts > resolved_ts: the lock may
+            // commit at or below read_ts.
+            (100, 0, true),
+            (100, 99, true),
+            (150, 0, true),
+            (150, 120, true),
+        ];
+        for (read_ts, resolved_ts, expected) in cases {
+            let read_ts = TimeStamp::from(read_ts);
+            let resolved_ts = TimeStamp::from(resolved_ts);
+            assert_eq!(
+                lock.blocks_stale_read(read_ts, resolved_ts),
+                expected,
+                "read_ts: {}, resolved_ts: {}",
+                read_ts,
+                resolved_ts
+            );
+            assert_eq!(
+                TxnLockRef::from(&lock).blocks_stale_read(read_ts, resolved_ts),
+                expected,
+                "read_ts: {}, resolved_ts: {}",
+                read_ts,
+                resolved_ts
+            );
+        }
+
+        // Lock and Pessimistic locks never hide data, so they never block.
+        for lock_type in [LockType::Lock, LockType::Pessimistic] {
+            lock.lock_type = lock_type;
+            assert!(!lock.blocks_stale_read(150.into(), TimeStamp::zero()));
+            assert!(!TxnLockRef::from(&lock).blocks_stale_read(150.into(), TimeStamp::zero()));
+        }
+
+        // An in-memory pessimistic lock never blocks either.
+        let pessimistic_lock = PessimisticLock {
+            primary: b"pk".to_vec().into_boxed_slice(),
+            start_ts: 100.into(),
+            ttl: 1000,
+            for_update_ts: 100.into(),
+            min_commit_ts: 110.into(),
+            last_change: LastChange::Unknown,
+            is_locked_with_conflict: false,
+        };
+        assert!(
+            !TxnLockRef::from(&pessimistic_lock).blocks_stale_read(150.into(), TimeStamp::zero())
+        );
+    }
+
+    #[test]
+    fn test_request_fingerprint() {
+        let base = Lock::new(
+            LockType::Put,
+            b"pk".to_vec(),
+            100.into(),
+            3,
+            Some(b"short_value".to_vec()),
+            120.into(),
+            10,
+            130.into(),
+            false,
+        )
+        .with_generation(2);
+
+        // Fields a retry is allowed to change do not take part.
+        let mut retry = base.clone();
+        retry.ttl = 2000;
+        retry.min_commit_ts = 999.into();
+        retry.short_value = None;
+        retry.txn_size = 0;
+        assert_eq!(base.request_fingerprint(), retry.request_fingerprint());
+        assert!(base.is_same_request(&retry));
+
+        // Changing any identifying component changes the fingerprint.
+        let variants: Vec<Box<dyn Fn(&mut Lock)>> = vec![
+            Box::new(|l| l.lock_type = LockType::Delete),
+            Box::new(|l| l.primary = b"pk2".to_vec()),
+            Box::new(|l| l.ts = 101.into()),
+            Box::new(|l| l.for_update_ts = 121.into()),
+            Box::new(|l| l.generation = 3),
+        ];
+        for (i, mutate) in variants.iter().enumerate() {
+            let mut other = base.clone();
+            mutate(&mut other);
+            assert_ne!(
+                base.request_fingerprint(),
+                other.request_fingerprint(),
+                "#{}",
+                i
+            );
+            assert!(!base.is_same_request(&other), "#{}", i);
+        }
+
+        // An in-memory pessimistic lock fingerprints like the lock it
+        // converts into.
+        let pessimistic_lock = PessimisticLock {
+            primary: b"pk".to_vec().into_boxed_slice(),
+            start_ts: 100.into(),
+            ttl: 3,
+            for_update_ts: 120.into(),
+            min_commit_ts: 130.into(),
+            last_change: LastChange::make_exist(8.into(), 2),
+            is_locked_with_conflict: false,
+        };
+        assert_eq!(
+            pessimistic_lock.request_fingerprint(),
+            pessimistic_lock.to_lock().request_fingerprint()
+        );
     }
 
     #[test]
@@ -1233,7 +3029,7 @@ mod tests {
         );
 
         lock.short_value = None;
-        lock.secondaries = Vec::default();
+        lock.secondaries = SecondaryKeys::default();
         lock.generation = 10;
         assert_eq!(
             format!("{:?}", lock),
@@ -1301,12 +3097,13 @@ mod tests {
             txn_size: 0,
             min_commit_ts: 20.into(),
             use_async_commit: false,
-            secondaries: vec![],
+            secondaries: SecondaryKeys::default(),
             rollback_ts: vec![],
             last_change: LastChange::make_exist(8.into(), 2),
             txn_source: 0,
             is_locked_with_conflict: false,
             generation: 0,
+            allow_long_short_value: false,
         };
         assert_eq!(pessimistic_lock.to_lock(), expected_lock);
         assert_eq!(pessimistic_lock.into_lock(), expected_lock);
@@ -1357,4 +3154,119 @@ mod tests {
         // enum (8 + 2 * 8) and a bool.
         assert_eq!(lock.memory_size(), 7 + 16 + 5 * 8 + 24);
     }
+
+    // Golden test: the field names and value encodings below are what
+    // downstream dump-parsing scripts see, so any mismatch here is a breaking
+    // change, not a test to update casually.
+    #[cfg(feature = "lock-dump")]
+    #[test]
+    fn test_lock_dump_json() {
+        let lock = Lock::new(
+            LockType::Put,
+            b"pk".to_vec(),
+            100.into(),
+            3,
+            Some(b"short_value".to_vec()),
+            101.into(),
+            10,
+            127.into(),
+            false,
+        )
+        .use_async_commit(vec![b"secondary_k1".to_vec(), b"secondary_kkkkk2".to_vec()])
+        .set_last_change(LastChange::make_exist(80.into(), 4));
+
+        assert_eq!(
+            lock.to_json_value(),
+            serde_json::json!({
+                "lock_type": "put",
+                "primary_key": "706B",
+                "start_ts": 100,
+                "ttl": 3,
+                "short_value": "73686F72745F76616C7565",
+                "for_update_ts": 101,
+                "txn_size": 10,
+                "min_commit_ts": 127,
+                "use_async_commit": true,
+                "secondaries": ["7365636F6E646172795F6B31", "7365636F6E646172795F6B6B6B6B6B32"],
+                "rollback_ts": [],
+                "last_change": {
+                    "last_change_ts": 80,
+                    "estimated_versions_to_last_change": 4,
+                },
+                "txn_source": 0,
+                "is_locked_with_conflict": false,
+                "generation": 0,
+            })
+        );
+
+        // The unit variants of `LastChange` come out as plain strings.
+        let mut lock = lock;
+        lock.last_change = LastChange::Unknown;
+        assert_eq!(lock.to_json_value()["last_change"], "unknown");
+        lock.last_change = LastChange::NotExist;
+        assert_eq!(lock.to_json_value()["last_change"], "not_exist");
+    }
+
+    #[cfg(feature = "lock-dump")]
+    #[test]
+    fn test_pessimistic_lock_dump_json() {
+        let lock = PessimisticLock {
+            primary: b"primary".to_vec().into_boxed_slice(),
+            start_ts: 5.into(),
+            ttl: 1000,
+            for_update_ts: 10.into(),
+            min_commit_ts: 20.into(),
+            last_change: LastChange::make_exist(8.into(), 2),
+            is_locked_with_conflict: false,
+        };
+        assert_eq!(
+            serde_json::to_value(&lock).unwrap(),
+            serde_json::json!({
+                "primary_key": "7072696D617279",
+                "start_ts": 5,
+                "ttl": 1000,
+                "for_update_ts": 10,
+                "min_commit_ts": 20,
+                "last_change": {
+                    "last_change_ts": 8,
+                    "estimated_versions_to_last_change": 2,
+                },
+                "is_locked_with_conflict": false,
+            })
+        );
+    }
+
+    #[cfg(feature = "lock-dump")]
+    #[test]
+    fn test_lock_dump_redaction() {
+        let lock = Lock::new(
+            LockType::Put,
+            b"pk".to_vec(),
+            100.into(),
+            3,
+            Some(b"short_value".to_vec()),
+            TimeStamp::zero(),
+            1,
+            TimeStamp::zero(),
+            false,
+        );
+
+        set_redact_lock_info(true);
+        let redacted = lock.to_json_value();
+        set_redact_lock_info(false);
+        let plain = lock.to_json_value();
+
+        // Redaction replaces the bytes but keeps the shape: still hex strings
+        // of the original lengths, under the same field names.
+        for field in ["primary_key", "short_value"] {
+            let p = plain[field].as_str().unwrap();
+            let r = redacted[field].as_str().unwrap();
+            assert_ne!(p, r);
+            assert_eq!(p.len(), r.len());
+            assert!(r.bytes().all(|b| b.is_ascii_hexdigit()));
+        }
+        // The non-key fields are untouched.
+        assert_eq!(plain["start_ts"], redacted["start_ts"]);
+        assert_eq!(plain["lock_type"], redacted["lock_type"]);
+    }
 }
@@ -6,6 +6,8 @@ use bitflags::bitflags;
 use byteorder::{ByteOrder, NativeEndian};
 use collections::HashMap;
 use kvproto::kvrpcpb::{self, Assertion};
+#[cfg(feature = "lock-dump")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use tikv_util::{
     codec,
     codec::{
@@ -661,6 +663,31 @@ impl LastChange {
     }
 }
 
+// The field names and variant strings are pinned by `test_lock_dump_json` in
+// `lock.rs`: downstream scripts parse the dumps, so renames here are breaking
+// changes.
+#[cfg(feature = "lock-dump")]
+impl Serialize for LastChange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            LastChange::Unknown => serializer.serialize_str("unknown"),
+            LastChange::NotExist => serializer.serialize_str("not_exist"),
+            LastChange::Exist {
+                last_change_ts,
+                estimated_versions_to_last_change,
+            } => {
+                let mut s = serializer.serialize_struct("LastChange", 2)?;
+                s.serialize_field("last_change_ts", &last_change_ts.into_inner())?;
+                s.serialize_field(
+                    "estimated_versions_to_last_change",
+                    estimated_versions_to_last_change,
+                )?;
+                s.end()
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -371,6 +371,9 @@ impl From<txn_types::Error> for ErrorInner {
                 key,
                 primary,
                 reason,
+                // The mvcc error doesn't carry the lock type; it's only
+                // surfaced through the txn_types error's detail string.
+                lock_type: _,
             }) => ErrorInner::WriteConflict {
                 start_ts,
                 conflict_start_ts,
@@ -711,6 +711,11 @@ impl<S: EngineSnapshot> MvccReader<S> {
         }
         let mut locks = Vec::with_capacity(limit);
         let mut has_remain = false;
+        // Most locks a resolve-lock scan parses are rejected by `filter`;
+        // recycling their allocations through a scratch `LockBuffers` avoids
+        // a few allocations per scanned lock.
+        #[cfg(feature = "lock-parse-scratch")]
+        let mut buffers = txn_types::LockBuffers::default();
         while cursor.valid()? {
             let key = Key::from_encoded_slice(cursor.key(&mut self.statistics.lock));
             if let Some(end) = end {
@@ -720,6 +725,9 @@ impl<S: EngineSnapshot> MvccReader<S> {
                 }
             }
 
+            #[cfg(feature = "lock-parse-scratch")]
+            let lock = Lock::parse_in(cursor.value(&mut self.statistics.lock), &mut buffers)?;
+            #[cfg(not(feature = "lock-parse-scratch"))]
             let lock = Lock::parse(cursor.value(&mut self.statistics.lock))?;
             if filter(&key, &lock) {
                 locks.push((key, lock));
@@ -727,6 +735,10 @@ impl<S: EngineSnapshot> MvccReader<S> {
                     has_remain = true;
                     break;
                 }
+            } else {
+                // The rejected lock's allocations feed the next parse.
+                #[cfg(feature = "lock-parse-scratch")]
+                buffers.recycle(lock);
             }
             cursor.next(&mut self.statistics.lock);
         }
@@ -610,7 +610,7 @@ impl<'a> PrewriteMutation<'a> {
 
         if let Some(secondary_keys) = self.secondary_keys {
             lock.use_async_commit = true;
-            lock.secondaries = secondary_keys.to_owned();
+            lock.secondaries = secondary_keys.to_owned().into();
         }
 
         let final_min_commit_ts = if lock.use_async_commit || try_one_pc {
@@ -626,7 +626,7 @@ impl<'a> PrewriteMutation<'a> {
             if let Err(Error(box ErrorInner::CommitTsTooLarge { .. })) = &res {
                 try_one_pc = false;
                 lock.use_async_commit = false;
-                lock.secondaries = Vec::new();
+                lock.secondaries = Default::default();
             }
             res
         } else {